@@ -0,0 +1,64 @@
+;; Keyword Spotter: suggests a follow-up when the keyword set in the plugin settings
+;; (a JSON string, e.g. "TODO") appears in the hook context.
+;; Build: wat2wasm keyword_spotter.wat -o keyword_spotter.wasm
+(module
+  (import "shadowlearn" "context_read" (func $context_read (param i32 i32) (result i32)))
+  (import "shadowlearn" "settings_read" (func $settings_read (param i32 i32) (result i32)))
+  (import "shadowlearn" "emit_suggestion" (func $emit_suggestion (param i32 i32)))
+  (import "shadowlearn" "log" (func $log (param i32 i32 i32)))
+
+  ;; 0..1024: constants, 1024..2048: settings, 2048..4096: suggestion, 4096..: context
+  (memory (export "memory") 2)
+  (data (i32.const 0) "Spotted \22")
+  (data (i32.const 16) "\22 in the current context")
+  (data (i32.const 48) "settings must be a JSON string")
+  (data (i32.const 80) "context scanned")
+
+  ;; 1 if `needle` occurs in `haystack`
+  (func $contains (param $haystack i32) (param $haystack_len i32)
+                  (param $needle i32) (param $needle_len i32) (result i32)
+    (local $i i32) (local $j i32)
+    (block $done
+      (loop $outer
+        (br_if $done
+          (i32.gt_s (i32.add (local.get $i) (local.get $needle_len)) (local.get $haystack_len)))
+        (local.set $j (i32.const 0))
+        (block $mismatch
+          (loop $inner
+            (br_if $mismatch
+              (i32.ne
+                (i32.load8_u (i32.add (local.get $haystack) (i32.add (local.get $i) (local.get $j))))
+                (i32.load8_u (i32.add (local.get $needle) (local.get $j)))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (if (i32.eq (local.get $j) (local.get $needle_len))
+              (then (return (i32.const 1))))
+            (br $inner)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $outer)))
+    (i32.const 0))
+
+  (func (export "on_context") (result i32)
+    (local $settings_len i32) (local $needle_len i32) (local $context_len i32)
+    (local.set $settings_len (call $settings_read (i32.const 1024) (i32.const 1024)))
+
+    ;; Settings must be a non-empty JSON string: "keyword"
+    (if (i32.or
+          (i32.lt_s (local.get $settings_len) (i32.const 3))
+          (i32.ne (i32.load8_u (i32.const 1024)) (i32.const 34)))
+      (then
+        (call $log (i32.const 2) (i32.const 48) (i32.const 30))
+        (return (i32.const 0))))
+    (local.set $needle_len (i32.sub (local.get $settings_len) (i32.const 2)))
+
+    (local.set $context_len (call $context_read (i32.const 4096) (i32.const 126976)))
+    (call $log (i32.const 0) (i32.const 80) (i32.const 15))
+
+    (if (call $contains (i32.const 4096) (local.get $context_len)
+                        (i32.const 1025) (local.get $needle_len))
+      (then
+        (memory.copy (i32.const 2048) (i32.const 0) (i32.const 9))
+        (memory.copy (i32.const 2057) (i32.const 1025) (local.get $needle_len))
+        (memory.copy (i32.add (i32.const 2057) (local.get $needle_len)) (i32.const 16) (i32.const 24))
+        (call $emit_suggestion (i32.const 2048) (i32.add (local.get $needle_len) (i32.const 33)))))
+    (i32.const 0))
+)
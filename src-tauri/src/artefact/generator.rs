@@ -7,26 +7,37 @@ use std::time::Duration;
 use tokio::time::timeout;
 
 use crate::adaptive::AdaptivePromptEngine;
-use crate::intent::llm_client::{LLMClient, LLMProvider};
+use crate::llm::{LlmClient, LlmRequest};
+use crate::privacy::RequestOrigin;
 use crate::validator::{ArtefactValidator, ArtefactType, ValidationResult};
 use std::path::Path;
+use std::sync::Arc;
 
 /// Generator for context-aware artifacts
 pub struct ArtefactGenerator {
     adaptive_engine: AdaptivePromptEngine,
-    llm_client: LLMClient,
+    llm_client: Arc<LlmClient>,
     validator: ArtefactValidator,
+    /// Active app the artifact is generated for (egress policy)
+    origin: Option<RequestOrigin>,
 }
 
 impl ArtefactGenerator {
-    pub fn new() -> Self {
+    pub fn new(llm_client: Arc<LlmClient>) -> Self {
         Self {
             adaptive_engine: AdaptivePromptEngine::new(),
-            llm_client: LLMClient::new(LLMProvider::Ollama, None),
+            llm_client,
             validator: ArtefactValidator::new(),
+            origin: None,
         }
     }
 
+    /// Set the app the generated content relates to
+    pub fn with_origin(mut self, origin: Option<RequestOrigin>) -> Self {
+        self.origin = origin;
+        self
+    }
+
     /// Generate an artifact based on context
     pub async fn generate(
         &mut self,
@@ -51,10 +62,13 @@ impl ArtefactGenerator {
         // Step 2: Call LLM with timeout
         let timeout_duration = Duration::from_secs(30);
         
-        let llm_response = match timeout(timeout_duration, async {
-            self.llm_client.generate(&prompt, 500).await
-        }).await {
-            Ok(Ok(response)) => response,
+        let request = LlmRequest::prompt(prompt)
+            .temperature(0.3)
+            .max_tokens(500)
+            .origin(self.origin.clone());
+
+        let llm_response = match timeout(timeout_duration, self.llm_client.complete(request)).await {
+            Ok(Ok(response)) => response.content,
             Ok(Err(e)) => {
                 warn!("[ARTEFACT] LLM error: {}, using fallback", e);
                 return self.generate_fallback_artifact(artefact_type, &intent);
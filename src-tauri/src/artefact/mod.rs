@@ -6,7 +6,9 @@ pub mod generator;
 // Re-export types for external use
 pub use generator::{ArtefactGenerator, GeneratedArtifact, ArtefactMetadata};
 
+use crate::llm::LlmClient;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tracing::{debug, info, error};
 
 /// Main artifact management system
@@ -16,9 +18,9 @@ pub struct ArtefactSystem {
 }
 
 impl ArtefactSystem {
-    pub fn new() -> Self {
+    pub fn new(llm_client: Arc<LlmClient>) -> Self {
         Self {
-            generator: generator::ArtefactGenerator::new(),
+            generator: generator::ArtefactGenerator::new(llm_client),
             stats: ArtefactStats::new(),
         }
     }
@@ -0,0 +1,647 @@
+//! Gouverneur de budget de ressources.
+//!
+//! Compte le temps CPU, le temps écoulé et la croissance mémoire de chaque sous-système
+//! (capture, OCR, vision, LLM, triggers) sur une fenêtre glissante, et décide avant chaque
+//! tâche de fond si elle tourne, est espacée ou est sautée : sur batterie, quand le budget
+//! configuré du sous-système est épuisé ou quand la mémoire du processus dépasse la limite.
+//! Les requêtes LLM sont comptées mais jamais refusées : elles répondent à l'utilisateur.
+//!
+//! Le temps CPU n'est mesuré que pour le travail bloquant (`measure`, dans `spawn_blocking`) :
+//! une tâche async change de thread à chaque `.await`. Le travail async (capture, vision,
+//! triggers) a donc des budgets en temps écoulé.
+
+pub mod power;
+
+pub use power::{PowerProbe, PowerSource};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::config::types::{BudgetConfig, SubsystemBudget};
+
+/// Durée d'un tick d'horloge de `/proc/*/stat` (USER_HZ vaut 100 sur Linux)
+#[cfg(target_os = "linux")]
+const CLOCK_TICK_MS: u64 = 10;
+
+/// Taille de page utilisée par `/proc/self/statm`
+#[cfg(target_os = "linux")]
+const PAGE_SIZE: u64 = 4096;
+
+/// Au-delà, l'intervalle espacé ne tient plus dans une `Duration`
+pub const MAX_BATTERY_THROTTLE_FACTOR: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Monitor,
+    Ocr,
+    Vision,
+    Llm,
+    Triggers,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 5] = [
+        Subsystem::Monitor,
+        Subsystem::Ocr,
+        Subsystem::Vision,
+        Subsystem::Llm,
+        Subsystem::Triggers,
+    ];
+
+    /// Tâche périodique : espacée plutôt que sautée sur batterie
+    fn is_periodic(self) -> bool {
+        matches!(self, Subsystem::Monitor | Subsystem::Triggers)
+    }
+
+    /// Analyse d'une capture, sautée sur batterie si configuré
+    fn is_analysis(self) -> bool {
+        matches!(self, Subsystem::Ocr | Subsystem::Vision)
+    }
+
+    /// Travail de fond qui peut être reporté
+    fn is_deferrable(self) -> bool {
+        self != Subsystem::Llm
+    }
+
+    fn limits(self, config: &BudgetConfig) -> &SubsystemBudget {
+        match self {
+            Subsystem::Monitor => &config.monitor,
+            Subsystem::Ocr => &config.ocr,
+            Subsystem::Vision => &config.vision,
+            Subsystem::Llm => &config.llm,
+            Subsystem::Triggers => &config.triggers,
+        }
+    }
+}
+
+/// Décision du gouverneur avant une tâche
+#[derive(Debug, Clone, PartialEq)]
+pub enum Admission {
+    Run,
+    /// Tourne, mais l'intervalle de la tâche périodique est multiplié par `factor`
+    Throttle {
+        factor: f32,
+        reason: String,
+    },
+    Skip {
+        reason: String,
+    },
+}
+
+impl Admission {
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self, Admission::Skip { .. })
+    }
+
+    /// Facteur à appliquer à l'intervalle d'une tâche périodique
+    pub fn interval_factor(&self) -> f32 {
+        match self {
+            Admission::Throttle { factor, .. } => *factor,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    cpu: Duration,
+    wall: Duration,
+    memory_growth: u64,
+}
+
+#[derive(Debug, Default)]
+struct SubsystemState {
+    samples: VecDeque<Sample>,
+    skipped: u64,
+    throttled: u64,
+}
+
+/// Consommation d'un sous-système sur la fenêtre
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemUsage {
+    pub subsystem: Subsystem,
+    pub runs: usize,
+    pub cpu_ms: u64,
+    pub wall_ms: u64,
+    /// Plus forte croissance de la mémoire résidente pendant une tâche
+    pub peak_memory_growth_bytes: u64,
+    pub max_cpu_ms: Option<u64>,
+    pub max_wall_ms: Option<u64>,
+    pub over_budget: bool,
+    /// Tâches sautées / espacées depuis le démarrage
+    pub skipped: u64,
+    pub throttled: u64,
+}
+
+/// Rapport exposé dans `get_health_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub enabled: bool,
+    pub window_secs: u64,
+    pub power: PowerSource,
+    pub memory_bytes: Option<u64>,
+    pub max_memory_bytes: Option<u64>,
+    pub subsystems: Vec<SubsystemUsage>,
+}
+
+pub struct BudgetGovernor {
+    config: RwLock<BudgetConfig>,
+    state: Mutex<HashMap<Subsystem, SubsystemState>>,
+    power: PowerProbe,
+}
+
+impl BudgetGovernor {
+    pub fn new(config: BudgetConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            state: Mutex::new(HashMap::new()),
+            power: PowerProbe::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_power_probe(mut self, power: PowerProbe) -> Self {
+        self.power = power;
+        self
+    }
+
+    /// Applique une nouvelle configuration ; les mesures déjà faites sont conservées
+    pub fn apply(&self, config: &BudgetConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+    }
+
+    /// Décide si une tâche du sous-système peut tourner maintenant
+    pub fn admit(&self, subsystem: Subsystem) -> Admission {
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if !config.enabled {
+            return Admission::Run;
+        }
+
+        let admission = self.decide(subsystem, &config);
+        if admission != Admission::Run {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = state.entry(subsystem).or_default();
+            match &admission {
+                Admission::Skip { reason } => {
+                    entry.skipped += 1;
+                    debug!("⏸️ {:?} skipped: {}", subsystem, reason);
+                }
+                Admission::Throttle { reason, .. } => {
+                    entry.throttled += 1;
+                    debug!("🐢 {:?} throttled: {}", subsystem, reason);
+                }
+                Admission::Run => {}
+            }
+        }
+        admission
+    }
+
+    fn decide(&self, subsystem: Subsystem, config: &BudgetConfig) -> Admission {
+        if !subsystem.is_deferrable() {
+            return Admission::Run;
+        }
+
+        let window = Duration::from_secs(config.window_secs);
+        let limits = subsystem.limits(config);
+        let (cpu, wall) = self.totals(subsystem, window);
+        if let Some(max) = limits.max_cpu_ms {
+            if cpu.as_millis() as u64 >= max {
+                return Admission::Skip {
+                    reason: format!(
+                        "CPU budget exhausted ({}ms / {}ms over {}s)",
+                        cpu.as_millis(),
+                        max,
+                        config.window_secs
+                    ),
+                };
+            }
+        }
+        if let Some(max) = limits.max_wall_ms {
+            if wall.as_millis() as u64 >= max {
+                return Admission::Skip {
+                    reason: format!(
+                        "time budget exhausted ({}ms / {}ms over {}s)",
+                        wall.as_millis(),
+                        max,
+                        config.window_secs
+                    ),
+                };
+            }
+        }
+
+        if let (Some(max_mb), Some(resident)) = (config.max_memory_mb, resident_memory()) {
+            if resident >= max_mb * 1024 * 1024 {
+                return Admission::Skip {
+                    reason: format!(
+                        "memory limit reached ({}MB / {}MB)",
+                        resident / (1024 * 1024),
+                        max_mb
+                    ),
+                };
+            }
+        }
+
+        if self.power.source() == PowerSource::Battery {
+            if subsystem.is_analysis() && config.battery_skip_analysis {
+                return Admission::Skip {
+                    reason: "on battery".to_string(),
+                };
+            }
+            if subsystem.is_periodic() && config.battery_throttle_factor > 1.0 {
+                // Borné aussi ici : le fichier de configuration n'est validé qu'à l'enregistrement
+                return Admission::Throttle {
+                    factor: config
+                        .battery_throttle_factor
+                        .min(MAX_BATTERY_THROTTLE_FACTOR),
+                    reason: "on battery".to_string(),
+                };
+            }
+        }
+
+        Admission::Run
+    }
+
+    /// Temps CPU et temps écoulé cumulés sur la fenêtre ; purge les mesures plus anciennes
+    fn totals(&self, subsystem: Subsystem, window: Duration) -> (Duration, Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = state.entry(subsystem).or_default();
+        prune(&mut entry.samples, window);
+        entry
+            .samples
+            .iter()
+            .fold((Duration::ZERO, Duration::ZERO), |(cpu, wall), sample| {
+                (cpu + sample.cpu, wall + sample.wall)
+            })
+    }
+
+    /// Commence à mesurer une tâche async ; la mesure est enregistrée quand le garde est
+    /// libéré. Seuls le temps écoulé et la mémoire sont comptés, pas le temps CPU.
+    pub fn start(self: &Arc<Self>, subsystem: Subsystem) -> WorkGuard {
+        WorkGuard {
+            governor: self.clone(),
+            subsystem,
+            started: Instant::now(),
+            cpu: None,
+            memory: resident_memory(),
+        }
+    }
+
+    /// Mesure une tâche bloquante, temps CPU compris : celui du thread et celui des
+    /// processus enfants terminés pendant la tâche (tesseract)
+    pub fn measure<T>(self: &Arc<Self>, subsystem: Subsystem, work: impl FnOnce() -> T) -> T {
+        let mut _work = self.start(subsystem);
+        _work.cpu = cpu_time();
+        work()
+    }
+
+    pub fn record(&self, subsystem: Subsystem, cpu: Duration, wall: Duration, memory_growth: u64) {
+        let window = Duration::from_secs(
+            self.config
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .window_secs,
+        );
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = state.entry(subsystem).or_default();
+        prune(&mut entry.samples, window);
+        entry.samples.push_back(Sample {
+            at: Instant::now(),
+            cpu,
+            wall,
+            memory_growth,
+        });
+    }
+
+    pub fn usage(&self) -> BudgetUsage {
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let window = Duration::from_secs(config.window_secs);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let subsystems = Subsystem::ALL
+            .iter()
+            .map(|&subsystem| {
+                let entry = state.entry(subsystem).or_default();
+                prune(&mut entry.samples, window);
+                let cpu: Duration = entry.samples.iter().map(|s| s.cpu).sum();
+                let wall: Duration = entry.samples.iter().map(|s| s.wall).sum();
+                let limits = subsystem.limits(&config);
+                let cpu_ms = cpu.as_millis() as u64;
+                let wall_ms = wall.as_millis() as u64;
+
+                SubsystemUsage {
+                    subsystem,
+                    runs: entry.samples.len(),
+                    cpu_ms,
+                    wall_ms,
+                    peak_memory_growth_bytes: entry
+                        .samples
+                        .iter()
+                        .map(|s| s.memory_growth)
+                        .max()
+                        .unwrap_or(0),
+                    max_cpu_ms: limits.max_cpu_ms,
+                    max_wall_ms: limits.max_wall_ms,
+                    over_budget: limits.max_cpu_ms.is_some_and(|max| cpu_ms >= max)
+                        || limits.max_wall_ms.is_some_and(|max| wall_ms >= max),
+                    skipped: entry.skipped,
+                    throttled: entry.throttled,
+                }
+            })
+            .collect();
+
+        BudgetUsage {
+            enabled: config.enabled,
+            window_secs: config.window_secs,
+            power: self.power.source(),
+            memory_bytes: resident_memory(),
+            max_memory_bytes: config.max_memory_mb.map(|mb| mb * 1024 * 1024),
+            subsystems,
+        }
+    }
+}
+
+impl Default for BudgetGovernor {
+    fn default() -> Self {
+        Self::new(BudgetConfig::default())
+    }
+}
+
+fn prune(samples: &mut VecDeque<Sample>, window: Duration) {
+    while samples
+        .front()
+        .is_some_and(|sample| sample.at.elapsed() > window)
+    {
+        samples.pop_front();
+    }
+}
+
+/// Mesure en cours, enregistrée à la libération
+pub struct WorkGuard {
+    governor: Arc<BudgetGovernor>,
+    subsystem: Subsystem,
+    started: Instant,
+    /// Temps CPU au départ, pour le travail bloquant uniquement
+    cpu: Option<Duration>,
+    memory: Option<u64>,
+}
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        let cpu = match (self.cpu, cpu_time()) {
+            (Some(start), Some(now)) => now.saturating_sub(start),
+            _ => Duration::ZERO,
+        };
+        let memory_growth = match (self.memory, resident_memory()) {
+            (Some(start), Some(now)) => now.saturating_sub(start),
+            _ => 0,
+        };
+        self.governor
+            .record(self.subsystem, cpu, self.started.elapsed(), memory_growth);
+    }
+}
+
+/// Temps CPU (utilisateur + système) du thread courant, plus celui des processus enfants
+/// attendus par le processus. Un enfant attendu par un autre thread pendant la mesure
+/// est compté aussi : c'est rare, les commandes externes sont lancées depuis l'OCR.
+#[cfg(target_os = "linux")]
+fn cpu_time() -> Option<Duration> {
+    let thread = std::fs::read_to_string("/proc/thread-self/stat").ok()?;
+    let process = std::fs::read_to_string("/proc/self/stat").ok()?;
+    let ticks = parse_cpu_ticks(&thread)? + parse_children_cpu_ticks(&process)?;
+    Some(Duration::from_millis(ticks * CLOCK_TICK_MS))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_time() -> Option<Duration> {
+    None
+}
+
+/// Champs de `/proc/*/stat` après le nom du processus, qui peut contenir espaces et parenthèses
+#[cfg(any(target_os = "linux", test))]
+fn stat_fields(stat: &str) -> Option<Vec<&str>> {
+    Some(stat[stat.rfind(')')? + 1..].split_whitespace().collect())
+}
+
+/// utime + stime (champs 14 et 15)
+#[cfg(any(target_os = "linux", test))]
+fn parse_cpu_ticks(stat: &str) -> Option<u64> {
+    let fields = stat_fields(stat)?;
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// cutime + cstime (champs 16 et 17) : enfants terminés et attendus
+#[cfg(any(target_os = "linux", test))]
+fn parse_children_cpu_ticks(stat: &str) -> Option<u64> {
+    let fields = stat_fields(stat)?;
+    let cutime: u64 = fields.get(13)?.parse().ok()?;
+    let cstime: u64 = fields.get(14)?.parse().ok()?;
+    Some(cutime + cstime)
+}
+
+/// Vérifie une configuration de budget avant de l'appliquer
+pub fn validate(config: &BudgetConfig) -> Result<(), String> {
+    if config.window_secs == 0 {
+        return Err("Budget window must be at least 1 second".to_string());
+    }
+    if !(1.0..=MAX_BATTERY_THROTTLE_FACTOR).contains(&config.battery_throttle_factor) {
+        return Err(format!(
+            "Battery throttle factor must be between 1 and {}",
+            MAX_BATTERY_THROTTLE_FACTOR
+        ));
+    }
+    Ok(())
+}
+
+/// Mémoire résidente du processus (octets)
+#[cfg(target_os = "linux")]
+fn resident_memory() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * PAGE_SIZE)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_memory() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn governor(config: BudgetConfig, power: &TempDir) -> Arc<BudgetGovernor> {
+        Arc::new(BudgetGovernor::new(config).with_power_probe(PowerProbe::new(power.path())))
+    }
+
+    fn on_battery(root: &TempDir) {
+        let battery = root.path().join("BAT0");
+        fs::create_dir_all(&battery).unwrap();
+        fs::write(battery.join("type"), "Battery\n").unwrap();
+        fs::write(battery.join("status"), "Discharging\n").unwrap();
+    }
+
+    #[test]
+    fn test_parse_cpu_ticks() {
+        let stat = "1234 (tokio-rt (worker) 1) S 1 1234 1234 0 -1 4194560 \
+                    500 0 0 0 42 8 0 0 20 0 4 0 100 0 0";
+        assert_eq!(parse_cpu_ticks(stat), Some(50));
+        assert_eq!(parse_children_cpu_ticks(stat), Some(0));
+        assert_eq!(parse_cpu_ticks("garbage"), None);
+
+        let waited = "1234 (app) S 1 1234 1234 0 -1 4194560 500 0 0 0 42 8 30 7 20 0 4 0";
+        assert_eq!(parse_children_cpu_ticks(waited), Some(37));
+    }
+
+    #[test]
+    fn test_measure_counts_child_processes() {
+        let power = TempDir::new().unwrap();
+        let governor = governor(BudgetConfig::default(), &power);
+
+        governor.measure(Subsystem::Ocr, || {
+            std::process::Command::new("sh")
+                .args(["-c", "i=0; while [ $i -lt 200000 ]; do i=$((i+1)); done"])
+                .status()
+                .unwrap();
+        });
+        // Travail async : le temps écoulé seulement
+        drop(governor.start(Subsystem::Vision));
+
+        let usage = governor.usage();
+        let find = |subsystem| {
+            usage
+                .subsystems
+                .iter()
+                .find(|u| u.subsystem == subsystem)
+                .unwrap()
+                .clone()
+        };
+        if cfg!(target_os = "linux") {
+            assert!(find(Subsystem::Ocr).cpu_ms > 0);
+        }
+        assert_eq!(find(Subsystem::Vision).runs, 1);
+        assert_eq!(find(Subsystem::Vision).cpu_ms, 0);
+    }
+
+    #[test]
+    fn test_validate_rejects_unusable_config() {
+        assert!(validate(&BudgetConfig::default()).is_ok());
+        for factor in [f32::INFINITY, f32::NAN, 0.5, 1e30] {
+            let config = BudgetConfig {
+                battery_throttle_factor: factor,
+                ..BudgetConfig::default()
+            };
+            assert!(validate(&config).is_err(), "{}", factor);
+        }
+        let config = BudgetConfig {
+            window_secs: 0,
+            ..BudgetConfig::default()
+        };
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_budget_exhausted_skips_until_window_passes() {
+        let power = TempDir::new().unwrap();
+        let mut config = BudgetConfig::default();
+        config.ocr.max_cpu_ms = Some(100);
+        let governor = governor(config.clone(), &power);
+
+        assert_eq!(governor.admit(Subsystem::Ocr), Admission::Run);
+        governor.record(
+            Subsystem::Ocr,
+            Duration::from_millis(150),
+            Duration::from_millis(200),
+            0,
+        );
+        assert!(!governor.admit(Subsystem::Ocr).is_allowed());
+        // Les autres sous-systèmes ont leur propre budget
+        assert_eq!(governor.admit(Subsystem::Monitor), Admission::Run);
+
+        let usage = governor.usage();
+        let ocr = usage
+            .subsystems
+            .iter()
+            .find(|u| u.subsystem == Subsystem::Ocr)
+            .unwrap();
+        assert_eq!(ocr.runs, 1);
+        assert_eq!(ocr.cpu_ms, 150);
+        assert!(ocr.over_budget);
+        assert_eq!(ocr.skipped, 1);
+
+        config.window_secs = 0;
+        governor.apply(&config);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(governor.admit(Subsystem::Ocr), Admission::Run);
+    }
+
+    #[test]
+    fn test_battery_throttles_and_skips_analysis() {
+        let power = TempDir::new().unwrap();
+        on_battery(&power);
+        let governor = governor(BudgetConfig::default(), &power);
+
+        assert!(!governor.admit(Subsystem::Vision).is_allowed());
+        assert!(!governor.admit(Subsystem::Ocr).is_allowed());
+        assert_eq!(governor.admit(Subsystem::Monitor).interval_factor(), 3.0);
+        assert_eq!(governor.admit(Subsystem::Llm), Admission::Run);
+        assert_eq!(governor.usage().power, PowerSource::Battery);
+
+        let disabled = BudgetConfig {
+            enabled: false,
+            ..BudgetConfig::default()
+        };
+        governor.apply(&disabled);
+        assert_eq!(governor.admit(Subsystem::Vision), Admission::Run);
+
+        // Un facteur démesuré lu depuis le fichier est borné
+        let extreme = BudgetConfig {
+            battery_throttle_factor: f32::INFINITY,
+            ..BudgetConfig::default()
+        };
+        governor.apply(&extreme);
+        assert_eq!(
+            governor.admit(Subsystem::Monitor).interval_factor(),
+            MAX_BATTERY_THROTTLE_FACTOR
+        );
+    }
+
+    #[test]
+    fn test_llm_is_measured_but_never_refused() {
+        let power = TempDir::new().unwrap();
+        let mut config = BudgetConfig::default();
+        config.llm.max_wall_ms = Some(1);
+        let governor = governor(config, &power);
+
+        governor.measure(Subsystem::Llm, || {
+            std::thread::sleep(Duration::from_millis(5))
+        });
+        assert_eq!(governor.admit(Subsystem::Llm), Admission::Run);
+
+        let usage = governor.usage();
+        let llm = usage
+            .subsystems
+            .iter()
+            .find(|u| u.subsystem == Subsystem::Llm)
+            .unwrap();
+        assert_eq!(llm.runs, 1);
+        assert!(llm.wall_ms >= 5);
+        assert!(llm.over_budget);
+    }
+}
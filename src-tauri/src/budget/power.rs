@@ -0,0 +1,181 @@
+//! Source d'alimentation lue dans `/sys/class/power_supply`.
+//!
+//! Chaque entrée expose un fichier `type` ("Battery", "Mains", "USB"...) ; une batterie
+//! indique son état dans `status`, un secteur sa présence dans `online`. Les batteries de
+//! périphériques (souris, clavier sans fil) ont `scope` à "Device" et sont ignorées.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Répertoire sysfs des sources d'alimentation (Linux)
+pub const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Durée pendant laquelle une lecture reste valable
+const POWER_CACHE_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    /// Pas de sysfs (macOS, Windows) ou aucune source déclarée
+    Unknown,
+}
+
+/// Lit les sources d'alimentation avec un cache de quelques secondes
+pub struct PowerProbe {
+    dir: PathBuf,
+    cache: Mutex<Option<(Instant, PowerSource)>>,
+}
+
+impl PowerProbe {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    pub fn source(&self) -> PowerSource {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((read_at, source)) = *cache {
+            if read_at.elapsed() < Duration::from_secs(POWER_CACHE_SECS) {
+                return source;
+            }
+        }
+
+        let source = read_power_source(&self.dir);
+        *cache = Some((Instant::now(), source));
+        source
+    }
+}
+
+impl Default for PowerProbe {
+    fn default() -> Self {
+        Self::new(POWER_SUPPLY_DIR)
+    }
+}
+
+/// Secteur branché → `Ac` ; sinon une batterie qui se décharge → `Battery`
+pub fn read_power_source(dir: &Path) -> PowerSource {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return PowerSource::Unknown;
+    };
+
+    let mut found = false;
+    let mut discharging = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(kind) = read_attribute(&path, "type") else {
+            continue;
+        };
+        // Batterie d'un périphérique : n'alimente pas la machine
+        if read_attribute(&path, "scope").as_deref() == Some("Device") {
+            continue;
+        }
+        found = true;
+
+        match kind.as_str() {
+            "Battery" => {
+                if read_attribute(&path, "status").as_deref() == Some("Discharging") {
+                    discharging = true;
+                }
+            }
+            _ => {
+                if read_attribute(&path, "online").as_deref() == Some("1") {
+                    return PowerSource::Ac;
+                }
+            }
+        }
+    }
+
+    match (found, discharging) {
+        (_, true) => PowerSource::Battery,
+        (true, false) => PowerSource::Ac,
+        (false, false) => PowerSource::Unknown,
+    }
+}
+
+fn read_attribute(supply: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(supply.join(name))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn supply(root: &Path, name: &str, attributes: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (attribute, value) in attributes {
+            fs::write(dir.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_read_power_source() {
+        let root = TempDir::new().unwrap();
+        assert_eq!(read_power_source(root.path()), PowerSource::Unknown);
+        assert_eq!(
+            read_power_source(&root.path().join("missing")),
+            PowerSource::Unknown
+        );
+
+        supply(
+            root.path(),
+            "BAT0",
+            &[("type", "Battery"), ("status", "Discharging")],
+        );
+        supply(root.path(), "AC", &[("type", "Mains"), ("online", "0")]);
+        assert_eq!(read_power_source(root.path()), PowerSource::Battery);
+
+        supply(root.path(), "AC", &[("type", "Mains"), ("online", "1")]);
+        assert_eq!(read_power_source(root.path()), PowerSource::Ac);
+
+        // Batterie pleine sans secteur déclaré : pas sur batterie
+        let desktop = TempDir::new().unwrap();
+        supply(
+            desktop.path(),
+            "BAT1",
+            &[("type", "Battery"), ("status", "Full")],
+        );
+        assert_eq!(read_power_source(desktop.path()), PowerSource::Ac);
+    }
+
+    #[test]
+    fn test_device_batteries_are_ignored() {
+        let root = TempDir::new().unwrap();
+        supply(
+            root.path(),
+            "hidpp_battery_0",
+            &[
+                ("type", "Battery"),
+                ("scope", "Device"),
+                ("status", "Discharging"),
+            ],
+        );
+        assert_eq!(read_power_source(root.path()), PowerSource::Unknown);
+
+        supply(root.path(), "AC", &[("type", "Mains"), ("online", "1")]);
+        assert_eq!(read_power_source(root.path()), PowerSource::Ac);
+
+        // Portable sur batterie avec une souris sans fil
+        supply(root.path(), "AC", &[("type", "Mains"), ("online", "0")]);
+        supply(
+            root.path(),
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("scope", "System"),
+                ("status", "Discharging"),
+            ],
+        );
+        assert_eq!(read_power_source(root.path()), PowerSource::Battery);
+    }
+}
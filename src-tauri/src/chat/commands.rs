@@ -1,66 +1,340 @@
-use crate::chat::llm_client::{ChatMessage, ChatRequest, LLMChatClient};
+use crate::chat::context::{self as chat_context, ChatContext, CONTEXT_TOKEN_BUDGET};
+use crate::chat::history;
+use crate::chat::stream::{ChatDelta, ChatStreamRegistry, CHAT_DELTA_EVENT};
+use crate::context::ContextAggregator;
+use crate::learning::LearningSystem;
+use crate::llm::{ChatMessage, LlmClient, LlmError, LlmRequest, LlmStats, ProviderHealth};
+use crate::monitor::ScreenMonitor;
+use crate::persistence::{ConversationSummary, MessageRole, PersistenceManager};
+use crate::privacy::{EgressError, EgressPolicy, PrivacyZoneManager, RequestOrigin};
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::sync::Arc;
 use std::time::Instant;
-use tracing::info;
+use tauri::Emitter;
+use tracing::{info, warn};
+use uuid::Uuid;
 
-/// Send a chat message to the LLM
+/// Result of a chat turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatReply {
+    pub conversation_id: String,
+    /// Id of the persisted assistant message (None if nothing was generated)
+    pub message_id: Option<String>,
+    pub content: String,
+    pub provider: String,
+    pub cancelled: bool,
+    /// Context sent with this turn, as filtered and truncated (None without `include_context`)
+    pub context: Option<ChatContext>,
+}
+
+/// Prefix of the error returned when the egress policy wants the user to confirm
+/// before this turn reaches a remote provider; resend with `confirm_egress: true`
+pub const EGRESS_CONFIRMATION_REQUIRED: &str = "EGRESS_CONFIRMATION_REQUIRED";
+
+/// Send a chat message to the LLM within a conversation.
+/// Without `conversation_id` a new conversation is created. Prior turns are loaded from
+/// persistence, and both the user and assistant messages are saved.
+/// Tokens are streamed as `chat:delta` events tagged with `request_id`; the full text is returned at the end.
+/// With `include_context`, the captured context (privacy-filtered) is sent as a system message and returned.
+/// When the egress policy requires confirmation, nothing is saved and the call fails with
+/// `EGRESS_CONFIRMATION_REQUIRED`; `confirm_egress` carries the user's consent.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn chat_with_ai(
     message: String,
     include_context: bool,
-    llm_client: tauri::State<'_, Arc<tokio::sync::Mutex<LLMChatClient>>>,
-) -> Result<String, String> {
+    conversation_id: Option<String>,
+    request_id: Option<String>,
+    confirm_egress: Option<bool>,
+    app: tauri::AppHandle,
+    llm_client: tauri::State<'_, Arc<LlmClient>>,
+    egress: tauri::State<'_, Arc<EgressPolicy>>,
+    streams: tauri::State<'_, Arc<ChatStreamRegistry>>,
+    persistence: tauri::State<'_, Arc<tokio::sync::Mutex<PersistenceManager>>>,
+    context_aggregator: tauri::State<'_, Arc<tokio::sync::Mutex<ContextAggregator>>>,
+    learning: tauri::State<'_, Arc<tokio::sync::Mutex<LearningSystem>>>,
+    monitor: tauri::State<'_, Arc<tokio::sync::Mutex<ScreenMonitor>>>,
+    privacy: tauri::State<'_, Arc<tokio::sync::Mutex<PrivacyZoneManager>>>,
+) -> Result<ChatReply, String> {
     let start = Instant::now();
-    let _timestamp = Utc::now().to_rfc3339();
-
-    info!("💬 Chat request: {}", message);
-    tracing::info!("Building ChatRequest...");
-
-    // Build request
-    let request = ChatRequest {
-        messages: vec![ChatMessage {
-            role: "user".to_string(),
-            content: message,
-        }],
-        include_context,
-        temperature: 0.7,
+    let request_id = request_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    info!(
+        "💬 Chat request {} (include_context={}): {}",
+        request_id, include_context, message
+    );
+
+    let context = if include_context {
+        let sources = chat_context::gather(&context_aggregator, &learning, &monitor).await;
+        let context = chat_context::assemble(
+            sources,
+            &*privacy.lock().await,
+            Utc::now().timestamp() as u64,
+            CONTEXT_TOKEN_BUDGET,
+        );
+        info!(
+            "🧭 Chat context: {} chars, withheld={:?}, truncated={}",
+            context.system_message.len(),
+            context.withheld,
+            context.truncated
+        );
+        Some(context)
+    } else {
+        None
+    };
+
+    // Content of the active app is subject to the egress policy
+    let origin = context.as_ref().and_then(|c| {
+        let window_title = c.window_title.clone().unwrap_or_default();
+        c.app_name
+            .as_ref()
+            .map(|app_name| RequestOrigin::new(app_name, window_title))
+    });
+    let confirmed = confirm_egress.unwrap_or(false);
+    if let (true, Err(EgressError::ConfirmationRequired { reason })) = (
+        llm_client.has_remote_providers(),
+        egress.check_remote(origin.as_ref(), confirmed),
+    ) {
+        info!(
+            "🔒 Chat request {} awaits egress confirmation ({})",
+            request_id, reason
+        );
+        return Err(format!("{}: {}", EGRESS_CONFIRMATION_REQUIRED, reason));
+    }
+
+    // The user message is persisted before calling the model, so it survives LLM failures
+    let conversation_id = {
+        let manager = persistence.lock().await;
+        let conversation_id = match conversation_id {
+            Some(id) => {
+                manager
+                    .get_conversation(&id)
+                    .await?
+                    .ok_or_else(|| format!("Conversation not found: {}", id))?
+                    .id
+            }
+            None => {
+                manager
+                    .create_conversation(conversation_title(&message), None)
+                    .await?
+                    .id
+            }
+        };
+        manager
+            .save_message(&conversation_id, MessageRole::User, message, None)
+            .await?;
+        conversation_id
     };
 
-    // Call LLM
-    tracing::info!("Calling LLM client...");
-    let response = {
-        let client = llm_client.lock().await;
-        let result = client.chat(request).await;
-        tracing::info!("LLM response received");
-        result
+    let cancel = streams.register(&request_id).await;
+    let emit_delta = |delta: &str| {
+        let _ = app.emit(
+            CHAT_DELTA_EVENT,
+            ChatDelta {
+                request_id: request_id.clone(),
+                delta: delta.to_string(),
+                done: false,
+                cancelled: false,
+                error: None,
+            },
+        );
     };
 
-    let ttfr_ms = start.elapsed().as_millis() as u64;
-    let (ok, content, provider, used_fallback) = match response {
-        Ok(resp) => (true, resp.content, resp.provider, resp.used_fallback),
-        Err(e) => (false, e.clone(), "unknown".to_string(), false),
+    // Call LLM with the conversation window
+    let response =
+        match load_history(&persistence, &llm_client, &conversation_id, &origin, confirmed).await {
+            Ok(mut messages) => {
+                if let Some(context) = context.as_ref().filter(|c| !c.is_empty()) {
+                    messages.insert(0, context.to_message());
+                }
+                let request = LlmRequest::new(messages)
+                    .temperature(0.7)
+                    .origin(origin.clone())
+                    .egress_confirmed(confirmed);
+                llm_client.stream(request, cancel, emit_delta).await
+            }
+            Err(e) => Err(LlmError::Failed(e)),
+        };
+    streams.finish(&request_id).await;
+
+    let total_ms = start.elapsed().as_millis() as u64;
+    let final_event = ChatDelta {
+        request_id: request_id.clone(),
+        delta: String::new(),
+        done: true,
+        cancelled: response.as_ref().map(|r| r.cancelled).unwrap_or(false),
+        error: response.as_ref().err().map(|e| e.to_string()),
     };
+    let _ = app.emit(CHAT_DELTA_EVENT, final_event);
 
-    // Log to console (jsonl disabled to avoid rebuild loops)
-    info!("📊 Chat metrics: provider={}, used_fallback={}, ttfr_ms={}, ok={}", provider, used_fallback, ttfr_ms, ok);
-    
-    // Note: JSONL logging disabled because it triggers Vite HMR rebuild
-    // To re-enable, use a file path outside the project root or in tmp/
+    match response {
+        Ok(resp) => {
+            info!(
+                "📊 Chat metrics: provider={}, used_fallback={}, ttfr_ms={}, total_ms={}, cancelled={}",
+                resp.provider, resp.used_fallback, resp.ttfr_ms, total_ms, resp.cancelled
+            );
+
+            // Partial answers of cancelled generations are kept too
+            let message_id = if resp.content.is_empty() {
+                None
+            } else {
+                let metadata = serde_json::json!({
+                    "request_id": request_id,
+                    "provider": resp.provider,
+                    "model": resp.model,
+                    "used_fallback": resp.used_fallback,
+                    "ttfr_ms": resp.ttfr_ms,
+                    "cancelled": resp.cancelled,
+                });
+                let saved = persistence
+                    .lock()
+                    .await
+                    .save_message(
+                        &conversation_id,
+                        MessageRole::Assistant,
+                        resp.content.clone(),
+                        Some(metadata.to_string()),
+                    )
+                    .await?;
+                Some(saved.id)
+            };
+
+            Ok(ChatReply {
+                conversation_id,
+                message_id,
+                content: resp.content,
+                provider: resp.provider,
+                cancelled: resp.cancelled,
+                context,
+            })
+        }
+        Err(LlmError::Egress(e)) => {
+            info!("📊 Chat metrics: total_ms={}, ok=false (egress)", total_ms);
+            Err(egress_refusal_message(&e))
+        }
+        Err(e) => {
+            info!("📊 Chat metrics: total_ms={}, ok=false", total_ms);
+            // Return user-friendly error
+            Err(format!(
+                "Cloud indisponible. Essayez Ollama (modèle local). Erreur: {}",
+                e
+            ))
+        }
+    }
+}
 
-    if ok {
-        Ok(content)
+/// User-facing reason why the egress policy refused the request
+fn egress_refusal_message(error: &EgressError) -> String {
+    match error {
+        EgressError::Blocked { reason } => format!(
+            "Envoi vers un modèle distant refusé par la politique de confidentialité ({}), \
+             qui refuse au lieu de basculer en local.",
+            reason
+        ),
+        // Same marker as the check above: the UI asks for consent and retries
+        EgressError::ConfirmationRequired { reason } => {
+            format!("{}: {}", EGRESS_CONFIRMATION_REQUIRED, reason)
+        }
+    }
+}
+
+/// Load the conversation within the token budget; turns that no longer fit are folded
+/// into the stored running summary (kept as-is if summarisation fails).
+/// The summary request shares the turn's origin and egress consent.
+async fn load_history(
+    persistence: &tokio::sync::Mutex<PersistenceManager>,
+    client: &LlmClient,
+    conversation_id: &str,
+    origin: &Option<RequestOrigin>,
+    confirmed: bool,
+) -> Result<Vec<ChatMessage>, String> {
+    let (messages, summary) = {
+        let manager = persistence.lock().await;
+        (
+            manager.get_conversation_messages(conversation_id).await?,
+            manager.get_conversation_summary(conversation_id).await?,
+        )
+    };
+
+    let pending = history::uncovered(&messages, summary.as_ref());
+    let summary_tokens = summary
+        .as_ref()
+        .map(|s| history::estimate_tokens(&s.summary))
+        .unwrap_or(0);
+    let split = history::split_window(
+        pending,
+        history::HISTORY_TOKEN_BUDGET.saturating_sub(summary_tokens),
+    );
+    let mut summary_text = summary.as_ref().map(|s| s.summary.clone());
+
+    if split > 0 {
+        let older = &pending[..split];
+        let request = LlmRequest::new(history::summary_prompt(summary_text.as_deref(), older))
+            .temperature(0.2)
+            .origin(origin.clone())
+            .egress_confirmed(confirmed);
+
+        match client.complete(request).await {
+            Ok(resp) if !resp.content.trim().is_empty() => {
+                let updated = ConversationSummary {
+                    conversation_id: conversation_id.to_string(),
+                    summary: resp.content.trim().to_string(),
+                    covers_until_message_id: older[older.len() - 1].id.clone(),
+                    covered_messages: summary.as_ref().map(|s| s.covered_messages).unwrap_or(0)
+                        + older.len() as i64,
+                    updated_at: Utc::now(),
+                };
+                persistence
+                    .lock()
+                    .await
+                    .save_conversation_summary(&updated)
+                    .await?;
+                info!(
+                    "🧾 Summarised {} older messages of conversation {}",
+                    older.len(),
+                    conversation_id
+                );
+                summary_text = Some(updated.summary);
+            }
+            Ok(_) => warn!("⚠️ Empty summary, older turns left out of this request"),
+            Err(e) => warn!("⚠️ Failed to summarise older turns: {}", e),
+        }
+    }
+
+    Ok(history::build_messages(
+        summary_text.as_deref(),
+        &pending[split..],
+    ))
+}
+
+/// Conversation title derived from its first message
+fn conversation_title(message: &str) -> String {
+    const MAX_TITLE_CHARS: usize = 60;
+
+    let first_line = message.lines().next().unwrap_or("").trim();
+    if first_line.chars().count() > MAX_TITLE_CHARS {
+        let truncated: String = first_line.chars().take(MAX_TITLE_CHARS).collect();
+        format!("{}…", truncated.trim_end())
+    } else if first_line.is_empty() {
+        "Nouvelle conversation".to_string()
     } else {
-        // Return user-friendly error
-        Err(format!(
-            "Cloud indisponible. Essayez Ollama (modèle local). Erreur: {}",
-            content
-        ))
+        first_line.to_string()
     }
 }
 
+/// Abort an in-flight generation started by `chat_with_ai`
+#[tauri::command]
+pub async fn cancel_chat(
+    request_id: String,
+    streams: tauri::State<'_, Arc<ChatStreamRegistry>>,
+) -> Result<bool, String> {
+    info!("🛑 cancel_chat {}", request_id);
+    Ok(streams.cancel(&request_id).await)
+}
+
 /// Log an event to events.jsonl
 fn log_to_jsonl(entry: &serde_json::Value) -> Result<(), Box<dyn std::error::Error>> {
     let log_path = "src-tauri/events.jsonl";
@@ -78,19 +352,16 @@ fn log_to_jsonl(entry: &serde_json::Value) -> Result<(), Box<dyn std::error::Err
 /// Get LLM stats
 #[tauri::command]
 pub async fn get_llm_stats(
-    llm_client: tauri::State<'_, Arc<tokio::sync::Mutex<LLMChatClient>>>,
-) -> std::result::Result<crate::chat::llm_client::LLMClientStats, String> {
-    let client = llm_client.lock().await;
-    let stats = client.get_stats().await;
-    Ok(stats)
+    llm_client: tauri::State<'_, Arc<LlmClient>>,
+) -> std::result::Result<LlmStats, String> {
+    Ok(llm_client.get_stats().await)
 }
 
-/// Check LLM health
+/// Check LLM health of every provider of the chain (primary first)
 #[tauri::command]
 pub async fn check_llm_health(
-    llm_client: tauri::State<'_, Arc<tokio::sync::Mutex<LLMChatClient>>>,
-) -> Result<(bool, bool), String> {
-    let client = llm_client.lock().await;
-    Ok(client.check_health().await)
+    llm_client: tauri::State<'_, Arc<LlmClient>>,
+) -> Result<Vec<ProviderHealth>, String> {
+    Ok(llm_client.check_health().await)
 }
 
@@ -0,0 +1,400 @@
+use crate::context::{Context, ContextAggregator};
+use crate::learning::{LearningSystem, ProcessedContextWithIntent};
+use crate::llm::ChatMessage;
+use crate::monitor::{ScreenFinding, ScreenMonitor};
+use crate::privacy::PrivacyZoneManager;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Token budget for the context system message
+pub const CONTEXT_TOKEN_BUDGET: usize = 600;
+
+/// Intent/cluster older than this (or from another app) is not sent
+const INSIGHT_MAX_AGE_SECS: u64 = 600;
+
+/// OCR findings older than this are not sent
+const FINDING_MAX_AGE_SECS: u64 = 300;
+
+/// Clipboard content copied longer ago than this is not sent
+const CLIPBOARD_MAX_AGE: Duration = Duration::from_secs(300);
+
+const MAX_WINDOW_TITLE_CHARS: usize = 200;
+
+/// Below this, a truncated clipboard is not worth sending
+const MIN_CLIPBOARD_CHARS: usize = 40;
+
+/// Raw inputs of the context-assembly stage
+#[derive(Debug, Clone, Default)]
+pub struct ContextSources {
+    pub context: Option<Context>,
+    pub insight: Option<ProcessedContextWithIntent>,
+    pub findings: Vec<ScreenFinding>,
+    pub clipboard: Option<String>,
+}
+
+/// Context actually sent with a chat turn, after privacy filters and the size budget
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ChatContext {
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    pub intent: Option<String>,
+    pub intent_confidence: Option<f32>,
+    pub cluster_id: Option<String>,
+    /// Recent OCR findings, most recent first
+    pub screen_findings: Vec<String>,
+    pub clipboard: Option<String>,
+    /// Sources left out by the privacy filters
+    pub withheld: Vec<String>,
+    /// True if findings or clipboard were cut to fit the budget
+    pub truncated: bool,
+    /// Exact system message sent to the model
+    pub system_message: String,
+}
+
+impl ChatContext {
+    pub fn is_empty(&self) -> bool {
+        self.system_message.is_empty()
+    }
+
+    pub fn to_message(&self) -> ChatMessage {
+        ChatMessage::system(self.system_message.clone())
+    }
+}
+
+/// Collect the latest context, intent/cluster, OCR findings and clipboard
+pub async fn gather(
+    aggregator: &Mutex<ContextAggregator>,
+    learning: &Mutex<LearningSystem>,
+    monitor: &Mutex<ScreenMonitor>,
+) -> ContextSources {
+    let (context, clipboard) = {
+        let mut aggregator = aggregator.lock().await;
+        match aggregator.capture().await {
+            Ok(mut context) => {
+                let clipboard = context
+                    .clipboard
+                    .take()
+                    .or_else(|| aggregator.recent_clipboard(CLIPBOARD_MAX_AGE));
+                (Some(context), clipboard)
+            }
+            Err(e) => {
+                warn!("⚠️ Failed to capture context for chat: {}", e);
+                (None, None)
+            }
+        }
+    };
+
+    let insight = learning.lock().await.last_processed().cloned();
+    let findings = monitor.lock().await.recent_findings().await;
+
+    ContextSources {
+        context,
+        insight,
+        findings,
+        clipboard,
+    }
+}
+
+/// Apply the privacy filters and render the sources into a system message within `budget` tokens
+pub fn assemble(
+    sources: ContextSources,
+    privacy: &PrivacyZoneManager,
+    now: u64,
+    budget: usize,
+) -> ChatContext {
+    let mut ctx = ChatContext::default();
+
+    let Some(context) = sources.context else {
+        return ctx;
+    };
+
+    // Nothing captured from a protected window leaves the machine, clipboard included
+    if privacy.is_window_protected(&context.app.name, &context.app.window_title) {
+        ctx.withheld = vec![
+            format!("application protégée : {}", context.app.name),
+            "presse-papiers".to_string(),
+            "écran".to_string(),
+        ];
+        debug!(
+            "🔒 Chat context withheld for protected app {}",
+            context.app.name
+        );
+        return ctx;
+    }
+
+    let app_name = context.app.name.clone();
+    ctx.app_name = Some(app_name.clone());
+    if !context.app.window_title.trim().is_empty() {
+        ctx.window_title = Some(truncate_chars(
+            context.app.window_title.trim(),
+            MAX_WINDOW_TITLE_CHARS,
+        ));
+    }
+
+    if let Some(insight) = sources.insight {
+        let processed = &insight.processed_context;
+        let fresh = now.saturating_sub(processed.context.timestamp) <= INSIGHT_MAX_AGE_SECS;
+        if fresh && processed.context.app.name == app_name {
+            ctx.intent = Some(insight.intent.intent_type.as_str().to_string());
+            ctx.intent_confidence = Some(insight.intent.confidence);
+            ctx.cluster_id = Some(processed.cluster_id.clone());
+        }
+    }
+
+    let mut withheld_findings = 0;
+    let findings: Vec<String> = sources
+        .findings
+        .into_iter()
+        .rev()
+        .filter(|f| now.saturating_sub(f.timestamp) <= FINDING_MAX_AGE_SECS)
+        .filter(|f| {
+            let protected = f
+                .app_name
+                .as_deref()
+                .is_some_and(|app| privacy.is_app_protected(app));
+            if protected {
+                withheld_findings += 1;
+            }
+            !protected
+        })
+        .map(|f| f.summary.trim().to_string())
+        .filter(|summary| !summary.is_empty())
+        .collect();
+    if withheld_findings > 0 {
+        ctx.withheld.push(format!(
+            "{} observation(s) d'écran d'applications protégées",
+            withheld_findings
+        ));
+    }
+
+    // Fixed header first, then findings (most recent first), then the clipboard in what remains
+    let max_chars = budget.saturating_sub(4) * 4;
+    for finding in findings {
+        ctx.screen_findings.push(finding);
+        if render(&ctx).chars().count() > max_chars {
+            ctx.screen_findings.pop();
+            ctx.truncated = true;
+            break;
+        }
+    }
+
+    if let Some(clipboard) = sources.clipboard.filter(|c| !c.trim().is_empty()) {
+        ctx.clipboard = Some(String::new());
+        let remaining_chars = max_chars.saturating_sub(render(&ctx).chars().count());
+        if remaining_chars < MIN_CLIPBOARD_CHARS {
+            ctx.clipboard = None;
+            ctx.truncated = true;
+        } else if clipboard.chars().count() > remaining_chars {
+            ctx.clipboard = Some(format!(
+                "{}…",
+                truncate_chars(&clipboard, remaining_chars - 1)
+            ));
+            ctx.truncated = true;
+        } else {
+            ctx.clipboard = Some(clipboard);
+        }
+    }
+
+    ctx.system_message = render(&ctx);
+    ctx
+}
+
+fn render(ctx: &ChatContext) -> String {
+    let Some(app_name) = &ctx.app_name else {
+        return String::new();
+    };
+
+    let mut text = String::from(
+        "Contexte de l'utilisateur, capturé automatiquement sur son poste. \
+         Utilise-le seulement s'il aide à répondre.\n",
+    );
+    text.push_str(&format!("Application active : {}\n", app_name));
+    if let Some(title) = &ctx.window_title {
+        text.push_str(&format!("Fenêtre : {}\n", title));
+    }
+    if let Some(intent) = &ctx.intent {
+        text.push_str(&format!(
+            "Intention détectée : {} (confiance {:.2})\n",
+            intent,
+            ctx.intent_confidence.unwrap_or(0.0)
+        ));
+    }
+    if let Some(cluster_id) = &ctx.cluster_id {
+        text.push_str(&format!("Cluster d'activité : {}\n", cluster_id));
+    }
+    if !ctx.screen_findings.is_empty() {
+        text.push_str("Observé à l'écran récemment :\n");
+        for finding in &ctx.screen_findings {
+            text.push_str(&format!("- {}\n", finding));
+        }
+    }
+    if let Some(clipboard) = &ctx.clipboard {
+        text.push_str("Presse-papiers :\n");
+        text.push_str(clipboard);
+        text.push('\n');
+    }
+
+    text
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::history::estimate_tokens;
+    use crate::clustering::fingerprint::ContextFingerprint;
+    use crate::clustering::ProcessedContext;
+    use crate::context::app_detector::ActiveApp;
+    use crate::intent::{Intent, IntentType};
+    use crate::privacy::{PrivacyZone, PrivacyZonesConfig};
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn context(app: &str) -> Context {
+        Context {
+            id: "ctx".to_string(),
+            app: ActiveApp {
+                bundle_id: app.to_lowercase(),
+                name: app.to_string(),
+                window_title: "main.rs — shadowlearn".to_string(),
+                pid: 42,
+                timestamp: NOW,
+                tcc_status: None,
+                bounds: None,
+            },
+            clipboard: None,
+            idle_seconds: 3.0,
+            timestamp: NOW - 10,
+            capture_duration_ms: 1,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn finding(app: &str, summary: &str, age: u64) -> ScreenFinding {
+        ScreenFinding {
+            timestamp: NOW - age,
+            app_name: Some(app.to_string()),
+            summary: summary.to_string(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn privacy(protected: &str) -> PrivacyZoneManager {
+        let mut config = PrivacyZonesConfig::default();
+        config.zones.push(PrivacyZone::Window {
+            app_name: protected.to_string(),
+            fuzzy_match: true,
+        });
+        PrivacyZoneManager::new(config)
+    }
+
+    fn insight(app: &str) -> ProcessedContextWithIntent {
+        let ctx = context(app);
+        ProcessedContextWithIntent {
+            processed_context: ProcessedContext {
+                fingerprint: ContextFingerprint {
+                    simhash: 0,
+                    domain: app.to_string(),
+                    features: vec![],
+                    generated_at_ms: 0,
+                },
+                context: ctx,
+                cluster_id: "cluster_1".to_string(),
+                processing_time_ms: 1,
+            },
+            intent: Intent {
+                intent_type: IntentType::Debugging,
+                confidence: 0.8,
+                reason: "error in terminal".to_string(),
+                detected_at_ms: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_assemble_renders_all_sources() {
+        let sources = ContextSources {
+            context: Some(context("Code")),
+            insight: Some(insight("Code")),
+            findings: vec![
+                finding("Code", "Terminal with errors", 30),
+                finding("Code", "Code editor (rust)", 5),
+            ],
+            clipboard: Some("fn main() {}".to_string()),
+        };
+
+        let ctx = assemble(sources, &privacy("Bank"), NOW, CONTEXT_TOKEN_BUDGET);
+
+        assert_eq!(ctx.app_name.as_deref(), Some("Code"));
+        assert_eq!(ctx.intent.as_deref(), Some("debugging"));
+        assert_eq!(ctx.cluster_id.as_deref(), Some("cluster_1"));
+        assert_eq!(
+            ctx.screen_findings,
+            vec!["Code editor (rust)", "Terminal with errors"]
+        );
+        assert_eq!(ctx.clipboard.as_deref(), Some("fn main() {}"));
+        assert!(!ctx.truncated);
+        assert!(ctx.system_message.contains("Application active : Code"));
+        assert!(ctx.system_message.contains("fn main() {}"));
+        assert_eq!(ctx.to_message().role, "system");
+    }
+
+    #[test]
+    fn test_protected_app_withholds_everything() {
+        let sources = ContextSources {
+            context: Some(context("Bank Online")),
+            insight: Some(insight("Bank Online")),
+            findings: vec![finding("Bank Online", "IBAN FR76…", 5)],
+            clipboard: Some("secret".to_string()),
+        };
+
+        let ctx = assemble(sources, &privacy("Bank"), NOW, CONTEXT_TOKEN_BUDGET);
+
+        assert!(ctx.is_empty());
+        assert!(ctx.clipboard.is_none());
+        assert!(ctx.screen_findings.is_empty());
+        assert!(!ctx.withheld.is_empty());
+    }
+
+    #[test]
+    fn test_filters_protected_stale_and_foreign_sources() {
+        let sources = ContextSources {
+            context: Some(context("Code")),
+            insight: Some(insight("Firefox")),
+            findings: vec![
+                finding("Bank Online", "IBAN FR76…", 5),
+                finding("Code", "old finding", FINDING_MAX_AGE_SECS + 1),
+                finding("Code", "Code editor (rust)", 5),
+            ],
+            clipboard: None,
+        };
+
+        let ctx = assemble(sources, &privacy("Bank"), NOW, CONTEXT_TOKEN_BUDGET);
+
+        assert!(ctx.intent.is_none());
+        assert!(ctx.cluster_id.is_none());
+        assert_eq!(ctx.screen_findings, vec!["Code editor (rust)"]);
+        assert_eq!(ctx.withheld.len(), 1);
+        assert!(!ctx.system_message.contains("IBAN"));
+    }
+
+    #[test]
+    fn test_budget_truncates_clipboard() {
+        let sources = ContextSources {
+            context: Some(context("Code")),
+            clipboard: Some("x".repeat(10_000)),
+            ..Default::default()
+        };
+
+        let ctx = assemble(sources, &privacy("Bank"), NOW, 200);
+
+        assert!(ctx.truncated);
+        assert!(ctx.clipboard.as_ref().unwrap().ends_with('…'));
+        assert!(estimate_tokens(&ctx.system_message) <= 200);
+    }
+}
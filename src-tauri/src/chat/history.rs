@@ -0,0 +1,160 @@
+use crate::llm::ChatMessage;
+use crate::persistence::{ConversationSummary, Message, MessageRole};
+
+/// Token budget for the history sent with each turn (summary + recent messages)
+pub const HISTORY_TOKEN_BUDGET: usize = 3000;
+
+/// Rough token estimate (~4 characters per token, plus per-message overhead)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4) + 4
+}
+
+/// Index splitting `messages` into older turns (`..split`, to summarise) and the
+/// recent window (`split..`) that fits in `budget` tokens. The most recent message is always kept.
+pub fn split_window(messages: &[Message], budget: usize) -> usize {
+    let mut used = 0;
+    let mut split = messages.len();
+
+    for (index, message) in messages.iter().enumerate().rev() {
+        let cost = estimate_tokens(&message.content);
+        if used + cost > budget && split < messages.len() {
+            break;
+        }
+        used += cost;
+        split = index;
+    }
+
+    split
+}
+
+/// Messages not yet covered by the stored summary
+pub fn uncovered<'a>(messages: &'a [Message], summary: Option<&ConversationSummary>) -> &'a [Message] {
+    let Some(summary) = summary else {
+        return messages;
+    };
+    match messages
+        .iter()
+        .position(|m| m.id == summary.covers_until_message_id)
+    {
+        Some(index) => &messages[index + 1..],
+        None => messages,
+    }
+}
+
+/// Prompt asking the model to fold older turns into the running summary
+pub fn summary_prompt(previous: Option<&str>, older: &[Message]) -> Vec<ChatMessage> {
+    let mut transcript = String::new();
+    if let Some(previous) = previous {
+        transcript.push_str("Résumé existant :\n");
+        transcript.push_str(previous);
+        transcript.push_str("\n\nNouveaux échanges :\n");
+    }
+    for message in older {
+        transcript.push_str(role_label(&message.role));
+        transcript.push_str(": ");
+        transcript.push_str(&message.content);
+        transcript.push('\n');
+    }
+
+    vec![
+        ChatMessage {
+            role: "system".to_string(),
+            content: "Tu résumes une conversation pour qu'un assistant puisse la poursuivre. \
+                      Conserve les faits, décisions, noms de fichiers, erreurs et questions en suspens. \
+                      Réponds uniquement par le résumé, en 10 lignes maximum."
+                .to_string(),
+        },
+        ChatMessage {
+            role: "user".to_string(),
+            content: transcript,
+        },
+    ]
+}
+
+/// Messages sent to the model: running summary (if any) then the recent window
+pub fn build_messages(summary: Option<&str>, recent: &[Message]) -> Vec<ChatMessage> {
+    let mut messages = Vec::with_capacity(recent.len() + 1);
+
+    if let Some(summary) = summary {
+        messages.push(ChatMessage {
+            role: "system".to_string(),
+            content: format!("Résumé des échanges précédents de cette conversation :\n{}", summary),
+        });
+    }
+
+    messages.extend(recent.iter().map(|message| ChatMessage {
+        role: role_label(&message.role).to_string(),
+        content: message.content.clone(),
+    }));
+
+    messages
+}
+
+fn role_label(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::User => "user",
+        MessageRole::Assistant => "assistant",
+        MessageRole::System => "system",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn message(id: &str, role: MessageRole, content: &str) -> Message {
+        Message {
+            id: id.to_string(),
+            conversation_id: "c1".to_string(),
+            role,
+            content: content.to_string(),
+            created_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_split_window_keeps_recent_turns() {
+        let long = "x".repeat(400); // ~104 tokens each
+        let messages: Vec<Message> = (0..6)
+            .map(|i| message(&format!("m{}", i), MessageRole::User, &long))
+            .collect();
+
+        assert_eq!(split_window(&messages, 10_000), 0);
+        assert_eq!(split_window(&messages, 250), 4);
+
+        // The latest message survives even when it alone exceeds the budget
+        assert_eq!(split_window(&messages, 10), 5);
+    }
+
+    #[test]
+    fn test_uncovered_skips_summarised_messages() {
+        let messages = vec![
+            message("m1", MessageRole::User, "a"),
+            message("m2", MessageRole::Assistant, "b"),
+            message("m3", MessageRole::User, "c"),
+        ];
+        let summary = ConversationSummary {
+            conversation_id: "c1".to_string(),
+            summary: "a, b".to_string(),
+            covers_until_message_id: "m2".to_string(),
+            covered_messages: 2,
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(uncovered(&messages, Some(&summary)).len(), 1);
+        assert_eq!(uncovered(&messages, None).len(), 3);
+    }
+
+    #[test]
+    fn test_build_messages_prepends_summary() {
+        let recent = vec![message("m3", MessageRole::User, "and now?")];
+        let built = build_messages(Some("we fixed E0502"), &recent);
+
+        assert_eq!(built.len(), 2);
+        assert_eq!(built[0].role, "system");
+        assert!(built[0].content.contains("E0502"));
+        assert_eq!(built[1].role, "user");
+    }
+}
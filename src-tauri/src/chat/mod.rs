@@ -1,6 +1,4 @@
 pub mod commands;
-pub mod llm_client;
-
-// Re-exports (available for external use if needed)
-pub use llm_client::{LLMChatClient, ChatRequest, ChatMessage};
-
+pub mod context;
+pub mod history;
+pub mod stream;
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::{watch, Mutex};
+
+/// Event emitted for every streamed chunk (and once more with `done: true`)
+pub const CHAT_DELTA_EVENT: &str = "chat:delta";
+
+/// Payload of a `chat:delta` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatDelta {
+    pub request_id: String,
+    pub delta: String,
+    pub done: bool,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+/// In-flight generations, so `cancel_chat` can abort them by request id
+#[derive(Default)]
+pub struct ChatStreamRegistry {
+    active: Mutex<HashMap<String, watch::Sender<bool>>>,
+}
+
+impl ChatStreamRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a generation and get the receiver it should watch
+    pub async fn register(&self, request_id: &str) -> watch::Receiver<bool> {
+        let (sender, receiver) = watch::channel(false);
+        self.active
+            .lock()
+            .await
+            .insert(request_id.to_string(), sender);
+        receiver
+    }
+
+    /// Ask a generation to stop. Returns false if the id is unknown (already finished)
+    pub async fn cancel(&self, request_id: &str) -> bool {
+        match self.active.lock().await.get(request_id) {
+            Some(sender) => sender.send(true).is_ok(),
+            None => false,
+        }
+    }
+
+    pub async fn finish(&self, request_id: &str) {
+        self.active.lock().await.remove(request_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::wait_cancelled;
+
+    #[tokio::test]
+    async fn test_registry_cancels_by_id() {
+        let registry = ChatStreamRegistry::new();
+        let mut receiver = registry.register("req-1").await;
+
+        assert!(!registry.cancel("unknown").await);
+        assert!(registry.cancel("req-1").await);
+        wait_cancelled(&mut receiver).await;
+
+        registry.finish("req-1").await;
+        assert!(!registry.cancel("req-1").await);
+    }
+}
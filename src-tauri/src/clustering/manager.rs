@@ -7,6 +7,7 @@ use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::clustering::fingerprint::{ContextFingerprint, FingerprintGenerator};
+use crate::privacy::erasure::AppMatcher;
 
 /// Cluster manager with LRU cache and similarity-based clustering
 #[derive(Debug)]
@@ -200,6 +201,28 @@ impl ClusterManager {
         }
     }
 
+    /// Remove the clusters created from a forgotten app
+    pub fn forget_app(&mut self, matcher: &AppMatcher) -> usize {
+        let forgotten: Vec<String> = self
+            .clusters
+            .iter()
+            .filter(|(_, cluster)| matcher.matches_app(&cluster.domain))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for cluster_id in &forgotten {
+            self.clusters.pop(cluster_id);
+        }
+
+        if !forgotten.is_empty() {
+            info!(
+                "[CLUSTER] Removed {} clusters (forgotten app)",
+                forgotten.len()
+            );
+        }
+        forgotten.len()
+    }
+
     /// Get cluster manager statistics
     pub fn get_stats(&self) -> &ClusterManagerStats {
         &self.stats
@@ -85,6 +85,11 @@ impl ClusteringSystem {
         );
     }
 
+    /// Remove the clusters created from a forgotten app
+    pub fn forget_app(&mut self, matcher: &crate::privacy::erasure::AppMatcher) -> usize {
+        self.cluster_manager.forget_app(matcher)
+    }
+
     /// Get all clusters (for debugging/monitoring)
     pub fn get_all_clusters(&self) -> Vec<&Cluster> {
         self.cluster_manager.get_all_clusters()
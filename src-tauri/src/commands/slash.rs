@@ -3,6 +3,7 @@
  * Handles execution of slash commands from chat input
  */
 
+use crate::llm::{LlmClient, LlmRequest};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use std::sync::Arc;
@@ -19,7 +20,7 @@ pub struct SlashCommandResult {
 pub async fn execute_slash_command(
     command: String,
     context: String,
-    llm_client: State<'_, Arc<tokio::sync::Mutex<crate::chat::LLMChatClient>>>,
+    llm_client: State<'_, Arc<LlmClient>>,
 ) -> Result<SlashCommandResult, String> {
     info!("Executing slash command: {} with context: {}", command, context);
 
@@ -33,18 +34,8 @@ pub async fn execute_slash_command(
         }
         "explain" => {
             let prompt = format!("Explique clairement et simplement : {}", context);
-            let request = crate::chat::ChatRequest {
-                messages: vec![crate::chat::ChatMessage {
-                    role: "user".to_string(),
-                    content: prompt,
-                }],
-                include_context: false,
-                temperature: 0.7,
-            };
             let response = llm_client
-                .lock()
-                .await
-                .chat(request)
+                .complete(LlmRequest::prompt(prompt).temperature(0.7))
                 .await
                 .map_err(|e| format!("Failed to chat with LLM: {}", e))?;
 
@@ -56,18 +47,8 @@ pub async fn execute_slash_command(
         }
         "resume" => {
             let prompt = format!("Résume ce texte de manière concise : {}", context);
-            let request = crate::chat::ChatRequest {
-                messages: vec![crate::chat::ChatMessage {
-                    role: "user".to_string(),
-                    content: prompt,
-                }],
-                include_context: false,
-                temperature: 0.7,
-            };
             let response = llm_client
-                .lock()
-                .await
-                .chat(request)
+                .complete(LlmRequest::prompt(prompt).temperature(0.7))
                 .await
                 .map_err(|e| format!("Failed to chat with LLM: {}", e))?;
 
@@ -79,18 +60,8 @@ pub async fn execute_slash_command(
         }
         "debug" => {
             let prompt = format!("Analyse cette erreur et propose une solution : {}", context);
-            let request = crate::chat::ChatRequest {
-                messages: vec![crate::chat::ChatMessage {
-                    role: "user".to_string(),
-                    content: prompt,
-                }],
-                include_context: false,
-                temperature: 0.7,
-            };
             let response = llm_client
-                .lock()
-                .await
-                .chat(request)
+                .complete(LlmRequest::prompt(prompt).temperature(0.7))
                 .await
                 .map_err(|e| format!("Failed to chat with LLM: {}", e))?;
 
@@ -102,18 +73,8 @@ pub async fn execute_slash_command(
         }
         "improve" => {
             let prompt = format!("Suggère des améliorations pour : {}", context);
-            let request = crate::chat::ChatRequest {
-                messages: vec![crate::chat::ChatMessage {
-                    role: "user".to_string(),
-                    content: prompt,
-                }],
-                include_context: false,
-                temperature: 0.7,
-            };
             let response = llm_client
-                .lock()
-                .await
-                .chat(request)
+                .complete(LlmRequest::prompt(prompt).temperature(0.7))
                 .await
                 .map_err(|e| format!("Failed to chat with LLM: {}", e))?;
 
@@ -125,18 +86,8 @@ pub async fn execute_slash_command(
         }
         "translate" => {
             let prompt = format!("Traduis ce texte en français (ou en anglais s'il est déjà en français) : {}", context);
-            let request = crate::chat::ChatRequest {
-                messages: vec![crate::chat::ChatMessage {
-                    role: "user".to_string(),
-                    content: prompt,
-                }],
-                include_context: false,
-                temperature: 0.7,
-            };
             let response = llm_client
-                .lock()
-                .await
-                .chat(request)
+                .complete(LlmRequest::prompt(prompt).temperature(0.7))
                 .await
                 .map_err(|e| format!("Failed to chat with LLM: {}", e))?;
 
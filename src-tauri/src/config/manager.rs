@@ -1,5 +1,11 @@
 use super::types::AppConfig;
-use serde::{Deserialize, Serialize};
+use crate::budget::BudgetGovernor;
+use crate::crypto::keymanager::SecretStore;
+use crate::llm::selection::{api_key, api_key_secret, build_chain};
+use crate::llm::LlmClient;
+use crate::monitor::ScreenMonitor;
+use crate::privacy::redaction::build_detectors;
+use crate::privacy::{EgressPolicy, Redactor};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tracing::{debug, info, warn};
@@ -8,24 +14,51 @@ use dirs;
 pub struct ConfigManager {
     config: Arc<Mutex<AppConfig>>,
     config_file: PathBuf,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RetentionConfig {
-    pub period: String, // "never" | "1h" | "24h" | "7d" | "30d"
+    /// Clés d'API, conservées dans le trousseau plutôt que dans config.json
+    secrets: Arc<SecretStore>,
 }
 
 impl ConfigManager {
     pub fn new() -> Result<Self, String> {
         let config_file = Self::get_config_file_path()?;
-        let config = Self::load_or_create_config(&config_file)?;
-        
+        let mut config = Self::load_or_create_config(&config_file)?;
+        let secrets = Arc::new(SecretStore::new());
+
+        // Migration : une clé d'API en clair dans config.json part dans le trousseau
+        let migrated = Self::move_api_key_to_keychain(&secrets, &mut config).unwrap_or_else(|e| {
+            warn!("⚠️ API key left in config.json: {}", e);
+            false
+        });
+
         info!("✅ ConfigManager initialized: {:?}", config_file);
-        
-        Ok(Self {
+
+        let manager = Self {
             config: Arc::new(Mutex::new(config)),
             config_file,
-        })
+            secrets,
+        };
+        if migrated {
+            manager.save_config()?;
+            info!("🔐 LLM API key moved from config.json to the keychain");
+            if !manager.secrets.is_persistent() {
+                warn!("⚠️ Keychain unavailable: the API key will have to be entered again after restart");
+            }
+        }
+        Ok(manager)
+    }
+
+    /// Range `llm.api_key` dans le trousseau (pour le fournisseur configuré) et la retire
+    /// de la configuration. Retourne true si une clé a été déplacée.
+    fn move_api_key_to_keychain(secrets: &SecretStore, config: &mut AppConfig) -> Result<bool, String> {
+        match config.llm.api_key.take() {
+            Some(key) if !key.trim().is_empty() => {
+                secrets
+                    .set(&api_key_secret(&config.llm.provider), key.trim())
+                    .map_err(|e| format!("Failed to store API key: {}", e))?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
     }
 
     fn get_config_file_path() -> Result<PathBuf, String> {
@@ -75,11 +108,26 @@ impl ConfigManager {
         Ok(self.config.lock().unwrap().clone())
     }
 
-    pub fn update_config(&self, new_config: AppConfig) -> Result<(), String> {
+    pub fn update_config(&self, mut new_config: AppConfig) -> Result<(), String> {
+        Self::move_api_key_to_keychain(&self.secrets, &mut new_config)?;
         *self.config.lock().unwrap() = new_config;
         self.save_config()
     }
 
+    pub fn secrets(&self) -> Arc<SecretStore> {
+        self.secrets.clone()
+    }
+
+    /// Enregistre (ou supprime avec `None`) la clé d'API d'un fournisseur LLM
+    pub fn set_api_key(&self, provider: &str, key: Option<&str>) -> Result<(), String> {
+        let name = api_key_secret(provider);
+        match key.map(str::trim).filter(|k| !k.is_empty()) {
+            Some(key) => self.secrets.set(&name, key),
+            None => self.secrets.delete(&name),
+        }
+        .map_err(|e| format!("Failed to update API key: {}", e))
+    }
+
     pub fn get_config_path(&self) -> PathBuf {
         self.config_file.clone()
     }
@@ -87,26 +135,96 @@ impl ConfigManager {
 
 #[tauri::command]
 pub async fn get_config(
-    manager: tauri::State<'_, Arc<Mutex<ConfigManager>>>,
+    manager: tauri::State<'_, Arc<tokio::sync::Mutex<ConfigManager>>>,
 ) -> Result<AppConfig, String> {
-    let manager_guard = manager.lock().unwrap();
+    let manager_guard = manager.lock().await;
     manager_guard.get_config()
 }
 
+/// Enregistre la configuration et applique à chaud les réglages LLM, de masquage, de sortie
+/// et de budget de ressources.
+/// Une configuration LLM inutilisable (fournisseur inconnu, clé absente), un motif de
+/// masquage / de titre invalide, une durée de rétention illisible ou un budget hors bornes
+/// est refusé.
 #[tauri::command]
 pub async fn update_config(
-    config: AppConfig,
-    manager: tauri::State<'_, Arc<Mutex<ConfigManager>>>,
+    mut config: AppConfig,
+    manager: tauri::State<'_, Arc<tokio::sync::Mutex<ConfigManager>>>,
+    llm_client: tauri::State<'_, Arc<LlmClient>>,
+    monitor: tauri::State<'_, Arc<tokio::sync::Mutex<ScreenMonitor>>>,
+    redactor: tauri::State<'_, Arc<Redactor>>,
+    egress: tauri::State<'_, Arc<EgressPolicy>>,
+    budget: tauri::State<'_, Arc<BudgetGovernor>>,
+) -> Result<(), String> {
+    let manager_guard = manager.lock().await;
+    let secrets = manager_guard.secrets();
+
+    // Tout est vérifié avant d'appliquer quoi que ce soit : un refus laisse en place
+    // le trousseau, la politique de sortie, la chaîne LLM et le masquage
+    let detectors = build_detectors(&config.redaction)?;
+    let egress_policy = EgressPolicy::compile(&config.egress)?;
+    let chain = build_chain(&config.llm, &secrets)?;
+    crate::retention::validate(&config.retention)?;
+    crate::budget::validate(&config.budget)?;
+
+    let key_updated = ConfigManager::move_api_key_to_keychain(&secrets, &mut config)?;
+    egress.install(egress_policy);
+    llm_client.reconfigure(chain);
+    redactor.install(config.redaction.enabled, detectors);
+    budget.apply(&config.budget);
+    manager_guard.update_config(config)?;
+
+    if key_updated {
+        monitor
+            .lock()
+            .await
+            .set_vision_api_key(api_key(&secrets, "anthropic"))
+            .await;
+    }
+    Ok(())
+}
+
+/// Enregistre ou supprime (`api_key: null`) la clé d'API d'un fournisseur et réapplique la chaîne LLM
+#[tauri::command]
+pub async fn set_llm_api_key(
+    provider: String,
+    api_key: Option<String>,
+    manager: tauri::State<'_, Arc<tokio::sync::Mutex<ConfigManager>>>,
+    llm_client: tauri::State<'_, Arc<LlmClient>>,
+    monitor: tauri::State<'_, Arc<tokio::sync::Mutex<ScreenMonitor>>>,
 ) -> Result<(), String> {
-    let manager_guard = manager.lock().unwrap();
-    manager_guard.update_config(config)
+    let manager_guard = manager.lock().await;
+    manager_guard.set_api_key(&provider, api_key.as_deref())?;
+
+    let secrets = manager_guard.secrets();
+    if let Err(e) = llm_client.apply_config(&manager_guard.get_config()?.llm, &secrets) {
+        warn!("⚠️ LLM chain unchanged after key update: {}", e);
+    }
+    if provider == "anthropic" {
+        monitor
+            .lock()
+            .await
+            .set_vision_api_key(crate::llm::selection::api_key(&secrets, "anthropic"))
+            .await;
+    }
+    Ok(())
+}
+
+/// Indique si une clé d'API est enregistrée pour ce fournisseur (la clé n'est jamais renvoyée)
+#[tauri::command]
+pub async fn has_llm_api_key(
+    provider: String,
+    manager: tauri::State<'_, Arc<tokio::sync::Mutex<ConfigManager>>>,
+) -> Result<bool, String> {
+    let secrets = manager.lock().await.secrets();
+    Ok(api_key(&secrets, &provider).is_some())
 }
 
 #[tauri::command]
 pub async fn get_config_path(
-    manager: tauri::State<'_, Arc<Mutex<ConfigManager>>>,
+    manager: tauri::State<'_, Arc<tokio::sync::Mutex<ConfigManager>>>,
 ) -> Result<String, String> {
-    let manager_guard = manager.lock().unwrap();
+    let manager_guard = manager.lock().await;
     Ok(manager_guard.get_config_path().to_string_lossy().to_string())
 }
 
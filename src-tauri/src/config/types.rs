@@ -1,3 +1,5 @@
+use crate::privacy::RedactionStats;
+use crate::screenshot::CaptureMode;
 use serde::{Deserialize, Serialize};
 
 /// Configuration globale de ShadowLearn
@@ -8,6 +10,14 @@ pub struct AppConfig {
     pub llm: LLMConfig,
     pub shortcuts: ShortcutsConfig,
     pub captures: CapturesConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub egress: EgressConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,11 +35,36 @@ pub struct TriggersConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMConfig {
-    pub provider: String, // "openai" | "anthropic" | "ollama"
+    pub provider: String, // "openai" | "anthropic" | "ollama" | "openai-compatible"
     pub model: String,
+    /// URL de l'API ; celle du fournisseur par défaut si absente
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Fournisseurs essayés dans l'ordre quand le principal échoue
+    #[serde(default = "default_llm_fallbacks")]
+    pub fallbacks: Vec<LLMProviderConfig>,
+    /// Uniquement en écriture : déplacée dans le trousseau, jamais enregistrée dans config.json
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
 }
 
+/// Un maillon de la chaîne de repli
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LLMProviderConfig {
+    pub provider: String,
+    pub model: String,
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+fn default_llm_fallbacks() -> Vec<LLMProviderConfig> {
+    vec![LLMProviderConfig {
+        provider: "ollama".to_string(),
+        model: "llama3.2".to_string(),
+        base_url: None,
+    }]
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShortcutsConfig {
     pub show: String,        // Default: "CmdOrCtrl+Shift+S"
@@ -40,6 +75,218 @@ pub struct ShortcutsConfig {
 pub struct CapturesConfig {
     pub enabled: bool,
     pub min_interval_s: u64,
+    /// Écran entier de la fenêtre active, ou la fenêtre seule
+    #[serde(default)]
+    pub mode: CaptureMode,
+    /// Captures conservées dans le magasin chiffrées avec la clé du trousseau
+    #[serde(default)]
+    pub encrypt_screenshots: bool,
+}
+
+/// Rétention des données : durée par défaut et politiques par catégorie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    pub period: String, // "never" | "1h" | "24h" | "7d" | "30d"
+    #[serde(default = "default_retention_interval")]
+    pub interval_minutes: u64,
+    #[serde(default)]
+    pub screenshots: RetentionPolicy,
+    #[serde(default)]
+    pub contexts: RetentionPolicy,
+    #[serde(default)]
+    pub replay_events: RetentionPolicy,
+    #[serde(default)]
+    pub pattern_history: RetentionPolicy,
+    #[serde(default)]
+    pub ml_events: RetentionPolicy,
+    /// Journal d'audit de confidentialité ; `max_age` n'hérite pas de `period`
+    #[serde(default = "default_audit_log_retention")]
+    pub audit_log: RetentionPolicy,
+}
+
+/// Politique de rétention d'une catégorie (les limites absentes ne s'appliquent pas)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Âge maximum ("1h", "7d", "never"...) ; hérite de `RetentionConfig::period` si absent
+    #[serde(default)]
+    pub max_age: Option<String>,
+    #[serde(default)]
+    pub max_count: Option<usize>,
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+    /// Au-delà de cet âge, les éléments sont réduits plutôt que conservés tels quels (captures d'écran)
+    #[serde(default)]
+    pub downsample_after: Option<String>,
+}
+
+fn default_retention_interval() -> u64 {
+    60
+}
+
+fn default_audit_log_retention() -> RetentionPolicy {
+    RetentionPolicy {
+        max_age: Some(crate::privacy::audit::AUDIT_DEFAULT_PERIOD.to_string()),
+        max_bytes: Some(50 * 1024 * 1024),
+        ..Default::default()
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            period: "30d".to_string(),
+            interval_minutes: default_retention_interval(),
+            screenshots: RetentionPolicy {
+                max_age: Some("7d".to_string()),
+                max_count: Some(500),
+                max_bytes: Some(200 * 1024 * 1024),
+                downsample_after: Some("24h".to_string()),
+            },
+            contexts: RetentionPolicy {
+                max_count: Some(10_000),
+                ..Default::default()
+            },
+            replay_events: RetentionPolicy {
+                max_count: Some(10_000),
+                ..Default::default()
+            },
+            pattern_history: RetentionPolicy::default(),
+            ml_events: RetentionPolicy::default(),
+            audit_log: default_audit_log_retention(),
+        }
+    }
+}
+
+/// Masquage des secrets et données personnelles avant persistance et avant tout appel LLM / vision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// Masque aussi les chaînes longues à forte entropie (tokens sans format connu)
+    pub entropy_detection: bool,
+    /// Motifs ajoutés par l'utilisateur
+    pub custom_patterns: Vec<RedactionPattern>,
+}
+
+/// Motif utilisateur : expression régulière (syntaxe du crate `regex`) ; si elle contient un
+/// groupe nommé `secret`, seul ce groupe est masqué
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            entropy_detection: true,
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Politique de sortie : ce qui peut partir vers un fournisseur distant (OpenAI, Anthropic...)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EgressConfig {
+    /// Mode local uniquement : aucune requête vers un fournisseur distant
+    pub local_only: bool,
+    /// Demande une confirmation avant tout envoi distant (hors règles)
+    pub require_confirmation: bool,
+    /// Règles par app / titre de fenêtre, la première qui correspond s'applique
+    pub rules: Vec<EgressRule>,
+    /// Sort d'une requête bloquée
+    pub on_block: EgressFallback,
+}
+
+/// Règle de sortie ; une règle sans critère s'applique à tout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressRule {
+    /// Nom d'app (sous-chaîne, insensible à la casse)
+    #[serde(default)]
+    pub app: Option<String>,
+    /// Expression régulière sur le titre de fenêtre
+    #[serde(default)]
+    pub title_pattern: Option<String>,
+    pub action: EgressAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EgressAction {
+    Allow,
+    LocalOnly,
+    Confirm,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EgressFallback {
+    /// Bascule sur les fournisseurs locaux (Ollama)
+    #[default]
+    Reroute,
+    /// Refuse la requête (`EgressError`)
+    Refuse,
+}
+
+/// Budget de ressources du travail de fond (capture, OCR, vision, LLM, triggers)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    pub enabled: bool,
+    /// Fenêtre glissante sur laquelle la consommation est comptée
+    pub window_secs: u64,
+    /// Sur batterie, les tâches périodiques (capture, triggers) sont espacées de ce facteur
+    pub battery_throttle_factor: f32,
+    /// Sur batterie, l'OCR et la vision ne tournent plus
+    pub battery_skip_analysis: bool,
+    /// Mémoire résidente du processus au-delà de laquelle le travail différable est sauté
+    pub max_memory_mb: Option<u64>,
+    pub monitor: SubsystemBudget,
+    pub ocr: SubsystemBudget,
+    pub vision: SubsystemBudget,
+    pub llm: SubsystemBudget,
+    pub triggers: SubsystemBudget,
+}
+
+/// Limites d'un sous-système sur la fenêtre (les limites absentes ne s'appliquent pas)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubsystemBudget {
+    /// Temps CPU cumulé maximum (ms) ; mesuré pour le travail bloquant seulement (OCR)
+    pub max_cpu_ms: Option<u64>,
+    /// Temps écoulé cumulé maximum (ms), attente réseau comprise
+    pub max_wall_ms: Option<u64>,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 300,
+            battery_throttle_factor: 3.0,
+            battery_skip_analysis: true,
+            max_memory_mb: None,
+            monitor: SubsystemBudget {
+                max_cpu_ms: None,
+                max_wall_ms: Some(60_000),
+            },
+            ocr: SubsystemBudget {
+                max_cpu_ms: Some(60_000),
+                max_wall_ms: None,
+            },
+            vision: SubsystemBudget {
+                max_cpu_ms: None,
+                max_wall_ms: Some(120_000),
+            },
+            llm: SubsystemBudget::default(),
+            triggers: SubsystemBudget {
+                max_cpu_ms: None,
+                max_wall_ms: Some(15_000),
+            },
+        }
+    }
 }
 
 /// Statistiques de confidentialité
@@ -50,6 +297,9 @@ pub struct PrivacyStats {
     pub contexts_count: usize,
     pub db_size_bytes: u64,
     pub data_dir: String,
+    /// Masquages effectués depuis le démarrage
+    #[serde(default)]
+    pub redactions: RedactionStats,
 }
 
 impl Default for AppConfig {
@@ -67,6 +317,8 @@ impl Default for AppConfig {
             llm: LLMConfig {
                 provider: "ollama".to_string(),
                 model: "llama3".to_string(),
+                base_url: None,
+                fallbacks: default_llm_fallbacks(),
                 api_key: None,
             },
             shortcuts: ShortcutsConfig {
@@ -76,7 +328,13 @@ impl Default for AppConfig {
             captures: CapturesConfig {
                 enabled: false,
                 min_interval_s: 60,
+                mode: CaptureMode::default(),
+                encrypt_screenshots: false,
             },
+            retention: RetentionConfig::default(),
+            redaction: RedactionConfig::default(),
+            egress: EgressConfig::default(),
+            budget: BudgetConfig::default(),
         }
     }
 }
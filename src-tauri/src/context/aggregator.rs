@@ -2,8 +2,11 @@ use super::app_detector::{ActiveApp, AppDetector};
 use super::clipboard_monitor::ClipboardMonitor;
 use super::errors::ContextError;
 use super::idle_detector::{ActivityType, IdleDetector, IdleState};
+use crate::diagnostics::{parse_diagnostics, Diagnostic};
+use crate::privacy::Redactor;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::debug;
 use uuid::Uuid;
 
@@ -23,6 +26,10 @@ pub struct Context {
     pub idle_seconds: f64,
     pub timestamp: u64,
     pub capture_duration_ms: u64,
+    /// Erreurs de compilation/exécution repérées dans le clipboard (et, fusionnées par
+    /// l'appelant, dans le texte OCR récent)
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 /// Agrégateur de contexte avec cache fast-path
@@ -30,6 +37,8 @@ pub struct ContextAggregator {
     app_detector: AppDetector,
     clipboard_monitor: ClipboardMonitor,
     idle_detector: IdleDetector,
+    /// Titres de fenêtre et clipboard sont masqués avant de sortir de la couche contexte
+    redactor: Arc<Redactor>,
     last_capture: Option<Instant>,
 }
 
@@ -39,15 +48,29 @@ impl ContextAggregator {
             app_detector: AppDetector::new(),
             clipboard_monitor: ClipboardMonitor::new()?,
             idle_detector: IdleDetector::new(),
+            redactor: Arc::new(Redactor::default()),
             last_capture: None,
         })
     }
 
+    /// Utilise le `Redactor` partagé (configuration utilisateur, statistiques communes)
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// App active avec le titre de fenêtre masqué
+    fn active_app(&mut self) -> Result<ActiveApp, ContextError> {
+        let mut app = self.app_detector.get_active_app()?;
+        app.window_title = self.redactor.redact(&app.window_title);
+        Ok(app)
+    }
+
     /// Peek rapide (< 10ms) : app + idle uniquement
     /// Utilisé pour les checks de trigger sans overhead
     pub fn peek(&mut self) -> Result<PeekResult, ContextError> {
         Ok(PeekResult {
-            app: self.app_detector.get_active_app()?,
+            app: self.active_app()?,
             idle_seconds: self.idle_detector.get_idle_seconds(),
         })
     }
@@ -58,10 +81,19 @@ impl ContextAggregator {
         let start = Instant::now();
 
         // Get active app (with cache)
-        let app = self.app_detector.get_active_app()?;
+        let app = self.active_app()?;
 
         // Get clipboard (only if changed)
-        let clipboard = self.clipboard_monitor.get_recent_content();
+        let clipboard = self
+            .clipboard_monitor
+            .get_recent_content()
+            .map(|text| self.redactor.redact(&text));
+
+        // Erreurs copiées (message d'erreur, log de build...)
+        let diagnostics = clipboard
+            .as_deref()
+            .map(parse_diagnostics)
+            .unwrap_or_default();
 
         // Idle detection
         let idle_seconds = self.idle_detector.get_idle_seconds();
@@ -78,20 +110,30 @@ impl ContextAggregator {
                 .unwrap()
                 .as_secs(),
             capture_duration_ms,
+            diagnostics,
         };
 
         self.last_capture = Some(Instant::now());
 
         debug!(
-            "Context captured in {}ms: app={}, clipboard={}",
+            "Context captured in {}ms: app={}, clipboard={}, diagnostics={}",
             capture_duration_ms,
             context.app.name,
-            context.clipboard.is_some()
+            context.clipboard.is_some(),
+            context.diagnostics.len()
         );
 
         Ok(context)
     }
 
+    /// Dernier contenu du clipboard s'il a changé il y a moins de `max_age`
+    /// (`capture` ne le renvoie que lorsqu'il vient de changer)
+    pub fn recent_clipboard(&self, max_age: Duration) -> Option<String> {
+        self.clipboard_monitor
+            .last_content(max_age)
+            .map(|text| self.redactor.redact(text))
+    }
+
     /// Reset l'activité utilisateur avec type (appelé sur mouvement souris/clavier)
     pub fn reset_user_activity(&mut self, activity_type: ActivityType) {
         self.idle_detector.reset_activity(activity_type);
@@ -116,6 +158,7 @@ impl ContextAggregator {
                 .unwrap()
                 .as_secs(),
             capture_duration_ms: 0,
+            diagnostics: Vec::new(),
         })
     }
 }
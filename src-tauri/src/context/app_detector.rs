@@ -13,6 +13,18 @@ pub struct ActiveApp {
     pub timestamp: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tcc_status: Option<TCCStatus>,
+    /// Position de la fenêtre active sur le bureau (si la plateforme la fournit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<WindowBounds>,
+}
+
+/// Rectangle d'une fenêtre en coordonnées globales du bureau (pixels)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
 }
 
 /// Détecteur d'application active avec cache
@@ -133,6 +145,7 @@ impl AppDetector {
                     .unwrap()
                     .as_secs(),
                 tcc_status: Some(tcc_status),
+                bounds: None,
             })
         }
     }
@@ -156,12 +169,12 @@ impl AppDetector {
     // ============================================
     #[cfg(target_os = "windows")]
     fn get_active_app_windows(&self) -> Result<ActiveApp, AppDetectionError> {
-        use windows::Win32::Foundation::HWND;
+        use windows::Win32::Foundation::{HWND, RECT};
         use windows::Win32::System::Threading::{
             OpenProcess, QueryFullProcessImageNameW, PROCESS_QUERY_LIMITED_INFORMATION,
         };
         use windows::Win32::UI::WindowsAndMessaging::{
-            GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+            GetForegroundWindow, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId,
         };
 
         unsafe {
@@ -202,6 +215,15 @@ impl AppDetector {
                 .unwrap_or("Unknown")
                 .to_string();
 
+            // Window bounds
+            let mut rect = RECT::default();
+            let bounds = GetWindowRect(hwnd, &mut rect).ok().map(|_| WindowBounds {
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left).max(0) as u32,
+                height: (rect.bottom - rect.top).max(0) as u32,
+            });
+
             Ok(ActiveApp {
                 bundle_id: format!("windows.{}", pid),
                 name,
@@ -212,6 +234,7 @@ impl AppDetector {
                     .unwrap()
                     .as_secs(),
                 tcc_status: None,
+                bounds,
             })
         }
     }
@@ -291,6 +314,14 @@ impl AppDetector {
             .parse()
             .unwrap_or(0);
 
+        // Get window geometry (optional: used by window-relative privacy zones)
+        let bounds = Command::new("xdotool")
+            .args(["getwindowgeometry", "--shell", &window_id])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_xdotool_geometry(&String::from_utf8_lossy(&output.stdout)));
+
         // Get process name from /proc
         let name = if pid > 0 {
             std::fs::read_to_string(format!("/proc/{}/comm", pid))
@@ -311,6 +342,7 @@ impl AppDetector {
                 .unwrap()
                 .as_secs(),
             tcc_status: None,
+            bounds,
         })
     }
 
@@ -324,6 +356,23 @@ impl AppDetector {
     }
 }
 
+/// Lit la sortie de `xdotool getwindowgeometry --shell` (lignes `X=`, `Y=`, `WIDTH=`, `HEIGHT=`)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_xdotool_geometry(output: &str) -> Option<WindowBounds> {
+    let value = |key: &str| {
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(key)?.strip_prefix('='))
+            .and_then(|v| v.trim().parse::<i64>().ok())
+    };
+    Some(WindowBounds {
+        x: value("X")? as i32,
+        y: value("Y")? as i32,
+        width: value("WIDTH")?.max(0) as u32,
+        height: value("HEIGHT")?.max(0) as u32,
+    })
+}
+
 impl Default for AppDetector {
     fn default() -> Self {
         Self::new()
@@ -363,6 +412,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_xdotool_geometry() {
+        let output = "WINDOW=44040195\nX=1920\nY=-10\nWIDTH=1280\nHEIGHT=720\nSCREEN=0\n";
+        assert_eq!(
+            parse_xdotool_geometry(output),
+            Some(WindowBounds {
+                x: 1920,
+                y: -10,
+                width: 1280,
+                height: 720,
+            })
+        );
+        assert_eq!(parse_xdotool_geometry("WINDOW=1\n"), None);
+    }
+
     #[test]
     fn test_cache_behavior() {
         let mut detector = AppDetector::new();
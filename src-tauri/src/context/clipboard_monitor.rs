@@ -1,6 +1,6 @@
 use super::errors::ClipboardError;
 use arboard::Clipboard;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::debug;
 
 const MAX_CLIPBOARD_LENGTH: usize = 10_000; // 10KB
@@ -55,6 +55,14 @@ impl ClipboardMonitor {
             }
         }
     }
+
+    /// Dernier contenu connu, s'il a changé il y a moins de `max_age`
+    pub fn last_content(&self, max_age: Duration) -> Option<&str> {
+        if self.last_update.elapsed() > max_age {
+            return None;
+        }
+        self.last_content.as_deref()
+    }
 }
 
 impl Default for ClipboardMonitor {
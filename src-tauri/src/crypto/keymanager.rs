@@ -8,6 +8,7 @@ use aes_gcm::{
 use base64::{Engine, engine::general_purpose};
 use keyring::Entry;
 use rand::RngCore;
+use sha2::{Digest, Sha256};
 use std::{path::Path, sync::Arc};
 use thiserror::Error;
 use tokio::sync::Mutex;
@@ -31,6 +32,8 @@ pub enum KeyStorage {
 
 pub struct KeyManager {
     storage: KeyStorage,
+    /// Clé précédente, conservée pendant une rotation pour relire les anciennes données
+    previous: KeyStorage,
 }
 
 impl KeyManager {
@@ -38,60 +41,102 @@ impl KeyManager {
         match Entry::new("shadowlearn", "encryption_key") {
             Ok(entry) => {
                 tracing::info!("✅ Keychain OK - encryption key stored securely");
+                let previous = match Entry::new("shadowlearn", "encryption_key_previous") {
+                    Ok(previous) => KeyStorage::Keychain(previous),
+                    Err(_) => KeyStorage::Volatile(Arc::new(Mutex::new(None))),
+                };
                 Self {
                     storage: KeyStorage::Keychain(entry),
+                    previous,
                 }
             }
             Err(e) => {
                 tracing::warn!("⚠️ Keychain unavailable: {} → using volatile key (no persistence)", e);
-                Self {
-                    storage: KeyStorage::Volatile(Arc::new(Mutex::new(None))),
-                }
+                Self::volatile()
             }
         }
     }
 
+    /// Gestionnaire sans trousseau : la clé ne survit pas au redémarrage
+    pub fn volatile() -> Self {
+        Self {
+            storage: KeyStorage::Volatile(Arc::new(Mutex::new(None))),
+            previous: KeyStorage::Volatile(Arc::new(Mutex::new(None))),
+        }
+    }
+
     pub fn is_persistent(&self) -> bool {
         matches!(self.storage, KeyStorage::Keychain(_))
     }
 
-    pub async fn get_or_create_key(&self) -> Result<Key<Aes256Gcm>, KeyManagerError> {
-        match &self.storage {
-            KeyStorage::Keychain(entry) => {
-                match entry.get_password() {
-                    Ok(b64) => {
-                        let bytes = general_purpose::STANDARD.decode(&b64)
-                            .map_err(|e| KeyManagerError::KeyDecodeFailed(e.to_string()))?;
-                        if bytes.len() != 32 {
-                            return Err(KeyManagerError::KeyDecodeFailed("Invalid key length".into()));
-                        }
-                        Ok(*Key::<Aes256Gcm>::from_slice(&bytes))
-                    }
-                    Err(_) => {
-                        // Generate new key
-                        let mut k = [0u8; 32];
-                        OsRng.fill_bytes(&mut k);
-                        entry.set_password(&general_purpose::STANDARD.encode(k))
-                            .map_err(|e| KeyManagerError::KeyringUnavailable(e.to_string()))?;
-                        tracing::info!("🔑 Generated new encryption key and stored in keychain");
-                        Ok(*Key::<Aes256Gcm>::from_slice(&k))
+    /// Identifiant court d'une clé (8 premiers octets hex du SHA-256), préfixé aux blobs chiffrés
+    pub fn key_id(key: &Key<Aes256Gcm>) -> String {
+        let digest = Sha256::digest(key.as_slice());
+        digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn generate_key() -> Key<Aes256Gcm> {
+        let mut k = [0u8; 32];
+        OsRng.fill_bytes(&mut k);
+        *Key::<Aes256Gcm>::from_slice(&k)
+    }
+
+    async fn read_slot(slot: &KeyStorage) -> Result<Option<Key<Aes256Gcm>>, KeyManagerError> {
+        match slot {
+            KeyStorage::Keychain(entry) => match entry.get_password() {
+                Ok(b64) => {
+                    let bytes = general_purpose::STANDARD.decode(&b64)
+                        .map_err(|e| KeyManagerError::KeyDecodeFailed(e.to_string()))?;
+                    if bytes.len() != 32 {
+                        return Err(KeyManagerError::KeyDecodeFailed("Invalid key length".into()));
                     }
+                    Ok(Some(*Key::<Aes256Gcm>::from_slice(&bytes)))
                 }
-            }
+                Err(keyring::Error::NoEntry) => Ok(None),
+                // Une erreur passagère ne doit pas faire générer (et écraser) une nouvelle clé
+                Err(e) => Err(KeyManagerError::KeyringUnavailable(e.to_string())),
+            },
+            KeyStorage::Volatile(m) => Ok(*m.lock().await),
+        }
+    }
+
+    async fn write_slot(slot: &KeyStorage, key: Option<&Key<Aes256Gcm>>) -> Result<(), KeyManagerError> {
+        match slot {
+            KeyStorage::Keychain(entry) => match key {
+                Some(key) => entry
+                    .set_password(&general_purpose::STANDARD.encode(key))
+                    .map_err(|e| KeyManagerError::KeyringUnavailable(e.to_string())),
+                None => match entry.delete_password() {
+                    Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                    Err(e) => Err(KeyManagerError::KeyringUnavailable(e.to_string())),
+                },
+            },
             KeyStorage::Volatile(m) => {
-                let mut g = m.lock().await;
-                if let Some(k) = *g {
-                    return Ok(k);
-                }
-                let mut k = [0u8; 32];
-                OsRng.fill_bytes(&mut k);
-                let kk = *Key::<Aes256Gcm>::from_slice(&k);
-                *g = Some(kk);
-                Ok(kk)
+                *m.lock().await = key.copied();
+                Ok(())
             }
         }
     }
 
+    pub async fn get_or_create_key(&self) -> Result<Key<Aes256Gcm>, KeyManagerError> {
+        if let Some(key) = Self::read_slot(&self.storage).await? {
+            return Ok(key);
+        }
+
+        // Generate new key
+        let key = Self::generate_key();
+        Self::write_slot(&self.storage, Some(&key)).await?;
+        if self.is_persistent() {
+            tracing::info!("🔑 Generated new encryption key and stored in keychain");
+        }
+        Ok(key)
+    }
+
+    /// Clé précédente si une rotation est en cours
+    pub async fn previous_key(&self) -> Result<Option<Key<Aes256Gcm>>, KeyManagerError> {
+        Self::read_slot(&self.previous).await
+    }
+
     pub fn encrypt_with_key(&self, key: &Key<Aes256Gcm>, data: &[u8]) -> Result<Vec<u8>, KeyManagerError> {
         let cipher = Aes256Gcm::new(key);
         let mut nonce = [0u8; 12];
@@ -115,37 +160,143 @@ impl KeyManager {
             .map_err(|e| KeyManagerError::DecryptionFailed(e.to_string()))
     }
 
-    pub async fn rotate_key(&self, dir: &Path) -> Result<(), String> {
+    /// Démarre une rotation : la nouvelle clé devient courante, l'ancienne est gardée
+    /// comme clé précédente jusqu'à `finish_rotation`. Retourne (ancienne, nouvelle).
+    /// Une rotation interrompue (clé précédente encore présente) est reprise avec les mêmes
+    /// clés : en générer une troisième rendrait illisible ce qui est encore sous l'ancienne.
+    pub async fn begin_rotation(&self) -> Result<(Key<Aes256Gcm>, Key<Aes256Gcm>), String> {
+        if let Some(old) = self.previous_key().await.map_err(|e| e.to_string())? {
+            let new = self.get_or_create_key().await.map_err(|e| e.to_string())?;
+            tracing::warn!("🔑 Resuming interrupted key rotation");
+            return Ok((old, new));
+        }
+
         let old = self.get_or_create_key().await.map_err(|e| e.to_string())?;
-        let mut nb = [0u8; 32];
-        OsRng.fill_bytes(&mut nb);
-        let new = Key::<Aes256Gcm>::from_slice(&nb);
-        
+        let new = Self::generate_key();
+
+        Self::write_slot(&self.previous, Some(&old))
+            .await
+            .map_err(|e| format!("store previous key: {}", e))?;
+        Self::write_slot(&self.storage, Some(&new))
+            .await
+            .map_err(|e| format!("store new key: {}", e))?;
+
+        Ok((old, new))
+    }
+
+    /// Termine une rotation en oubliant la clé précédente
+    pub async fn finish_rotation(&self) -> Result<(), String> {
+        Self::write_slot(&self.previous, None)
+            .await
+            .map_err(|e| format!("clear previous key: {}", e))
+    }
+
+    /// Re-chiffre les fichiers `.enc` d'un répertoire avec la nouvelle clé
+    pub async fn reencrypt_files(
+        &self,
+        dir: &Path,
+        old: &Key<Aes256Gcm>,
+        new: &Key<Aes256Gcm>,
+    ) -> Result<usize, String> {
+        let mut count = 0;
         let mut rd = tokio::fs::read_dir(dir).await.map_err(|e| e.to_string())?;
-        
+
         while let Some(e) = rd.next_entry().await.map_err(|e| e.to_string())? {
             if e.path().extension().and_then(|s| s.to_str()) == Some("enc") {
                 let enc = tokio::fs::read(e.path()).await.map_err(|e| format!("read: {}", e))?;
-                let dec = self
-                    .decrypt_with_key(&old, &enc)
-                    .map_err(|e| format!("decrypt during rotation: {}", e))?;
+                let dec = match self.decrypt_with_key(old, &enc) {
+                    Ok(dec) => dec,
+                    // Déjà re-chiffré lors d'une rotation interrompue
+                    Err(_) if self.decrypt_with_key(new, &enc).is_ok() => continue,
+                    Err(e) => return Err(format!("decrypt during rotation: {}", e)),
+                };
                 let re = self
                     .encrypt_with_key(new, &dec)
                     .map_err(|e| format!("encrypt during rotation: {}", e))?;
-                
+
                 // Atomic write
                 let tmp = e.path().with_extension("enc.tmp");
                 tokio::fs::write(&tmp, &re).await.map_err(|e| format!("write temp: {}", e))?;
                 tokio::fs::rename(&tmp, e.path()).await.map_err(|e| format!("rename: {}", e))?;
+                count += 1;
             }
         }
-        
-        if let KeyStorage::Keychain(entry) = &self.storage {
-            entry.set_password(&general_purpose::STANDARD.encode(nb)).map_err(|e| format!("store new key: {}", e))?;
+
+        Ok(count)
+    }
+}
+
+/// Secrets applicatifs (clés d'API) rangés dans le trousseau, à côté de la clé de chiffrement.
+/// Sans trousseau, ils ne vivent qu'en mémoire le temps de la session.
+pub struct SecretStore {
+    volatile: Option<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+}
+
+impl SecretStore {
+    pub fn new() -> Self {
+        match Entry::new("shadowlearn", "secret_probe") {
+            Ok(_) => Self { volatile: None },
+            Err(e) => {
+                tracing::warn!("⚠️ Keychain unavailable: {} → secrets kept in memory only", e);
+                Self::volatile()
+            }
+        }
+    }
+
+    pub fn volatile() -> Self {
+        Self {
+            volatile: Some(std::sync::Mutex::new(std::collections::HashMap::new())),
+        }
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.volatile.is_none()
+    }
+
+    fn entry(name: &str) -> Result<Entry, KeyManagerError> {
+        Entry::new("shadowlearn", &format!("secret_{}", name))
+            .map_err(|e| KeyManagerError::KeyringUnavailable(e.to_string()))
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<String>, KeyManagerError> {
+        if let Some(volatile) = &self.volatile {
+            return Ok(volatile.lock().unwrap().get(name).cloned());
+        }
+        match Self::entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(KeyManagerError::KeyringUnavailable(e.to_string())),
+        }
+    }
+
+    pub fn set(&self, name: &str, value: &str) -> Result<(), KeyManagerError> {
+        if let Some(volatile) = &self.volatile {
+            volatile
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value.to_string());
+            return Ok(());
+        }
+        Self::entry(name)?
+            .set_password(value)
+            .map_err(|e| KeyManagerError::KeyringUnavailable(e.to_string()))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), KeyManagerError> {
+        if let Some(volatile) = &self.volatile {
+            volatile.lock().unwrap().remove(name);
+            return Ok(());
+        }
+        match Self::entry(name)?.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(KeyManagerError::KeyringUnavailable(e.to_string())),
         }
-        
-        tracing::info!("✅ Key rotation complete");
-        Ok(())
+    }
+}
+
+impl Default for SecretStore {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -206,4 +357,39 @@ mod tests {
 
         assert_eq!(original, decrypted.as_slice());
     }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_previous_key_until_finished() {
+        let manager = KeyManager::volatile();
+        let original = manager.get_or_create_key().await.unwrap();
+
+        let (old, new) = manager.begin_rotation().await.unwrap();
+        assert_eq!(KeyManager::key_id(&old), KeyManager::key_id(&original));
+        assert_ne!(KeyManager::key_id(&old), KeyManager::key_id(&new));
+        assert_eq!(manager.get_or_create_key().await.unwrap(), new);
+        assert_eq!(manager.previous_key().await.unwrap(), Some(old));
+
+        // Relancée avant la fin, elle reprend les mêmes clés
+        assert_eq!(manager.begin_rotation().await.unwrap(), (old, new));
+        assert_eq!(manager.previous_key().await.unwrap(), Some(old));
+
+        manager.finish_rotation().await.unwrap();
+        assert_eq!(manager.previous_key().await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_volatile_secret_store_roundtrip() {
+        let secrets = SecretStore::volatile();
+        assert!(!secrets.is_persistent());
+        assert_eq!(secrets.get("llm_api_key_openai").unwrap(), None);
+
+        secrets.set("llm_api_key_openai", "sk-test").unwrap();
+        assert_eq!(
+            secrets.get("llm_api_key_openai").unwrap().as_deref(),
+            Some("sk-test")
+        );
+
+        secrets.delete("llm_api_key_openai").unwrap();
+        assert_eq!(secrets.get("llm_api_key_openai").unwrap(), None);
+    }
 }
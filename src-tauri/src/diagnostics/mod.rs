@@ -0,0 +1,159 @@
+//! Structured compiler/runtime diagnostics extracted from screen (OCR) or clipboard text.
+//!
+//! `parse_diagnostics` scans the text line by line and lets each tool parser try to
+//! recognise a diagnostic starting at the current line: rustc/cargo, TypeScript (tsc),
+//! Python and Blender tracebacks, Node stack traces and GCC/Clang.
+
+use serde::{Deserialize, Serialize};
+
+mod parsers;
+
+/// Max diagnostics kept from one text (a full build log can hold hundreds)
+const MAX_DIAGNOSTICS: usize = 20;
+
+/// Tool that produced a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticTool {
+    /// rustc/cargo errors and Rust panics
+    Rust,
+    #[serde(rename = "typescript")]
+    TypeScript,
+    Python,
+    Node,
+    /// GCC or Clang (same output format)
+    GccClang,
+    /// Python errors raised inside Blender
+    Blender,
+}
+
+impl DiagnosticTool {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticTool::Rust => "rust",
+            DiagnosticTool::TypeScript => "typescript",
+            DiagnosticTool::Python => "python",
+            DiagnosticTool::Node => "node",
+            DiagnosticTool::GccClang => "gcc/clang",
+            DiagnosticTool::Blender => "blender",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One frame of a stack trace, innermost last for Python and first for Node
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackFrame {
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+/// A compiler error/warning or runtime exception
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub tool: DiagnosticTool,
+    pub severity: Severity,
+    /// Error code or exception type (`E0382`, `TS2322`, `TypeError`, `-Wunused-variable`...)
+    pub code: Option<String>,
+    pub message: String,
+    /// Location the error points at (innermost user frame for runtime errors)
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    #[serde(default)]
+    pub frames: Vec<StackFrame>,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Tool and code, e.g. `rust E0382` or `python ModuleNotFoundError`
+    pub fn label(&self) -> String {
+        match &self.code {
+            Some(code) => format!("{} {}", self.tool.as_str(), code),
+            None => self.tool.as_str().to_string(),
+        }
+    }
+
+    /// Short one-line description, e.g. `rust E0382 at src/main.rs:5: borrow of moved value`
+    pub fn summary(&self) -> String {
+        let mut summary = self.label();
+        if let Some(file) = &self.file {
+            summary.push_str(" at ");
+            summary.push_str(file);
+            if let Some(line) = self.line {
+                summary.push_str(&format!(":{}", line));
+            }
+        }
+        if !self.message.is_empty() {
+            summary.push_str(": ");
+            summary.push_str(&self.message);
+        }
+        summary
+    }
+}
+
+/// Extract every diagnostic found in `text`, in order of appearance
+pub fn parse_diagnostics(text: &str) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = text.lines().map(|line| line.trim_end()).collect();
+    let mut diagnostics = Vec::new();
+
+    let mut i = 0;
+    while i < lines.len() && diagnostics.len() < MAX_DIAGNOSTICS {
+        match parsers::parse_at(&lines, i) {
+            Some((diagnostic, consumed)) => {
+                diagnostics.push(diagnostic);
+                i += consumed.max(1);
+            }
+            None => i += 1,
+        }
+    }
+
+    diagnostics
+}
+
+/// True if at least one diagnostic is an error (warnings alone don't mean the user is stuck)
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(Diagnostic::is_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mixed_log() {
+        let text = "$ cargo build\n\
+            warning: unused variable: `y`\n \
+            --> src/lib.rs:3:9\n\
+            error[E0382]: borrow of moved value: `x`\n  \
+            --> src/main.rs:5:20\n   \
+            |\n\
+            error: could not compile `demo` due to previous error\n";
+
+        let diagnostics = parse_diagnostics(text);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(has_errors(&diagnostics));
+        assert_eq!(
+            diagnostics[1].summary(),
+            "rust E0382 at src/main.rs:5: borrow of moved value: `x`"
+        );
+    }
+
+    #[test]
+    fn test_no_diagnostics_in_prose() {
+        let text = "There was an error: the meeting moved to 3pm.\nSee notes.txt:12 for details";
+        assert!(parse_diagnostics(text).is_empty());
+    }
+}
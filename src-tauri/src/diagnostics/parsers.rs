@@ -0,0 +1,389 @@
+//! Per-tool parsers: each one recognises a diagnostic starting at `lines[i]` and
+//! returns it with the number of lines it spans.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use super::{Diagnostic, DiagnosticTool, Severity, StackFrame};
+
+/// Lines searched after a Python traceback header for the exception line
+const MAX_TRACEBACK_LINES: usize = 80;
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("valid diagnostic regex")
+}
+
+static RUST_HEADER: Lazy<Regex> = Lazy::new(|| regex(r"^(error|warning)(?:\[(E\d{4})\])?: (.+)$"));
+static RUST_LOCATION: Lazy<Regex> = Lazy::new(|| regex(r"^\s*--> (.+?):(\d+):(\d+)$"));
+static RUST_PANIC: Lazy<Regex> =
+    Lazy::new(|| regex(r"^thread '(.+?)' panicked at (?:'(.+)', )?(.+?):(\d+):(\d+):?$"));
+
+static TSC: Lazy<Regex> = Lazy::new(|| {
+    regex(r"^(?:(\S.*?)(?:\((\d+),(\d+)\)|:(\d+):(\d+))(?::| -) )?(error|warning) (TS\d+): (.+)$")
+});
+
+static PYTHON_HEADER: Lazy<Regex> =
+    Lazy::new(|| regex(r"^(?:Error: )?(Python: )?Traceback \(most recent call last\):$"));
+static PYTHON_FRAME: Lazy<Regex> =
+    Lazy::new(|| regex(r#"^\s*File "(.+)", line (\d+)(?:, in (.+))?$"#));
+static PYTHON_EXCEPTION: Lazy<Regex> = Lazy::new(|| {
+    regex(r"^([A-Za-z_][\w.]*(?:Error|Exception|Warning|Interrupt|Exit|StopIteration))(?:: (.*))?$")
+});
+static BLENDER_LOCATION: Lazy<Regex> = Lazy::new(|| regex(r"^location: .+:-?\d+$"));
+
+static NODE_ERROR: Lazy<Regex> = Lazy::new(|| {
+    regex(r"^(?:Uncaught )?([A-Z]\w*(?:Error|Exception)|Error)(?: \[([A-Z0-9_]+)\])?: (.+)$")
+});
+static NODE_FRAME: Lazy<Regex> =
+    Lazy::new(|| regex(r"^\s*at (?:(.+?) \()?(.+?):(\d+)(?::(\d+))?\)?$"));
+
+static GCC: Lazy<Regex> = Lazy::new(|| {
+    regex(
+        r"^(\S.*?):(\d+):(\d+): (?:fatal )?(error|warning): (.+?)(?: \[(-W[^\]]+|-fpermissive)\])?$",
+    )
+});
+
+/// Try every parser at `lines[i]`, most specific first
+pub(super) fn parse_at(lines: &[&str], i: usize) -> Option<(Diagnostic, usize)> {
+    parse_rust(lines, i)
+        .or_else(|| parse_typescript(lines, i))
+        .or_else(|| parse_python(lines, i))
+        .or_else(|| parse_node(lines, i))
+        .or_else(|| parse_gcc(lines, i))
+}
+
+fn number(caps: &Captures, group: usize) -> Option<u32> {
+    caps.get(group).and_then(|m| m.as_str().parse().ok())
+}
+
+fn text(caps: &Captures, group: usize) -> Option<String> {
+    caps.get(group).map(|m| m.as_str().trim().to_string())
+}
+
+fn severity(word: &str) -> Severity {
+    if word == "warning" {
+        Severity::Warning
+    } else {
+        Severity::Error
+    }
+}
+
+/// `error[E0382]: ...` followed by ` --> file:line:col`, or a panic message
+fn parse_rust(lines: &[&str], i: usize) -> Option<(Diagnostic, usize)> {
+    let line = lines[i].trim_start();
+
+    if let Some(caps) = RUST_PANIC.captures(line) {
+        // Rust >= 1.73 prints the message on the next line
+        let (message, consumed) = match text(&caps, 2) {
+            Some(message) => (message, 1),
+            None => match lines.get(i + 1) {
+                Some(next) if !next.trim().is_empty() => (next.trim().to_string(), 2),
+                _ => (String::new(), 1),
+            },
+        };
+        return Some((
+            Diagnostic {
+                tool: DiagnosticTool::Rust,
+                severity: Severity::Error,
+                code: Some("panic".to_string()),
+                message,
+                file: text(&caps, 3),
+                line: number(&caps, 4),
+                column: number(&caps, 5),
+                frames: Vec::new(),
+            },
+            consumed,
+        ));
+    }
+
+    let caps = RUST_HEADER.captures(line)?;
+    let location = (i + 1..lines.len().min(i + 3))
+        .find_map(|j| RUST_LOCATION.captures(lines[j]).map(|loc| (j, loc)));
+    // Without a code or a `-->` line, `error: ...` is too generic to be rustc's
+    if location.is_none() && caps.get(2).is_none() {
+        return None;
+    }
+
+    let mut diagnostic = Diagnostic {
+        tool: DiagnosticTool::Rust,
+        severity: severity(&caps[1]),
+        code: text(&caps, 2),
+        message: caps[3].trim().to_string(),
+        file: None,
+        line: None,
+        column: None,
+        frames: Vec::new(),
+    };
+    let consumed = match location {
+        Some((j, loc)) => {
+            diagnostic.file = text(&loc, 1);
+            diagnostic.line = number(&loc, 2);
+            diagnostic.column = number(&loc, 3);
+            j - i + 1
+        }
+        None => 1,
+    };
+    Some((diagnostic, consumed))
+}
+
+/// `file.ts(12,5): error TS2322: ...` or `file.ts:12:5 - error TS2322: ...`
+fn parse_typescript(lines: &[&str], i: usize) -> Option<(Diagnostic, usize)> {
+    let caps = TSC.captures(lines[i].trim_start())?;
+    Some((
+        Diagnostic {
+            tool: DiagnosticTool::TypeScript,
+            severity: severity(&caps[6]),
+            code: text(&caps, 7),
+            message: caps[8].trim().to_string(),
+            file: text(&caps, 1),
+            line: number(&caps, 2).or_else(|| number(&caps, 4)),
+            column: number(&caps, 3).or_else(|| number(&caps, 5)),
+            frames: Vec::new(),
+        },
+        1,
+    ))
+}
+
+/// Python traceback (header or bare `File "..."` frame for syntax errors) up to the
+/// exception line; tracebacks printed by Blender are reported as `Blender`
+fn parse_python(lines: &[&str], i: usize) -> Option<(Diagnostic, usize)> {
+    let first = lines[i].trim_start();
+    let (mut blender, start) = match PYTHON_HEADER.captures(first) {
+        Some(caps) => (caps.get(1).is_some(), i + 1),
+        None if PYTHON_FRAME.is_match(first) => (false, i),
+        None => return None,
+    };
+
+    let mut frames = Vec::new();
+    let end = lines.len().min(start + MAX_TRACEBACK_LINES);
+    for j in start..end {
+        let line = lines[j];
+        if let Some(caps) = PYTHON_FRAME.captures(line) {
+            frames.push(StackFrame {
+                function: text(&caps, 3),
+                file: text(&caps, 1),
+                line: number(&caps, 2),
+                column: None,
+            });
+            continue;
+        }
+        // Source lines and carets are indented; the exception line is not (OCR may lose
+        // the indentation, so other unindented lines are skipped too)
+        if line.starts_with(char::is_whitespace) || line.trim().is_empty() {
+            continue;
+        }
+        let Some(caps) = PYTHON_EXCEPTION.captures(line) else {
+            continue;
+        };
+
+        let code = caps[1].to_string();
+        let message = text(&caps, 2).unwrap_or_default();
+        blender |= message.contains("bpy")
+            || frames.iter().any(|f| {
+                f.file
+                    .as_deref()
+                    .is_some_and(|file| file.to_lowercase().contains("blender"))
+            });
+        let mut consumed = j - i + 1;
+        if lines
+            .get(j + 1)
+            .is_some_and(|next| BLENDER_LOCATION.is_match(next.trim()))
+        {
+            blender = true;
+            consumed += 1;
+        }
+
+        let innermost = frames.last().cloned().unwrap_or_default();
+        return Some((
+            Diagnostic {
+                tool: if blender {
+                    DiagnosticTool::Blender
+                } else {
+                    DiagnosticTool::Python
+                },
+                severity: if code.ends_with("Warning") {
+                    Severity::Warning
+                } else {
+                    Severity::Error
+                },
+                code: Some(code),
+                message,
+                file: innermost.file,
+                line: innermost.line,
+                column: None,
+                frames,
+            },
+            consumed,
+        ));
+    }
+
+    None
+}
+
+/// `TypeError: ...` followed by `    at fn (file:line:col)` frames
+fn parse_node(lines: &[&str], i: usize) -> Option<(Diagnostic, usize)> {
+    let caps = NODE_ERROR.captures(lines[i].trim_start())?;
+
+    let frames: Vec<StackFrame> = lines[i + 1..]
+        .iter()
+        .map_while(|line| NODE_FRAME.captures(line))
+        .map(|frame| StackFrame {
+            function: text(&frame, 1),
+            file: text(&frame, 2),
+            line: number(&frame, 3),
+            column: number(&frame, 4),
+        })
+        .collect();
+    if frames.is_empty() {
+        return None;
+    }
+
+    // Point at the first frame in the user's code rather than in Node internals
+    let location = frames
+        .iter()
+        .find(|f| {
+            f.file
+                .as_deref()
+                .is_some_and(|file| !file.starts_with("node:") && !file.contains("node_modules"))
+        })
+        .unwrap_or(&frames[0])
+        .clone();
+
+    let consumed = frames.len() + 1;
+    Some((
+        Diagnostic {
+            tool: DiagnosticTool::Node,
+            severity: Severity::Error,
+            code: text(&caps, 2).or_else(|| text(&caps, 1)),
+            message: caps[3].trim().to_string(),
+            file: location.file,
+            line: location.line,
+            column: location.column,
+            frames,
+        },
+        consumed,
+    ))
+}
+
+/// `file.c:12:5: error: ... [-Wflag]`
+fn parse_gcc(lines: &[&str], i: usize) -> Option<(Diagnostic, usize)> {
+    let caps = GCC.captures(lines[i].trim_start())?;
+    Some((
+        Diagnostic {
+            tool: DiagnosticTool::GccClang,
+            severity: severity(&caps[4]),
+            code: text(&caps, 6),
+            message: caps[5].trim().to_string(),
+            file: text(&caps, 1),
+            line: number(&caps, 2),
+            column: number(&caps, 3),
+            frames: Vec::new(),
+        },
+        1,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_diagnostics;
+    use super::*;
+
+    fn single(text: &str) -> Diagnostic {
+        let mut diagnostics = parse_diagnostics(text);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        diagnostics.remove(0)
+    }
+
+    #[test]
+    fn test_rust() {
+        let d = single("error[E0308]: mismatched types\n --> src/lib.rs:10:5\n  |");
+        assert_eq!(d.code.as_deref(), Some("E0308"));
+        assert_eq!(
+            (d.file.as_deref(), d.line, d.column),
+            (Some("src/lib.rs"), Some(10), Some(5))
+        );
+
+        let panic = single("thread 'main' panicked at src/main.rs:4:5:\nindex out of bounds");
+        assert_eq!(panic.code.as_deref(), Some("panic"));
+        assert_eq!(panic.message, "index out of bounds");
+        assert_eq!(panic.line, Some(4));
+    }
+
+    #[test]
+    fn test_typescript() {
+        let d = single(
+            "src/app.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.",
+        );
+        assert_eq!(d.tool, DiagnosticTool::TypeScript);
+        assert_eq!(
+            (d.file.as_deref(), d.line, d.column),
+            (Some("src/app.ts"), Some(12), Some(5))
+        );
+
+        let pretty = single("src/app.ts:3:1 - error TS2304: Cannot find name 'foo'.");
+        assert_eq!(pretty.code.as_deref(), Some("TS2304"));
+        assert_eq!(pretty.line, Some(3));
+    }
+
+    #[test]
+    fn test_python_and_blender() {
+        let d = single(
+            "Traceback (most recent call last):\n  \
+             File \"app.py\", line 3, in <module>\n    \
+             main()\n  \
+             File \"/src/lib.py\", line 8, in main\n    \
+             import flask\n\
+             ModuleNotFoundError: No module named 'flask'",
+        );
+        assert_eq!(d.tool, DiagnosticTool::Python);
+        assert_eq!(d.code.as_deref(), Some("ModuleNotFoundError"));
+        assert_eq!(d.frames.len(), 2);
+        assert_eq!((d.file.as_deref(), d.line), (Some("/src/lib.py"), Some(8)));
+
+        let syntax =
+            single("  File \"x.py\", line 1\n    def f(\n         ^\nSyntaxError: invalid syntax");
+        assert_eq!(syntax.code.as_deref(), Some("SyntaxError"));
+
+        let blender = single(
+            "Error: Python: Traceback (most recent call last):\n  \
+             File \"\\Text\", line 4, in execute\n\
+             AttributeError: 'NoneType' object has no attribute 'data'\n\
+             location: <unknown location>:-1",
+        );
+        assert_eq!(blender.tool, DiagnosticTool::Blender);
+        assert_eq!(blender.line, Some(4));
+    }
+
+    #[test]
+    fn test_node() {
+        let d = single(
+            "TypeError: Cannot read properties of undefined (reading 'bar')\n    \
+             at Module._compile (node:internal/modules/cjs/loader:1256:14)\n    \
+             at Object.<anonymous> (/app/index.js:3:7)\n\
+             Node.js v20.5.0",
+        );
+        assert_eq!(d.tool, DiagnosticTool::Node);
+        assert_eq!(d.code.as_deref(), Some("TypeError"));
+        assert_eq!(d.frames.len(), 2);
+        assert_eq!(
+            (d.file.as_deref(), d.line, d.column),
+            (Some("/app/index.js"), Some(3), Some(7))
+        );
+
+        let coded = single("Error [ERR_MODULE_NOT_FOUND]: Cannot find module 'x'\n    at resolve (node:internal/esm:1:2)");
+        assert_eq!(coded.code.as_deref(), Some("ERR_MODULE_NOT_FOUND"));
+    }
+
+    #[test]
+    fn test_gcc_clang() {
+        let d = single("main.c:7:9: warning: unused variable 'x' [-Wunused-variable]");
+        assert_eq!(d.tool, DiagnosticTool::GccClang);
+        assert_eq!(d.severity, Severity::Warning);
+        assert_eq!(d.code.as_deref(), Some("-Wunused-variable"));
+        assert_eq!(d.message, "unused variable 'x'");
+
+        let fatal = single("src/io.cpp:1:10: fatal error: 'foo.h' file not found");
+        assert!(fatal.is_error());
+        assert_eq!(fatal.file.as_deref(), Some("src/io.cpp"));
+    }
+}
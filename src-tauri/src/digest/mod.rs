@@ -8,6 +8,8 @@ use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
 
+use crate::privacy::erasure::AppMatcher;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DigestStats {
     pub suggestions_shown: u32,
@@ -101,6 +103,23 @@ impl DigestManager {
         self.suggestions_accepted = 0;
         self.app_usage.clear();
     }
+
+    /// Forget an app's usage counters
+    pub fn forget_app(&mut self, matcher: &AppMatcher) -> usize {
+        let forgotten: Vec<String> = self
+            .app_usage
+            .keys()
+            .filter(|app_name| matcher.matches_app(app_name))
+            .cloned()
+            .collect();
+
+        for app_name in &forgotten {
+            if let Some(count) = self.app_usage.remove(app_name) {
+                self.suggestions_shown = self.suggestions_shown.saturating_sub(count);
+            }
+        }
+        forgotten.len()
+    }
 }
 
 #[tauri::command]
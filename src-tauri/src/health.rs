@@ -1,3 +1,4 @@
+use crate::budget::{BudgetGovernor, BudgetUsage};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -6,15 +7,23 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// Structure principale pour monitorer la santé des composants
 pub struct HealthMonitor {
     last_check: Arc<AtomicU64>,
+    budget: Option<Arc<BudgetGovernor>>,
 }
 
 impl HealthMonitor {
     pub fn new() -> Self {
         Self {
             last_check: Arc::new(AtomicU64::new(0)),
+            budget: None,
         }
     }
 
+    /// Ajoute la consommation du budget de ressources au status
+    pub fn with_budget_governor(mut self, budget: Arc<BudgetGovernor>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
     /// Met à jour le timestamp du dernier check
     pub fn update_check(&self) {
         let now = SystemTime::now()
@@ -37,6 +46,7 @@ impl HealthMonitor {
             idle_detector: self.check_idle_detector().await,
             screenshot: self.check_screenshot().await,
             permissions: self.check_permissions().await,
+            budget: self.budget.as_ref().map(|budget| budget.usage()),
             timestamp: self.get_last_check(),
         }
     }
@@ -130,6 +140,9 @@ pub struct HealthStatus {
     pub idle_detector: ComponentHealth,
     pub screenshot: ComponentHealth,
     pub permissions: PermissionStatus,
+    /// Consommation par sous-système sur la fenêtre du budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetUsage>,
     pub timestamp: u64,
 }
 
@@ -142,6 +155,21 @@ pub enum ComponentHealth {
     Down { reason: String },
 }
 
+#[cfg(test)]
+impl ComponentHealth {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, ComponentHealth::Healthy)
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        matches!(self, ComponentHealth::Degraded { .. })
+    }
+
+    pub fn is_down(&self) -> bool {
+        matches!(self, ComponentHealth::Down { .. })
+    }
+}
+
 /// Status des permissions système
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionStatus {
@@ -149,6 +177,13 @@ pub struct PermissionStatus {
     pub accessibility: bool,
 }
 
+#[cfg(test)]
+impl PermissionStatus {
+    pub fn is_all_granted(&self) -> bool {
+        self.screen_capture && self.accessibility
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,5 +232,17 @@ mod tests {
         assert!(status.screenshot.is_healthy());
         assert!(status.permissions.is_all_granted());
         assert!(status.timestamp > 0);
+        assert!(status.budget.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_health_reports_budget() {
+        let monitor =
+            HealthMonitor::new().with_budget_governor(Arc::new(BudgetGovernor::default()));
+        let status = monitor.check_health().await;
+
+        let budget = status.budget.expect("budget usage");
+        assert_eq!(budget.subsystems.len(), 5);
+        assert!(budget.subsystems.iter().all(|usage| usage.runs == 0));
     }
 }
@@ -8,14 +8,19 @@ use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
 use crate::context::aggregator::Context;
-use crate::intent::llm_client::LLMClient;
+use crate::diagnostics;
 use crate::intent::{Intent, IntentType};
+use crate::llm::{ChatMessage, LlmClient, LlmRequest};
+use crate::privacy::RequestOrigin;
+
+/// Max diagnostics described in the prompt
+const MAX_PROMPT_DIAGNOSTICS: usize = 3;
 
 /// Intent detector with LLM integration and caching
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct IntentDetector {
-    llm_client: Arc<Mutex<LLMClient>>,
+    llm_client: Arc<LlmClient>,
     intent_cache: LruCache<String, CachedIntent>,
     confidence_threshold: f32,
     stats: IntentDetectorStats,
@@ -42,7 +47,7 @@ pub struct IntentDetectorStats {
 
 #[allow(dead_code)]
 impl IntentDetector {
-    pub fn new(llm_client: Arc<Mutex<LLMClient>>) -> Self {
+    pub fn new(llm_client: Arc<LlmClient>) -> Self {
         Self {
             llm_client,
             intent_cache: LruCache::new(NonZeroUsize::new(500).unwrap()),
@@ -96,30 +101,41 @@ impl IntentDetector {
         let start_time = Instant::now();
         let timeout_duration = Duration::from_secs(30);
 
-        let response = match tokio::time::timeout(timeout_duration, async {
-            self.llm_client.lock().unwrap().generate(&prompt, 200).await
-        })
-        .await
-        {
-            Ok(Ok(response)) => {
-                self.stats.llm_calls += 1;
-                let duration = start_time.elapsed();
-                self.stats.total_response_time_ms += duration.as_millis() as u64;
-                self.stats.average_response_time_ms =
-                    self.stats.total_response_time_ms as f64 / self.stats.llm_calls as f64;
-                response
-            }
-            Ok(Err(e)) => {
-                self.stats.llm_errors += 1;
-                error!("[INTENT] LLM call failed: {}", e);
-                return self.create_fallback_intent(ctx);
-            }
-            Err(_timeout) => {
-                self.stats.llm_errors += 1;
-                warn!("[INTENT] LLM timeout after 30s, using fallback");
-                return self.create_fallback_intent(ctx);
-            }
-        };
+        let request = LlmRequest::new(vec![
+            ChatMessage::system(
+                "You are an AI assistant that analyzes user intent from software context. Respond only with valid JSON.",
+            ),
+            ChatMessage::user(prompt),
+        ])
+        .temperature(0.3)
+        .max_tokens(200)
+        .stop(&["```", "\n\n\n"])
+        .origin(Some(RequestOrigin::new(
+            ctx.app.name.clone(),
+            ctx.app.window_title.clone(),
+        )));
+
+        let response =
+            match tokio::time::timeout(timeout_duration, self.llm_client.complete(request)).await {
+                Ok(Ok(response)) => {
+                    self.stats.llm_calls += 1;
+                    let duration = start_time.elapsed();
+                    self.stats.total_response_time_ms += duration.as_millis() as u64;
+                    self.stats.average_response_time_ms =
+                        self.stats.total_response_time_ms as f64 / self.stats.llm_calls as f64;
+                    response.content
+                }
+                Ok(Err(e)) => {
+                    self.stats.llm_errors += 1;
+                    error!("[INTENT] LLM call failed: {}", e);
+                    return self.create_fallback_intent(ctx);
+                }
+                Err(_timeout) => {
+                    self.stats.llm_errors += 1;
+                    warn!("[INTENT] LLM timeout after 30s, using fallback");
+                    return self.create_fallback_intent(ctx);
+                }
+            };
 
         // Parse response
         let intent = self.parse_response(&response)?;
@@ -160,9 +176,17 @@ impl IntentDetector {
             })
             .unwrap_or_else(|| "none".to_string());
 
+        // Same window with a different error is a different situation
+        let diagnostics_key = ctx
+            .diagnostics
+            .iter()
+            .map(|d| d.label())
+            .collect::<Vec<_>>()
+            .join(",");
+
         format!(
-            "{}|{}|{}",
-            ctx.app.name, ctx.app.window_title, clipboard_hash
+            "{}|{}|{}|{}",
+            ctx.app.name, ctx.app.window_title, clipboard_hash, diagnostics_key
         )
     }
 
@@ -187,6 +211,12 @@ impl IntentDetector {
             clipboard_length: ctx.clipboard.as_ref().map(|c| c.len()).unwrap_or(0),
             clipboard_keywords: self.extract_clipboard_keywords(ctx),
             domain_hints: self.extract_domain_hints(ctx),
+            diagnostics: ctx
+                .diagnostics
+                .iter()
+                .take(MAX_PROMPT_DIAGNOSTICS)
+                .map(|d| d.summary())
+                .collect(),
         }
     }
 
@@ -256,6 +286,12 @@ impl IntentDetector {
             }
         }
 
+        // Parsed compiler/runtime errors (screen or clipboard)
+        if diagnostics::has_errors(&ctx.diagnostics) && !hints.iter().any(|h| h == "error_analysis")
+        {
+            hints.push("error_analysis".to_string());
+        }
+
         hints
     }
 
@@ -273,6 +309,7 @@ impl IntentDetector {
     fn build_prompt(&self, ctx: &Context, fingerprint: &ContextFingerprint) -> String {
         let domain_hints = fingerprint.domain_hints.join(", ");
         let clipboard_keywords = fingerprint.clipboard_keywords.join(", ");
+        let diagnostics = fingerprint.diagnostics.join("; ");
 
         format!(
             r#"You are analyzing user intent in a software application context.
@@ -284,9 +321,11 @@ Context:
 - Clipboard length: {} characters
 - Clipboard keywords: {}
 - Domain hints: {}
+- Compiler/runtime errors: {}
 
 Additional signals:
 - Idle > 20s suggests user might be stuck
+- Compiler/runtime errors suggest debugging, or stuck if the user is also idle
 - Clipboard contains code suggests debugging/learning
 - Browser + StackOverflow suggests researching
 - Terminal suggests system administration
@@ -313,6 +352,11 @@ Response:"#,
                 "none"
             } else {
                 &domain_hints
+            },
+            if diagnostics.is_empty() {
+                "none"
+            } else {
+                &diagnostics
             }
         )
     }
@@ -380,9 +424,19 @@ Response:"#,
     fn create_fallback_intent(&self, ctx: &Context) -> Result<Intent, String> {
         // Heuristic-based intent detection as fallback
         let intent_type = self.heuristic_intent_detection(ctx);
-        let confidence = 0.3; // Low confidence for fallback
 
-        let reason = format!("Fallback heuristic: {}", intent_type.as_str());
+        // A parsed error is a strong signal on its own; other heuristics stay low confidence
+        let (confidence, reason) = match ctx.diagnostics.iter().find(|d| d.is_error()) {
+            Some(diagnostic) => (
+                0.6,
+                format!(
+                    "Fallback heuristic: {} ({})",
+                    intent_type.as_str(),
+                    diagnostic.label()
+                ),
+            ),
+            None => (0.3, format!("Fallback heuristic: {}", intent_type.as_str())),
+        };
 
         Ok(Intent {
             intent_type,
@@ -400,6 +454,15 @@ Response:"#,
         let app_lower = ctx.app.name.to_lowercase();
         let title_lower = ctx.app.window_title.to_lowercase();
 
+        // Compiler/runtime errors: debugging, or stuck on them when idle
+        if diagnostics::has_errors(&ctx.diagnostics) {
+            return if ctx.idle_seconds > 60.0 {
+                IntentType::Stuck
+            } else {
+                IntentType::Debugging
+            };
+        }
+
         // Debugging patterns
         if title_lower.contains("error")
             || title_lower.contains("exception")
@@ -450,4 +513,68 @@ struct ContextFingerprint {
     clipboard_length: usize,
     clipboard_keywords: Vec<String>,
     domain_hints: Vec<String>,
+    /// Summaries of the first parsed diagnostics
+    diagnostics: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::app_detector::ActiveApp;
+
+    fn detector() -> IntentDetector {
+        IntentDetector::new(Arc::new(LlmClient::new(vec![Arc::new(
+            crate::llm::mock::MockProvider::new("mock"),
+        )])))
+    }
+
+    fn context(clipboard: &str, idle_seconds: f64) -> Context {
+        Context {
+            id: "ctx".to_string(),
+            app: ActiveApp {
+                bundle_id: "com.apple.terminal".to_string(),
+                name: "Terminal".to_string(),
+                window_title: "zsh".to_string(),
+                pid: 1,
+                timestamp: 0,
+                tcc_status: None,
+                bounds: None,
+            },
+            clipboard: Some(clipboard.to_string()),
+            idle_seconds,
+            timestamp: 0,
+            capture_duration_ms: 0,
+            diagnostics: diagnostics::parse_diagnostics(clipboard),
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_drive_fallback_intent() {
+        let detector = detector();
+        let error = "error[E0382]: borrow of moved value: `x`\n  --> src/main.rs:5:20";
+
+        let intent = detector
+            .create_fallback_intent(&context(error, 5.0))
+            .unwrap();
+        assert_eq!(intent.intent_type, IntentType::Debugging);
+        assert!(detector.should_proceed(&intent));
+        assert!(intent.reason.contains("rust E0382"));
+
+        let stuck = detector
+            .create_fallback_intent(&context(error, 90.0))
+            .unwrap();
+        assert_eq!(stuck.intent_type, IntentType::Stuck);
+
+        // Without a parsed error the fallback stays below the threshold
+        let plain = detector
+            .create_fallback_intent(&context("hello", 5.0))
+            .unwrap();
+        assert!(!detector.should_proceed(&plain));
+
+        let prompt = detector.build_prompt(
+            &context(error, 5.0),
+            &detector.generate_context_fingerprint(&context(error, 5.0)),
+        );
+        assert!(prompt.contains("rust E0382 at src/main.rs:5"));
+    }
 }
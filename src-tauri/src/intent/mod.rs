@@ -7,10 +7,9 @@ use std::time::{Duration, Instant};
 use tracing::{debug, info};
 
 pub mod detector;
-pub mod llm_client;
 
+use crate::llm::LlmClient;
 use detector::*;
-use llm_client::*;
 
 /// Intent detection system with LLM integration
 #[derive(Debug)]
@@ -22,7 +21,7 @@ pub struct IntentSystem {
 
 #[allow(dead_code)]
 impl IntentSystem {
-    pub fn new(llm_client: Arc<Mutex<LLMClient>>) -> Self {
+    pub fn new(llm_client: Arc<LlmClient>) -> Self {
         Self {
             detector: IntentDetector::new(llm_client),
             stats: IntentStats::new(),
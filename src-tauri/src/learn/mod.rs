@@ -9,7 +9,9 @@ use std::collections::VecDeque;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::screenshot::store::{ScreenshotHolder, ScreenshotStore};
 
 pub mod exporter;
 
@@ -86,6 +88,7 @@ pub struct LearnManager {
     tutorials: Vec<Tutorial>,
     action_buffer: VecDeque<WorkflowAction>,
     max_workflows: usize,
+    screenshots: Option<Arc<ScreenshotStore>>,
 }
 
 impl LearnManager {
@@ -97,9 +100,16 @@ impl LearnManager {
             tutorials: Vec::new(),
             action_buffer: VecDeque::new(),
             max_workflows,
+            screenshots: None,
         }
     }
 
+    /// Keep action screenshots in the shared content-addressed store
+    pub fn with_screenshot_store(mut self, store: Arc<ScreenshotStore>) -> Self {
+        self.screenshots = Some(store);
+        self
+    }
+
     pub fn start_recording(&mut self, title: &str, app_name: &str) -> Result<String, String> {
         if self.is_recording {
             return Err("Already recording a workflow".to_string());
@@ -154,11 +164,15 @@ impl LearnManager {
         Ok(workflow)
     }
 
-    pub fn record_action(&mut self, action: WorkflowAction) {
+    pub fn record_action(&mut self, mut action: WorkflowAction) {
         if !self.is_recording {
             return;
         }
 
+        if let (Some(path), Some(workflow)) = (&action.screenshot_path, &self.current_workflow) {
+            let stored = self.store_screenshot(&workflow.id, path);
+            action.screenshot_path = Some(stored);
+        }
         self.action_buffer.push_back(action);
 
         // Limit buffer size
@@ -167,6 +181,47 @@ impl LearnManager {
         }
     }
 
+    /// Move a captured file into the screenshot store, held by `workflow_id`.
+    /// Returns the `screenshot:<id>` reference, or the path unchanged without a store.
+    fn store_screenshot(&self, workflow_id: &str, screenshot_path: &str) -> String {
+        let Some(store) = &self.screenshots else {
+            return screenshot_path.to_string();
+        };
+        let holder = ScreenshotHolder::Workflow(workflow_id.to_string());
+
+        match store.adopt(screenshot_path, holder) {
+            Ok(reference) => reference,
+            Err(e) => {
+                warn!("⚠️ Failed to store workflow screenshot: {}", e);
+                screenshot_path.to_string()
+            }
+        }
+    }
+
+    /// `(holder id, screenshot_path)` for every screenshot still shown by a workflow (recorded
+    /// or in progress) or by a tutorial generated from one
+    pub fn screenshot_refs(&self) -> Vec<(String, String)> {
+        let workflows = self
+            .workflows
+            .iter()
+            .flat_map(|w| w.actions.iter().map(move |a| (&w.id, &a.screenshot_path)));
+        let buffer = &self.action_buffer;
+        let recording = self
+            .current_workflow
+            .iter()
+            .flat_map(|w| buffer.iter().map(move |a| (&w.id, &a.screenshot_path)));
+        let tutorials = self
+            .tutorials
+            .iter()
+            .flat_map(|t| t.steps.iter().map(move |step| (&t.id, &step.screenshot_path)));
+
+        workflows
+            .chain(recording)
+            .chain(tutorials)
+            .filter_map(|(id, path)| Some((id.clone(), path.clone()?)))
+            .collect()
+    }
+
     pub fn add_comment(&mut self, comment: &str) -> Result<(), String> {
         if !self.is_recording {
             return Err("Not recording".to_string());
@@ -8,8 +8,8 @@ use crate::adaptive::AdaptivePromptEngine;
 use crate::artefact::ArtefactSystem;
 use crate::clustering::{ClusteringSystem, ProcessedContext};
 use crate::context::aggregator::Context;
-use crate::intent::llm_client::{LLMClient, LLMProvider};
 use crate::intent::{Intent, IntentSystem};
+use crate::llm::LlmClient;
 use crate::persistence::database::DatabaseManager;
 use crate::validator::{ArtefactType, ArtefactValidator, ValidationResult};
 
@@ -54,18 +54,17 @@ pub struct LearningSystem {
     intent_system: IntentSystem,
     adaptive_engine: AdaptivePromptEngine, // J22
     artefact_system: ArtefactSystem, // J23
+    last_processed: Option<ProcessedContextWithIntent>,
     db: Arc<Mutex<DatabaseManager>>,
     device_id: String,
 }
 
 impl LearningSystem {
-    pub fn new(db: Arc<Mutex<DatabaseManager>>, device_id: String) -> Self {
-        // Initialize LLM client (default to Ollama, can be configured later)
-        let llm_client = Arc::new(std::sync::Mutex::new(LLMClient::new(
-            LLMProvider::Ollama,
-            None,
-        )));
-
+    pub fn new(
+        db: Arc<Mutex<DatabaseManager>>,
+        device_id: String,
+        llm_client: Arc<LlmClient>,
+    ) -> Self {
         // Initialize trust manager
         let trust_manager = TrustManager::new(db.clone(), device_id.clone());
 
@@ -76,9 +75,10 @@ impl LearningSystem {
             feedback_collector: FeedbackCollector::new(TrustManager::new(db.clone(), device_id.clone())), // J24
             validator: ArtefactValidator::new(),
             clustering_system: ClusteringSystem::new(),
-            intent_system: IntentSystem::new(llm_client),
+            intent_system: IntentSystem::new(llm_client.clone()),
             adaptive_engine: AdaptivePromptEngine::new(), // J22
-            artefact_system: ArtefactSystem::new(), // J23
+            artefact_system: ArtefactSystem::new(llm_client), // J23
+            last_processed: None,
             db,
             device_id,
         }
@@ -407,6 +407,11 @@ impl LearningSystem {
         let intent = self.intent_system.detect_intent(ctx).await?;
         let intent_ms = intent_start.elapsed().as_millis();
 
+        self.last_processed = Some(ProcessedContextWithIntent {
+            processed_context: processed_context.clone(),
+            intent: intent.clone(),
+        });
+
         // Step 3: Check if intent is confident enough to proceed
         if !self.intent_system.should_proceed(&intent) {
             warn!(
@@ -426,6 +431,12 @@ impl LearningSystem {
         })
     }
 
+    /// Last context processed by `process_context` (cluster + detected intent),
+    /// including low-confidence intents
+    pub fn last_processed(&self) -> Option<&ProcessedContextWithIntent> {
+        self.last_processed.as_ref()
+    }
+
     /// Get clustering statistics
     #[allow(dead_code)]
     pub fn get_clustering_stats(&self) -> &crate::clustering::ClusteringStats {
@@ -467,11 +478,20 @@ impl LearningSystem {
         self.intent_system.clear_cache();
     }
 
-    /// Switch LLM provider
-    pub fn switch_llm_provider(&mut self, provider: LLMProvider, _api_key: Option<String>) {
-        // This would require modifying IntentSystem to expose the LLM client
-        // For now, we'll just log the request
-        info!("[LEARNING] LLM provider switch requested: {:?}", provider);
+    /// Forget an app: its clusters, the last processed context and cached intents
+    pub fn forget_app(&mut self, matcher: &crate::privacy::erasure::AppMatcher) -> usize {
+        let removed = self.clustering_system.forget_app(matcher);
+
+        let last_app_forgotten = self.last_processed.as_ref().is_some_and(|last| {
+            let app = &last.processed_context.context.app;
+            matcher.matches_window(&app.name, &app.window_title)
+        });
+        if last_app_forgotten {
+            self.last_processed = None;
+        }
+        self.intent_system.clear_cache();
+
+        removed
     }
 
     /// Record feedback for an opportunity or message
@@ -513,7 +533,10 @@ mod tests {
     #[tokio::test]
     async fn test_learning_system() {
         let db = Arc::new(DatabaseManager::new(":memory:").await.unwrap());
-        let mut learning_system = LearningSystem::new(db, "test_device".to_string());
+        let llm_client = Arc::new(LlmClient::new(vec![Arc::new(
+            crate::llm::mock::MockProvider::new("mock"),
+        )]));
+        let mut learning_system = LearningSystem::new(db, "test_device".to_string(), llm_client);
         learning_system.initialize().await.unwrap();
 
         // Test outcome positif
@@ -528,6 +551,7 @@ mod tests {
             idle_seconds: 5.0,
             timestamp: 1234567890,
             capture_duration_ms: 100,
+            diagnostics: Vec::new(),
         };
 
         let outcome = Outcome::Used {
@@ -12,8 +12,10 @@ mod crypto;
 mod permissions;
 mod privacy; // Privacy zones for screen monitoring
 mod artefact;
+mod budget; // Resource budget governor
 mod clustering;
 mod context;
+mod diagnostics; // Compiler/runtime error parsing (screen + clipboard)
 mod digest; // Clueless: Daily Digest
 mod features;
 mod flow; // Clueless: Flow State Detection
@@ -23,6 +25,7 @@ mod productivity; // Phase 3: Productivity Dashboard & Weekly Insights
 mod health;
 mod intent;
 mod learning;
+mod llm; // Unified LLM providers
 mod ml;
 mod monitor; // Screen Monitoring avec détection de changements
 mod shortcuts; // Global keyboard shortcuts
@@ -32,9 +35,10 @@ mod patterns; // Phase 2.1: Pattern Recognition ML
 mod persistence;
 mod personality; // Clueless Phase 3: Personalities
 mod pills; // Clueless: Smart Pills / Micro Suggestions
-mod plugins; // Phase 4: Plugin System
+pub mod plugins; // Phase 4: Plugin System
 mod recovery;
 mod replay; // Killer Feature: Shadow Replay
+mod retention; // Data retention enforcement
 mod streaks; // Clueless Phase 3: Streaks
 mod screenshot;
 mod snooze;
@@ -50,7 +54,8 @@ use ml::{
     UserResponse,
 };
 use persistence::{
-    CapturedContext, Conversation, Message, MessageRole, PersistenceManager, PersistenceStats,
+    CapturedContext, Conversation, ImportReport, Message, MessageRole, PersistenceManager,
+    PersistenceStats, SearchHit, SearchQuery,
 };
 use recovery::{RecoveryManager, RecoveryStats};
 use snooze::{SnoozeDuration, SnoozeManager};
@@ -487,9 +492,54 @@ async fn get_persistence_stats(
 async fn export_data(
     file_path: String,
     persistence_manager: tauri::State<'_, Arc<Mutex<PersistenceManager>>>,
+    pattern_manager: tauri::State<'_, Arc<patterns::commands::PatternManager>>,
+    replay_manager: tauri::State<'_, Arc<Mutex<replay::ReplayManager>>>,
 ) -> Result<(), String> {
     let manager = persistence_manager.lock().await;
-    manager.export_data(&file_path).await
+    let mut bundle = manager.export_bundle().await?;
+
+    bundle.patterns = pattern_manager.get_patterns().await;
+    bundle.repetitive_tasks = pattern_manager.get_repetitive_tasks().await;
+    bundle.replay_events = replay_manager.lock().await.get_recent_events(usize::MAX);
+
+    manager.export_data(&file_path, &bundle).await
+}
+
+#[tauri::command]
+async fn import_data(
+    file_path: String,
+    persistence_manager: tauri::State<'_, Arc<Mutex<PersistenceManager>>>,
+    pattern_manager: tauri::State<'_, Arc<patterns::commands::PatternManager>>,
+    replay_manager: tauri::State<'_, Arc<Mutex<replay::ReplayManager>>>,
+) -> Result<ImportReport, String> {
+    let mut bundle = PersistenceManager::read_export_file(&file_path)?;
+    let mut report = ImportReport {
+        bundle_version: bundle.version,
+        ..Default::default()
+    };
+
+    persistence_manager
+        .lock()
+        .await
+        .import_bundle(&mut bundle, &mut report)
+        .await?;
+
+    let (patterns_added, tasks_added) = pattern_manager
+        .merge_import(bundle.patterns.clone(), bundle.repetitive_tasks.clone())
+        .await?;
+    report.patterns.imported = patterns_added;
+    report.patterns.skipped = bundle.patterns.len() - patterns_added;
+    report.repetitive_tasks.imported = tasks_added;
+    report.repetitive_tasks.skipped = bundle.repetitive_tasks.len() - tasks_added;
+
+    let events_added = replay_manager
+        .lock()
+        .await
+        .import_events(bundle.replay_events.clone());
+    report.replay_events.imported = events_added;
+    report.replay_events.skipped = bundle.replay_events.len() - events_added;
+
+    Ok(report)
 }
 
 #[tauri::command]
@@ -511,6 +561,23 @@ async fn save_context(
     manager.save_context(context).await
 }
 
+#[tauri::command]
+async fn search_history(
+    query: SearchQuery,
+    persistence_manager: tauri::State<'_, Arc<Mutex<PersistenceManager>>>,
+) -> Result<Vec<SearchHit>, String> {
+    let manager = persistence_manager.lock().await;
+    manager.search(&query).await
+}
+
+#[tauri::command]
+async fn rotate_encryption_key(
+    persistence_manager: tauri::State<'_, Arc<Mutex<PersistenceManager>>>,
+) -> Result<usize, String> {
+    let manager = persistence_manager.lock().await;
+    manager.rotate_encryption_key().await
+}
+
 // ========== J16: DÉCLENCHEMENT DISCRET COMMANDS ==========
 #[tauri::command]
 async fn set_bubble_visible(
@@ -795,6 +862,7 @@ async fn record_user_feedback(
             .unwrap()
             .as_secs(),
         capture_duration_ms: 0,
+        diagnostics: Vec::new(),
     };
 
     let mut system = learning_system.lock().await;
@@ -1052,6 +1120,8 @@ async fn generate_artifact(
     idle_time: f32,
     cluster_id: String,
     artefact_type: String,
+    llm_client: tauri::State<'_, Arc<llm::LlmClient>>,
+    context_aggregator: tauri::State<'_, Arc<Mutex<ContextAggregator>>>,
 ) -> Result<crate::artefact::GeneratedArtifact, String> {
     // TODO: Get learning system from app state
     // For now, return a placeholder
@@ -1068,7 +1138,15 @@ async fn generate_artifact(
         _ => ArtefactType::Unknown,
     };
 
-    let mut generator = ArtefactGenerator::new();
+    // The active app decides what the egress policy lets through
+    let origin = context_aggregator
+        .lock()
+        .await
+        .peek()
+        .ok()
+        .map(|peek| crate::privacy::RequestOrigin::new(peek.app.name, peek.app.window_title));
+
+    let mut generator = ArtefactGenerator::new(llm_client.inner().clone()).with_origin(origin);
     generator.generate(
         domain,
         intent,
@@ -1122,13 +1200,72 @@ pub async fn run() {
 
     // Initialize system components
     let feature_flags = Arc::new(FeatureFlags::from_env());
-    let health_monitor = Arc::new(HealthMonitor::new());
     let recovery_manager = Arc::new(RecoveryManager::new()); // Max 3 restarts
     let telemetry = Arc::new(Telemetry::new(1000, 100)); // 1000 events, 100 samples per histogram
 
+    // Initialize config manager (J5)
+    let config_manager = Arc::new(Mutex::new(
+        config::ConfigManager::new().expect("Failed to initialize config manager")
+    ));
+    info!("✅ Config manager initialized");
+
+    // Initialize redactor (secrets/PII masked before persistence and LLM/vision calls)
+    let redaction_config = config_manager
+        .lock()
+        .await
+        .get_config()
+        .unwrap_or_default()
+        .redaction;
+    let redactor = Arc::new(privacy::Redactor::new(&redaction_config).unwrap_or_else(|e| {
+        warn!("⚠️ Invalid redaction config ({}), using built-in detectors", e);
+        privacy::Redactor::default()
+    }));
+    info!("✅ Redactor initialized");
+
+    // Initialize egress policy (what may reach remote LLM / vision providers)
+    let egress_config = config_manager
+        .lock()
+        .await
+        .get_config()
+        .unwrap_or_default()
+        .egress;
+    let egress_policy = Arc::new(privacy::EgressPolicy::new(&egress_config).unwrap_or_else(|e| {
+        warn!("⚠️ Invalid egress config ({}), remote providers restricted to local-only", e);
+        privacy::EgressPolicy::new(&config::types::EgressConfig {
+            local_only: true,
+            ..Default::default()
+        })
+        .expect("local-only egress config is valid")
+    }));
+    info!("✅ Egress policy initialized");
+
+    // Initialize resource budget governor (capture, OCR, vision, LLM, triggers)
+    let budget_config = config_manager
+        .lock()
+        .await
+        .get_config()
+        .unwrap_or_default()
+        .budget;
+    let budget_governor = Arc::new(budget::BudgetGovernor::new(budget_config));
+    let health_monitor =
+        Arc::new(HealthMonitor::new().with_budget_governor(budget_governor.clone()));
+    info!("✅ Budget governor initialized");
+
+    // Initialize privacy audit log (what was captured, persisted and sent; own retention)
+    let audit_log = Arc::new(privacy::AuditLog::new(
+        config_manager
+            .lock()
+            .await
+            .get_config_path()
+            .with_file_name("privacy_audit.jsonl"),
+    ));
+    info!("✅ Privacy audit log: {}", audit_log.path().display());
+
     // Initialize context aggregator
     let context_aggregator = Arc::new(Mutex::new(
-        ContextAggregator::new().expect("Failed to initialize context aggregator"),
+        ContextAggregator::new()
+            .expect("Failed to initialize context aggregator")
+            .with_redactor(redactor.clone()),
     ));
 
     // Initialize trigger manager
@@ -1139,10 +1276,6 @@ pub async fn run() {
     let state_machine = Arc::new(Mutex::new(triggers::state_machine::TriggerStateMachine::new()));
     info!("✅ State machine initialized");
 
-    // Initialize LLM chat client (J3)
-    let llm_client = Arc::new(tokio::sync::Mutex::new(chat::LLMChatClient::new()));
-    info!("✅ LLM chat client initialized");
-
     // Initialize snooze manager
     let snooze_manager = Arc::new(Mutex::new(
         SnoozeManager::new().expect("Failed to initialize snooze manager"),
@@ -1150,22 +1283,52 @@ pub async fn run() {
     info!("✅ Snooze manager initialized");
 
     // Initialize persistence manager
-    let persistence_manager = Arc::new(Mutex::new(
-        PersistenceManager::new()
-            .await
-            .expect("Failed to initialize persistence manager"),
-    ));
+    let mut persistence = PersistenceManager::new()
+        .await
+        .expect("Failed to initialize persistence manager");
+    persistence.set_redactor(redactor.clone());
+    persistence.set_audit_log(audit_log.clone());
+
+    // Initialize screenshot store (content-addressed, shared by replay, workflows and contexts)
+    let encrypt_screenshots = config_manager
+        .lock()
+        .await
+        .get_config()
+        .map(|config| config.captures.encrypt_screenshots)
+        .unwrap_or_default();
+    let screenshot_store = Arc::new(
+        screenshot::ScreenshotStore::open(
+            persistence
+                .data_dir()
+                .await
+                .unwrap_or_else(screenshot::screenshot_dir)
+                .join(screenshot::store::STORE_DIR_NAME),
+            persistence.cipher().await,
+            encrypt_screenshots,
+        )
+        .expect("Failed to open screenshot store"),
+    );
+    persistence.set_screenshot_store(screenshot_store.clone());
+    let persistence_manager = Arc::new(Mutex::new(persistence));
     info!("✅ Persistence manager initialized");
 
     // Initialize personalization manager (J18)
     let personalization_manager = Arc::new(Mutex::new(PersonalizationManager::new()));
     info!("✅ Personalization manager initialized");
 
-    // Initialize config manager (J5)
-    let config_manager = Arc::new(Mutex::new(
-        config::ConfigManager::new().expect("Failed to initialize config manager")
-    ));
-    info!("✅ Config manager initialized");
+    // Initialize shared LLM client (chat, intent, slash commands, artefacts) from config
+    let (llm_config, secrets) = {
+        let manager = config_manager.lock().await;
+        (manager.get_config().unwrap_or_default().llm, manager.secrets())
+    };
+    let llm_client = Arc::new(
+        llm::LlmClient::from_config(&llm_config, &secrets)
+            .with_redactor(redactor.clone())
+            .with_egress_policy(egress_policy.clone())
+            .with_audit_log(audit_log.clone())
+            .with_budget_governor(budget_governor.clone()),
+    );
+    info!("✅ LLM client initialized: {:?}", llm_client.provider_names());
 
     // Initialize pause manager
     let pause_manager = Arc::new(Mutex::new(pause::PauseManager::new()));
@@ -1207,7 +1370,9 @@ pub async fn run() {
     };
 
     // Initialize replay manager (Killer Feature)
-    let replay_manager = Arc::new(Mutex::new(replay::ReplayManager::new(10000))); // Store last 10k events
+    let replay_manager = Arc::new(Mutex::new(
+        replay::ReplayManager::new(10000).with_screenshot_store(screenshot_store.clone()),
+    )); // Store last 10k events
     info!("✅ Replay manager initialized");
 
     // Initialize focus manager (Killer Feature)
@@ -1215,7 +1380,9 @@ pub async fn run() {
     info!("✅ Focus manager initialized");
 
     // Initialize learn manager (Killer Feature)
-    let learn_manager = Arc::new(Mutex::new(learn::LearnManager::new(100))); // Store 100 workflows
+    let learn_manager = Arc::new(Mutex::new(
+        learn::LearnManager::new(100).with_screenshot_store(screenshot_store.clone()),
+    )); // Store 100 workflows
     info!("✅ Learn manager initialized");
 
     // Initialize screenshot capturer
@@ -1228,7 +1395,13 @@ pub async fn run() {
 
     // Initialize screen monitor
     let monitor_config = monitor::MonitorConfig::default();
-    let screen_monitor = Arc::new(Mutex::new(monitor::ScreenMonitor::new(monitor_config)));
+    let screen_monitor = Arc::new(Mutex::new(
+        monitor::ScreenMonitor::new(monitor_config, llm::selection::api_key(&secrets, "anthropic"))
+            .with_redactor(redactor.clone())
+            .with_egress_policy(egress_policy.clone())
+            .with_audit_log(audit_log.clone())
+            .with_budget_governor(budget_governor.clone()),
+    ));
     info!("✅ Screen monitor initialized");
 
     // Initialize shortcut manager
@@ -1236,9 +1409,13 @@ pub async fn run() {
     let shortcut_manager = Arc::new(Mutex::new(shortcuts::ShortcutManager::new(shortcut_config)));
     info!("✅ Shortcut manager initialized");
 
-    // Initialize privacy zone manager
-    let privacy_config = privacy::PrivacyZonesConfig::default();
-    let privacy_manager = Arc::new(Mutex::new(privacy::PrivacyZoneManager::new(privacy_config)));
+    // Initialize privacy zone manager (persisted next to config.json, migrated on load)
+    let privacy_zones_path = config_manager
+        .lock()
+        .await
+        .get_config_path()
+        .with_file_name("privacy_zones.json");
+    let privacy_manager = Arc::new(Mutex::new(privacy::PrivacyZoneManager::load(privacy_zones_path)));
     info!("✅ Privacy zone manager initialized");
 
     // Initialize pattern recognition manager (Phase 2.1)
@@ -1256,6 +1433,18 @@ pub async fn run() {
         }
     };
 
+    // Initialize retention engine (purges every store according to RetentionConfig)
+    let retention_engine = Arc::new(retention::RetentionEngine::new(
+        persistence_manager.clone(),
+        replay_manager.clone(),
+        learn_manager.clone(),
+        pattern_manager.clone(),
+        personalization_manager.clone(),
+        audit_log.clone(),
+        screenshot_store.clone(),
+    ));
+    info!("✅ Retention engine initialized");
+
     // Log feature state
     let state = feature_flags.get_state();
     info!("✅ Features enabled: {}/{}", state.enabled_count(), 4);
@@ -1318,10 +1507,12 @@ pub async fn run() {
         .manage(trigger_manager.clone())
         .manage(state_machine.clone())
         .manage(llm_client.clone())
+        .manage(Arc::new(chat::stream::ChatStreamRegistry::new()))
         .manage(snooze_manager.clone())
         .manage(Arc::new(Mutex::new(learning::LearningSystem::new(
             persistence_manager.lock().await.get_database(),
             "default_device".to_string(),
+            llm_client.clone(),
         ))))
         .manage(persistence_manager.clone())
         .manage(personalization_manager.clone())
@@ -1340,6 +1531,12 @@ pub async fn run() {
         .manage(shortcut_manager) // Global Shortcuts
         .manage(privacy_manager) // Privacy Zones
         .manage(pattern_manager) // Phase 2.1: Pattern Recognition ML
+        .manage(retention_engine) // Data retention
+        .manage(redactor) // Secret/PII redaction
+        .manage(egress_policy) // Remote provider egress policy
+        .manage(budget_governor) // Resource budget governor
+        .manage(audit_log) // Privacy audit log
+        .manage(screenshot_store) // Content-addressed screenshot store
         .invoke_handler(tauri::generate_handler![
             toggle_window,
             ensure_chat_visible,
@@ -1363,6 +1560,8 @@ pub async fn run() {
             screenshot::capture_screenshot,
             screenshot::check_screenshot_permission,
             screenshot::request_screenshot_permission,
+            screenshot::read_stored_screenshot,
+            screenshot::get_screenshot_store_stats,
             reset_user_activity,
             get_idle_state,
             snooze_triggers,
@@ -1375,8 +1574,11 @@ pub async fn run() {
             get_conversation_messages,
             get_persistence_stats,
             export_data,
+            import_data,
             get_recent_contexts_for_app,
             save_context,
+            search_history,
+            rotate_encryption_key,
             // J16: Anti-spam & UX commands
             record_trigger_ignored,
             mute_app,
@@ -1465,11 +1667,14 @@ pub async fn run() {
             triggers::state_machine::get_state_history,
             // J3: Chat LLM commands
             chat::commands::chat_with_ai,
+            chat::commands::cancel_chat,
             chat::commands::check_llm_health,
             chat::commands::get_llm_stats,
             // J5: Config & Privacy commands
             config::manager::get_config,
             config::manager::update_config,
+            config::manager::set_llm_api_key,
+            config::manager::has_llm_api_key,
             config::manager::get_config_path,
             // Screen Monitor commands
             monitor::commands::start_screen_monitor,
@@ -1478,6 +1683,7 @@ pub async fn run() {
             monitor::commands::reset_monitor_detector,
             monitor::commands::reset_monitor_cache,
             monitor::commands::get_monitor_cache_stats,
+            monitor::commands::set_monitor_vision_provider,
             // Keyboard Shortcuts commands
             shortcuts::commands::get_shortcuts_config,
             shortcuts::commands::list_shortcuts,
@@ -1485,10 +1691,17 @@ pub async fn run() {
             shortcuts::commands::toggle_spotlight,
             // Privacy Zones commands
             privacy::commands::get_privacy_zones_config,
+            // Data retention
+            retention::commands::run_retention_now,
+            retention::commands::get_last_retention_report,
+            retention::commands::get_privacy_stats,
             privacy::commands::add_privacy_zone,
             privacy::commands::remove_privacy_zone,
             privacy::commands::set_privacy_zones_enabled,
             privacy::commands::is_app_protected,
+            privacy::commands::forget_app,
+            privacy::commands::query_audit_log,
+            privacy::commands::generate_audit_report,
             // Phase 3: Productivity Dashboard commands
             productivity::get_productivity_metrics,
             productivity::record_productivity_event,
@@ -1713,6 +1926,14 @@ pub async fn run() {
                 warn!("⚠️ HUD window NOT FOUND!");
             }
 
+            // Rétention périodique des données
+            let retention_engine = app.state::<Arc<retention::RetentionEngine>>().inner().clone();
+            let config_manager = app.state::<Arc<Mutex<config::ConfigManager>>>().inner().clone();
+            tauri::async_runtime::spawn(retention::run_retention_loop(
+                retention_engine,
+                config_manager,
+            ));
+
             // 🔥 Lance automatiquement la boucle de triggers
             tauri::async_runtime::spawn(triggers::trigger_loop::start_trigger_loop(
                 app.handle().clone(),
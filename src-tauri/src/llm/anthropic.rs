@@ -0,0 +1,148 @@
+use super::stream::{read_stream, send, AnthropicDecoder};
+use super::{ChatMessage, LlmProvider, LlmRequest, StreamEnd};
+use crate::privacy::egress::is_local_url;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+
+pub const DEFAULT_ANTHROPIC_URL: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// The Messages API requires `max_tokens`
+const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+/// Anthropic Messages API (`/messages`, typed SSE streaming)
+pub struct AnthropicProvider {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(base_url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: api_key.into(),
+            model: "claude-3-haiku-20240307".to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct AnthropicRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<&'a ChatMessage>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop_sequences: &'a [String],
+    stream: bool,
+}
+
+/// System messages go to the top-level `system` field, the rest stays in order
+fn split_system(messages: &[ChatMessage]) -> (Option<String>, Vec<&ChatMessage>) {
+    let system: Vec<&str> = messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let turns = messages.iter().filter(|m| m.role != "system").collect();
+
+    let system = if system.is_empty() {
+        None
+    } else {
+        Some(system.join("\n\n"))
+    };
+    (system, turns)
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    fn is_local(&self) -> bool {
+        is_local_url(&self.base_url)
+    }
+
+    async fn is_available(&self) -> bool {
+        let request = self
+            .http
+            .get(format!("{}/models", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .timeout(Duration::from_secs(5));
+        match request.send().await {
+            Ok(resp) => resp.status().is_success(),
+            Err(_) => false,
+        }
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        cancel: &mut watch::Receiver<bool>,
+        on_delta: &mut (dyn for<'a> FnMut(&'a str) + Send),
+    ) -> Result<StreamEnd, String> {
+        let model = request.options.model.as_deref().unwrap_or(&self.model);
+        let (system, messages) = split_system(&request.messages);
+        let body = AnthropicRequest {
+            model,
+            max_tokens: request.options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            temperature: request.options.temperature,
+            system,
+            messages,
+            stop_sequences: &request.options.stop,
+            stream: true,
+        };
+
+        let builder = self
+            .http
+            .post(format!("{}/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body);
+        let response = send(builder.send()).await?;
+        let cancelled =
+            read_stream(response, AnthropicDecoder::default(), cancel, on_delta).await?;
+
+        Ok(StreamEnd {
+            model: model.to_string(),
+            cancelled,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_system_moves_system_messages() {
+        let messages = vec![
+            ChatMessage::system("Be brief."),
+            ChatMessage::user("Hi"),
+            ChatMessage::system("Answer in French."),
+        ];
+        let (system, turns) = split_system(&messages);
+
+        assert_eq!(system.as_deref(), Some("Be brief.\n\nAnswer in French."));
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].role, "user");
+    }
+}
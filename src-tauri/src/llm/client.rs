@@ -0,0 +1,724 @@
+use super::selection::{build_chain, build_fallbacks};
+use super::{
+    wait_cancelled, LlmError, LlmProvider, LlmRequest, LlmResponse, OllamaProvider, StreamEnd,
+};
+use crate::budget::{BudgetGovernor, Subsystem};
+use crate::config::types::{EgressFallback, LLMConfig};
+use crate::crypto::keymanager::SecretStore;
+use crate::privacy::audit::{count_redactions, EgressOutcome, OutboundChannel};
+use crate::privacy::egress::EgressDecision;
+use crate::privacy::{AuditEvent, AuditLog, EgressError, EgressPolicy, Redactor};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
+use tracing::{error, info, warn};
+
+/// Attempts on the primary provider before moving down the fallback chain
+const PRIMARY_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmStats {
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub failed_requests: u64,
+    pub fallback_used: u64,
+    pub avg_ttfr_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub model: String,
+    pub available: bool,
+}
+
+/// Progress of a streamed generation, shared across retries
+struct StreamState {
+    started_at: Instant,
+    content: String,
+    first_token_ms: Option<u64>,
+}
+
+impl StreamState {
+    fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            content: String::new(),
+            first_token_ms: None,
+        }
+    }
+
+    fn push(&mut self, delta: &str) {
+        if self.first_token_ms.is_none() {
+            self.first_token_ms = Some(self.started_at.elapsed().as_millis() as u64);
+        }
+        self.content.push_str(delta);
+    }
+
+    fn has_output(&self) -> bool {
+        self.first_token_ms.is_some()
+    }
+
+    /// Time to first token (whole duration if nothing was received)
+    fn ttfr_ms(&self) -> u64 {
+        self.first_token_ms
+            .unwrap_or_else(|| self.started_at.elapsed().as_millis() as u64)
+    }
+}
+
+/// Providers the egress policy lets a request use
+struct AllowedProviders {
+    providers: Vec<Arc<dyn LlmProvider>>,
+    /// Outcome recorded for the remote providers of the chain
+    egress: EgressOutcome,
+    /// Why the policy rerouted or asked for confirmation
+    reason: Option<String>,
+}
+
+impl AllowedProviders {
+    fn new(
+        providers: Vec<Arc<dyn LlmProvider>>,
+        egress: EgressOutcome,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            providers,
+            egress,
+            reason,
+        }
+    }
+}
+
+/// Shared entry point for every LLM call: primary provider with retries, then the
+/// fallback chain. Retries and fallback only happen before the first token, so
+/// streamed chunks are never repeated.
+pub struct LlmClient {
+    /// Replaced as a whole on reconfiguration; in-flight requests keep their snapshot
+    providers: RwLock<Vec<Arc<dyn LlmProvider>>>,
+    retry_backoff: Duration,
+    /// Applied to every message before it reaches a provider
+    redactor: Arc<Redactor>,
+    /// Decides whether a request may reach remote providers
+    egress: Arc<EgressPolicy>,
+    /// Every provider a request is sent to (or refused from) is recorded here
+    audit: Option<Arc<AuditLog>>,
+    /// Time spent generating is accounted to the LLM budget (requests are never refused)
+    budget: Option<Arc<BudgetGovernor>>,
+    stats: Mutex<LlmStats>,
+}
+
+impl std::fmt::Debug for LlmClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LlmClient")
+            .field("providers", &self.provider_names())
+            .finish()
+    }
+}
+
+impl LlmClient {
+    /// `providers[0]` is the primary, the others are tried in order when it fails
+    pub fn new(providers: Vec<Arc<dyn LlmProvider>>) -> Self {
+        Self {
+            providers: RwLock::new(providers),
+            retry_backoff: Duration::from_secs(2),
+            redactor: Arc::new(Redactor::default()),
+            egress: Arc::new(EgressPolicy::default()),
+            audit: None,
+            budget: None,
+            stats: Mutex::new(LlmStats::default()),
+        }
+    }
+
+    /// Share the app-wide redactor (user patterns, common stats)
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Share the app-wide egress policy (hot-applied on config updates)
+    pub fn with_egress_policy(mut self, egress: Arc<EgressPolicy>) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    /// Record outbound requests in the app-wide privacy audit log
+    pub fn with_audit_log(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Account generation time in the app-wide resource budget
+    pub fn with_budget_governor(mut self, budget: Arc<BudgetGovernor>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Provider chain described by the configuration (API keys come from the keychain).
+    /// If the configured primary is unusable, the usable fallbacks are kept.
+    pub fn from_config(config: &LLMConfig, secrets: &SecretStore) -> Self {
+        let providers = build_chain(config, secrets).unwrap_or_else(|e| {
+            warn!(
+                "⚠️ Configured LLM provider unusable ({}), using fallbacks only",
+                e
+            );
+            build_fallbacks(config, secrets)
+        });
+        Self::new(providers)
+    }
+
+    /// Rebuild the provider chain from the configuration (hot-apply).
+    /// On error the current chain is kept.
+    pub fn apply_config(&self, config: &LLMConfig, secrets: &SecretStore) -> Result<(), String> {
+        let chain = build_chain(config, secrets)?;
+        self.reconfigure(chain);
+        Ok(())
+    }
+
+    /// Swap the provider chain; requests already running finish on the previous one
+    pub fn reconfigure(&self, providers: Vec<Arc<dyn LlmProvider>>) {
+        *self.providers.write().unwrap() = providers;
+        info!("🔁 LLM providers: {:?}", self.provider_names());
+    }
+
+    /// Base delay of the exponential backoff between primary attempts (2s, 4s, ...)
+    #[cfg(test)]
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Provider names, primary first
+    pub fn provider_names(&self) -> Vec<String> {
+        self.providers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect()
+    }
+
+    /// True if some provider of the chain is remote (egress policy applies)
+    pub fn has_remote_providers(&self) -> bool {
+        self.providers.read().unwrap().iter().any(|p| !p.is_local())
+    }
+
+    /// Generate a full completion (no streaming to the caller)
+    pub async fn complete(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        let (_never_cancelled, cancel) = watch::channel(false);
+        self.stream(request, cancel, |_| {}).await
+    }
+
+    /// Stream a completion: `on_delta` is called for every token chunk as it arrives.
+    /// Requests the egress policy keeps local only reach local providers, or fail with `LlmError::Egress`.
+    pub async fn stream<F>(
+        &self,
+        mut request: LlmRequest,
+        mut cancel: watch::Receiver<bool>,
+        mut on_delta: F,
+    ) -> Result<LlmResponse, LlmError>
+    where
+        F: FnMut(&str) + Send,
+    {
+        let mut state = StreamState::new();
+        let _work = self
+            .budget
+            .as_ref()
+            .map(|budget| budget.start(Subsystem::Llm));
+        self.stats.lock().await.total_requests += 1;
+
+        for message in &mut request.messages {
+            message.content = self.redactor.redact(&message.content);
+        }
+
+        let providers = self.providers.read().unwrap().clone();
+        let primary_name = providers.first().map(|p| p.name().to_string());
+        let AllowedProviders {
+            providers,
+            egress,
+            reason,
+        } = match self.allowed_providers(&request, providers) {
+            Ok(allowed) => allowed,
+            Err(e) => {
+                self.record_failure().await;
+                warn!("🔒 LLM request refused: {}", e);
+                self.audit_outbound(
+                    &request,
+                    primary_name.unwrap_or_default(),
+                    true,
+                    EgressOutcome::Refused,
+                    Some(e.to_string()),
+                );
+                return Err(e.into());
+            }
+        };
+        let Some(primary) = providers.first() else {
+            self.record_failure().await;
+            return Err(LlmError::Failed("No LLM provider configured".to_string()));
+        };
+
+        let mut last_error = String::new();
+        for (index, provider) in providers.iter().enumerate() {
+            let attempts = if index == 0 { PRIMARY_ATTEMPTS } else { 1 };
+            if index > 0 {
+                warn!(
+                    "{} failed, trying fallback {}...",
+                    primary.name(),
+                    provider.name()
+                );
+            }
+
+            if self.audit.is_some() {
+                let outcome = match (provider.is_local(), egress) {
+                    (true, EgressOutcome::Rerouted) => EgressOutcome::Rerouted,
+                    (true, _) => EgressOutcome::Local,
+                    (false, outcome) => outcome,
+                };
+                self.audit_outbound(
+                    &request,
+                    provider.name().to_string(),
+                    !provider.is_local(),
+                    outcome,
+                    reason.clone(),
+                );
+            }
+
+            for attempt in 0..attempts {
+                let result = {
+                    let mut forward = |delta: &str| {
+                        state.push(delta);
+                        on_delta(delta);
+                    };
+                    provider.stream(&request, &mut cancel, &mut forward).await
+                };
+
+                match result {
+                    Ok(end) => {
+                        if index > 0 {
+                            info!("✅ Fallback successful");
+                        }
+                        return Ok(self.finish(state, provider.as_ref(), end, index > 0).await);
+                    }
+                    Err(e) if state.has_output() => {
+                        self.record_failure().await;
+                        error!("❌ Stream interrupted: {}", e);
+                        return Err(LlmError::Failed(format!("LLM stream interrupted: {}", e)));
+                    }
+                    Err(e) => {
+                        warn!("{} attempt {} failed: {}", provider.name(), attempt + 1, e);
+                        last_error = e;
+                    }
+                }
+
+                if attempt + 1 < attempts {
+                    // Exponential backoff (interrupted by cancellation)
+                    let delay = self.retry_backoff * 2_u32.pow(attempt);
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = wait_cancelled(&mut cancel) => {
+                            let end = StreamEnd {
+                                model: provider.default_model().to_string(),
+                                cancelled: true,
+                            };
+                            return Ok(self.finish(state, provider.as_ref(), end, index > 0).await);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.record_failure().await;
+        error!("❌ All LLM providers failed. Last error: {}", last_error);
+        Err(LlmError::Failed(format!(
+            "LLM unavailable (all providers failed): {}",
+            last_error
+        )))
+    }
+
+    /// Provider chain this request may use: the whole chain if the egress policy allows
+    /// remote providers, otherwise its local providers (local Ollama if it has none),
+    /// or an error when the policy refuses blocked requests.
+    fn allowed_providers(
+        &self,
+        request: &LlmRequest,
+        providers: Vec<Arc<dyn LlmProvider>>,
+    ) -> Result<AllowedProviders, EgressError> {
+        let blocked = match self.egress.decide(request.origin.as_ref()) {
+            EgressDecision::Allow => {
+                return Ok(AllowedProviders::new(
+                    providers,
+                    EgressOutcome::Allowed,
+                    None,
+                ))
+            }
+            EgressDecision::Confirm { reason } if request.egress_confirmed => {
+                return Ok(AllowedProviders::new(
+                    providers,
+                    EgressOutcome::Confirmed,
+                    Some(reason),
+                ));
+            }
+            EgressDecision::Confirm { reason } => EgressError::ConfirmationRequired { reason },
+            EgressDecision::LocalOnly { reason } => EgressError::Blocked { reason },
+        };
+        if providers.iter().all(|p| p.is_local()) {
+            return Ok(AllowedProviders::new(providers, EgressOutcome::Local, None));
+        }
+        if self.egress.on_block() == EgressFallback::Refuse {
+            return Err(blocked);
+        }
+
+        let mut local: Vec<Arc<dyn LlmProvider>> =
+            providers.into_iter().filter(|p| p.is_local()).collect();
+        if local.is_empty() {
+            local.push(Arc::new(OllamaProvider::default()));
+        }
+        info!(
+            "🔒 {}, rerouting to {:?}",
+            blocked,
+            local.iter().map(|p| p.name()).collect::<Vec<_>>()
+        );
+        Ok(AllowedProviders::new(
+            local,
+            EgressOutcome::Rerouted,
+            Some(blocked.to_string()),
+        ))
+    }
+
+    /// Audit entry for the (already redacted) request sent to, or refused from, `provider`
+    fn audit_outbound(
+        &self,
+        request: &LlmRequest,
+        provider: String,
+        remote: bool,
+        egress: EgressOutcome,
+        reason: Option<String>,
+    ) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        audit.record(AuditEvent::Outbound {
+            channel: OutboundChannel::Llm,
+            provider,
+            remote,
+            bytes: request.messages.iter().map(|m| m.content.len()).sum(),
+            redactions: request
+                .messages
+                .iter()
+                .map(|m| count_redactions(&m.content))
+                .sum(),
+            app_name: request.origin.as_ref().map(|o| o.app_name.clone()),
+            egress,
+            reason,
+        });
+    }
+
+    /// Reachability of every provider of the chain, primary first
+    pub async fn check_health(&self) -> Vec<ProviderHealth> {
+        let providers = self.providers.read().unwrap().clone();
+        let mut health = Vec::with_capacity(providers.len());
+        for provider in &providers {
+            health.push(ProviderHealth {
+                provider: provider.name().to_string(),
+                model: provider.default_model().to_string(),
+                available: provider.is_available().await,
+            });
+        }
+        health
+    }
+
+    pub async fn get_stats(&self) -> LlmStats {
+        self.stats.lock().await.clone()
+    }
+
+    async fn finish(
+        &self,
+        state: StreamState,
+        provider: &dyn LlmProvider,
+        end: StreamEnd,
+        used_fallback: bool,
+    ) -> LlmResponse {
+        let ttfr = state.ttfr_ms();
+        if !end.cancelled {
+            self.record_success(used_fallback, ttfr).await;
+        }
+        LlmResponse {
+            content: state.content,
+            provider: provider.name().to_string(),
+            model: end.model,
+            used_fallback,
+            ttfr_ms: ttfr,
+            cancelled: end.cancelled,
+        }
+    }
+
+    async fn record_success(&self, used_fallback: bool, ttfr: u64) {
+        let mut stats = self.stats.lock().await;
+        stats.successful_requests += 1;
+        if used_fallback {
+            stats.fallback_used += 1;
+        }
+        // Simple moving average
+        let n = stats.successful_requests;
+        stats.avg_ttfr_ms = (stats.avg_ttfr_ms * (n - 1) as f64 + ttfr as f64) / n as f64;
+    }
+
+    async fn record_failure(&self) {
+        self.stats.lock().await.failed_requests += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::{EgressAction, EgressConfig, EgressRule};
+    use crate::llm::mock::MockProvider;
+    use crate::privacy::RequestOrigin;
+
+    fn client(providers: Vec<Arc<dyn LlmProvider>>) -> LlmClient {
+        LlmClient::new(providers).with_retry_backoff(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_complete_forwards_options() {
+        let mock = Arc::new(MockProvider::new("mock").reply("hello world"));
+        let client = client(vec![mock.clone()]);
+
+        let mut request = LlmRequest::prompt("hi").temperature(0.1).max_tokens(42);
+        request.options.model = Some("tiny".to_string());
+        let response = client.complete(request).await.unwrap();
+
+        assert_eq!(response.content, "hello world");
+        assert_eq!(response.provider, "mock");
+        assert_eq!(response.model, "tiny");
+        assert!(!response.used_fallback);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].options.temperature, 0.1);
+        assert_eq!(requests[0].options.max_tokens, Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_redacted_before_providers() {
+        let mock = Arc::new(MockProvider::new("mock").reply("ok"));
+        let client = client(vec![mock.clone()]);
+
+        client
+            .complete(LlmRequest::prompt(
+                "my key is sk-abcdefghijklmnopqrstuvwx123 ok?",
+            ))
+            .await
+            .unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(
+            requests[0].messages[0].content,
+            "my key is [REDACTED:api_key] ok?"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_primary_then_falls_back() {
+        let primary = Arc::new(
+            MockProvider::new("primary")
+                .fail("down")
+                .fail("down")
+                .fail("down"),
+        );
+        let fallback = Arc::new(MockProvider::new("fallback").reply("ok"));
+        let client = client(vec![primary.clone(), fallback]);
+
+        let mut streamed = String::new();
+        let (_tx, cancel) = watch::channel(false);
+        let response = client
+            .stream(LlmRequest::prompt("hi"), cancel, |d| streamed.push_str(d))
+            .await
+            .unwrap();
+
+        assert_eq!(primary.requests().len(), PRIMARY_ATTEMPTS as usize);
+        assert_eq!(response.provider, "fallback");
+        assert!(response.used_fallback);
+        assert_eq!(streamed, "ok");
+
+        let stats = client.get_stats().await;
+        assert_eq!(stats.successful_requests, 1);
+        assert_eq!(stats.fallback_used, 1);
+    }
+
+    #[tokio::test]
+    async fn test_all_providers_failing_is_an_error() {
+        let client = client(vec![Arc::new(MockProvider::new("empty"))]);
+        assert!(client.complete(LlmRequest::prompt("hi")).await.is_err());
+        assert_eq!(client.get_stats().await.failed_requests, 1);
+
+        let unconfigured = LlmClient::new(Vec::new());
+        assert!(unconfigured
+            .complete(LlmRequest::prompt("hi"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_returns_partial_response() {
+        let client = client(vec![Arc::new(MockProvider::new("mock").hang())]);
+        let (tx, cancel) = watch::channel(false);
+
+        let handle = tokio::spawn(async move {
+            client
+                .stream(LlmRequest::prompt("hi"), cancel, |_| {})
+                .await
+        });
+        tx.send(true).unwrap();
+
+        let response = handle.await.unwrap().unwrap();
+        assert!(response.cancelled);
+        assert!(response.content.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconfigure_swaps_chain() {
+        let client = client(vec![Arc::new(MockProvider::new("old").reply("before"))]);
+        assert_eq!(
+            client
+                .complete(LlmRequest::prompt("hi"))
+                .await
+                .unwrap()
+                .provider,
+            "old"
+        );
+
+        client.reconfigure(vec![Arc::new(MockProvider::new("new").reply("after"))]);
+        let response = client.complete(LlmRequest::prompt("hi")).await.unwrap();
+        assert_eq!(response.provider, "new");
+        assert_eq!(response.content, "after");
+    }
+
+    #[tokio::test]
+    async fn test_egress_policy_reroutes_to_local_providers() {
+        let remote = Arc::new(MockProvider::new("remote").reply("cloud"));
+        let local = Arc::new(
+            MockProvider::new("local")
+                .local()
+                .reply("local")
+                .reply("local"),
+        );
+        let egress = Arc::new(
+            EgressPolicy::new(&EgressConfig {
+                rules: vec![EgressRule {
+                    app: Some("KeePassXC".to_string()),
+                    title_pattern: None,
+                    action: EgressAction::LocalOnly,
+                }],
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let gated = client(vec![remote.clone(), local.clone()]).with_egress_policy(egress.clone());
+
+        let origin = Some(RequestOrigin::new("KeePassXC", "Passwords.kdbx"));
+        let response = gated
+            .complete(LlmRequest::prompt("hi").origin(origin.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.provider, "local");
+        assert!(remote.requests().is_empty());
+
+        // Unrelated apps still use the whole chain
+        let response = gated
+            .complete(LlmRequest::prompt("hi").origin(Some(RequestOrigin::new("Code", "main.rs"))))
+            .await
+            .unwrap();
+        assert_eq!(response.provider, "remote");
+
+        // Refusing instead of rerouting surfaces a typed error
+        egress.install(
+            EgressPolicy::compile(&EgressConfig {
+                local_only: true,
+                on_block: EgressFallback::Refuse,
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let error = gated.complete(LlmRequest::prompt("hi")).await.unwrap_err();
+        assert!(matches!(
+            error,
+            LlmError::Egress(EgressError::Blocked { .. })
+        ));
+        assert_eq!(remote.requests().len(), 1);
+
+        // A local-only chain is never blocked
+        let local_client = client(vec![local.clone()]).with_egress_policy(egress);
+        assert_eq!(
+            local_client
+                .complete(LlmRequest::prompt("hi"))
+                .await
+                .unwrap()
+                .provider,
+            "local"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_outbound_requests_are_audited() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let audit = Arc::new(AuditLog::new(dir.path().join("privacy_audit.jsonl")));
+        let remote = Arc::new(MockProvider::new("remote").reply("cloud"));
+        let egress = Arc::new(EgressPolicy::default());
+        let client = client(vec![remote.clone()])
+            .with_egress_policy(egress.clone())
+            .with_audit_log(audit.clone());
+
+        client
+            .complete(
+                LlmRequest::prompt("token sk-abcdefghijklmnopqrstuvwx123")
+                    .origin(Some(RequestOrigin::new("Code", "main.rs"))),
+            )
+            .await
+            .unwrap();
+        egress.install(
+            EgressPolicy::compile(&EgressConfig {
+                local_only: true,
+                on_block: EgressFallback::Refuse,
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        assert!(client.complete(LlmRequest::prompt("hi")).await.is_err());
+
+        let entries = audit.query(None, None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].event,
+            AuditEvent::Outbound {
+                channel: OutboundChannel::Llm,
+                provider: "remote".to_string(),
+                remote: true,
+                bytes: "token [REDACTED:api_key]".len(),
+                redactions: 1,
+                app_name: Some("Code".to_string()),
+                egress: EgressOutcome::Allowed,
+                reason: None,
+            }
+        );
+        assert!(matches!(
+            &entries[1].event,
+            AuditEvent::Outbound { egress: EgressOutcome::Refused, provider, .. } if provider == "remote"
+        ));
+        assert_eq!(remote.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_health_lists_chain() {
+        let client = client(vec![
+            Arc::new(MockProvider::new("a")),
+            Arc::new(MockProvider::new("b").unavailable()),
+        ]);
+        let health = client.check_health().await;
+
+        assert_eq!(health.len(), 2);
+        assert!(health[0].available);
+        assert!(!health[1].available);
+        assert_eq!(client.provider_names(), vec!["a", "b"]);
+    }
+}
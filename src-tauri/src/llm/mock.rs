@@ -0,0 +1,132 @@
+use super::{wait_cancelled, LlmProvider, LlmRequest, StreamEnd};
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// One scripted answer of the mock provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockStep {
+    /// Stream this text, word by word
+    Reply(String),
+    /// Fail before producing any token
+    Fail(String),
+    /// Produce nothing until the request is cancelled
+    Hang,
+}
+
+/// Deterministic provider replaying a script, one step per call.
+/// Records every request it receives so callers can assert on prompts and options.
+pub struct MockProvider {
+    name: String,
+    available: bool,
+    local: bool,
+    script: Mutex<VecDeque<MockStep>>,
+    requests: Mutex<Vec<LlmRequest>>,
+}
+
+impl MockProvider {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            available: true,
+            local: false,
+            script: Mutex::new(VecDeque::new()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn reply(self, text: impl Into<String>) -> Self {
+        self.push(MockStep::Reply(text.into()))
+    }
+
+    pub fn fail(self, error: impl Into<String>) -> Self {
+        self.push(MockStep::Fail(error.into()))
+    }
+
+    pub fn hang(self) -> Self {
+        self.push(MockStep::Hang)
+    }
+
+    pub fn unavailable(mut self) -> Self {
+        self.available = false;
+        self
+    }
+
+    /// Behave as a local provider for the egress policy (remote by default)
+    pub fn local(mut self) -> Self {
+        self.local = true;
+        self
+    }
+
+    fn push(self, step: MockStep) -> Self {
+        self.script.lock().unwrap().push_back(step);
+        self
+    }
+
+    /// Requests received so far, in order
+    pub fn requests(&self) -> Vec<LlmRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_model(&self) -> &str {
+        "mock"
+    }
+
+    fn is_local(&self) -> bool {
+        self.local
+    }
+
+    async fn is_available(&self) -> bool {
+        self.available
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        cancel: &mut watch::Receiver<bool>,
+        on_delta: &mut (dyn for<'a> FnMut(&'a str) + Send),
+    ) -> Result<StreamEnd, String> {
+        self.requests.lock().unwrap().push(request.clone());
+        let step = self.script.lock().unwrap().pop_front();
+        let model = request
+            .options
+            .model
+            .clone()
+            .unwrap_or_else(|| self.default_model().to_string());
+
+        match step {
+            Some(MockStep::Reply(text)) => {
+                for word in text.split_inclusive(' ') {
+                    if *cancel.borrow() {
+                        return Ok(StreamEnd {
+                            model,
+                            cancelled: true,
+                        });
+                    }
+                    on_delta(word);
+                }
+                Ok(StreamEnd {
+                    model,
+                    cancelled: false,
+                })
+            }
+            Some(MockStep::Fail(error)) => Err(error),
+            Some(MockStep::Hang) => {
+                wait_cancelled(cancel).await;
+                Ok(StreamEnd {
+                    model,
+                    cancelled: true,
+                })
+            }
+            None => Err(format!("Mock '{}' script exhausted", self.name)),
+        }
+    }
+}
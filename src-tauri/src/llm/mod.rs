@@ -0,0 +1,192 @@
+//! Unified LLM access: one async `LlmProvider` trait implemented for Ollama,
+//! OpenAI-compatible servers and Anthropic (plus a scripted mock in tests), and an
+//! `LlmClient` that adds retries, fallback chain and stats on top of it.
+
+pub mod anthropic;
+pub mod client;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod ollama;
+pub mod openai;
+pub mod selection;
+pub mod stream;
+
+use crate::privacy::{EgressError, RequestOrigin};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+pub use anthropic::AnthropicProvider;
+pub use client::{LlmClient, LlmStats, ProviderHealth};
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: "system".to_string(),
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Per-call generation options; `None` fields fall back to the provider defaults
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CompletionOptions {
+    pub model: Option<String>,
+    pub temperature: f32,
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop: Vec<String>,
+}
+
+impl Default for CompletionOptions {
+    fn default() -> Self {
+        Self {
+            model: None,
+            temperature: 0.7,
+            max_tokens: None,
+            stop: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmRequest {
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub options: CompletionOptions,
+    /// App the content comes from, checked against the egress policy
+    #[serde(default)]
+    pub origin: Option<RequestOrigin>,
+    /// The user explicitly allowed this request to reach a remote provider
+    #[serde(default)]
+    pub egress_confirmed: bool,
+}
+
+impl LlmRequest {
+    pub fn new(messages: Vec<ChatMessage>) -> Self {
+        Self {
+            messages,
+            options: CompletionOptions::default(),
+            origin: None,
+            egress_confirmed: false,
+        }
+    }
+
+    /// Single user prompt
+    pub fn prompt(prompt: impl Into<String>) -> Self {
+        Self::new(vec![ChatMessage::user(prompt)])
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.options.temperature = temperature;
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.options.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn stop(mut self, stop: &[&str]) -> Self {
+        self.options.stop = stop.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub fn origin(mut self, origin: Option<RequestOrigin>) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn egress_confirmed(mut self, confirmed: bool) -> Self {
+        self.egress_confirmed = confirmed;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmResponse {
+    pub content: String,
+    pub provider: String,
+    pub model: String,
+    pub used_fallback: bool,
+    pub ttfr_ms: u64,
+    pub cancelled: bool,
+}
+
+/// Why an `LlmClient` call failed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LlmError {
+    /// Refused by the egress policy before reaching any provider
+    #[error(transparent)]
+    Egress(#[from] EgressError),
+    /// Every provider failed (or none is configured)
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl From<String> for LlmError {
+    fn from(error: String) -> Self {
+        LlmError::Failed(error)
+    }
+}
+
+impl From<LlmError> for String {
+    fn from(error: LlmError) -> Self {
+        error.to_string()
+    }
+}
+
+/// How a provider stream ended (errors are returned as `Err`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamEnd {
+    /// Model that actually answered
+    pub model: String,
+    pub cancelled: bool,
+}
+
+/// A chat-completion backend
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Identifier reported in responses and stats ("ollama", "openai", ...)
+    fn name(&self) -> &str;
+
+    /// Model used when the request does not choose one
+    fn default_model(&self) -> &str;
+
+    /// Runs on this machine: content sent to it never leaves it (egress policy)
+    fn is_local(&self) -> bool;
+
+    /// Quick reachability check (health endpoints, short timeout)
+    async fn is_available(&self) -> bool;
+
+    /// Generate a completion, calling `on_delta` for every text chunk as it arrives.
+    /// Must stop early and return `cancelled: true` once `cancel` flips to true.
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        cancel: &mut watch::Receiver<bool>,
+        on_delta: &mut (dyn for<'a> FnMut(&'a str) + Send),
+    ) -> Result<StreamEnd, String>;
+}
+
+/// Resolves once the request is cancelled; never resolves if nobody can cancel it anymore
+pub async fn wait_cancelled(cancel: &mut watch::Receiver<bool>) {
+    if cancel.wait_for(|cancelled| *cancelled).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
@@ -0,0 +1,138 @@
+use super::stream::{read_stream, send, NdjsonDecoder};
+use super::{ChatMessage, LlmProvider, LlmRequest, StreamEnd};
+use crate::privacy::egress::is_local_url;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Local Ollama server (`/api/chat`, NDJSON streaming)
+pub struct OllamaProvider {
+    http: reqwest::Client,
+    base_url: String,
+    /// Models tried in order when the request does not choose one
+    models: Vec<String>,
+}
+
+impl OllamaProvider {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            models: vec![
+                "llama3".to_string(),
+                "qwen2.5:3b".to_string(),
+                "llama3.2".to_string(),
+            ],
+        }
+    }
+
+    /// Replace the default model list (first one preferred)
+    pub fn with_models(mut self, models: Vec<String>) -> Self {
+        if !models.is_empty() {
+            self.models = models;
+        }
+        self
+    }
+}
+
+impl Default for OllamaProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_OLLAMA_URL)
+    }
+}
+
+#[derive(Serialize)]
+struct OllamaRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    stream: bool,
+    options: OllamaOptions<'a>,
+}
+
+#[derive(Serialize)]
+struct OllamaOptions<'a> {
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.models[0]
+    }
+
+    fn is_local(&self) -> bool {
+        is_local_url(&self.base_url)
+    }
+
+    async fn is_available(&self) -> bool {
+        let health_url = format!("{}/api/tags", self.base_url);
+        match self
+            .http
+            .get(&health_url)
+            .timeout(Duration::from_secs(2))
+            .send()
+            .await
+        {
+            Ok(resp) => resp.status().is_success(),
+            Err(_) => false,
+        }
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        cancel: &mut watch::Receiver<bool>,
+        on_delta: &mut (dyn for<'a> FnMut(&'a str) + Send),
+    ) -> Result<StreamEnd, String> {
+        let url = format!("{}/api/chat", self.base_url);
+        let models: Vec<&str> = match &request.options.model {
+            Some(model) => vec![model.as_str()],
+            None => self.models.iter().map(String::as_str).collect(),
+        };
+
+        // A missing model is answered before any token, so the next one can be tried
+        let mut last_error = None;
+        for model in models {
+            let body = OllamaRequest {
+                model,
+                messages: &request.messages,
+                stream: true,
+                options: OllamaOptions {
+                    temperature: request.options.temperature,
+                    num_predict: request.options.max_tokens,
+                    stop: &request.options.stop,
+                },
+            };
+
+            match send(self.http.post(&url).json(&body).send()).await {
+                Ok(response) => {
+                    info!("✅ Used model: {}", model);
+                    let cancelled =
+                        read_stream(response, NdjsonDecoder::default(), cancel, on_delta).await?;
+                    return Ok(StreamEnd {
+                        model: model.to_string(),
+                        cancelled,
+                    });
+                }
+                Err(e) => {
+                    warn!("❌ Model '{}' failed: {}", model, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| "All models failed".to_string()))
+    }
+}
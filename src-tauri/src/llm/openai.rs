@@ -0,0 +1,117 @@
+use super::stream::{read_stream, send, SseDecoder};
+use super::{ChatMessage, LlmProvider, LlmRequest, StreamEnd};
+use crate::privacy::egress::is_local_url;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::watch;
+
+pub const DEFAULT_OPENAI_URL: &str = "https://api.openai.com/v1";
+
+/// OpenAI or any server exposing the same `/chat/completions` API (LM Studio, vLLM, ...)
+pub struct OpenAiProvider {
+    http: reqwest::Client,
+    name: String,
+    base_url: String,
+    /// Optional for local OpenAI-compatible servers
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(base_url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            name: "openai".to_string(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key,
+            model: "gpt-4o-mini".to_string(),
+        }
+    }
+
+    /// Name reported in responses, to tell several compatible endpoints apart
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(api_key) => builder.header("Authorization", format!("Bearer {}", api_key)),
+            None => builder,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OpenAiRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+    stream: bool,
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    fn is_local(&self) -> bool {
+        is_local_url(&self.base_url)
+    }
+
+    async fn is_available(&self) -> bool {
+        let request = self
+            .authorize(self.http.get(format!("{}/models", self.base_url)))
+            .timeout(Duration::from_secs(5));
+        match request.send().await {
+            Ok(resp) => resp.status().is_success(),
+            Err(_) => false,
+        }
+    }
+
+    async fn stream(
+        &self,
+        request: &LlmRequest,
+        cancel: &mut watch::Receiver<bool>,
+        on_delta: &mut (dyn for<'a> FnMut(&'a str) + Send),
+    ) -> Result<StreamEnd, String> {
+        let model = request.options.model.as_deref().unwrap_or(&self.model);
+        let body = OpenAiRequest {
+            model,
+            messages: &request.messages,
+            temperature: request.options.temperature,
+            max_tokens: request.options.max_tokens,
+            stop: &request.options.stop,
+            stream: true,
+        };
+
+        let builder = self
+            .authorize(
+                self.http
+                    .post(format!("{}/chat/completions", self.base_url)),
+            )
+            .json(&body);
+        let response = send(builder.send()).await?;
+        let cancelled = read_stream(response, SseDecoder::default(), cancel, on_delta).await?;
+
+        Ok(StreamEnd {
+            model: model.to_string(),
+            cancelled,
+        })
+    }
+}
@@ -0,0 +1,229 @@
+use super::anthropic::DEFAULT_ANTHROPIC_URL;
+use super::ollama::DEFAULT_OLLAMA_URL;
+use super::openai::DEFAULT_OPENAI_URL;
+use super::{AnthropicProvider, LlmProvider, OllamaProvider, OpenAiProvider};
+use crate::config::types::{LLMConfig, LLMProviderConfig};
+use crate::crypto::keymanager::SecretStore;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Providers accepted in `LLMConfig.provider` and in the fallback chain
+pub const SUPPORTED_PROVIDERS: &[&str] = &["ollama", "openai", "anthropic", "openai-compatible"];
+
+/// Keychain entry holding the API key of a provider
+pub fn api_key_secret(provider: &str) -> String {
+    format!("llm_api_key_{}", provider)
+}
+
+/// API key of a provider, read from the keychain
+pub fn api_key(secrets: &SecretStore, provider: &str) -> Option<String> {
+    match secrets.get(&api_key_secret(provider)) {
+        Ok(key) => key.filter(|k| !k.is_empty()),
+        Err(e) => {
+            warn!(
+                "⚠️ Failed to read {} API key from keychain: {}",
+                provider, e
+            );
+            None
+        }
+    }
+}
+
+/// API key of a provider for `config`: the key still inline in the config (not yet moved
+/// to the keychain) for the configured provider, the keychain's otherwise
+fn resolve_api_key(config: &LLMConfig, secrets: &SecretStore, provider: &str) -> Option<String> {
+    let inline = config
+        .api_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty());
+    match inline {
+        Some(key) if provider == config.provider => Some(key.to_string()),
+        _ => api_key(secrets, provider),
+    }
+}
+
+/// Build one provider from its configuration; `api_key` looks up a provider's key
+pub fn build_provider(
+    entry: &LLMProviderConfig,
+    api_key: impl Fn(&str) -> Option<String>,
+) -> Result<Arc<dyn LlmProvider>, String> {
+    let base_url = entry.base_url.as_deref().filter(|url| !url.is_empty());
+
+    let provider: Arc<dyn LlmProvider> = match entry.provider.as_str() {
+        "ollama" => Arc::new(
+            OllamaProvider::new(base_url.unwrap_or(DEFAULT_OLLAMA_URL))
+                .with_models(vec![entry.model.clone()]),
+        ),
+        "openai" => {
+            let key = api_key("openai").ok_or("OpenAI API key not configured")?;
+            Arc::new(
+                OpenAiProvider::new(base_url.unwrap_or(DEFAULT_OPENAI_URL), Some(key))
+                    .with_model(&entry.model),
+            )
+        }
+        "anthropic" => {
+            let key = api_key("anthropic").ok_or("Anthropic API key not configured")?;
+            Arc::new(
+                AnthropicProvider::new(base_url.unwrap_or(DEFAULT_ANTHROPIC_URL), key)
+                    .with_model(&entry.model),
+            )
+        }
+        "openai-compatible" => {
+            let base_url = base_url.ok_or("base_url is required for openai-compatible")?;
+            Arc::new(
+                OpenAiProvider::new(base_url, api_key("openai-compatible"))
+                    .named("openai-compatible")
+                    .with_model(&entry.model),
+            )
+        }
+        other => {
+            return Err(format!(
+                "Unknown LLM provider '{}' (expected one of {})",
+                other,
+                SUPPORTED_PROVIDERS.join(", ")
+            ))
+        }
+    };
+
+    Ok(provider)
+}
+
+fn primary_entry(config: &LLMConfig) -> LLMProviderConfig {
+    LLMProviderConfig {
+        provider: config.provider.clone(),
+        model: config.model.clone(),
+        base_url: config.base_url.clone(),
+    }
+}
+
+/// Primary provider followed by the fallback chain; an unusable primary is an error.
+/// Nothing is written: a key still inline in `config` is used as is.
+pub fn build_chain(
+    config: &LLMConfig,
+    secrets: &SecretStore,
+) -> Result<Vec<Arc<dyn LlmProvider>>, String> {
+    let keys = |provider: &str| resolve_api_key(config, secrets, provider);
+    let mut chain = vec![build_provider(&primary_entry(config), keys)?];
+    chain.extend(build_fallbacks(config, secrets));
+    Ok(chain)
+}
+
+/// Usable fallbacks, in order; duplicates (of the primary or of each other) and
+/// unusable entries are skipped with a warning
+pub fn build_fallbacks(config: &LLMConfig, secrets: &SecretStore) -> Vec<Arc<dyn LlmProvider>> {
+    let mut seen = vec![primary_entry(config)];
+    let mut chain = Vec::new();
+
+    for fallback in &config.fallbacks {
+        if seen.contains(fallback) {
+            continue;
+        }
+        match build_provider(fallback, |provider| {
+            resolve_api_key(config, secrets, provider)
+        }) {
+            Ok(provider) => {
+                chain.push(provider);
+                seen.push(fallback.clone());
+            }
+            Err(e) => warn!(
+                "⚠️ Skipping LLM fallback {}/{}: {}",
+                fallback.provider, fallback.model, e
+            ),
+        }
+    }
+
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: &str, fallbacks: Vec<LLMProviderConfig>) -> LLMConfig {
+        LLMConfig {
+            provider: provider.to_string(),
+            model: "some-model".to_string(),
+            base_url: None,
+            fallbacks,
+            api_key: None,
+        }
+    }
+
+    fn ollama(model: &str) -> LLMProviderConfig {
+        LLMProviderConfig {
+            provider: "ollama".to_string(),
+            model: model.to_string(),
+            base_url: None,
+        }
+    }
+
+    #[test]
+    fn test_cloud_provider_requires_keychain_key() {
+        let secrets = SecretStore::volatile();
+        assert!(build_chain(&config("openai", vec![]), &secrets).is_err());
+
+        secrets.set(&api_key_secret("openai"), "sk-test").unwrap();
+        let chain = build_chain(&config("openai", vec![ollama("llama3.2")]), &secrets).unwrap();
+
+        let names: Vec<&str> = chain.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["openai", "ollama"]);
+        assert_eq!(chain[0].default_model(), "some-model");
+    }
+
+    #[test]
+    fn test_inline_key_is_used_without_touching_keychain() {
+        let secrets = SecretStore::volatile();
+        let mut anthropic = config("anthropic", vec![]);
+        anthropic.api_key = Some(" sk-ant-test ".to_string());
+
+        let chain = build_chain(&anthropic, &secrets).unwrap();
+        assert_eq!(chain[0].name(), "anthropic");
+        assert!(api_key(&secrets, "anthropic").is_none());
+
+        // Only for the configured provider: the OpenAI fallback has no key
+        anthropic.fallbacks = vec![LLMProviderConfig {
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+        }];
+        let chain = build_chain(&anthropic, &secrets).unwrap();
+        assert_eq!(chain.len(), 1);
+    }
+
+    #[test]
+    fn test_fallbacks_skip_duplicates_and_unusable_entries() {
+        let secrets = SecretStore::volatile();
+        let mut anthropic = ollama("claude");
+        anthropic.provider = "anthropic".to_string();
+
+        let chain = build_chain(
+            &config(
+                "ollama",
+                vec![
+                    ollama("some-model"),
+                    anthropic,
+                    ollama("llama3.2"),
+                    ollama("llama3.2"),
+                ],
+            ),
+            &secrets,
+        )
+        .unwrap();
+
+        let models: Vec<&str> = chain.iter().map(|p| p.default_model()).collect();
+        assert_eq!(models, vec!["some-model", "llama3.2"]);
+    }
+
+    #[test]
+    fn test_unknown_or_incomplete_provider_is_rejected() {
+        let secrets = SecretStore::volatile();
+        assert!(build_chain(&config("gemini", vec![]), &secrets).is_err());
+        assert!(build_chain(&config("openai-compatible", vec![]), &secrets).is_err());
+
+        let mut local = config("openai-compatible", vec![]);
+        local.base_url = Some("http://localhost:1234/v1".to_string());
+        let chain = build_chain(&local, &secrets).unwrap();
+        assert_eq!(chain[0].name(), "openai-compatible");
+    }
+}
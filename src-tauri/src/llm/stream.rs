@@ -0,0 +1,354 @@
+use super::wait_cancelled;
+use serde::Deserialize;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::timeout;
+use tracing::info;
+
+/// Max wait for the response headers and for the first token
+pub const FIRST_TOKEN_TIMEOUT: Duration = Duration::from_secs(12);
+
+/// Max silence between two chunks once tokens are flowing
+pub const CHUNK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Incremental decoder for a streamed HTTP body
+pub trait StreamDecoder {
+    /// Feed raw bytes, get back the text deltas contained in the complete lines
+    fn push(&mut self, bytes: &[u8]) -> Result<Vec<String>, String>;
+
+    /// True once the end-of-stream marker was seen
+    fn is_done(&self) -> bool;
+}
+
+/// Splits a byte stream into complete lines (UTF-8 is only decoded on whole lines)
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if !line.is_empty() {
+                lines.push(line.to_string());
+            }
+        }
+        lines
+    }
+}
+
+/// Ollama `/api/chat` with `stream: true`: one JSON object per line
+#[derive(Default)]
+pub struct NdjsonDecoder {
+    lines: LineBuffer,
+    done: bool,
+}
+
+impl StreamDecoder for NdjsonDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Result<Vec<String>, String> {
+        #[derive(Deserialize)]
+        struct OllamaChunk {
+            #[serde(default)]
+            message: Option<OllamaMessage>,
+            #[serde(default)]
+            done: bool,
+            #[serde(default)]
+            error: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct OllamaMessage {
+            #[serde(default)]
+            content: String,
+        }
+
+        let mut deltas = Vec::new();
+        for line in self.lines.push(bytes) {
+            let chunk: OllamaChunk =
+                serde_json::from_str(&line).map_err(|e| format!("Parse error: {}", e))?;
+            if let Some(error) = chunk.error {
+                return Err(format!("API error: {}", error));
+            }
+            if let Some(message) = chunk.message {
+                if !message.content.is_empty() {
+                    deltas.push(message.content);
+                }
+            }
+            if chunk.done {
+                self.done = true;
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// OpenAI-compatible `/chat/completions` with `stream: true`: server-sent events
+#[derive(Default)]
+pub struct SseDecoder {
+    lines: LineBuffer,
+    done: bool,
+}
+
+impl StreamDecoder for SseDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Result<Vec<String>, String> {
+        #[derive(Deserialize)]
+        struct SseChunk {
+            #[serde(default)]
+            choices: Vec<SseChoice>,
+        }
+
+        #[derive(Deserialize)]
+        struct SseChoice {
+            #[serde(default)]
+            delta: SseDelta,
+        }
+
+        #[derive(Deserialize, Default)]
+        struct SseDelta {
+            #[serde(default)]
+            content: Option<String>,
+        }
+
+        let mut deltas = Vec::new();
+        for line in self.lines.push(bytes) {
+            // Comments, `event:` and `id:` fields carry no text
+            let Some(data) = line.strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                self.done = true;
+                continue;
+            }
+
+            let chunk: SseChunk =
+                serde_json::from_str(data).map_err(|e| format!("Parse error: {}", e))?;
+            for choice in chunk.choices {
+                if let Some(content) = choice.delta.content.filter(|c| !c.is_empty()) {
+                    deltas.push(content);
+                }
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Anthropic `/messages` with `stream: true`: typed server-sent events
+#[derive(Default)]
+pub struct AnthropicDecoder {
+    lines: LineBuffer,
+    done: bool,
+}
+
+impl StreamDecoder for AnthropicDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Result<Vec<String>, String> {
+        #[derive(Deserialize)]
+        struct AnthropicEvent {
+            #[serde(rename = "type")]
+            kind: String,
+            #[serde(default)]
+            delta: Option<AnthropicDelta>,
+            #[serde(default)]
+            error: Option<AnthropicError>,
+        }
+
+        #[derive(Deserialize)]
+        struct AnthropicDelta {
+            #[serde(default)]
+            text: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct AnthropicError {
+            #[serde(default)]
+            message: String,
+        }
+
+        let mut deltas = Vec::new();
+        for line in self.lines.push(bytes) {
+            // The `event:` lines duplicate the `type` field of the data payload
+            let Some(data) = line.strip_prefix("data:") else {
+                continue;
+            };
+
+            let event: AnthropicEvent =
+                serde_json::from_str(data.trim()).map_err(|e| format!("Parse error: {}", e))?;
+            match event.kind.as_str() {
+                "content_block_delta" => {
+                    if let Some(text) = event.delta.and_then(|d| d.text).filter(|t| !t.is_empty()) {
+                        deltas.push(text);
+                    }
+                }
+                "message_stop" => self.done = true,
+                "error" => {
+                    let message = event.error.map(|e| e.message).unwrap_or_default();
+                    return Err(format!("API error: {}", message));
+                }
+                _ => {}
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Send a request, waiting at most `FIRST_TOKEN_TIMEOUT` for the headers; non-2xx become errors
+pub async fn send(
+    request: impl Future<Output = Result<reqwest::Response, reqwest::Error>>,
+) -> Result<reqwest::Response, String> {
+    let response = timeout(FIRST_TOKEN_TIMEOUT, request)
+        .await
+        .map_err(|_| format!("Timeout after {}s", FIRST_TOKEN_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("Network error: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("API error {}: {}", status, text));
+    }
+    Ok(response)
+}
+
+/// Read a streamed body chunk by chunk until the end marker, EOF or cancellation.
+/// Returns true if the generation was cancelled.
+pub async fn read_stream<D: StreamDecoder>(
+    mut response: reqwest::Response,
+    mut decoder: D,
+    cancel: &mut watch::Receiver<bool>,
+    on_delta: &mut (dyn for<'a> FnMut(&'a str) + Send),
+) -> Result<bool, String> {
+    let mut has_output = false;
+
+    loop {
+        let wait = if has_output {
+            CHUNK_TIMEOUT
+        } else {
+            FIRST_TOKEN_TIMEOUT
+        };
+        let chunk = tokio::select! {
+            _ = wait_cancelled(cancel) => {
+                info!("🛑 LLM generation cancelled");
+                return Ok(true);
+            }
+            chunk = timeout(wait, response.chunk()) => chunk,
+        };
+
+        let bytes = match chunk {
+            Err(_) => return Err(format!("Timeout after {}s without data", wait.as_secs())),
+            Ok(Err(e)) => return Err(format!("Network error: {}", e)),
+            Ok(Ok(None)) => break,
+            Ok(Ok(Some(bytes))) => bytes,
+        };
+
+        for delta in decoder.push(&bytes)? {
+            has_output = true;
+            on_delta(&delta);
+        }
+        if decoder.is_done() {
+            break;
+        }
+    }
+
+    if !has_output && !decoder.is_done() {
+        return Err("Stream ended without any content".to_string());
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ndjson_handles_split_lines() {
+        let mut decoder = NdjsonDecoder::default();
+        let body = concat!(
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Bon\"},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"jour é\"},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true}\n"
+        )
+        .as_bytes();
+
+        // Split in the middle of a multi-byte character
+        let split = body.iter().position(|b| *b == 0xC3).unwrap() + 1;
+        let mut deltas = decoder.push(&body[..split]).unwrap();
+        assert!(!decoder.is_done());
+        deltas.extend(decoder.push(&body[split..]).unwrap());
+
+        assert_eq!(deltas, vec!["Bon", "jour é"]);
+        assert!(decoder.is_done());
+    }
+
+    #[test]
+    fn test_ndjson_surfaces_errors() {
+        let mut decoder = NdjsonDecoder::default();
+        assert!(decoder
+            .push(b"{\"error\":\"model 'x' not found\"}\n")
+            .is_err());
+    }
+
+    #[test]
+    fn test_sse_parses_deltas_and_done() {
+        let mut decoder = SseDecoder::default();
+        let deltas = decoder
+            .push(
+                concat!(
+                    ": keep-alive\r\n",
+                    "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\r\n\r\n",
+                    "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+                    "data: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\n",
+                    "data: [DONE]\n\n"
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        assert!(decoder.is_done());
+    }
+
+    #[test]
+    fn test_anthropic_parses_text_deltas() {
+        let mut decoder = AnthropicDecoder::default();
+        let deltas = decoder
+            .push(
+                concat!(
+                    "event: message_start\n",
+                    "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n",
+                    "event: content_block_delta\n",
+                    "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Sal\"}}\n\n",
+                    "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"ut\"}}\n\n",
+                    "data: {\"type\":\"ping\"}\n\n",
+                    "data: {\"type\":\"message_stop\"}\n\n"
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+
+        assert_eq!(deltas, vec!["Sal", "ut"]);
+        assert!(decoder.is_done());
+
+        let mut decoder = AnthropicDecoder::default();
+        assert!(decoder
+            .push(b"data: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n")
+            .is_err());
+    }
+}
@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, info};
 
+use crate::privacy::erasure::AppMatcher;
+use crate::retention::{entry_size, CategoryReport, ResolvedPolicy};
+
 /// Patterns d'usage détectés pour un utilisateur
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsagePatterns {
@@ -330,6 +333,51 @@ impl PersonalizationManager {
             .retain(|event| event.timestamp > cutoff_date);
     }
 
+    /// Applique une politique de rétention à l'historique d'événements
+    pub fn apply_retention(&mut self, policy: &ResolvedPolicy, now: DateTime<Utc>) -> CategoryReport {
+        let items: Vec<(DateTime<Utc>, u64)> = self
+            .event_history
+            .iter()
+            .map(|event| (event.timestamp, entry_size(event)))
+            .collect();
+        let expired = policy.select_expired(&items, now);
+
+        if !expired.is_empty() {
+            let mut index = 0;
+            self.event_history.retain(|_| {
+                let keep = expired.binary_search(&index).is_err();
+                index += 1;
+                keep
+            });
+            info!("🧹 Removed {} ML events (retention)", expired.len());
+        }
+
+        CategoryReport::from_selection(&items, &expired)
+    }
+
+    /// Oublie une app : ses événements et ses entrées dans les patterns d'usage
+    pub fn forget_app(&mut self, matcher: &AppMatcher) -> usize {
+        let events = self.event_history.len();
+        self.event_history
+            .retain(|event| !matcher.matches_app(&event.app_name));
+        let mut removed = events - self.event_history.len();
+
+        let apps = self.patterns.favorite_apps.len() + self.patterns.frequently_ignored_apps.len();
+        self.patterns
+            .favorite_apps
+            .retain(|app_name, _| !matcher.matches_app(app_name));
+        self.patterns
+            .frequently_ignored_apps
+            .retain(|app_name, _| !matcher.matches_app(app_name));
+        removed +=
+            apps - self.patterns.favorite_apps.len() - self.patterns.frequently_ignored_apps.len();
+
+        if removed > 0 {
+            info!("🗑️ Removed {} ML entries (forgotten app)", removed);
+        }
+        removed
+    }
+
     /// Obtient les patterns actuels
     pub fn get_patterns(&self) -> &UsagePatterns {
         &self.patterns
@@ -1,51 +1,211 @@
-use std::path::PathBuf;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GenericImageView, GrayImage};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
-/// Détecte les changements significatifs entre deux screenshots
+/// Grille de tuiles comparées une à une (colonnes x lignes)
+pub const GRID_COLS: u32 = 8;
+pub const GRID_ROWS: u32 = 6;
+
+/// dHash d'une tuile : 9x8 pixels, 8 comparaisons par ligne → 64 bits
+const DHASH_WIDTH: u32 = 9;
+const DHASH_HEIGHT: u32 = 8;
+
+/// Écart de luminosité ignoré entre deux pixels voisins (bruit JPEG des zones unies)
+const GRADIENT_TOLERANCE: u8 = 2;
+
+/// Au-delà de cette part de l'image modifiée, OCR et Vision travaillent sur l'image entière
+pub const FULL_FRAME_RATIO: f32 = 0.6;
+
+/// Qualité JPEG de la zone modifiée envoyée à Vision
+const CROP_JPEG_QUALITY: u8 = 85;
+
+/// Zone modifiée depuis la dernière capture retenue, en pixels de l'image
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChangedRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Part des bits du hash qui ont changé (0.0 = identique, 1.0 = tout a changé)
+    pub difference: f32,
+}
+
+impl ChangedRegion {
+    fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    /// Vrai si les deux zones se chevauchent ou se touchent
+    fn touches(&self, other: &ChangedRegion) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    /// Plus petit rectangle contenant les deux zones
+    fn union(&self, other: &ChangedRegion) -> ChangedRegion {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        ChangedRegion {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+            difference: self.difference.max(other.difference),
+        }
+    }
+}
+
+/// Résultat de la comparaison avec la dernière capture retenue
+#[derive(Debug, Clone)]
+pub struct ChangeReport {
+    pub has_change: bool,
+    /// Hash global de l'écran (average hash), utilisé par le SmartCache
+    pub hash: u64,
+    /// Similarité moyenne des tuiles (0.0 = différent, 1.0 = identique)
+    pub similarity: f32,
+    /// Tuiles modifiées ; l'image entière pour la première capture ou si sa taille change
+    pub regions: Vec<ChangedRegion>,
+}
+
+/// Hashes d'une capture : global et par tuile
+struct FrameHashes {
+    width: u32,
+    height: u32,
+    global: u64,
+    tiles: Vec<u64>,
+}
+
+/// Détecte les changements significatifs entre deux screenshots, tuile par tuile :
+/// un curseur qui clignote ne change que quelques bits d'une tuile, une nouvelle
+/// erreur de compilation en change beaucoup même si elle n'occupe qu'un coin de l'écran
 pub struct ChangeDetector {
-    last_hash: Option<u64>,
+    last_frame: Option<FrameHashes>,
     similarity_threshold: f32, // 0.0 = différent, 1.0 = identique
 }
 
 impl ChangeDetector {
     pub fn new(similarity_threshold: f32) -> Self {
         Self {
-            last_hash: None,
+            last_frame: None,
             similarity_threshold,
         }
     }
 
-    /// Détecte si un changement significatif s'est produit
-    /// Retourne (has_change, current_hash) pour permettre l'utilisation avec SmartCache
-    pub fn has_significant_change(&mut self, image_path: &PathBuf) -> Result<(bool, u64), String> {
-        let current_hash = self.calculate_perceptual_hash(image_path)?;
+    /// Compare la capture à la dernière capture retenue et liste les tuiles modifiées.
+    /// Une tuile est modifiée si sa similarité passe sous le seuil ; la capture devient la
+    /// nouvelle référence dès qu'une tuile l'est
+    pub fn detect_changes(&mut self, image_path: &PathBuf) -> Result<ChangeReport, String> {
+        let img = image::open(image_path).map_err(|e| format!("Failed to open image: {}", e))?;
+        let current = Self::frame_hashes(&img);
 
-        let is_different = if let Some(last) = self.last_hash {
-            let similarity = self.hash_similarity(last, current_hash);
-            debug!("🔍 Similarity: {:.2}% (threshold: {:.2}%)",
-                   similarity * 100.0,
-                   self.similarity_threshold * 100.0);
-
-            similarity < self.similarity_threshold
-        } else {
-            // Premier screenshot = toujours un changement
-            true
+        let (similarity, regions) = match &self.last_frame {
+            Some(last) if last.width == current.width && last.height == current.height => {
+                let similarities: Vec<f32> = last
+                    .tiles
+                    .iter()
+                    .zip(&current.tiles)
+                    .map(|(&before, &after)| self.hash_similarity(before, after))
+                    .collect();
+                let regions = similarities
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &similarity)| similarity < self.similarity_threshold)
+                    .map(|(index, &similarity)| {
+                        tile_region(
+                            index as u32,
+                            current.width,
+                            current.height,
+                            1.0 - similarity,
+                        )
+                    })
+                    .collect();
+                let mean = similarities.iter().sum::<f32>() / similarities.len() as f32;
+                (mean, regions)
+            }
+            // Premier screenshot (ou écran de taille différente) = toujours un changement
+            _ => (
+                0.0,
+                vec![ChangedRegion {
+                    x: 0,
+                    y: 0,
+                    width: current.width,
+                    height: current.height,
+                    difference: 1.0,
+                }],
+            ),
         };
 
-        if is_different {
-            info!("✨ Changement significatif détecté!");
-            self.last_hash = Some(current_hash);
+        debug!(
+            "🔍 Similarity: {:.2}% (threshold: {:.2}%), {} changed tiles",
+            similarity * 100.0,
+            self.similarity_threshold * 100.0,
+            regions.len()
+        );
+
+        let has_change = !regions.is_empty();
+        let hash = current.global;
+        if has_change {
+            info!(
+                "✨ Changement significatif détecté ({} zones)!",
+                regions.len()
+            );
+            self.last_frame = Some(current);
         }
 
-        Ok((is_different, current_hash))
+        Ok(ChangeReport {
+            has_change,
+            hash,
+            similarity,
+            regions,
+        })
     }
 
-    /// Calcule un hash perceptuel simple (average hash)
-    /// Plus rapide que pHash mais suffisant pour nos besoins
-    fn calculate_perceptual_hash(&self, image_path: &PathBuf) -> Result<u64, String> {
-        let img = image::open(image_path)
-            .map_err(|e| format!("Failed to open image: {}", e))?;
+    /// Hash global et dHash de chaque tuile, calculés sur une seule réduction de l'image
+    fn frame_hashes(img: &DynamicImage) -> FrameHashes {
+        let (width, height) = img.dimensions();
+        let grid = img
+            .resize_exact(
+                GRID_COLS * DHASH_WIDTH,
+                GRID_ROWS * DHASH_HEIGHT,
+                image::imageops::FilterType::Triangle,
+            )
+            .to_luma8();
+
+        let tiles = (0..GRID_ROWS)
+            .flat_map(|row| (0..GRID_COLS).map(move |col| (col, row)))
+            .map(|(col, row)| Self::tile_dhash(&grid, col * DHASH_WIDTH, row * DHASH_HEIGHT))
+            .collect();
+
+        FrameHashes {
+            width,
+            height,
+            global: Self::calculate_perceptual_hash(img),
+            tiles,
+        }
+    }
+
+    /// dHash (gradient horizontal) du bloc 9x8 commençant en (`x0`, `y0`)
+    fn tile_dhash(grid: &GrayImage, x0: u32, y0: u32) -> u64 {
+        let mut hash: u64 = 0;
+        for y in 0..DHASH_HEIGHT {
+            for x in 0..DHASH_WIDTH - 1 {
+                let left = grid.get_pixel(x0 + x, y0 + y).0[0];
+                let right = grid.get_pixel(x0 + x + 1, y0 + y).0[0];
+                if right > left.saturating_add(GRADIENT_TOLERANCE) {
+                    hash |= 1 << (y * (DHASH_WIDTH - 1) + x);
+                }
+            }
+        }
+        hash
+    }
 
+    /// Calcule un hash perceptuel simple (average hash) de l'écran entier
+    /// Plus rapide que pHash mais suffisant pour repérer un écran déjà vu
+    fn calculate_perceptual_hash(img: &DynamicImage) -> u64 {
         // Redimensionner à 8x8 pour le hash
         let small = img.resize_exact(8, 8, image::imageops::FilterType::Triangle);
         let gray = small.to_luma8();
@@ -62,7 +222,7 @@ impl ChangeDetector {
             }
         }
 
-        Ok(hash)
+        hash
     }
 
     /// Calcule la similarité entre deux hash (0.0 = différent, 1.0 = identique)
@@ -73,11 +233,74 @@ impl ChangeDetector {
 
     /// Reset le détecteur (utile après un changement d'app ou contexte)
     pub fn reset(&mut self) {
-        self.last_hash = None;
+        self.last_frame = None;
         info!("🔄 Change detector reset");
     }
 }
 
+/// Rectangle de la tuile `index` (ligne par ligne) dans une image `width` x `height`
+fn tile_region(index: u32, width: u32, height: u32, difference: f32) -> ChangedRegion {
+    let (col, row) = (index % GRID_COLS, index / GRID_COLS);
+    let x = col * width / GRID_COLS;
+    let y = row * height / GRID_ROWS;
+    ChangedRegion {
+        x,
+        y,
+        width: (col + 1) * width / GRID_COLS - x,
+        height: (row + 1) * height / GRID_ROWS - y,
+        difference,
+    }
+}
+
+/// Regroupe les tuiles voisines en rectangles (une zone par groupe de tuiles qui se touchent)
+pub fn merge_regions(regions: &[ChangedRegion]) -> Vec<ChangedRegion> {
+    let mut merged: Vec<ChangedRegion> = Vec::with_capacity(regions.len());
+    for region in regions {
+        let mut current = *region;
+        // Une union peut toucher des zones déjà fusionnées : on les absorbe jusqu'à stabilité
+        while let Some(index) = merged.iter().position(|other| other.touches(&current)) {
+            current = current.union(&merged.swap_remove(index));
+        }
+        merged.push(current);
+    }
+    merged.sort_by_key(|region| (region.y, region.x));
+    merged
+}
+
+/// Vrai si les zones couvrent l'essentiel d'une image `width` x `height`
+pub fn covers_most_of(regions: &[ChangedRegion], width: u32, height: u32) -> bool {
+    let covered: u64 = regions.iter().map(ChangedRegion::area).sum();
+    covered as f32 >= (width as u64 * height as u64) as f32 * FULL_FRAME_RATIO
+}
+
+/// JPEG du rectangle englobant les zones modifiées, pour l'envoyer à Vision à la place de
+/// l'écran entier. None si les zones couvrent l'essentiel de l'image
+pub fn crop_changed_area(
+    image_path: &Path,
+    regions: &[ChangedRegion],
+) -> Result<Option<Vec<u8>>, String> {
+    let Some(area) = regions.iter().copied().reduce(|a, b| a.union(&b)) else {
+        return Ok(None);
+    };
+    let img = image::open(image_path).map_err(|e| format!("Failed to open image: {}", e))?;
+    let (width, height) = img.dimensions();
+    if covers_most_of(&[area], width, height) {
+        return Ok(None);
+    }
+
+    let cropped = img
+        .crop_imm(area.x, area.y, area.width, area.height)
+        .to_rgb8();
+    let mut buffer = Vec::new();
+    cropped
+        .write_with_encoder(JpegEncoder::new_with_quality(
+            &mut buffer,
+            CROP_JPEG_QUALITY,
+        ))
+        .map_err(|e| format!("Failed to encode changed area: {}", e))?;
+    Ok(Some(buffer))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,4 +319,78 @@ mod tests {
         let sim = detector.hash_similarity(0xFF, 0xFE);
         assert!(sim > 0.98); // 63/64 bits identiques
     }
+
+    fn save(img: &GrayImage, dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        img.save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_changed_tiles() {
+        let dir = tempfile::tempdir().unwrap();
+        // Dégradé horizontal : chaque tuile a un dHash non trivial
+        let base = GrayImage::from_fn(800, 600, |x, y| image::Luma([((x + y) % 256) as u8]));
+        let mut detector = ChangeDetector::new(0.85);
+
+        let first = detector
+            .detect_changes(&save(&base, dir.path(), "a.jpg"))
+            .unwrap();
+        assert!(first.has_change);
+        assert_eq!(first.regions.len(), 1);
+        assert_eq!(
+            (first.regions[0].width, first.regions[0].height),
+            (800, 600)
+        );
+
+        // Un "curseur" de 2 pixels ne suffit pas
+        let mut cursor = base.clone();
+        for y in 300..318 {
+            for x in 400..402 {
+                cursor.put_pixel(x, y, image::Luma([255]));
+            }
+        }
+        let blink = detector
+            .detect_changes(&save(&cursor, dir.path(), "b.jpg"))
+            .unwrap();
+        assert!(!blink.has_change);
+
+        // Un bloc de texte dans le coin bas-droit : seule cette tuile change
+        let mut error = base.clone();
+        for y in 510..590 {
+            for x in 710..790 {
+                let stripe = if (y / 6) % 2 == 0 { 0 } else { 255 };
+                error.put_pixel(x, y, image::Luma([stripe]));
+            }
+        }
+        let report = detector
+            .detect_changes(&save(&error, dir.path(), "c.jpg"))
+            .unwrap();
+        assert!(report.has_change);
+        assert_eq!(report.regions.len(), 1);
+        let region = report.regions[0];
+        assert_eq!(
+            (region.x, region.y, region.width, region.height),
+            (700, 500, 100, 100)
+        );
+        assert!(region.difference > 0.15);
+        assert!(report.similarity > 0.9);
+    }
+
+    #[test]
+    fn test_merge_regions() {
+        let tile = |index| tile_region(index, 800, 600, 0.5);
+        // Deux tuiles voisines en haut à gauche, une isolée en bas à droite
+        let merged = merge_regions(&[tile(0), tile(47), tile(1)]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(
+            (merged[0].x, merged[0].width, merged[0].height),
+            (0, 200, 100)
+        );
+        assert_eq!((merged[1].x, merged[1].y), (700, 500));
+
+        assert!(!covers_most_of(&merged, 800, 600));
+        let all: Vec<ChangedRegion> = (0..GRID_COLS * GRID_ROWS).map(tile).collect();
+        assert!(covers_most_of(&merge_regions(&all), 800, 600));
+    }
 }
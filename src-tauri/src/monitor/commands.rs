@@ -1,4 +1,7 @@
+use super::screen_monitor::VisionProviderKind;
 use super::ScreenMonitor;
+use crate::config::ConfigManager;
+use crate::llm::selection::api_key;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::info;
@@ -65,3 +68,19 @@ pub async fn get_monitor_cache_stats(
     let mon = monitor.lock().await;
     Ok(mon.cache_stats().await)
 }
+
+/// Choose the vision backend (Claude Vision or a local Ollama model) and enable vision analysis
+#[tauri::command]
+pub async fn set_monitor_vision_provider(
+    provider: VisionProviderKind,
+    url: Option<String>,
+    model: Option<String>,
+    monitor: tauri::State<'_, Arc<Mutex<ScreenMonitor>>>,
+    config_manager: tauri::State<'_, Arc<Mutex<ConfigManager>>>,
+) -> Result<(), String> {
+    info!("👁️ set_monitor_vision_provider: {:?}", provider);
+    let secrets = config_manager.lock().await.secrets();
+    let mut mon = monitor.lock().await;
+    mon.set_vision_provider(provider, url, model, api_key(&secrets, "anthropic"))
+        .await
+}
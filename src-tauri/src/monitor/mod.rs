@@ -5,5 +5,5 @@ pub mod vision_client;
 pub mod ocr_client;
 pub mod smart_cache;
 
-pub use screen_monitor::{ScreenMonitor, MonitorConfig};
+pub use screen_monitor::{ScreenMonitor, MonitorConfig, ScreenFinding};
 pub use ocr_client::{OCRResult, DetectedPattern};
@@ -1,19 +1,62 @@
 use image::{DynamicImage, GenericImageView};
-use std::path::Path;
-use tracing::{debug, info};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use super::change_detector::{covers_most_of, merge_regions, ChangedRegion};
+use crate::diagnostics::{self, Diagnostic};
+
+/// Max time given to the tesseract binary for one frame
+const TESSERACT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Confidence reported when only the colour heuristics ran (no text was read)
+const HEURISTIC_CONFIDENCE: f32 = 0.3;
+
+/// Above this many separate changed areas, one pass over the whole image is cheaper
+const MAX_OCR_REGIONS: usize = 4;
 
 /// OCR client for extracting text and patterns from screenshots
-/// Uses lightweight pattern detection instead of heavy OCR libraries
+/// Reads text with a local tesseract binary when one is installed, and falls back
+/// to lightweight colour-based pattern detection otherwise
+#[derive(Debug, Clone)]
 pub struct LocalOCR {
     /// Minimum confidence for text detection (0.0 - 1.0)
     confidence_threshold: f32,
+    /// Local tesseract binary, if found in PATH
+    tesseract_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
 pub struct OCRResult {
+    /// Recognised text, one line per text line (description of the patterns if no text was read)
     pub text: String,
+    /// Mean word confidence (0.0 - 1.0)
     pub confidence: f32,
     pub detected_patterns: Vec<DetectedPattern>,
+    /// Recognised words with their bounding boxes, empty without an OCR engine
+    pub words: Vec<OcrWord>,
+    /// Short human-readable description of the detected patterns
+    pub description: String,
+    /// Compiler/runtime errors parsed from the recognised text
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A recognised word and its bounding box in image pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcrWord {
+    pub text: String,
+    /// Word confidence (0.0 - 1.0)
+    pub confidence: f32,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Index of the text line the word belongs to
+    pub line: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -38,13 +81,115 @@ impl LocalOCR {
     pub fn new() -> Self {
         Self {
             confidence_threshold: 0.7,
+            tesseract_path: Self::find_tesseract(),
+        }
+    }
+
+    /// True if text recognition is available (tesseract found)
+    pub fn has_text_recognition(&self) -> bool {
+        self.tesseract_path.is_some()
+    }
+
+    fn find_tesseract() -> Option<PathBuf> {
+        if let Ok(path) = which::which("tesseract") {
+            debug!("[OCR] Found tesseract at: {:?}", path);
+            return Some(path);
         }
+
+        debug!("[OCR] tesseract not found, falling back to pattern heuristics");
+        None
     }
 
     /// Extract text and patterns from an image
     pub fn analyze(&self, image_path: &Path) -> Result<OCRResult, String> {
-        let start = std::time::Instant::now();
+        let start = Instant::now();
+
+        let result = match &self.tesseract_path {
+            Some(tesseract) => match self.recognize(tesseract, image_path) {
+                Ok(words) => Ok(self.result_from_words(words)),
+                Err(e) => {
+                    warn!("⚠️ Text recognition failed, using pattern heuristics: {}", e);
+                    self.analyze_heuristics(image_path)
+                }
+            },
+            None => self.analyze_heuristics(image_path),
+        }?;
 
+        let duration = start.elapsed();
+        info!(
+            "🔍 OCR analysis completed in {}ms ({} words)",
+            duration.as_millis(),
+            result.words.len()
+        );
+
+        Ok(result)
+    }
+
+    /// Extract text from the changed regions only, with word boxes in full-image pixels.
+    /// Falls back to `analyze` on the whole image when the regions cover most of it, when
+    /// there are too many of them, or without tesseract (the colour heuristics need the
+    /// full frame)
+    pub fn analyze_regions(
+        &self,
+        image_path: &Path,
+        regions: &[ChangedRegion],
+    ) -> Result<OCRResult, String> {
+        let Some(tesseract) = &self.tesseract_path else {
+            return self.analyze(image_path);
+        };
+        let img = image::open(image_path).map_err(|e| format!("Failed to open image: {}", e))?;
+        let (width, height) = img.dimensions();
+        let areas = merge_regions(regions);
+        if areas.is_empty()
+            || areas.len() > MAX_OCR_REGIONS
+            || covers_most_of(&areas, width, height)
+        {
+            return self.analyze(image_path);
+        }
+
+        let start = Instant::now();
+        let crop_dir =
+            tempfile::tempdir().map_err(|e| format!("Failed to create OCR crop dir: {}", e))?;
+        let mut words: Vec<OcrWord> = Vec::new();
+        for (index, area) in areas.iter().enumerate() {
+            let crop_path = crop_dir.path().join(format!("region-{}.png", index));
+            img.crop_imm(area.x, area.y, area.width, area.height)
+                .save(&crop_path)
+                .map_err(|e| format!("Failed to save OCR crop: {}", e))?;
+
+            let region_words = match self.recognize(tesseract, &crop_path) {
+                Ok(region_words) => region_words,
+                Err(e) => {
+                    warn!(
+                        "⚠️ Text recognition failed, using pattern heuristics: {}",
+                        e
+                    );
+                    return self.analyze_heuristics(image_path);
+                }
+            };
+            // Lines of each area stay separate from those of the previous areas
+            let line_offset = words.last().map_or(0, |word| word.line + 1);
+            words.extend(region_words.into_iter().map(|mut word| {
+                word.x += area.x;
+                word.y += area.y;
+                word.line += line_offset;
+                word
+            }));
+        }
+
+        let result = self.result_from_words(words);
+        info!(
+            "🔍 OCR analysis of {} changed regions completed in {}ms ({} words)",
+            areas.len(),
+            start.elapsed().as_millis(),
+            result.words.len()
+        );
+
+        Ok(result)
+    }
+
+    /// Colour-based fallback: no text is read, only the kind of window is guessed
+    fn analyze_heuristics(&self, image_path: &Path) -> Result<OCRResult, String> {
         // Load image
         let img = image::open(image_path)
             .map_err(|e| format!("Failed to open image: {}", e))?;
@@ -53,18 +198,100 @@ impl LocalOCR {
         let patterns = self.detect_patterns(&img);
 
         // Generate text description based on patterns
-        let text = self.generate_description(&patterns);
-
-        let duration = start.elapsed();
-        info!("🔍 OCR analysis completed in {}ms", duration.as_millis());
+        let description = self.generate_description(&patterns);
 
         Ok(OCRResult {
-            text,
-            confidence: 0.85, // Pattern-based detection is quite reliable
+            text: description.clone(),
+            confidence: HEURISTIC_CONFIDENCE,
             detected_patterns: patterns,
+            words: Vec::new(),
+            description,
+            diagnostics: Vec::new(),
         })
     }
 
+    /// Run tesseract on the image and parse its TSV output into words
+    fn recognize(&self, tesseract: &Path, image_path: &Path) -> Result<Vec<OcrWord>, String> {
+        // Output goes to a file so a large TSV can't fill the pipe while we wait
+        let out_dir = tempfile::tempdir()
+            .map_err(|e| format!("Failed to create OCR output dir: {}", e))?;
+        let out_base = out_dir.path().join("ocr");
+
+        let mut child = Command::new(tesseract)
+            .arg(image_path)
+            .arg(&out_base)
+            .args(["--psm", "3", "tsv"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run tesseract: {}", e))?;
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if started.elapsed() > TESSERACT_TIMEOUT => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!(
+                        "tesseract timed out after {}s",
+                        TESSERACT_TIMEOUT.as_secs()
+                    ));
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+                Err(e) => return Err(format!("Failed to wait for tesseract: {}", e)),
+            }
+        };
+        if !status.success() {
+            return Err(format!("tesseract exited with {}", status));
+        }
+
+        let tsv = std::fs::read_to_string(out_base.with_extension("tsv"))
+            .map_err(|e| format!("Failed to read tesseract output: {}", e))?;
+        Ok(parse_tesseract_tsv(&tsv))
+    }
+
+    /// Build the result from recognised words: text, mean confidence and patterns
+    fn result_from_words(&self, words: Vec<OcrWord>) -> OCRResult {
+        let confidence = if words.is_empty() {
+            0.0
+        } else {
+            words.iter().map(|w| w.confidence).sum::<f32>() / words.len() as f32
+        };
+
+        // Low-confidence words stay in `words` but are kept out of the text
+        let mut lines: Vec<Vec<&str>> = Vec::new();
+        let mut current_line = None;
+        for word in words.iter().filter(|w| w.confidence >= self.confidence_threshold) {
+            if current_line != Some(word.line) {
+                lines.push(Vec::new());
+                current_line = Some(word.line);
+            }
+            if let Some(line) = lines.last_mut() {
+                line.push(&word.text);
+            }
+        }
+        let text = lines
+            .iter()
+            .map(|line| line.join(" "))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let diagnostics = diagnostics::parse_diagnostics(&text);
+        let detected_patterns = classify_text(&text, &diagnostics);
+        let description = self.generate_description(&detected_patterns);
+
+        OCRResult {
+            text,
+            confidence,
+            detected_patterns,
+            words,
+            description,
+            diagnostics,
+        }
+    }
+
     /// Detect patterns in the image based on color distribution and layout
     fn detect_patterns(&self, img: &DynamicImage) -> Vec<DetectedPattern> {
         let mut patterns = Vec::new();
@@ -310,6 +537,148 @@ impl Default for LocalOCR {
     }
 }
 
+/// Parse tesseract TSV output into words (level 5 rows), in reading order
+pub fn parse_tesseract_tsv(tsv: &str) -> Vec<OcrWord> {
+    let mut line_indices: BTreeMap<(u32, u32, u32, u32), usize> = BTreeMap::new();
+    let mut words = Vec::new();
+
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.splitn(12, '\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let text = cols[11].trim();
+        let confidence = cols[10].trim().parse::<f32>().unwrap_or(-1.0);
+        if text.is_empty() || confidence < 0.0 {
+            continue;
+        }
+        let num = |i: usize| cols[i].trim().parse::<u32>().unwrap_or(0);
+
+        let next_line = line_indices.len();
+        let line = *line_indices
+            .entry((num(1), num(2), num(3), num(4)))
+            .or_insert(next_line);
+
+        words.push(OcrWord {
+            text: text.to_string(),
+            confidence: (confidence / 100.0).clamp(0.0, 1.0),
+            x: num(6),
+            y: num(7),
+            width: num(8),
+            height: num(9),
+            line,
+        });
+    }
+
+    words
+}
+
+static ERROR_TYPE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)(error\[E\d{4}\]|\b[A-Z][A-Za-z]*(?:Error|Exception)\b|panicked at|Segmentation fault|command not found|npm ERR!|\bFAILED\b|^\s*(?:error|fatal|ERROR):)",
+    )
+    .expect("valid error regex")
+});
+
+static STACK_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?m)(Traceback \(most recent call last\)|stack backtrace:|^\s*File ".+", line \d+|^\s*at .+[:(]\d+|^\s*#\d+\s+0x[0-9a-fA-F]+)"#,
+    )
+    .expect("valid stack frame regex")
+});
+
+static SHELL_PROMPT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*(?:[\w.-]+@[\w.-]+[^\n]*[$#%]|\$|❯|➜|>>>|PS [A-Z]:\\[^\n]*>)\s")
+        .expect("valid prompt regex")
+});
+
+static URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:https?://|localhost:\d+)\S*").expect("valid url regex"));
+
+/// Keywords used to guess the language of code on screen
+const LANGUAGE_KEYWORDS: &[(&str, &[&str])] = &[
+    ("Rust", &["fn ", "let mut ", "impl ", "pub fn", "&self", "Result<", "use std::", "-> "]),
+    ("Python", &["def ", "import ", "self.", "elif ", "None", "__init__", "print("]),
+    ("TypeScript", &["interface ", ": string", ": number", "export type ", "import type "]),
+    ("JavaScript", &["const ", "=> ", "function ", "require(", "console.log", "export default"]),
+    ("Go", &["func ", " := ", "package ", "fmt.", "err != nil"]),
+];
+
+/// IDE names as they appear in title bars and menus
+const IDE_NAMES: &[&str] = &[
+    "Visual Studio Code",
+    "IntelliJ IDEA",
+    "PyCharm",
+    "WebStorm",
+    "RustRover",
+    "CLion",
+    "Android Studio",
+    "Xcode",
+    "Sublime Text",
+];
+
+/// Detect patterns from recognised text and the diagnostics parsed from it
+pub fn classify_text(text: &str, diagnostics: &[Diagnostic]) -> Vec<DetectedPattern> {
+    let mut patterns = Vec::new();
+    if text.trim().is_empty() {
+        return patterns;
+    }
+
+    // A parsed diagnostic names the tool and code; the regex only catches the error word
+    let error_type = diagnostics
+        .iter()
+        .find(|d| d.is_error())
+        .map(Diagnostic::label)
+        .or_else(|| {
+            ERROR_TYPE
+                .find(text)
+                .map(|m| m.as_str().trim().trim_end_matches(':').to_string())
+        });
+    let has_stack_trace = diagnostics.iter().any(|d| !d.frames.is_empty())
+        || STACK_FRAME.find_iter(text).count() >= 2
+        || text.contains("Traceback (most recent call last)");
+    let language = guess_language_from_text(text);
+
+    if let Some(name) = IDE_NAMES.iter().find(|name| text.contains(*name)) {
+        patterns.push(DetectedPattern::IDE {
+            name: name.to_string(),
+        });
+    }
+
+    if language.is_some() {
+        patterns.push(DetectedPattern::CodeEditor {
+            language,
+            has_errors: error_type.is_some(),
+        });
+    }
+
+    if SHELL_PROMPT.is_match(text) {
+        patterns.push(DetectedPattern::Terminal {
+            has_errors: error_type.is_some(),
+            error_type: error_type.clone(),
+        });
+    }
+
+    if URL.is_match(text) {
+        patterns.push(DetectedPattern::Browser { has_stack_trace });
+    }
+
+    patterns
+}
+
+/// Guess the programming language from keywords (needs at least two hits)
+fn guess_language_from_text(text: &str) -> Option<String> {
+    LANGUAGE_KEYWORDS
+        .iter()
+        .map(|(language, keywords)| {
+            let hits = keywords.iter().filter(|k| text.contains(*k)).count();
+            (language, hits)
+        })
+        .filter(|(_, hits)| *hits >= 2)
+        .max_by_key(|(_, hits)| *hits)
+        .map(|(language, _)| language.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,4 +699,67 @@ mod tests {
 
         assert_eq!(samples.len(), 100); // 10x10 grid
     }
+
+    #[test]
+    fn test_parse_tesseract_tsv() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+            1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t\n\
+            4\t1\t1\t1\t1\t0\t10\t20\t300\t18\t-1\t\n\
+            5\t1\t1\t1\t1\t1\t10\t20\t40\t18\t96.5\terror[E0382]:\n\
+            5\t1\t1\t1\t1\t2\t55\t20\t60\t18\t91\tborrow\n\
+            5\t1\t1\t1\t1\t3\t120\t20\t10\t18\t-1\t \n\
+            5\t1\t1\t1\t2\t1\t10\t40\t30\t18\t42\tl1ne\n";
+
+        let words = parse_tesseract_tsv(tsv);
+        assert_eq!(words.len(), 3);
+        assert_eq!(
+            words[0],
+            OcrWord {
+                text: "error[E0382]:".to_string(),
+                confidence: 0.965,
+                x: 10,
+                y: 20,
+                width: 40,
+                height: 18,
+                line: 0,
+            }
+        );
+        assert_eq!(words[2].line, 1);
+
+        // The low-confidence word is returned but kept out of the text
+        let result = LocalOCR::new().result_from_words(words);
+        assert_eq!(result.text, "error[E0382]: borrow");
+        assert!((result.confidence - (0.965 + 0.91 + 0.42) / 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_classify_text() {
+        let rust = "pub fn main() -> Result<(), String> {\n    let mut x = 1;\n}";
+        let patterns = classify_text(rust, &[]);
+        assert!(matches!(
+            patterns.as_slice(),
+            [DetectedPattern::CodeEditor { language: Some(lang), has_errors: false }] if lang == "Rust"
+        ));
+
+        let terminal = "user@host:~/app$ python3 app.py\n\
+            Traceback (most recent call last):\n  \
+            File \"app.py\", line 3, in <module>\n\
+            ModuleNotFoundError: No module named 'flask'";
+        let patterns = classify_text(terminal, &diagnostics::parse_diagnostics(terminal));
+        assert!(patterns.iter().any(|p| matches!(
+            p,
+            DetectedPattern::Terminal { has_errors: true, error_type: Some(e) } if e == "python ModuleNotFoundError"
+        )));
+        assert!(matches!(
+            classify_text(terminal, &[]).as_slice(),
+            [DetectedPattern::Terminal { error_type: Some(e), .. }] if e == "ModuleNotFoundError"
+        ));
+
+        let browser = "https://example.com/app\nTypeError: x is undefined\n  at render (app.js:12)\n  at main (app.js:40)";
+        assert!(classify_text(browser, &[])
+            .iter()
+            .any(|p| matches!(p, DetectedPattern::Browser { has_stack_trace: true })));
+
+        assert!(classify_text("  ", &[]).is_empty());
+    }
 }
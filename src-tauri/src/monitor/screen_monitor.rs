@@ -1,24 +1,58 @@
-use super::change_detector::ChangeDetector;
-use super::vision_client::ClaudeVisionClient;
+use super::change_detector::{crop_changed_area, ChangeDetector, ChangedRegion};
+use super::vision_client::{AnthropicVision, OllamaVision, VisionClient, VisionProvider};
 use super::ocr_client::LocalOCR;
 use super::smart_cache::SmartCache;
-use crate::screenshot::ScreenshotCapturer;
+use crate::budget::{BudgetGovernor, Subsystem};
+use crate::context::ContextAggregator;
+use crate::diagnostics::Diagnostic;
+use crate::llm::ollama::DEFAULT_OLLAMA_URL;
+use crate::llm::LlmError;
+use crate::privacy::{AuditLog, EgressPolicy, Redactor, RequestOrigin};
+use crate::screenshot::{CaptureResult, ScreenshotCapturer};
+use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 
+/// Backend d'analyse visuelle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VisionProviderKind {
+    /// Claude Vision (cloud, clé Anthropic du trousseau)
+    #[default]
+    Anthropic,
+    /// Modèle multimodal local servi par Ollama, rien ne quitte la machine
+    Ollama,
+}
+
+fn default_vision_url() -> String {
+    DEFAULT_OLLAMA_URL.to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorConfig {
     /// Intervalle de capture en secondes
     pub interval_secs: u64,
     /// Seuil de similarité (0.0 = différent, 1.0 = identique)
     pub similarity_threshold: f32,
-    /// Activer l'analyse par Claude Vision (cloud API)
+    /// Activer l'analyse visuelle (Claude Vision ou modèle local selon `vision_provider`)
     pub use_vision: bool,
+    /// Backend d'analyse visuelle
+    #[serde(default)]
+    pub vision_provider: VisionProviderKind,
+    /// Serveur Ollama du backend local
+    #[serde(default = "default_vision_url")]
+    pub vision_url: String,
+    /// Modèle de vision (défaut du backend si absent : Haiku, llava)
+    #[serde(default)]
+    pub vision_model: Option<String>,
     /// Activer l'OCR local (pattern detection rapide, gratuit, privacy-first)
     pub use_local_ocr: bool,
+    /// Limiter l'OCR et Vision aux zones de l'écran qui ont changé
+    pub analyze_changed_regions: bool,
     /// Activer le monitoring
     pub enabled: bool,
 }
@@ -28,8 +62,12 @@ impl Default for MonitorConfig {
         Self {
             interval_secs: 5,
             similarity_threshold: 0.85,
-            use_vision: false, // Cloud API - désactivé par défaut
+            use_vision: false, // Désactivé par défaut
+            vision_provider: VisionProviderKind::default(),
+            vision_url: default_vision_url(),
+            vision_model: None,
             use_local_ocr: true, // OCR local - activé par défaut (gratuit et rapide)
+            analyze_changed_regions: true,
             enabled: true,
         }
     }
@@ -41,40 +79,67 @@ pub struct ScreenChange {
     pub image_path: String,
     pub image_base64: String,
     pub analysis: Option<String>, // Résultat de l'analyse Vision (si activée)
+    /// Tuiles modifiées depuis la capture précédente (l'écran entier pour la première)
+    #[serde(default)]
+    pub changed_regions: Vec<ChangedRegion>,
+    /// Similarité moyenne des tuiles avec la capture précédente (1.0 = identique)
+    #[serde(default)]
+    pub similarity: f32,
+}
+
+/// Nombre de résultats OCR récents conservés pour le contexte du chat
+const MAX_RECENT_FINDINGS: usize = 5;
+
+/// Longueur max de l'extrait de texte OCR gardé par résultat
+const MAX_FINDING_TEXT_CHARS: usize = 600;
+
+/// Résultat OCR récent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenFinding {
+    pub timestamp: u64,
+    /// App active au moment de la capture
+    pub app_name: Option<String>,
+    pub summary: String,
+    /// Erreurs de compilation/exécution lues à l'écran (messages masqués)
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 pub struct ScreenMonitor {
     config: MonitorConfig,
     change_detector: Arc<Mutex<ChangeDetector>>,
     capturer: Arc<Mutex<Option<ScreenshotCapturer>>>,
-    vision_client: Arc<Mutex<Option<ClaudeVisionClient>>>,
+    vision_client: Arc<Mutex<Option<VisionClient>>>,
     ocr_client: Arc<Mutex<Option<LocalOCR>>>,
     smart_cache: Arc<Mutex<SmartCache>>,
+    recent_findings: Arc<Mutex<VecDeque<ScreenFinding>>>,
+    /// Textes OCR / Vision masqués avant d'être émis ou gardés
+    redactor: Arc<Redactor>,
+    /// Politique de sortie appliquée aux envois Vision
+    egress: Arc<EgressPolicy>,
+    /// Journal d'audit des envois Vision
+    audit: Option<Arc<AuditLog>>,
+    /// Espace ou saute captures, OCR et Vision (batterie, budget épuisé)
+    budget: Arc<BudgetGovernor>,
     is_running: Arc<Mutex<bool>>,
 }
 
 impl ScreenMonitor {
-    pub fn new(config: MonitorConfig) -> Self {
-        // Try to init Claude Vision client if API key is available
-        let vision_client = if config.use_vision {
-            match ClaudeVisionClient::new() {
-                Ok(client) => {
-                    info!("✅ Claude Vision client initialized");
-                    Some(client)
-                }
-                Err(e) => {
-                    warn!("⚠️ Claude Vision client init failed: {}", e);
-                    None
-                }
-            }
-        } else {
-            None
-        };
+    /// `anthropic_api_key` vient du trousseau ; sans clé, Claude Vision reste désactivé
+    /// (le backend Ollama n'en a pas besoin)
+    pub fn new(config: MonitorConfig, anthropic_api_key: Option<String>) -> Self {
+        let egress = Arc::new(EgressPolicy::default());
+        let vision_client = Self::vision_client_for(&config, anthropic_api_key, &egress, None);
 
         // Init local OCR if enabled
         let ocr_client = if config.use_local_ocr {
-            info!("✅ Local OCR client initialized (pattern detection)");
-            Some(LocalOCR::new())
+            let ocr = LocalOCR::new();
+            if ocr.has_text_recognition() {
+                info!("✅ Local OCR client initialized (tesseract)");
+            } else {
+                warn!("⚠️ tesseract not found: local OCR falls back to pattern detection");
+            }
+            Some(ocr)
         } else {
             None
         };
@@ -89,11 +154,118 @@ impl ScreenMonitor {
                 2,  // min interval: 2s
                 30, // max interval: 30s
             ))),
+            recent_findings: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_RECENT_FINDINGS))),
+            redactor: Arc::new(Redactor::default()),
+            egress,
+            audit: None,
+            budget: Arc::new(BudgetGovernor::default()),
             is_running: Arc::new(Mutex::new(false)),
             config,
         }
     }
 
+    /// Utilise le `Redactor` partagé (configuration utilisateur, statistiques communes)
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Utilise la politique de sortie partagée (rechargée avec la configuration)
+    pub fn with_egress_policy(mut self, egress: Arc<EgressPolicy>) -> Self {
+        // Pas encore partagé : le client Vision est mis à jour sur place
+        if let Some(client) = Arc::get_mut(&mut self.vision_client)
+            .and_then(|vision| vision.get_mut().as_mut())
+        {
+            client.set_egress_policy(egress.clone());
+        }
+        self.egress = egress;
+        self
+    }
+
+    /// Consigne les envois Vision dans le journal d'audit partagé
+    pub fn with_audit_log(mut self, audit: Arc<AuditLog>) -> Self {
+        if let Some(client) = Arc::get_mut(&mut self.vision_client)
+            .and_then(|vision| vision.get_mut().as_mut())
+        {
+            client.set_audit_log(audit.clone());
+        }
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Utilise le gouverneur de budget partagé (rechargé avec la configuration)
+    pub fn with_budget_governor(mut self, budget: Arc<BudgetGovernor>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Client Vision du backend configuré, si l'analyse visuelle est activée.
+    /// Claude Vision bascule sur le modèle local quand la politique de sortie bloque l'app
+    fn vision_client_for(
+        config: &MonitorConfig,
+        api_key: Option<String>,
+        egress: &Arc<EgressPolicy>,
+        audit: Option<&Arc<AuditLog>>,
+    ) -> Option<VisionClient> {
+        if !config.use_vision {
+            return None;
+        }
+        let local: Arc<dyn VisionProvider> = Arc::new(OllamaVision::new(
+            config.vision_url.clone(),
+            config.vision_model.clone(),
+        ));
+        let mut client = match config.vision_provider {
+            VisionProviderKind::Ollama => VisionClient::new(local),
+            VisionProviderKind::Anthropic => {
+                match AnthropicVision::new(api_key.unwrap_or_default()) {
+                    Ok(provider) => {
+                        VisionClient::new(Arc::new(provider)).with_local_fallback(local)
+                    }
+                    Err(e) => {
+                        warn!("⚠️ Claude Vision client init failed: {}", e);
+                        return None;
+                    }
+                }
+            }
+        };
+
+        client.set_egress_policy(egress.clone());
+        if let Some(audit) = audit {
+            client.set_audit_log(audit.clone());
+        }
+        info!("✅ Vision client initialized ({})", client.provider_name());
+        Some(client)
+    }
+
+    /// Remplace la clé Claude Vision après une mise à jour de la configuration
+    pub async fn set_vision_api_key(&self, api_key: Option<String>) {
+        *self.vision_client.lock().await =
+            Self::vision_client_for(&self.config, api_key, &self.egress, self.audit.as_ref());
+    }
+
+    /// Choisit le backend Vision et active l'analyse visuelle ; le monitor en cours
+    /// d'exécution utilise le nouveau client dès la capture suivante
+    pub async fn set_vision_provider(
+        &mut self,
+        provider: VisionProviderKind,
+        url: Option<String>,
+        model: Option<String>,
+        anthropic_api_key: Option<String>,
+    ) -> Result<(), String> {
+        let mut config = self.config.clone();
+        config.use_vision = true;
+        config.vision_provider = provider;
+        config.vision_url = url.unwrap_or_else(default_vision_url);
+        config.vision_model = model;
+
+        let client =
+            Self::vision_client_for(&config, anthropic_api_key, &self.egress, self.audit.as_ref())
+                .ok_or_else(|| "Claude Vision requires an Anthropic API key".to_string())?;
+        *self.vision_client.lock().await = Some(client);
+        self.config = config;
+        Ok(())
+    }
+
     /// Démarre la boucle de monitoring
     pub async fn start(&self, app: AppHandle) {
         let mut is_running = self.is_running.lock().await;
@@ -115,16 +287,22 @@ impl ScreenMonitor {
         let vision_client = self.vision_client.clone();
         let ocr_client = self.ocr_client.clone();
         let smart_cache = self.smart_cache.clone();
+        let recent_findings = self.recent_findings.clone();
+        let redactor = self.redactor.clone();
+        let budget = self.budget.clone();
         let is_running = self.is_running.clone();
 
         tokio::spawn(async move {
             loop {
+                // Sur batterie l'intervalle est espacé ; budget épuisé, la capture est sautée
+                let admission = budget.admit(Subsystem::Monitor);
+
                 // Sleep avec intervalle adaptatif
                 let sleep_duration = {
                     let cache = smart_cache.lock().await;
                     cache.adaptive_interval()
                 };
-                tokio::time::sleep(sleep_duration).await;
+                tokio::time::sleep(sleep_duration.mul_f32(admission.interval_factor())).await;
 
                 // Check si on doit continuer
                 if !*is_running.lock().await {
@@ -132,18 +310,22 @@ impl ScreenMonitor {
                     break;
                 }
 
-                if !config.enabled {
+                if !config.enabled || !admission.is_allowed() {
                     continue;
                 }
 
                 // Capture + détection avec smart cache
                 match Self::capture_and_check_adaptive(
                     &app,
+                    &config,
                     &capturer,
                     &change_detector,
                     &vision_client,
                     &ocr_client,
                     &smart_cache,
+                    &recent_findings,
+                    &redactor,
+                    &budget,
                 ).await {
                     Ok(Some(change)) => {
                         info!("📸 Screen change detected, emitting event");
@@ -173,11 +355,15 @@ impl ScreenMonitor {
     /// Capture l'écran et vérifie avec smart cache + détection de changement
     async fn capture_and_check_adaptive(
         app: &AppHandle,
+        config: &MonitorConfig,
         capturer: &Arc<Mutex<Option<ScreenshotCapturer>>>,
         change_detector: &Arc<Mutex<ChangeDetector>>,
-        vision_client: &Arc<Mutex<Option<ClaudeVisionClient>>>,
+        vision_client: &Arc<Mutex<Option<VisionClient>>>,
         ocr_client: &Arc<Mutex<Option<LocalOCR>>>,
         smart_cache: &Arc<Mutex<SmartCache>>,
+        recent_findings: &Arc<Mutex<VecDeque<ScreenFinding>>>,
+        redactor: &Redactor,
+        budget: &Arc<BudgetGovernor>,
     ) -> Result<Option<ScreenChange>, String> {
         // Initialiser le capturer si nécessaire
         {
@@ -190,23 +376,32 @@ impl ScreenMonitor {
             }
         }
 
+        // Capture et hash comptés en temps écoulé pour le moniteur ;
+        // OCR et Vision ont leur propre budget
+        let monitor_work = budget.start(Subsystem::Monitor);
+
         // Capture via le Tauri command existant
-        let capture_result = crate::screenshot::capture_screenshot(app.clone())
+        // Zones de confidentialité appliquées à la capture ; rien n'est pris si l'app est protégée
+        let Some(capture_result) = crate::screenshot::capture_frame(app, None)
             .await
-            .map_err(|e| format!("Capture failed: {}", e))?;
+            .map_err(|e| format!("Capture failed: {}", e))?
+        else {
+            return Ok(None);
+        };
 
         let image_path = std::path::PathBuf::from(&capture_result.path);
 
-        // Vérifier si changement significatif ET obtenir le hash
-        let (has_change, current_hash) = {
+        // Vérifier si changement significatif, tuile par tuile, ET obtenir le hash
+        let report = {
             let mut detector = change_detector.lock().await;
-            detector.has_significant_change(&image_path)?
+            detector.detect_changes(&image_path)?
         };
+        drop(monitor_work);
 
         // Vérifier avec le smart cache si on doit analyser
         let should_analyze = {
             let mut cache = smart_cache.lock().await;
-            cache.should_analyze(current_hash)
+            cache.should_analyze(report.hash)
         };
 
         if !should_analyze {
@@ -214,20 +409,32 @@ impl ScreenMonitor {
             return Ok(None);
         }
 
-        if !has_change {
-            // Pas de changement significatif selon le seuil de similarité
+        if !report.has_change {
+            // Aucune tuile ne passe sous le seuil de similarité
             return Ok(None);
         }
 
-        // Changement détecté ET cache approuve ! Analyser avec OCR local OU Claude Vision
+        // Zones à analyser : vide = écran entier
+        let analysis_regions: &[ChangedRegion] = if config.analyze_changed_regions {
+            &report.regions
+        } else {
+            &[]
+        };
+
+        // Changement détecté ET cache approuve ! Analyser avec OCR local OU Vision
         let analysis = {
             // Priorité à l'OCR local (rapide, gratuit, privacy-first)
             let ocr = ocr_client.lock().await;
-            if let Some(ref local_ocr) = *ocr {
-                match local_ocr.analyze(&image_path) {
+            let admitted_ocr = ocr
+                .as_ref()
+                .filter(|_| budget.admit(Subsystem::Ocr).is_allowed());
+            if let Some(local_ocr) = admitted_ocr {
+                match Self::run_ocr(local_ocr, budget, &image_path, analysis_regions).await {
                     Ok(ocr_result) => {
-                        info!("✅ Local OCR: {} (confidence: {:.2})",
-                              ocr_result.text, ocr_result.confidence);
+                        info!("✅ Local OCR: {} ({} words, confidence: {:.2})",
+                              ocr_result.description, ocr_result.words.len(), ocr_result.confidence);
+
+                        Self::record_finding(app, recent_findings, redactor, &ocr_result).await;
 
                         // Générer une suggestion basée sur les patterns détectés
                         let suggestion = Self::generate_ocr_suggestion(&ocr_result);
@@ -236,14 +443,21 @@ impl ScreenMonitor {
                     Err(e) => {
                         warn!("⚠️ Local OCR failed: {}", e);
 
-                        // Fallback vers Claude Vision si disponible
+                        // Fallback vers Vision si disponible
                         let vision = vision_client.lock().await;
-                        if let Some(ref claude) = *vision {
-                            match claude.suggest_action(&capture_result.data).await {
+                        if let Some(vision) = Self::admitted_vision(&vision, budget) {
+                            let origin = Self::active_origin(app).await;
+                            let image = Self::vision_image(&capture_result, analysis_regions).await;
+                            let _work = budget.start(Subsystem::Vision);
+                            match vision.suggest_action(&image, origin.as_ref()).await {
                                 Ok(suggestion) => {
-                                    info!("✅ Claude Vision (fallback): {}", suggestion);
+                                    info!("✅ Vision (fallback): {}", suggestion);
                                     Some(suggestion)
                                 }
+                                Err(LlmError::Egress(e)) => {
+                                    info!("🔒 Vision skipped: {}", e);
+                                    None
+                                }
                                 Err(e) => {
                                     warn!("⚠️ Vision analysis also failed: {}", e);
                                     None
@@ -255,14 +469,21 @@ impl ScreenMonitor {
                     }
                 }
             } else {
-                // Pas d'OCR local, essayer Claude Vision
+                // Pas d'OCR local, essayer Vision
                 let vision = vision_client.lock().await;
-                if let Some(ref claude) = *vision {
-                    match claude.suggest_action(&capture_result.data).await {
+                if let Some(vision) = Self::admitted_vision(&vision, budget) {
+                    let origin = Self::active_origin(app).await;
+                    let image = Self::vision_image(&capture_result, analysis_regions).await;
+                    let _work = budget.start(Subsystem::Vision);
+                    match vision.suggest_action(&image, origin.as_ref()).await {
                         Ok(suggestion) => {
-                            info!("✅ Claude Vision suggestion: {}", suggestion);
+                            info!("✅ Vision suggestion: {}", suggestion);
                             Some(suggestion)
                         }
+                        Err(LlmError::Egress(e)) => {
+                            info!("🔒 Vision skipped: {}", e);
+                            None
+                        }
                         Err(e) => {
                             warn!("⚠️ Vision analysis failed: {}", e);
                             None
@@ -281,7 +502,9 @@ impl ScreenMonitor {
                 .as_secs(),
             image_path: capture_result.path,
             image_base64: capture_result.data,
-            analysis,
+            analysis: analysis.map(|text| redactor.redact(&text)),
+            changed_regions: report.regions,
+            similarity: report.similarity,
         }))
     }
 
@@ -292,8 +515,9 @@ impl ScreenMonitor {
         app: &AppHandle,
         capturer: &Arc<Mutex<Option<ScreenshotCapturer>>>,
         change_detector: &Arc<Mutex<ChangeDetector>>,
-        vision_client: &Arc<Mutex<Option<ClaudeVisionClient>>>,
+        vision_client: &Arc<Mutex<Option<VisionClient>>>,
         ocr_client: &Arc<Mutex<Option<LocalOCR>>>,
+        budget: &Arc<BudgetGovernor>,
     ) -> Result<Option<ScreenChange>, String> {
         // Initialiser le capturer si nécessaire
         {
@@ -307,31 +531,35 @@ impl ScreenMonitor {
         }
 
         // Capture via le Tauri command existant
-        let capture_result = crate::screenshot::capture_screenshot(app.clone())
+        // Zones de confidentialité appliquées à la capture ; rien n'est pris si l'app est protégée
+        let Some(capture_result) = crate::screenshot::capture_frame(app, None)
             .await
-            .map_err(|e| format!("Capture failed: {}", e))?;
+            .map_err(|e| format!("Capture failed: {}", e))?
+        else {
+            return Ok(None);
+        };
 
         let image_path = std::path::PathBuf::from(&capture_result.path);
 
         // Vérifier si changement significatif
-        let (has_change, _current_hash) = {
+        let report = {
             let mut detector = change_detector.lock().await;
-            detector.has_significant_change(&image_path)?
+            detector.detect_changes(&image_path)?
         };
 
-        if !has_change {
+        if !report.has_change {
             return Ok(None);
         }
 
-        // Changement détecté ! Analyser avec OCR local OU Claude Vision
+        // Changement détecté ! Analyser avec OCR local OU Vision
         let analysis = {
             // Priorité à l'OCR local (rapide, gratuit, privacy-first)
             let ocr = ocr_client.lock().await;
             if let Some(ref local_ocr) = *ocr {
-                match local_ocr.analyze(&image_path) {
+                match Self::run_ocr(local_ocr, budget, &image_path, &[]).await {
                     Ok(ocr_result) => {
-                        info!("✅ Local OCR: {} (confidence: {:.2})",
-                              ocr_result.text, ocr_result.confidence);
+                        info!("✅ Local OCR: {} ({} words, confidence: {:.2})",
+                              ocr_result.description, ocr_result.words.len(), ocr_result.confidence);
 
                         // Générer une suggestion basée sur les patterns détectés
                         let suggestion = Self::generate_ocr_suggestion(&ocr_result);
@@ -340,14 +568,19 @@ impl ScreenMonitor {
                     Err(e) => {
                         warn!("⚠️ Local OCR failed: {}", e);
 
-                        // Fallback vers Claude Vision si disponible
+                        // Fallback vers Vision si disponible
                         let vision = vision_client.lock().await;
-                        if let Some(ref claude) = *vision {
-                            match claude.suggest_action(&capture_result.data).await {
+                        if let Some(ref vision) = *vision {
+                            let origin = Self::active_origin(app).await;
+                            match vision.suggest_action(&capture_result.data, origin.as_ref()).await {
                                 Ok(suggestion) => {
-                                    info!("✅ Claude Vision (fallback): {}", suggestion);
+                                    info!("✅ Vision (fallback): {}", suggestion);
                                     Some(suggestion)
                                 }
+                                Err(LlmError::Egress(e)) => {
+                                    info!("🔒 Vision skipped: {}", e);
+                                    None
+                                }
                                 Err(e) => {
                                     warn!("⚠️ Vision analysis also failed: {}", e);
                                     None
@@ -359,14 +592,19 @@ impl ScreenMonitor {
                     }
                 }
             } else {
-                // Pas d'OCR local, essayer Claude Vision
+                // Pas d'OCR local, essayer Vision
                 let vision = vision_client.lock().await;
-                if let Some(ref claude) = *vision {
-                    match claude.suggest_action(&capture_result.data).await {
+                if let Some(ref vision) = *vision {
+                    let origin = Self::active_origin(app).await;
+                    match vision.suggest_action(&capture_result.data, origin.as_ref()).await {
                         Ok(suggestion) => {
-                            info!("✅ Claude Vision suggestion: {}", suggestion);
+                            info!("✅ Vision suggestion: {}", suggestion);
                             Some(suggestion)
                         }
+                        Err(LlmError::Egress(e)) => {
+                            info!("🔒 Vision skipped: {}", e);
+                            None
+                        }
                         Err(e) => {
                             warn!("⚠️ Vision analysis failed: {}", e);
                             None
@@ -386,9 +624,130 @@ impl ScreenMonitor {
             image_path: capture_result.path,
             image_base64: capture_result.data,
             analysis,
+            changed_regions: report.regions,
+            similarity: report.similarity,
         }))
     }
 
+    /// App et fenêtre actives au moment de la capture
+    async fn active_origin(app: &AppHandle) -> Option<RequestOrigin> {
+        let aggregator = app.try_state::<Arc<Mutex<ContextAggregator>>>()?;
+        let peek = aggregator.lock().await.peek().ok()?;
+        Some(RequestOrigin::new(peek.app.name, peek.app.window_title))
+    }
+
+    /// Garde le résultat OCR (avec l'app active) pour le contexte du chat
+    async fn record_finding(
+        app: &AppHandle,
+        recent_findings: &Arc<Mutex<VecDeque<ScreenFinding>>>,
+        redactor: &Redactor,
+        ocr_result: &crate::monitor::OCRResult,
+    ) {
+        let app_name = Self::active_origin(app).await.map(|origin| origin.app_name);
+
+        let mut findings = recent_findings.lock().await;
+        if findings.len() == MAX_RECENT_FINDINGS {
+            findings.pop_front();
+        }
+        findings.push_back(ScreenFinding {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            app_name,
+            summary: redactor.redact(&Self::finding_summary(ocr_result)),
+            diagnostics: ocr_result
+                .diagnostics
+                .iter()
+                .cloned()
+                .map(|mut diagnostic| {
+                    diagnostic.message = redactor.redact(&diagnostic.message);
+                    diagnostic
+                })
+                .collect(),
+        });
+    }
+
+    /// Description des patterns, suivie d'un extrait du texte reconnu
+    fn finding_summary(ocr_result: &crate::monitor::OCRResult) -> String {
+        if ocr_result.words.is_empty() {
+            return ocr_result.description.clone();
+        }
+        let excerpt: String = ocr_result.text.chars().take(MAX_FINDING_TEXT_CHARS).collect();
+        format!("{}\n{}", ocr_result.description, excerpt)
+    }
+
+    /// OCR hors du runtime async : tesseract peut prendre plusieurs secondes.
+    /// Limité aux zones `regions` (écran entier si vide)
+    async fn run_ocr(
+        local_ocr: &LocalOCR,
+        budget: &Arc<BudgetGovernor>,
+        image_path: &std::path::Path,
+        regions: &[ChangedRegion],
+    ) -> Result<crate::monitor::OCRResult, String> {
+        let local_ocr = local_ocr.clone();
+        let budget = budget.clone();
+        let image_path = image_path.to_path_buf();
+        let regions = regions.to_vec();
+        tokio::task::spawn_blocking(move || {
+            budget.measure(Subsystem::Ocr, || {
+                local_ocr.analyze_regions(&image_path, &regions)
+            })
+        })
+        .await
+        .map_err(|e| format!("OCR task failed: {}", e))?
+    }
+
+    /// Client Vision, s'il est configuré et que le budget Vision le permet
+    fn admitted_vision<'a>(
+        vision: &'a Option<VisionClient>,
+        budget: &BudgetGovernor,
+    ) -> Option<&'a VisionClient> {
+        vision
+            .as_ref()
+            .filter(|_| budget.admit(Subsystem::Vision).is_allowed())
+    }
+
+    /// Image envoyée à Vision (base64 JPEG) : la zone modifiée si elle est nettement plus
+    /// petite que l'écran, sinon la capture entière
+    async fn vision_image(capture_result: &CaptureResult, regions: &[ChangedRegion]) -> String {
+        let image_path = std::path::PathBuf::from(&capture_result.path);
+        let regions = regions.to_vec();
+        let cropped = tokio::task::spawn_blocking(move || crop_changed_area(&image_path, &regions))
+            .await
+            .map_err(|e| format!("Crop task failed: {}", e))
+            .and_then(|result| result);
+
+        match cropped {
+            Ok(Some(jpeg)) => general_purpose::STANDARD.encode(jpeg),
+            Ok(None) => capture_result.data.clone(),
+            Err(e) => {
+                warn!("⚠️ Changed area crop failed, sending the full frame: {}", e);
+                capture_result.data.clone()
+            }
+        }
+    }
+
+    /// Résultats OCR récents, du plus ancien au plus récent
+    pub async fn recent_findings(&self) -> Vec<ScreenFinding> {
+        self.recent_findings.lock().await.iter().cloned().collect()
+    }
+
+    /// Erreurs lues à l'écran depuis moins de `max_age`, de la plus ancienne à la plus récente
+    pub async fn recent_diagnostics(&self, max_age: std::time::Duration) -> Vec<Diagnostic> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.recent_findings
+            .lock()
+            .await
+            .iter()
+            .filter(|finding| now.saturating_sub(finding.timestamp) <= max_age.as_secs())
+            .flat_map(|finding| finding.diagnostics.iter().cloned())
+            .collect()
+    }
+
     /// Reset le détecteur de changement
     pub async fn reset_detector(&self) {
         let mut detector = self.change_detector.lock().await;
@@ -467,7 +826,7 @@ impl ScreenMonitor {
         }
 
         if suggestions.is_empty() {
-            format!("Activité détectée : {}. Besoin d'aide ?", ocr_result.text)
+            format!("Activité détectée : {}. Besoin d'aide ?", ocr_result.description)
         } else {
             suggestions.join(" ")
         }
@@ -485,5 +844,33 @@ mod tests {
         assert_eq!(config.similarity_threshold, 0.85);
         assert_eq!(config.use_vision, false);
         assert_eq!(config.enabled, true);
+        assert_eq!(config.vision_provider, VisionProviderKind::Anthropic);
+    }
+
+    #[test]
+    fn test_vision_provider_selection() {
+        let egress = Arc::new(EgressPolicy::default());
+        let mut config: MonitorConfig = serde_json::from_value(serde_json::json!({
+            "interval_secs": 5,
+            "similarity_threshold": 0.85,
+            "use_vision": true,
+            "use_local_ocr": false,
+            "analyze_changed_regions": true,
+            "enabled": true
+        }))
+        .unwrap();
+        assert_eq!(config.vision_url, DEFAULT_OLLAMA_URL);
+
+        // Claude Vision needs a key, the local model does not
+        assert!(ScreenMonitor::vision_client_for(&config, None, &egress, None).is_none());
+        let client = ScreenMonitor::vision_client_for(&config, Some("key".into()), &egress, None);
+        assert_eq!(client.unwrap().provider_name(), "anthropic");
+
+        config.vision_provider = VisionProviderKind::Ollama;
+        let client = ScreenMonitor::vision_client_for(&config, None, &egress, None);
+        assert_eq!(client.unwrap().provider_name(), "ollama");
+
+        config.use_vision = false;
+        assert!(ScreenMonitor::vision_client_for(&config, None, &egress, None).is_none());
     }
 }
@@ -1,8 +1,41 @@
+//! Analyse visuelle des captures.
+//!
+//! `VisionClient` applique la politique de sortie et le journal d'audit, puis délègue à un
+//! `VisionProvider` : Claude Vision (Anthropic), un modèle multimodal local servi par Ollama
+//! (llava, `/api/generate` avec images) ou un mock scripté pour les tests.
+
+use crate::config::types::EgressFallback;
+use crate::llm::ollama::DEFAULT_OLLAMA_URL;
+use crate::llm::LlmError;
+use crate::privacy::audit::{EgressOutcome, OutboundChannel};
+use crate::privacy::egress::is_local_url;
+use crate::privacy::{AuditEvent, AuditLog, EgressPolicy, RequestOrigin};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::timeout;
 use tracing::{error, info};
 
+/// Modèle de vision Ollama utilisé si la configuration n'en choisit pas
+pub const DEFAULT_OLLAMA_VISION_MODEL: &str = "llava";
+
+const MAX_TOKENS: u32 = 1024;
+
+/// Backend d'analyse d'image
+#[async_trait]
+pub trait VisionProvider: Send + Sync {
+    /// Identifiant consigné dans le journal d'audit ("anthropic", "ollama", ...)
+    fn name(&self) -> &str;
+
+    /// Tourne sur cette machine : l'image ne la quitte pas (politique de sortie)
+    fn is_local(&self) -> bool;
+
+    /// Analyse une image JPEG (base64) avec `prompt`
+    async fn analyze(&self, image_base64: &str, prompt: &str) -> Result<String, String>;
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct VisionMessage {
     role: String,
@@ -45,30 +78,38 @@ enum ResponseContent {
     Text { text: String },
 }
 
-pub struct ClaudeVisionClient {
+/// Claude Vision (API Messages d'Anthropic)
+pub struct AnthropicVision {
     api_key: String,
     base_url: String,
 }
 
-impl ClaudeVisionClient {
-    pub fn new() -> Result<Self, String> {
-        let api_key = std::env::var("ANTHROPIC_API_KEY")
-            .map_err(|_| "ANTHROPIC_API_KEY not found in environment".to_string())?;
+impl AnthropicVision {
+    /// La clé vient du trousseau (`llm::selection::api_key`), pas de l'environnement
+    pub fn new(api_key: impl Into<String>) -> Result<Self, String> {
+        let api_key = api_key.into();
+        if api_key.trim().is_empty() {
+            return Err("Anthropic API key not configured".to_string());
+        }
 
         Ok(Self {
             api_key,
             base_url: "https://api.anthropic.com/v1".to_string(),
         })
     }
+}
+
+#[async_trait]
+impl VisionProvider for AnthropicVision {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
 
-    /// Analyse une image avec Claude Vision
-    pub async fn analyze_screenshot(
-        &self,
-        image_base64: &str,
-        prompt: &str,
-    ) -> Result<String, String> {
-        let start = std::time::Instant::now();
+    fn is_local(&self) -> bool {
+        is_local_url(&self.base_url)
+    }
 
+    async fn analyze(&self, image_base64: &str, prompt: &str) -> Result<String, String> {
         let message = VisionMessage {
             role: "user".to_string(),
             content: vec![
@@ -87,7 +128,7 @@ impl ClaudeVisionClient {
 
         let request_body = VisionRequest {
             model: "claude-3-haiku-20240307".to_string(), // Utilise Haiku pour la rapidité
-            max_tokens: 1024,
+            max_tokens: MAX_TOKENS,
             messages: vec![message],
         };
 
@@ -122,22 +163,301 @@ impl ClaudeVisionClient {
             .await
             .map_err(|e| format!("Parse error: {}", e))?;
 
-        let analysis = vision_response
+        vision_response
             .content
             .iter()
             .find_map(|block| match block {
                 ResponseContent::Text { text } => Some(text.clone()),
             })
-            .ok_or_else(|| "No text response from API".to_string())?;
+            .ok_or_else(|| "No text response from API".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OllamaGenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    images: [&'a str; 1],
+    stream: bool,
+    options: OllamaGenerateOptions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OllamaGenerateOptions {
+    num_predict: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OllamaGenerateResponse {
+    response: String,
+}
+
+/// Modèle multimodal local servi par Ollama (llava, bakllava, llama3.2-vision...)
+pub struct OllamaVision {
+    http: reqwest::Client,
+    base_url: String,
+    model: String,
+}
+
+impl OllamaVision {
+    pub fn new(base_url: impl Into<String>, model: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            model: model
+                .filter(|model| !model.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_OLLAMA_VISION_MODEL.to_string()),
+        }
+    }
+}
+
+impl Default for OllamaVision {
+    fn default() -> Self {
+        Self::new(DEFAULT_OLLAMA_URL, None)
+    }
+}
+
+#[async_trait]
+impl VisionProvider for OllamaVision {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn is_local(&self) -> bool {
+        is_local_url(&self.base_url)
+    }
+
+    async fn analyze(&self, image_base64: &str, prompt: &str) -> Result<String, String> {
+        let body = OllamaGenerateRequest {
+            model: &self.model,
+            prompt,
+            images: [image_base64],
+            stream: false,
+            options: OllamaGenerateOptions {
+                num_predict: MAX_TOKENS,
+            },
+        };
+        let url = format!("{}/api/generate", self.base_url);
 
-        let duration = start.elapsed();
-        info!("✅ Claude Vision analysis completed in {}ms", duration.as_millis());
+        info!("🔍 Sending image to Ollama ({})...", self.model);
+
+        // Un modèle local peut mettre du temps à se charger
+        let response = timeout(
+            Duration::from_secs(90),
+            self.http.post(&url).json(&body).send(),
+        )
+        .await
+        .map_err(|_| "Request timeout after 90s".to_string())?
+        .map_err(|e| format!("Network error: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            error!("❌ Ollama vision error {}: {}", status, text);
+            return Err(format!("Ollama error {}: {}", status, text));
+        }
+
+        let generated: OllamaGenerateResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Parse error: {}", e))?;
+        let text = generated.response.trim();
+        if text.is_empty() {
+            return Err(format!("Empty response from {}", self.model));
+        }
+        Ok(text.to_string())
+    }
+}
+
+/// Backend scripté : une réponse (ou erreur) par appel, dans l'ordre.
+/// Garde les prompts reçus pour que les tests puissent les vérifier
+#[allow(dead_code)] // Scripted provider, used by tests
+pub struct MockVision {
+    name: String,
+    local: bool,
+    script: Mutex<VecDeque<Result<String, String>>>,
+    prompts: Mutex<Vec<String>>,
+}
+
+#[allow(dead_code)]
+impl MockVision {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            local: true,
+            script: Mutex::new(VecDeque::new()),
+            prompts: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn reply(self, text: impl Into<String>) -> Self {
+        self.script.lock().unwrap().push_back(Ok(text.into()));
+        self
+    }
+
+    pub fn fail(self, error: impl Into<String>) -> Self {
+        self.script.lock().unwrap().push_back(Err(error.into()));
+        self
+    }
+
+    /// Se comporte comme un fournisseur distant pour la politique de sortie (local par défaut)
+    pub fn remote(mut self) -> Self {
+        self.local = false;
+        self
+    }
+
+    /// Prompts reçus jusqu'ici, dans l'ordre
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl VisionProvider for MockVision {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_local(&self) -> bool {
+        self.local
+    }
+
+    async fn analyze(&self, _image_base64: &str, prompt: &str) -> Result<String, String> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        self.script
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Err(format!("Mock '{}' script exhausted", self.name)))
+    }
+}
+
+/// Analyse d'image : politique de sortie, audit, puis le backend choisi
+pub struct VisionClient {
+    provider: Arc<dyn VisionProvider>,
+    /// Backend local utilisé quand la politique de sortie bloque un backend distant
+    local_fallback: Option<Arc<dyn VisionProvider>>,
+    /// Consultée avant chaque envoi d'image
+    egress: Arc<EgressPolicy>,
+    /// Chaque envoi (ou refus) y est consigné
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl VisionClient {
+    pub fn new(provider: Arc<dyn VisionProvider>) -> Self {
+        Self {
+            provider,
+            local_fallback: None,
+            egress: Arc::new(EgressPolicy::default()),
+            audit: None,
+        }
+    }
+
+    /// Backend local vers lequel basculer si `on_block` le permet
+    pub fn with_local_fallback(mut self, provider: Arc<dyn VisionProvider>) -> Self {
+        self.local_fallback = Some(provider);
+        self
+    }
+
+    pub fn provider_name(&self) -> &str {
+        self.provider.name()
+    }
+
+    /// Utilise la politique de sortie partagée de l'app
+    pub fn set_egress_policy(&mut self, egress: Arc<EgressPolicy>) {
+        self.egress = egress;
+    }
+
+    /// Consigne les envois dans le journal d'audit partagé de l'app
+    pub fn set_audit_log(&mut self, audit: Arc<AuditLog>) {
+        self.audit = Some(audit);
+    }
+
+    fn audit_outbound(
+        &self,
+        provider: &dyn VisionProvider,
+        origin: Option<&RequestOrigin>,
+        bytes: usize,
+        egress: EgressOutcome,
+        reason: Option<String>,
+    ) {
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::Outbound {
+                channel: OutboundChannel::Vision,
+                provider: provider.name().to_string(),
+                remote: !provider.is_local(),
+                bytes,
+                redactions: 0,
+                app_name: origin.map(|o| o.app_name.clone()),
+                egress,
+                reason,
+            });
+        }
+    }
+
+    /// Backend à utiliser pour `origin` : le backend configuré, le backend local si la
+    /// politique de sortie garde l'app en local, ou `LlmError::Egress`
+    fn route(
+        &self,
+        origin: Option<&RequestOrigin>,
+        bytes: usize,
+    ) -> Result<(&Arc<dyn VisionProvider>, EgressOutcome), LlmError> {
+        if self.provider.is_local() {
+            return Ok((&self.provider, EgressOutcome::Local));
+        }
+        let blocked = match self.egress.check_remote(origin, false) {
+            Ok(()) => return Ok((&self.provider, EgressOutcome::Allowed)),
+            Err(e) => e,
+        };
+
+        match &self.local_fallback {
+            Some(local) if self.egress.on_block() == EgressFallback::Reroute => {
+                info!("🔒 {}, rerouting vision to {}", blocked, local.name());
+                Ok((local, EgressOutcome::Rerouted))
+            }
+            _ => {
+                self.audit_outbound(
+                    self.provider.as_ref(),
+                    origin,
+                    bytes,
+                    EgressOutcome::Refused,
+                    Some(blocked.to_string()),
+                );
+                Err(blocked.into())
+            }
+        }
+    }
+
+    /// Analyse une image.
+    /// `origin` : app capturée ; si la politique de sortie la garde en local, l'image n'est
+    /// envoyée qu'au backend local (ou pas du tout : `LlmError::Egress`)
+    pub async fn analyze_screenshot(
+        &self,
+        image_base64: &str,
+        prompt: &str,
+        origin: Option<&RequestOrigin>,
+    ) -> Result<String, LlmError> {
+        let bytes = image_base64.len() + prompt.len();
+        let (provider, egress) = self.route(origin, bytes)?;
+        self.audit_outbound(provider.as_ref(), origin, bytes, egress, None);
+
+        let start = std::time::Instant::now();
+        let analysis = provider.analyze(image_base64, prompt).await?;
+        info!(
+            "✅ Vision analysis ({}) completed in {}ms",
+            provider.name(),
+            start.elapsed().as_millis()
+        );
 
         Ok(analysis)
     }
 
     /// Génère une suggestion basée sur le contexte visuel
-    pub async fn suggest_action(&self, image_base64: &str) -> Result<String, String> {
+    pub async fn suggest_action(
+        &self,
+        image_base64: &str,
+        origin: Option<&RequestOrigin>,
+    ) -> Result<String, LlmError> {
         let prompt = r#"Analyze this screenshot and suggest helpful actions the user might want to take.
 
 Focus on:
@@ -147,11 +467,15 @@ Focus on:
 
 Respond with 1-3 concise, actionable suggestions. Be specific and helpful."#;
 
-        self.analyze_screenshot(image_base64, prompt).await
+        self.analyze_screenshot(image_base64, prompt, origin).await
     }
 
     /// Détecte le domaine/contexte de travail
-    pub async fn detect_context(&self, image_base64: &str) -> Result<String, String> {
+    pub async fn detect_context(
+        &self,
+        image_base64: &str,
+        origin: Option<&RequestOrigin>,
+    ) -> Result<String, LlmError> {
         let prompt = r#"Analyze this screenshot and identify:
 1. The domain (coding, design, music, writing, etc.)
 2. The current task or focus
@@ -159,26 +483,110 @@ Respond with 1-3 concise, actionable suggestions. Be specific and helpful."#;
 
 Respond in 1-2 sentences."#;
 
-        self.analyze_screenshot(image_base64, prompt).await
+        self.analyze_screenshot(image_base64, prompt, origin).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::types::EgressConfig;
+
+    fn local_only(on_block: EgressFallback) -> Arc<EgressPolicy> {
+        Arc::new(
+            EgressPolicy::new(&EgressConfig {
+                local_only: true,
+                on_block,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
 
     #[test]
     fn test_client_creation_without_key() {
         // Should fail if no API key
-        std::env::remove_var("ANTHROPIC_API_KEY");
-        let result = ClaudeVisionClient::new();
+        let result = AnthropicVision::new("");
         assert!(result.is_err());
     }
 
     #[test]
     fn test_client_creation_with_key() {
-        std::env::set_var("ANTHROPIC_API_KEY", "test-key");
-        let result = ClaudeVisionClient::new();
+        let result = AnthropicVision::new("test-key");
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_egress_policy_blocks_before_sending() {
+        let provider = Arc::new(AnthropicVision::new("test-key").unwrap());
+        let mut client = VisionClient::new(provider);
+        client.set_egress_policy(local_only(EgressFallback::Refuse));
+
+        let result = client.suggest_action("aGVsbG8=", None).await;
+        assert!(matches!(result, Err(LlmError::Egress(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_works_offline() {
+        let mock = Arc::new(
+            MockVision::new("mock")
+                .reply("Run the failing test again")
+                .reply("Coding in Rust")
+                .fail("model not loaded"),
+        );
+        let mut client = VisionClient::new(mock.clone());
+        // A local backend is never blocked
+        client.set_egress_policy(local_only(EgressFallback::Refuse));
+
+        let suggestion = client.suggest_action("aGVsbG8=", None).await.unwrap();
+        assert_eq!(suggestion, "Run the failing test again");
+        let context = client.detect_context("aGVsbG8=", None).await.unwrap();
+        assert_eq!(context, "Coding in Rust");
+        let error = client
+            .analyze_screenshot("aGVsbG8=", "What is this?", None)
+            .await
+            .unwrap_err();
+        assert_eq!(error, LlmError::Failed("model not loaded".to_string()));
+
+        let prompts = mock.prompts();
+        assert_eq!(prompts.len(), 3);
+        assert!(prompts[0].contains("suggest helpful actions"));
+        assert!(prompts[1].contains("identify"));
+        assert_eq!(prompts[2], "What is this?");
+    }
+
+    #[tokio::test]
+    async fn test_blocked_remote_reroutes_to_local_backend() {
+        let remote = Arc::new(MockVision::new("remote").remote().reply("remote answer"));
+        let local = Arc::new(MockVision::new("local").reply("local answer"));
+        let mut client = VisionClient::new(remote.clone()).with_local_fallback(local.clone());
+        client.set_egress_policy(local_only(EgressFallback::Reroute));
+
+        let answer = client.suggest_action("aGVsbG8=", None).await.unwrap();
+        assert_eq!(answer, "local answer");
+        assert!(remote.prompts().is_empty());
+
+        client.set_egress_policy(local_only(EgressFallback::Refuse));
+        let result = client.suggest_action("aGVsbG8=", None).await;
+        assert!(matches!(result, Err(LlmError::Egress(_))));
+        assert!(remote.prompts().is_empty());
+    }
+
+    #[test]
+    fn test_ollama_request_shape() {
+        let body = OllamaGenerateRequest {
+            model: DEFAULT_OLLAMA_VISION_MODEL,
+            prompt: "Describe",
+            images: ["aGVsbG8="],
+            stream: false,
+            options: OllamaGenerateOptions { num_predict: 16 },
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["model"], "llava");
+        assert_eq!(json["images"][0], "aGVsbG8=");
+        assert_eq!(json["stream"], false);
+
+        assert!(OllamaVision::default().is_local());
+        assert!(!OllamaVision::new("http://192.168.1.20:11434", None).is_local());
+    }
 }
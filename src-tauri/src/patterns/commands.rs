@@ -13,6 +13,9 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::info;
 
+use crate::privacy::erasure::{AppMatcher, ErasedPatterns};
+use crate::retention::{CategoryReport, ResolvedPolicy};
+
 /// Global pattern recognition manager
 pub struct PatternManager {
     learner: Arc<Mutex<PatternLearner>>,
@@ -26,18 +29,23 @@ impl PatternManager {
         let storage = PatternStorage::new(app_dir)?;
 
         // Try to load existing patterns
-        let learner = PatternLearner::new();
+        let mut learner = PatternLearner::new();
         let mut predictor = ActionPredictor::new();
+        let mut detector = RepetitionDetector::new();
 
         if let Ok(patterns) = storage.load_patterns() {
             info!("📊 Loaded {} patterns from storage", patterns.len());
+            learner.import_patterns(patterns.clone());
             predictor.update_patterns(patterns);
         }
+        if let Ok(tasks) = storage.load_tasks() {
+            detector.import_tasks(tasks);
+        }
 
         Ok(Self {
             learner: Arc::new(Mutex::new(learner)),
             predictor: Arc::new(Mutex::new(predictor)),
-            detector: Arc::new(Mutex::new(RepetitionDetector::new())),
+            detector: Arc::new(Mutex::new(detector)),
             storage: Arc::new(Mutex::new(storage)),
         })
     }
@@ -102,6 +110,63 @@ impl PatternManager {
         Ok(())
     }
 
+    /// Merge imported patterns and tasks (existing ids win), then persist.
+    /// Returns (patterns added, tasks added).
+    pub async fn merge_import(
+        &self,
+        patterns: Vec<WorkflowPattern>,
+        tasks: Vec<RepetitiveTask>,
+    ) -> Result<(usize, usize), String> {
+        let added_patterns = {
+            let mut learner = self.learner.lock().await;
+            let added = learner.import_patterns(patterns);
+            let mut predictor = self.predictor.lock().await;
+            predictor.update_patterns(learner.get_patterns());
+            added
+        };
+        let added_tasks = self.detector.lock().await.import_tasks(tasks);
+
+        self.save().await?;
+        Ok((added_patterns, added_tasks))
+    }
+
+    /// Apply a retention policy to the action histories of the learner and the detector
+    pub async fn apply_retention(
+        &self,
+        policy: &ResolvedPolicy,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> CategoryReport {
+        let mut report = self.learner.lock().await.apply_retention(policy, now);
+        report.merge(self.detector.lock().await.apply_retention(policy, now));
+
+        if report.deleted > 0 {
+            info!("🧹 Removed {} pattern history entries (retention)", report.deleted);
+        }
+        report
+    }
+
+    /// Forget an app in the learner, the detector and the predictor, then persist
+    pub async fn forget_app(&self, matcher: &AppMatcher) -> Result<ErasedPatterns, String> {
+        let mut erased = {
+            let mut learner = self.learner.lock().await;
+            let erased = learner.forget_app(matcher);
+            let mut predictor = self.predictor.lock().await;
+            predictor.update_patterns(learner.get_patterns());
+            predictor.forget_app(matcher);
+            erased
+        };
+        let tasks = self.detector.lock().await.forget_app(matcher);
+        erased.actions += tasks.actions;
+        erased.repetitive_tasks += tasks.repetitive_tasks;
+
+        self.save().await?;
+        info!(
+            "🗑️ Removed {} actions, {} patterns, {} repetitive tasks (forgotten app)",
+            erased.actions, erased.patterns, erased.repetitive_tasks
+        );
+        Ok(erased)
+    }
+
     /// Get comprehensive statistics
     pub async fn get_stats(&self) -> PatternSystemStats {
         let learner = self.learner.lock().await;
@@ -8,6 +8,9 @@ use std::collections::{HashMap, VecDeque};
 use chrono::{DateTime, Utc};
 use tracing::{info, debug};
 
+use crate::privacy::erasure::{AppMatcher, ErasedPatterns};
+use crate::retention::{entry_size, from_unix_seconds, CategoryReport, ResolvedPolicy};
+
 /// Maximum number of events to keep in memory
 const MAX_EVENT_HISTORY: usize = 1000;
 
@@ -281,7 +284,65 @@ impl PatternLearner {
         patterns
     }
 
+    /// Import previously learned patterns, keeping existing ones with the same id.
+    /// Returns the number of patterns added.
+    pub fn import_patterns(&mut self, patterns: Vec<WorkflowPattern>) -> usize {
+        let mut added = 0;
+        for pattern in patterns {
+            if !self.discovered_patterns.contains_key(&pattern.id) {
+                self.discovered_patterns.insert(pattern.id.clone(), pattern);
+                added += 1;
+            }
+        }
+        added
+    }
+
     /// Get patterns matching specific tags
+    /// Apply a retention policy to the raw action history (learned patterns are kept)
+    pub fn apply_retention(&mut self, policy: &ResolvedPolicy, now: DateTime<Utc>) -> CategoryReport {
+        let items: Vec<(DateTime<Utc>, u64)> = self
+            .event_history
+            .iter()
+            .map(|a| (from_unix_seconds(a.timestamp), entry_size(a)))
+            .collect();
+        let expired = policy.select_expired(&items, now);
+
+        if !expired.is_empty() {
+            let mut index = 0;
+            self.event_history.retain(|_| {
+                let keep = expired.binary_search(&index).is_err();
+                index += 1;
+                keep
+            });
+        }
+
+        CategoryReport::from_selection(&items, &expired)
+    }
+
+    /// Forget an app: drop its actions, the sequences and the patterns involving it
+    pub fn forget_app(&mut self, matcher: &AppMatcher) -> ErasedPatterns {
+        let involves = |signature: &ActionSignature| {
+            matcher.matches_window_opt(&signature.app_name, signature.window_pattern.as_deref())
+        };
+
+        let before = self.event_history.len();
+        self.event_history.retain(|action| {
+            !matcher.matches_window_opt(&action.app_name, action.window_title.as_deref())
+        });
+        self.sequence_counts
+            .retain(|sequence, _| !sequence.iter().any(involves));
+
+        let patterns = self.discovered_patterns.len();
+        self.discovered_patterns
+            .retain(|_, pattern| !pattern.sequence.iter().any(involves));
+
+        ErasedPatterns {
+            actions: before - self.event_history.len(),
+            patterns: patterns - self.discovered_patterns.len(),
+            repetitive_tasks: 0,
+        }
+    }
+
     pub fn get_patterns_by_tag(&self, tag: &str) -> Vec<WorkflowPattern> {
         self.discovered_patterns
             .values()
@@ -348,6 +409,35 @@ mod tests {
         assert!(stats.total_patterns_discovered > 0);
     }
 
+    #[test]
+    fn test_forget_app() {
+        let mut learner = PatternLearner::new();
+        for _ in 0..5 {
+            learner.record_action(create_test_action("VS Code", ActionType::AppSwitch));
+            learner.record_action(create_test_action("Acme CRM", ActionType::AppSwitch));
+        }
+        for _ in 0..5 {
+            learner.record_action(create_test_action("VS Code", ActionType::FileSave));
+            learner.record_action(create_test_action("Terminal", ActionType::Command));
+        }
+
+        let matcher = AppMatcher::new(&crate::privacy::ErasureTarget {
+            app_name: Some("Acme CRM".to_string()),
+            title_pattern: None,
+        })
+        .unwrap();
+        let erased = learner.forget_app(&matcher);
+
+        assert_eq!(erased.actions, 5);
+        assert!(erased.patterns > 0);
+        assert!(!learner.get_patterns().is_empty());
+        assert!(learner
+            .get_patterns()
+            .iter()
+            .all(|p| p.sequence.iter().all(|s| s.app_name != "Acme CRM")));
+        assert!(learner.event_history.iter().all(|a| a.app_name != "Acme CRM"));
+    }
+
     #[test]
     fn test_confidence_calculation() {
         let learner = PatternLearner::new();
@@ -4,6 +4,7 @@
  */
 
 use super::learning::{ActionSignature, UserAction, WorkflowPattern};
+use crate::privacy::erasure::AppMatcher;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, info};
@@ -50,6 +51,14 @@ impl ActionPredictor {
         self.recent_actions = actions;
     }
 
+    /// Drop recent actions from a forgotten app
+    pub fn forget_app(&mut self, matcher: &AppMatcher) {
+        self.recent_actions.retain(|action| {
+            !matcher.matches_window_opt(&action.app_name, action.window_title.as_deref())
+        });
+        self.prediction_cache.clear();
+    }
+
     /// Predict next action based on current context
     pub fn predict_next_action(&mut self) -> Option<Prediction> {
         if self.recent_actions.is_empty() {
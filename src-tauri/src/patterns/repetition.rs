@@ -9,6 +9,9 @@ use std::collections::{HashMap, VecDeque};
 use chrono::{DateTime, Utc, Duration};
 use tracing::info;
 
+use crate::privacy::erasure::{AppMatcher, ErasedPatterns};
+use crate::retention::{entry_size, from_unix_seconds, CategoryReport, ResolvedPolicy};
+
 /// Minimum repetitions to flag a task as repetitive
 const MIN_REPETITIONS: usize = 3;
 
@@ -340,7 +343,65 @@ impl RepetitionDetector {
         tasks
     }
 
+    /// Import previously detected tasks, keeping existing ones with the same id.
+    /// Returns the number of tasks added.
+    pub fn import_tasks(&mut self, tasks: Vec<RepetitiveTask>) -> usize {
+        let mut added = 0;
+        for task in tasks {
+            if !self.detected_tasks.contains_key(&task.id) {
+                self.detected_tasks.insert(task.id.clone(), task);
+                added += 1;
+            }
+        }
+        added
+    }
+
     /// Get high-priority tasks (high automation potential + high repetitions)
+    /// Apply a retention policy to the raw action history (detected tasks are kept)
+    pub fn apply_retention(&mut self, policy: &ResolvedPolicy, now: DateTime<Utc>) -> CategoryReport {
+        let items: Vec<(DateTime<Utc>, u64)> = self
+            .action_history
+            .iter()
+            .map(|a| (from_unix_seconds(a.timestamp), entry_size(a)))
+            .collect();
+        let expired = policy.select_expired(&items, now);
+
+        if !expired.is_empty() {
+            let mut index = 0;
+            self.action_history.retain(|_| {
+                let keep = expired.binary_search(&index).is_err();
+                index += 1;
+                keep
+            });
+        }
+
+        CategoryReport::from_selection(&items, &expired)
+    }
+
+    /// Forget an app: drop its actions and the tasks involving it
+    pub fn forget_app(&mut self, matcher: &AppMatcher) -> ErasedPatterns {
+        let involves = |signature: &ActionSignature| {
+            matcher.matches_window_opt(&signature.app_name, signature.window_pattern.as_deref())
+        };
+
+        let before = self.action_history.len();
+        self.action_history.retain(|action| {
+            !matcher.matches_window_opt(&action.app_name, action.window_title.as_deref())
+        });
+        self.task_occurrences
+            .retain(|sequence, _| !sequence.iter().any(involves));
+
+        let tasks = self.detected_tasks.len();
+        self.detected_tasks
+            .retain(|_, task| !task.actions.iter().any(involves));
+
+        ErasedPatterns {
+            actions: before - self.action_history.len(),
+            patterns: 0,
+            repetitive_tasks: tasks - self.detected_tasks.len(),
+        }
+    }
+
     pub fn get_high_priority_tasks(&self) -> Vec<RepetitiveTask> {
         self.detected_tasks
             .values()
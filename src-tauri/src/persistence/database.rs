@@ -1,35 +1,111 @@
 use chrono::Utc;
-use sqlx::{Row, SqlitePool};
-use std::path::PathBuf;
-use tracing::{debug, error, info};
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous,
+};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
 
+use crate::crypto::keymanager::KeyManager;
+use crate::persistence::encryption::{ColumnCipher, ENCRYPTED_COLUMNS};
+use crate::persistence::export::{
+    ExportBundle, ImportReport, OutcomeRecord, TrustEventRecord, TrustStateRecord,
+};
+use crate::persistence::migrator::Migrator;
 use crate::persistence::models::*;
+use crate::privacy::erasure::{AppMatcher, ErasedRecords};
+use crate::retention::{CategoryReport, ResolvedPolicy};
+use crate::screenshot::store::parse_reference;
 
 /// Gestionnaire de base de données SQLite pour la persistance
 pub struct DatabaseManager {
     pool: SqlitePool,
     db_path: PathBuf,
+    cipher: Option<Arc<ColumnCipher>>,
 }
 
 impl DatabaseManager {
-    /// Initialise la base de données et crée les tables
+    /// Ouvre la base de données persistante (colonnes sensibles chiffrées) dans le répertoire de données de l'app.
+    /// Sans clé persistante, le chiffrement est désactivé pour la session : une clé volatile
+    /// rendrait illisible tout ce qu'elle scelle, données existantes comprises.
     pub async fn new() -> Result<Self, String> {
-        // Utiliser une base de données en mémoire pour tester
-        let database_url = "sqlite::memory:";
+        let db_path = Self::get_database_path()?;
 
-        info!("🗄️ Initializing in-memory database");
+        let key_manager = Arc::new(KeyManager::new());
+        let cipher = if key_manager.is_persistent() {
+            match ColumnCipher::new(key_manager).await {
+                Ok(cipher) => Some(Arc::new(cipher)),
+                Err(e) => {
+                    error!(
+                        "❌ Encryption key unavailable ({}) → column encryption disabled for this session, stored values left untouched",
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            error!("❌ Keychain unavailable → column encryption disabled for this session, stored values left untouched");
+            None
+        };
+
+        Self::open_with_cipher(db_path, cipher).await
+    }
+
+    /// Ouvre (ou crée) une base sans chiffrement de colonnes
+    #[cfg(test)]
+    pub async fn open(db_path: impl Into<PathBuf>) -> Result<Self, String> {
+        Self::open_with_cipher(db_path, None).await
+    }
+
+    /// Ouvre (ou crée) la base SQLite à l'emplacement donné et applique les migrations.
+    /// `:memory:` ouvre une base volatile sur une connexion unique (tests).
+    pub async fn open_with_cipher(
+        db_path: impl Into<PathBuf>,
+        cipher: Option<Arc<ColumnCipher>>,
+    ) -> Result<Self, String> {
+        let db_path = db_path.into();
+        let in_memory = db_path.as_os_str() == ":memory:";
 
-        let pool = SqlitePool::connect(database_url)
+        let base_options = if in_memory {
+            info!("🗄️ Opening in-memory database");
+            SqliteConnectOptions::from_str("sqlite::memory:")
+                .map_err(|e| format!("Invalid database options: {}", e))?
+        } else {
+            info!("🗄️ Opening database at {:?}", db_path);
+            SqliteConnectOptions::new()
+                .filename(&db_path)
+                .create_if_missing(true)
+                .journal_mode(SqliteJournalMode::Wal)
+                .synchronous(SqliteSynchronous::Normal)
+        };
+        let options = base_options
+            .foreign_keys(true)
+            .busy_timeout(std::time::Duration::from_secs(5));
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(if in_memory { 1 } else { 4 })
+            .connect_with(options)
             .await
             .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
         let manager = Self {
             pool,
-            db_path: std::path::PathBuf::from(":memory:"),
+            db_path,
+            cipher,
         };
 
-        // Créer les tables
-        manager.create_tables().await?;
+        // Appliquer les migrations en attente
+        manager.run_migrations().await?;
+
+        // Chiffrer les données écrites avant l'activation du chiffrement
+        if manager.cipher.is_some() {
+            let sealed = manager.encrypt_plaintext_rows().await?;
+            if sealed > 0 {
+                info!("🔐 Encrypted {} legacy plaintext value(s)", sealed);
+            }
+        }
 
         info!("✅ Database initialized successfully");
         Ok(manager)
@@ -37,57 +113,186 @@ impl DatabaseManager {
 
     /// Obtient le chemin de la base de données
     fn get_database_path() -> Result<PathBuf, String> {
-        // Utiliser un chemin temporaire pour tester
-        let temp_dir = std::env::temp_dir();
-        let shadowlearn_dir = temp_dir.join("ShadowLearn");
+        let shadowlearn_dir = dirs::data_dir()
+            .ok_or_else(|| "Could not find data directory".to_string())?
+            .join("ShadowLearn");
         std::fs::create_dir_all(&shadowlearn_dir)
-            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
 
         Ok(shadowlearn_dir.join("shadowlearn.db"))
     }
 
-    /// Crée toutes les tables nécessaires
-    async fn create_tables(&self) -> Result<(), String> {
-        debug!("Creating database tables...");
+    /// Exécute les migrations en attente
+    async fn run_migrations(&self) -> Result<(), String> {
+        let migrator = Migrator::new(&self.pool);
+        let applied = migrator.run().await?;
+        debug!(
+            "Schema at version {} ({} migration(s) applied)",
+            migrator.latest_version(),
+            applied
+        );
+        Ok(())
+    }
+
+    /// Version actuelle du schéma
+    pub async fn schema_version(&self) -> Result<i64, String> {
+        Migrator::new(&self.pool).current_version().await
+    }
 
-        // Exécuter les migrations dans l'ordre
-        self.run_migration("001_initial_schema.sql").await?;
-        self.run_migration("002_add_trust_tables.sql").await?;
+    /// Chemin du fichier de base de données
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
 
-        debug!("✅ Database tables created successfully");
-        Ok(())
+    /// Chiffre des colonnes sensibles, partagé avec le magasin de captures
+    pub fn cipher(&self) -> Option<Arc<ColumnCipher>> {
+        self.cipher.clone()
+    }
+
+    /// Chiffre une colonne sensible (identité si le chiffrement est désactivé)
+    fn seal(&self, value: &str) -> Result<String, String> {
+        match &self.cipher {
+            Some(cipher) => cipher.encrypt(value),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    fn seal_opt(&self, value: Option<&str>) -> Result<Option<String>, String> {
+        value.map(|v| self.seal(v)).transpose()
     }
 
-    /// Exécuter une migration SQL
-    async fn run_migration(&self, migration_file: &str) -> Result<(), String> {
-        debug!("Running migration: {}", migration_file);
+    /// Déchiffre une colonne sensible lue en base
+    fn unseal(&self, value: String) -> String {
+        match &self.cipher {
+            Some(cipher) => cipher.decrypt_lossy(&value),
+            None => value,
+        }
+    }
+
+    fn unseal_opt(&self, value: Option<String>) -> Option<String> {
+        value.map(|v| self.unseal(v))
+    }
 
-        let migration_sql = match migration_file {
-            "001_initial_schema.sql" => include_str!("migrations/001_initial_schema.sql"),
-            "002_add_trust_tables.sql" => include_str!("migrations/002_add_trust_tables.sql"),
-            _ => return Err(format!("Unknown migration file: {}", migration_file)),
+    /// Chiffre les valeurs en clair restantes dans les colonnes sensibles
+    async fn encrypt_plaintext_rows(&self) -> Result<usize, String> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
         };
 
-        // Diviser les requêtes par ';' et les exécuter
-        for statement in migration_sql.split(';') {
-            let statement = statement.trim();
-            if !statement.is_empty() && !statement.starts_with("--") {
-                debug!("Executing statement: {}", statement);
-                sqlx::query(statement)
-                    .execute(&self.pool)
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin encryption transaction: {}", e))?;
+        let mut count = 0;
+
+        for (table, columns) in ENCRYPTED_COLUMNS {
+            for column in *columns {
+                let select = format!(
+                    "SELECT rowid, {col} FROM {table} WHERE {col} IS NOT NULL AND {col} NOT LIKE 'enc:v1:%'",
+                    col = column,
+                    table = table
+                );
+                let rows = sqlx::query(&select)
+                    .fetch_all(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Failed to scan {}.{}: {}", table, column, e))?;
+
+                let update = format!("UPDATE {} SET {} = ? WHERE rowid = ?", table, column);
+                for row in rows {
+                    let rowid: i64 = row.get(0);
+                    let plain: String = row.get(1);
+                    sqlx::query(&update)
+                        .bind(cipher.encrypt(&plain)?)
+                        .bind(rowid)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| format!("Failed to encrypt {}.{}: {}", table, column, e))?;
+                    count += 1;
+                }
+            }
+        }
+
+        // Les documents indexés en clair sont ré-indexés sous forme de jetons
+        if count > 0 {
+            rebuild_search_index(&mut tx, cipher).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit encryption: {}", e))?;
+        Ok(count)
+    }
+
+    /// Fait tourner la clé de chiffrement : re-chiffre les colonnes sensibles
+    /// puis les fichiers `.enc` des répertoires donnés.
+    /// L'ancienne clé reste lisible jusqu'à la fin, une rotation interrompue peut être relancée.
+    pub async fn rotate_encryption_key(&self, files_dirs: &[PathBuf]) -> Result<usize, String> {
+        let cipher = self
+            .cipher
+            .as_ref()
+            .ok_or_else(|| "Column encryption is disabled".to_string())?;
+        let key_manager = cipher.key_manager();
+
+        let (old, new) = key_manager.begin_rotation().await?;
+        cipher.reload().await?;
+        let current_id = cipher.current_key_id();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin rotation transaction: {}", e))?;
+        let mut count = 0;
+
+        for (table, columns) in ENCRYPTED_COLUMNS {
+            for column in *columns {
+                let select = format!(
+                    "SELECT rowid, {col} FROM {table} WHERE {col} LIKE 'enc:v1:%'",
+                    col = column,
+                    table = table
+                );
+                let rows = sqlx::query(&select)
+                    .fetch_all(&mut *tx)
                     .await
-                    .map_err(|e| {
-                        format!(
-                            "Failed to execute migration statement in {}: {} - Error: {}",
-                            migration_file, statement, e
-                        )
-                    })?;
-                debug!("Statement executed successfully");
+                    .map_err(|e| format!("Failed to scan {}.{}: {}", table, column, e))?;
+
+                let update = format!("UPDATE {} SET {} = ? WHERE rowid = ?", table, column);
+                for row in rows {
+                    let rowid: i64 = row.get(0);
+                    let sealed: String = row.get(1);
+                    if ColumnCipher::key_id_of(&sealed) == Some(current_id.as_str()) {
+                        continue;
+                    }
+                    let plain = cipher.decrypt(&sealed)?;
+                    sqlx::query(&update)
+                        .bind(cipher.encrypt(&plain)?)
+                        .bind(rowid)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| format!("Failed to re-encrypt {}.{}: {}", table, column, e))?;
+                    count += 1;
+                }
             }
         }
 
-        debug!("✅ Migration {} completed", migration_file);
-        Ok(())
+        // Les jetons de l'index dérivent de la clé
+        rebuild_search_index(&mut tx, cipher).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit key rotation: {}", e))?;
+
+        for dir in files_dirs.iter().filter(|dir| dir.is_dir()) {
+            let files = key_manager.reencrypt_files(dir, &old, &new).await?;
+            debug!("🔑 Re-encrypted {} file(s) in {:?}", files, dir);
+        }
+
+        key_manager.finish_rotation().await?;
+        cipher.reload().await?;
+
+        info!("✅ Key rotation complete: {} database value(s) re-encrypted", count);
+        Ok(count)
     }
 
     /// Sauvegarde une nouvelle conversation
@@ -97,6 +302,12 @@ impl DatabaseManager {
     ) -> Result<PersistenceResult<Conversation>, String> {
         let start = std::time::Instant::now();
 
+        // La conversation et son entrée d'index sont enregistrées ensemble
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin conversation transaction: {}", e))?;
         let result = sqlx::query(
             r#"
             INSERT INTO conversations (id, title, created_at, updated_at, app_context, message_count, is_archived)
@@ -110,13 +321,29 @@ impl DatabaseManager {
         .bind(&conversation.app_context)
         .bind(conversation.message_count)
         .bind(conversation.is_archived)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await;
 
         let operation_time = start.elapsed().as_millis() as u64;
 
         match result {
             Ok(_) => {
+                self.index_document(
+                    &mut tx,
+                    SearchHitKind::Conversation,
+                    &conversation.id,
+                    Some(&conversation.id),
+                    &conversation.title,
+                    "",
+                    conversation.app_context.as_deref(),
+                    None,
+                    &conversation.created_at.to_rfc3339(),
+                )
+                .await?;
+                tx.commit()
+                    .await
+                    .map_err(|e| format!("Failed to commit conversation: {}", e))?;
+
                 debug!("💾 Conversation saved: {}", conversation.id);
                 Ok(PersistenceResult::success(
                     conversation.clone(),
@@ -137,6 +364,12 @@ impl DatabaseManager {
     ) -> Result<PersistenceResult<Message>, String> {
         let start = std::time::Instant::now();
 
+        // Message, compteur et index enregistrés ensemble : un échec annule le tout
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin message transaction: {}", e))?;
         let result = sqlx::query(
             r#"
             INSERT INTO messages (id, conversation_id, role, content, created_at, metadata)
@@ -146,10 +379,10 @@ impl DatabaseManager {
         .bind(&message.id)
         .bind(&message.conversation_id)
         .bind(&message.role)
-        .bind(&message.content)
+        .bind(self.seal(&message.content)?)
         .bind(message.created_at.to_rfc3339())
-        .bind(&message.metadata)
-        .execute(&self.pool)
+        .bind(self.seal_opt(message.metadata.as_deref())?)
+        .execute(&mut *tx)
         .await;
 
         let operation_time = start.elapsed().as_millis() as u64;
@@ -157,8 +390,12 @@ impl DatabaseManager {
         match result {
             Ok(_) => {
                 // Mettre à jour le compteur de messages dans la conversation
-                self.update_conversation_message_count(&message.conversation_id)
+                self.update_conversation_message_count(&mut tx, &message.conversation_id)
                     .await?;
+                self.index_message(&mut tx, message).await?;
+                tx.commit()
+                    .await
+                    .map_err(|e| format!("Failed to commit message: {}", e))?;
 
                 debug!("💾 Message saved: {}", message.id);
                 Ok(PersistenceResult::success(message.clone(), operation_time))
@@ -171,7 +408,11 @@ impl DatabaseManager {
     }
 
     /// Met à jour le compteur de messages d'une conversation
-    async fn update_conversation_message_count(&self, conversation_id: &str) -> Result<(), String> {
+    async fn update_conversation_message_count(
+        &self,
+        conn: &mut SqliteConnection,
+        conversation_id: &str,
+    ) -> Result<(), String> {
         sqlx::query(
             r#"
             UPDATE conversations 
@@ -185,7 +426,7 @@ impl DatabaseManager {
         .bind(conversation_id)
         .bind(Utc::now().to_rfc3339())
         .bind(conversation_id)
-        .execute(&self.pool)
+        .execute(&mut *conn)
         .await
         .map_err(|e| format!("Failed to update message count: {}", e))?;
 
@@ -232,6 +473,87 @@ impl DatabaseManager {
         Ok(conversations)
     }
 
+    /// Récupère une conversation par son id
+    pub async fn get_conversation(&self, id: &str) -> Result<Option<Conversation>, String> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, title, CAST(created_at AS TEXT) AS created_at, CAST(updated_at AS TEXT) AS updated_at,
+                   app_context, message_count, is_archived
+            FROM conversations
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to fetch conversation {}: {}", id, e))?;
+
+        Ok(row.map(|row| Conversation {
+            id: row.get("id"),
+            title: row.get("title"),
+            created_at: parse_datetime(&row.get::<String, _>("created_at")),
+            updated_at: parse_datetime(&row.get::<String, _>("updated_at")),
+            app_context: row.get("app_context"),
+            message_count: row.get("message_count"),
+            is_archived: row.get("is_archived"),
+        }))
+    }
+
+    /// Récupère le résumé glissant d'une conversation
+    pub async fn get_conversation_summary(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Option<ConversationSummary>, String> {
+        let row = sqlx::query(
+            r#"
+            SELECT conversation_id, summary, covers_until_message_id, covered_messages, updated_at
+            FROM conversation_summaries
+            WHERE conversation_id = ?
+            "#,
+        )
+        .bind(conversation_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to fetch conversation summary: {}", e))?;
+
+        Ok(row.map(|row| ConversationSummary {
+            conversation_id: row.get("conversation_id"),
+            summary: self.unseal(row.get("summary")),
+            covers_until_message_id: row.get("covers_until_message_id"),
+            covered_messages: row.get("covered_messages"),
+            updated_at: parse_datetime(&row.get::<String, _>("updated_at")),
+        }))
+    }
+
+    /// Enregistre (ou remplace) le résumé glissant d'une conversation
+    pub async fn save_conversation_summary(
+        &self,
+        summary: &ConversationSummary,
+    ) -> Result<(), String> {
+        sqlx::query(
+            r#"
+            INSERT INTO conversation_summaries
+                (conversation_id, summary, covers_until_message_id, covered_messages, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(conversation_id) DO UPDATE SET
+                summary = excluded.summary,
+                covers_until_message_id = excluded.covers_until_message_id,
+                covered_messages = excluded.covered_messages,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&summary.conversation_id)
+        .bind(self.seal(&summary.summary)?)
+        .bind(&summary.covers_until_message_id)
+        .bind(summary.covered_messages)
+        .bind(summary.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to save conversation summary: {}", e))?;
+
+        Ok(())
+    }
+
     /// Récupère les messages d'une conversation
     pub async fn get_conversation_messages(
         &self,
@@ -261,13 +583,13 @@ impl DatabaseManager {
                     "system" => MessageRole::System,
                     _ => MessageRole::User,
                 },
-                content: row.get("content"),
+                content: self.unseal(row.get("content")),
                 created_at: chrono::DateTime::parse_from_rfc3339(
                     &row.get::<String, _>("created_at"),
                 )
                 .unwrap()
                 .with_timezone(&Utc),
-                metadata: row.get("metadata"),
+                metadata: self.unseal_opt(row.get("metadata")),
             })
             .collect();
 
@@ -291,9 +613,7 @@ impl DatabaseManager {
             .await
             .map_err(|e| format!("Failed to get contexts count: {}", e))?;
 
-        let db_size = std::fs::metadata(&self.db_path)
-            .map_err(|e| format!("Failed to get database size: {}", e))?
-            .len() as i64;
+        let db_size = self.database_size_bytes() as i64;
 
         Ok(PersistenceStats {
             total_conversations: conversations_count.0,
@@ -320,11 +640,260 @@ impl DatabaseManager {
         .await
         .map_err(|e| format!("Failed to fetch contexts for app {}: {}", app_name, e))?;
 
+        let contexts = contexts
+            .into_iter()
+            .map(|mut context| {
+                context.window_title = self.unseal(context.window_title);
+                context.clipboard_content = self.unseal_opt(context.clipboard_content);
+                context.screenshot_data = self.unseal_opt(context.screenshot_data);
+                context
+            })
+            .collect();
+
         Ok(contexts)
     }
 
+    /// Contextes dont la capture est rangée dans le magasin : paires (id du contexte, référence)
+    pub async fn context_screenshot_refs(&self) -> Result<Vec<(String, String)>, String> {
+        let rows = sqlx::query(
+            "SELECT id, screenshot_data FROM captured_contexts WHERE screenshot_data IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to list context screenshots: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let data = self.unseal(row.get("screenshot_data"));
+                parse_reference(&data)?;
+                Some((row.get("id"), data))
+            })
+            .collect())
+    }
+
+    /// Taille sur disque de la base, journal WAL compris (0 pour une base en mémoire)
+    pub fn database_size_bytes(&self) -> u64 {
+        ["", "-wal", "-shm"]
+            .iter()
+            .filter_map(|suffix| {
+                let mut path = self.db_path.clone().into_os_string();
+                path.push(suffix);
+                std::fs::metadata(path).ok()
+            })
+            .map(|m| m.len())
+            .sum()
+    }
+
+    /// Applique une politique de rétention aux contextes capturés et à leurs entrées d'index
+    pub async fn apply_context_retention(
+        &self,
+        policy: &ResolvedPolicy,
+        now: chrono::DateTime<Utc>,
+    ) -> Result<CategoryReport, String> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, CAST(created_at AS TEXT) AS created_at,
+                   COALESCE(LENGTH(window_title), 0)
+                   + COALESCE(LENGTH(clipboard_content), 0)
+                   + COALESCE(LENGTH(screenshot_data), 0) AS size
+            FROM captured_contexts
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to list contexts for retention: {}", e))?;
+
+        let items: Vec<(chrono::DateTime<Utc>, u64)> = rows
+            .iter()
+            .map(|row| {
+                let created_at: String = row.get("created_at");
+                let size: i64 = row.get("size");
+                (parse_datetime(&created_at), size.max(0) as u64)
+            })
+            .collect();
+        let expired = policy.select_expired(&items, now);
+        if expired.is_empty() {
+            return Ok(CategoryReport::from_selection(&items, &expired));
+        }
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin retention transaction: {}", e))?;
+
+        for &index in &expired {
+            let id: String = rows[index].get("id");
+            sqlx::query(
+                "DELETE FROM search_fts WHERE rowid IN (SELECT rowid FROM search_documents WHERE kind = 'context' AND ref_id = ?)",
+            )
+            .bind(&id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to unindex context {}: {}", id, e))?;
+            sqlx::query("DELETE FROM search_documents WHERE kind = 'context' AND ref_id = ?")
+                .bind(&id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to unindex context {}: {}", id, e))?;
+            sqlx::query("DELETE FROM captured_contexts WHERE id = ?")
+                .bind(&id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to delete context {}: {}", id, e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit retention: {}", e))?;
+
+        info!("🧹 Removed {} captured contexts (retention)", expired.len());
+        self.compact().await;
+
+        Ok(CategoryReport::from_selection(&items, &expired))
+    }
+
+    /// Supprime les contextes capturés correspondant à `matcher`, les conversations qui leur
+    /// sont liées ou ouvertes dans l'app (messages, résumés, contextes rattachés) et leurs
+    /// entrées d'index
+    pub async fn forget_app(&self, matcher: &AppMatcher) -> Result<ErasedRecords, String> {
+        let contexts = sqlx::query(
+            "SELECT id, conversation_id, app_name, window_title FROM captured_contexts",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to list contexts for erasure: {}", e))?;
+
+        let mut context_ids = Vec::new();
+        let mut conversation_ids = std::collections::BTreeSet::new();
+        for row in &contexts {
+            let app_name: String = row.get("app_name");
+            let window_title = self.unseal(row.get("window_title"));
+            if matcher.matches_window(&app_name, &window_title) {
+                context_ids.push(row.get::<String, _>("id"));
+                if let Some(conversation_id) = row.get::<Option<String>, _>("conversation_id") {
+                    conversation_ids.insert(conversation_id);
+                }
+            }
+        }
+
+        let conversations = sqlx::query("SELECT id, app_context FROM conversations")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to list conversations for erasure: {}", e))?;
+        for row in &conversations {
+            let app_context: Option<String> = row.get("app_context");
+            if app_context.is_some_and(|app| matcher.matches_app(&app)) {
+                conversation_ids.insert(row.get("id"));
+            }
+        }
+
+        let mut erased = ErasedRecords::default();
+        if context_ids.is_empty() && conversation_ids.is_empty() {
+            return Ok(erased);
+        }
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin erasure transaction: {}", e))?;
+
+        for id in &conversation_ids {
+            // Index entries of the conversation, its messages and its contexts
+            sqlx::query(
+                "DELETE FROM search_fts WHERE rowid IN (SELECT rowid FROM search_documents WHERE conversation_id = ?)",
+            )
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to unindex conversation {}: {}", id, e))?;
+            sqlx::query("DELETE FROM search_documents WHERE conversation_id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to unindex conversation {}: {}", id, e))?;
+
+            erased.contexts +=
+                sqlx::query("DELETE FROM captured_contexts WHERE conversation_id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Failed to delete contexts of {}: {}", id, e))?
+                    .rows_affected() as usize;
+            erased.messages += sqlx::query("DELETE FROM messages WHERE conversation_id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to delete messages of {}: {}", id, e))?
+                .rows_affected() as usize;
+            sqlx::query("DELETE FROM conversation_summaries WHERE conversation_id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to delete summary of {}: {}", id, e))?;
+            erased.conversations += sqlx::query("DELETE FROM conversations WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to delete conversation {}: {}", id, e))?
+                .rows_affected() as usize;
+        }
+
+        for id in &context_ids {
+            sqlx::query(
+                "DELETE FROM search_fts WHERE rowid IN (SELECT rowid FROM search_documents WHERE kind = 'context' AND ref_id = ?)",
+            )
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to unindex context {}: {}", id, e))?;
+            sqlx::query("DELETE FROM search_documents WHERE kind = 'context' AND ref_id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to unindex context {}: {}", id, e))?;
+            // Already gone if it belonged to an erased conversation
+            erased.contexts += sqlx::query("DELETE FROM captured_contexts WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to delete context {}: {}", id, e))?
+                .rows_affected() as usize;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit erasure: {}", e))?;
+
+        info!(
+            "🗑️ Erased {} contexts, {} conversations, {} messages",
+            erased.contexts, erased.conversations, erased.messages
+        );
+        // Les données supprimées ne doivent pas survivre dans les pages libres
+        self.compact().await;
+
+        Ok(erased)
+    }
+
+    /// Rend au système l'espace libéré par les suppressions
+    async fn compact(&self) {
+        for statement in ["VACUUM", "PRAGMA wal_checkpoint(TRUNCATE)"] {
+            if let Err(e) = sqlx::query(statement).execute(&self.pool).await {
+                warn!("⚠️ {} failed: {}", statement, e);
+            }
+        }
+    }
+
     /// Sauvegarde un contexte capturé
     pub async fn save_context(&self, context: &CapturedContext) -> Result<(), String> {
+        // Le contexte et son entrée d'index sont enregistrés ensemble
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin context transaction: {}", e))?;
         sqlx::query(
             r#"
             INSERT INTO captured_contexts (
@@ -337,98 +906,643 @@ impl DatabaseManager {
         .bind(&context.conversation_id)
         .bind(&context.app_name)
         .bind(&context.app_bundle_id)
-        .bind(&context.window_title)
-        .bind(&context.clipboard_content)
+        .bind(self.seal(&context.window_title)?)
+        .bind(self.seal_opt(context.clipboard_content.as_deref())?)
         .bind(context.idle_seconds)
-        .bind(&context.screenshot_data)
+        .bind(self.seal_opt(context.screenshot_data.as_deref())?)
         .bind(context.created_at)
         .bind(&context.trigger_reason)
         .bind(context.capture_duration_ms)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| format!("Failed to save context: {}", e))?;
 
-        Ok(())
-    }
-
-    /// Obtenir le trust d'un utilisateur
-    pub async fn get_user_trust(
-        &self,
-        device_id: &str,
-    ) -> Result<crate::learning::trust::UserTrust, String> {
-        let row = sqlx::query(
-            "SELECT id, device_id, pos, neg, trust, quarantine, last_updated, created_at FROM user_trust WHERE device_id = ?"
+        self.index_document(
+            &mut tx,
+            SearchHitKind::Context,
+            &context.id,
+            context.conversation_id.as_deref(),
+            &context.window_title,
+            context.clipboard_content.as_deref().unwrap_or(""),
+            Some(&context.app_name),
+            None,
+            &context.created_at.to_rfc3339(),
         )
-        .bind(device_id)
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to get user trust: {}", e))?;
+        .await?;
 
-        match row {
-            Some(row) => Ok(crate::learning::trust::UserTrust {
-                id: row.get::<String, _>("id"),
-                device_id: row.get::<String, _>("device_id"),
-                pos: row.get::<f32, _>("pos"),
-                neg: row.get::<f32, _>("neg"),
-                trust: row.get::<f32, _>("trust"),
-                quarantine: row.get::<i32, _>("quarantine") != 0,
-                last_updated: std::time::Instant::now(), // TODO: Convert from timestamp
-                created_at: std::time::Instant::now(),   // TODO: Convert from timestamp
-            }),
-            None => Err("User trust not found".to_string()),
-        }
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit context: {}", e))
     }
 
-    /// Créer un nouveau trust utilisateur
-    pub async fn create_user_trust(
+    /// Ajoute un document à l'index plein texte
+    #[allow(clippy::too_many_arguments)]
+    async fn index_document(
         &self,
-        trust: &crate::learning::trust::UserTrust,
+        conn: &mut SqliteConnection,
+        kind: SearchHitKind,
+        ref_id: &str,
+        conversation_id: Option<&str>,
+        title: &str,
+        content: &str,
+        app_name: Option<&str>,
+        role: Option<&str>,
+        created_at: &str,
     ) -> Result<(), String> {
-        sqlx::query(
-            "INSERT INTO user_trust (id, device_id, pos, neg, trust, quarantine, last_updated, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        // Le texte n'est conservé que sous forme d'index inversé (table FTS sans contenu)
+        let rowid = sqlx::query(
+            r#"
+            INSERT INTO search_documents (kind, ref_id, conversation_id, app_name, role, title, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
         )
-        .bind(&trust.id)
-        .bind(&trust.device_id)
-        .bind(trust.pos)
-        .bind(trust.neg)
-        .bind(trust.trust)
-        .bind(if trust.quarantine { 1 } else { 0 })
-        .bind(chrono::Utc::now())
-        .bind(chrono::Utc::now())
-        .execute(&self.pool)
+        .bind(kind.as_str())
+        .bind(ref_id)
+        .bind(conversation_id)
+        .bind(app_name)
+        .bind(role)
+        .bind(self.seal(title)?)
+        .bind(created_at)
+        .execute(&mut *conn)
         .await
-        .map_err(|e| format!("Failed to create user trust: {}", e))?;
+        .map_err(|e| format!("Failed to index {} {}: {}", kind.as_str(), ref_id, e))?
+        .last_insert_rowid();
+
+        sqlx::query("INSERT INTO search_fts (rowid, title, content) VALUES (?, ?, ?)")
+            .bind(rowid)
+            .bind(self.index_text(title))
+            .bind(self.index_text(content))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to index {} {}: {}", kind.as_str(), ref_id, e))?;
 
         Ok(())
     }
 
-    /// Mettre à jour le trust d'un utilisateur
-    pub async fn update_user_trust(
+    /// Texte versé dans l'index plein texte : tel quel sans chiffrement, sinon un jeton
+    /// à clé par mot pour que l'index ne contienne pas les mots des colonnes chiffrées
+    fn index_text(&self, text: &str) -> String {
+        match &self.cipher {
+            Some(cipher) => search_tokens(cipher, text),
+            None => text.to_string(),
+        }
+    }
+
+    /// Indexe un message avec le titre et l'app de sa conversation
+    async fn index_message(
         &self,
-        trust: &crate::learning::trust::UserTrust,
+        conn: &mut SqliteConnection,
+        message: &Message,
     ) -> Result<(), String> {
-        sqlx::query(
-            "UPDATE user_trust SET pos = ?, neg = ?, trust = ?, quarantine = ?, last_updated = ? WHERE device_id = ?"
+        let conversation = sqlx::query("SELECT title, app_context FROM conversations WHERE id = ?")
+            .bind(&message.conversation_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to fetch conversation for indexing: {}", e))?;
+
+        let (title, app_context): (String, Option<String>) = match conversation {
+            Some(row) => (row.get("title"), row.get("app_context")),
+            None => (String::new(), None),
+        };
+
+        self.index_document(
+            conn,
+            SearchHitKind::Message,
+            &message.id,
+            Some(&message.conversation_id),
+            &title,
+            &message.content,
+            app_context.as_deref(),
+            Some(message_role_str(&message.role)),
+            &message.created_at.to_rfc3339(),
         )
-        .bind(trust.pos)
-        .bind(trust.neg)
-        .bind(trust.trust)
-        .bind(if trust.quarantine { 1 } else { 0 })
-        .bind(chrono::Utc::now())
-        .bind(&trust.device_id)
-        .execute(&self.pool)
         .await
-        .map_err(|e| format!("Failed to update user trust: {}", e))?;
-
-        Ok(())
     }
 
-    /// Sauvegarder un événement de trust
-    pub async fn save_trust_event(
-        &self,
-        event: &crate::learning::trust::TrustEvent,
-    ) -> Result<(), String> {
-        sqlx::query(
+    /// Recherche plein texte classée (BM25) avec filtres app, période et rôle.
+    /// Avec le chiffrement, l'index ne contient que des jetons à clé : les mots doivent
+    /// correspondre exactement (ni préfixe, ni repli des accents).
+    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, String> {
+        let terms = search_terms(&query.query);
+        let match_expr = match &self.cipher {
+            Some(cipher) => build_token_match_expression(cipher, &terms),
+            None => build_match_expression(&terms),
+        };
+        let match_expr = match match_expr {
+            Some(expr) => expr,
+            None => return Ok(Vec::new()),
+        };
+
+        // Le texte source est relu (et déchiffré) depuis les tables d'origine
+        let mut sql = String::from(
+            r#"
+            SELECT d.kind, d.ref_id, d.conversation_id, d.title, d.app_name, d.role, d.created_at,
+                   CASE d.kind
+                       WHEN 'message' THEN m.content
+                       WHEN 'context' THEN c.clipboard_content
+                       ELSE NULL
+                   END AS source_content,
+                   bm25(search_fts, 2.0, 1.0) AS score
+            FROM search_fts
+            JOIN search_documents d ON d.rowid = search_fts.rowid
+            LEFT JOIN messages m ON d.kind = 'message' AND m.id = d.ref_id
+            LEFT JOIN captured_contexts c ON d.kind = 'context' AND c.id = d.ref_id
+            WHERE search_fts MATCH ?
+            "#,
+        );
+
+        if query.app_name.is_some() {
+            sql.push_str(" AND d.app_name = ?");
+        }
+        if query.since.is_some() {
+            sql.push_str(" AND d.created_at >= ?");
+        }
+        if query.until.is_some() {
+            sql.push_str(" AND d.created_at <= ?");
+        }
+        if query.role.is_some() {
+            sql.push_str(" AND d.kind = 'message' AND d.role = ?");
+        }
+        let kinds: Vec<&'static str> = query
+            .kinds
+            .as_ref()
+            .map(|kinds| kinds.iter().map(|k| k.as_str()).collect())
+            .unwrap_or_default();
+        if !kinds.is_empty() {
+            let placeholders = vec!["?"; kinds.len()].join(", ");
+            sql.push_str(&format!(" AND d.kind IN ({})", placeholders));
+        }
+        sql.push_str(" ORDER BY score LIMIT ?");
+
+        let mut q = sqlx::query(&sql).bind(match_expr);
+        if let Some(app_name) = &query.app_name {
+            q = q.bind(app_name);
+        }
+        if let Some(since) = &query.since {
+            q = q.bind(since.to_rfc3339());
+        }
+        if let Some(until) = &query.until {
+            q = q.bind(until.to_rfc3339());
+        }
+        if let Some(role) = &query.role {
+            q = q.bind(role.to_lowercase());
+        }
+        for kind in &kinds {
+            q = q.bind(*kind);
+        }
+        q = q.bind(query.limit.unwrap_or(50).clamp(1, 500));
+
+        let rows = q
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to search: {}", e))?;
+
+        let hits = rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind = SearchHitKind::parse(&row.get::<String, _>("kind"))?;
+                let title = self.unseal(row.get("title"));
+                let content = self
+                    .unseal_opt(row.get("source_content"))
+                    .unwrap_or_default();
+                let snippet = if has_term(&content, &terms) || !has_term(&title, &terms) {
+                    build_snippet(&content, &terms, SNIPPET_RADIUS)
+                } else {
+                    build_snippet(&title, &terms, SNIPPET_RADIUS)
+                };
+
+                Some(SearchHit {
+                    kind,
+                    id: row.get("ref_id"),
+                    conversation_id: row.get("conversation_id"),
+                    title,
+                    snippet,
+                    app_name: row.get("app_name"),
+                    role: row.get("role"),
+                    created_at: row.get("created_at"),
+                    score: row.get("score"),
+                })
+            })
+            .collect();
+
+        Ok(hits)
+    }
+
+    /// Remplit un bundle d'export avec toutes les tables (valeurs déchiffrées)
+    pub async fn export_records(&self, bundle: &mut ExportBundle) -> Result<(), String> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, title, created_at, updated_at, app_context, message_count, is_archived
+            FROM conversations
+            ORDER BY created_at ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to export conversations: {}", e))?;
+
+        bundle.conversations = rows
+            .into_iter()
+            .map(|row| Conversation {
+                id: row.get("id"),
+                title: row.get("title"),
+                created_at: parse_datetime(&row.get::<String, _>("created_at")),
+                updated_at: parse_datetime(&row.get::<String, _>("updated_at")),
+                app_context: row.get("app_context"),
+                message_count: row.get("message_count"),
+                is_archived: row.get("is_archived"),
+            })
+            .collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, conversation_id, role, content, created_at, metadata
+            FROM messages
+            ORDER BY created_at ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to export messages: {}", e))?;
+
+        bundle.messages = rows
+            .into_iter()
+            .map(|row| Message {
+                id: row.get("id"),
+                conversation_id: row.get("conversation_id"),
+                role: parse_message_role(&row.get::<String, _>("role")),
+                content: self.unseal(row.get("content")),
+                created_at: parse_datetime(&row.get::<String, _>("created_at")),
+                metadata: self.unseal_opt(row.get("metadata")),
+            })
+            .collect();
+
+        let contexts = sqlx::query_as::<_, CapturedContext>(
+            "SELECT * FROM captured_contexts ORDER BY created_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to export captured contexts: {}", e))?;
+
+        bundle.captured_contexts = contexts
+            .into_iter()
+            .map(|mut context| {
+                context.window_title = self.unseal(context.window_title);
+                context.clipboard_content = self.unseal_opt(context.clipboard_content);
+                context.screenshot_data = self.unseal_opt(context.screenshot_data);
+                context
+            })
+            .collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, device_id, pos, neg, trust,
+                   CAST(quarantine AS INTEGER) AS quarantine,
+                   CAST(last_updated AS TEXT) AS last_updated,
+                   CAST(created_at AS TEXT) AS created_at
+            FROM user_trust
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to export trust state: {}", e))?;
+
+        bundle.trust_state = rows
+            .into_iter()
+            .map(|row| TrustStateRecord {
+                id: row.get("id"),
+                device_id: row.get("device_id"),
+                pos: row.get("pos"),
+                neg: row.get("neg"),
+                trust: row.get("trust"),
+                quarantine: row.get::<i64, _>("quarantine") != 0,
+                last_updated: row.get("last_updated"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, device_id, reward, CAST(timestamp AS TEXT) AS timestamp
+            FROM trust_events
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to export trust events: {}", e))?;
+
+        bundle.trust_events = rows
+            .into_iter()
+            .map(|row| TrustEventRecord {
+                id: row.get("id"),
+                device_id: row.get("device_id"),
+                reward: row.get("reward"),
+                timestamp: row.get("timestamp"),
+            })
+            .collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, suggestion_id,
+                   CAST(used AS INTEGER) AS used,
+                   CAST(helpful AS INTEGER) AS helpful,
+                   CAST(reverted AS INTEGER) AS reverted,
+                   time_to_flow_ms, reward, cluster_id, artefact_type,
+                   CAST(timestamp AS TEXT) AS timestamp
+            FROM outcomes
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to export outcomes: {}", e))?;
+
+        bundle.outcomes = rows
+            .into_iter()
+            .map(|row| OutcomeRecord {
+                id: row.get("id"),
+                suggestion_id: row.get("suggestion_id"),
+                used: row.get::<i64, _>("used") != 0,
+                helpful: row.get::<i64, _>("helpful") != 0,
+                reverted: row.get::<i64, _>("reverted") != 0,
+                time_to_flow_ms: row.get("time_to_flow_ms"),
+                reward: row.get("reward"),
+                cluster_id: self.unseal(row.get("cluster_id")),
+                artefact_type: row.get("artefact_type"),
+                timestamp: row.get("timestamp"),
+            })
+            .collect();
+
+        Ok(())
+    }
+
+    /// Fusionne les tables d'un bundle dans la base : les ids déjà présents sont ignorés.
+    /// L'import se fait en une transaction : un échec n'en laisse rien.
+    pub async fn import_records(
+        &self,
+        bundle: &ExportBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), String> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin import transaction: {}", e))?;
+
+        for conversation in &bundle.conversations {
+            let inserted = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO conversations (id, title, created_at, updated_at, app_context, message_count, is_archived)
+                VALUES (?, ?, ?, ?, ?, 0, ?)
+                "#,
+            )
+            .bind(&conversation.id)
+            .bind(&conversation.title)
+            .bind(conversation.created_at.to_rfc3339())
+            .bind(conversation.updated_at.to_rfc3339())
+            .bind(&conversation.app_context)
+            .bind(conversation.is_archived)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to import conversation {}: {}", conversation.id, e))?
+            .rows_affected()
+                == 1;
+
+            if inserted {
+                self.index_document(
+                    &mut tx,
+                    SearchHitKind::Conversation,
+                    &conversation.id,
+                    Some(&conversation.id),
+                    &conversation.title,
+                    "",
+                    conversation.app_context.as_deref(),
+                    None,
+                    &conversation.created_at.to_rfc3339(),
+                )
+                .await?;
+            }
+            report.conversations.record(inserted);
+        }
+
+        let mut touched_conversations = std::collections::HashSet::new();
+        for message in &bundle.messages {
+            let inserted = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO messages (id, conversation_id, role, content, created_at, metadata)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&message.id)
+            .bind(&message.conversation_id)
+            .bind(message_role_str(&message.role))
+            .bind(self.seal(&message.content)?)
+            .bind(message.created_at.to_rfc3339())
+            .bind(self.seal_opt(message.metadata.as_deref())?)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to import message {}: {}", message.id, e))?
+            .rows_affected()
+                == 1;
+
+            if inserted {
+                self.index_message(&mut tx, message).await?;
+                touched_conversations.insert(message.conversation_id.clone());
+            }
+            report.messages.record(inserted);
+        }
+        for conversation_id in &touched_conversations {
+            self.update_conversation_message_count(&mut tx, conversation_id)
+                .await?;
+        }
+
+        for context in &bundle.captured_contexts {
+            let inserted = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO captured_contexts (
+                    id, conversation_id, app_name, app_bundle_id, window_title,
+                    clipboard_content, idle_seconds, screenshot_data, created_at, trigger_reason, capture_duration_ms
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&context.id)
+            .bind(&context.conversation_id)
+            .bind(&context.app_name)
+            .bind(&context.app_bundle_id)
+            .bind(self.seal(&context.window_title)?)
+            .bind(self.seal_opt(context.clipboard_content.as_deref())?)
+            .bind(context.idle_seconds)
+            .bind(self.seal_opt(context.screenshot_data.as_deref())?)
+            .bind(context.created_at)
+            .bind(&context.trigger_reason)
+            .bind(context.capture_duration_ms)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to import context {}: {}", context.id, e))?
+            .rows_affected()
+                == 1;
+
+            if inserted {
+                self.index_document(
+                    &mut tx,
+                    SearchHitKind::Context,
+                    &context.id,
+                    context.conversation_id.as_deref(),
+                    &context.window_title,
+                    context.clipboard_content.as_deref().unwrap_or(""),
+                    Some(&context.app_name),
+                    None,
+                    &context.created_at.to_rfc3339(),
+                )
+                .await?;
+            }
+            report.captured_contexts.record(inserted);
+        }
+
+        // Un seul état de confiance par appareil : l'état local est conservé
+        for trust in &bundle.trust_state {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO user_trust (id, device_id, pos, neg, trust, quarantine, last_updated, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&trust.id)
+            .bind(&trust.device_id)
+            .bind(trust.pos)
+            .bind(trust.neg)
+            .bind(trust.trust)
+            .bind(if trust.quarantine { 1 } else { 0 })
+            .bind(&trust.last_updated)
+            .bind(&trust.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to import trust state {}: {}", trust.id, e))?
+            .rows_affected()
+                == 1;
+            report.trust_state.record(inserted);
+        }
+
+        for event in &bundle.trust_events {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO trust_events (id, device_id, reward, timestamp) VALUES (?, ?, ?, ?)",
+            )
+            .bind(&event.id)
+            .bind(&event.device_id)
+            .bind(event.reward)
+            .bind(&event.timestamp)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to import trust event {}: {}", event.id, e))?
+            .rows_affected()
+                == 1;
+            report.trust_events.record(inserted);
+        }
+
+        for outcome in &bundle.outcomes {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO outcomes (id, suggestion_id, used, helpful, reverted, time_to_flow_ms, reward, cluster_id, artefact_type, timestamp) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&outcome.id)
+            .bind(&outcome.suggestion_id)
+            .bind(if outcome.used { 1 } else { 0 })
+            .bind(if outcome.helpful { 1 } else { 0 })
+            .bind(if outcome.reverted { 1 } else { 0 })
+            .bind(outcome.time_to_flow_ms)
+            .bind(outcome.reward)
+            .bind(self.seal(&outcome.cluster_id)?)
+            .bind(&outcome.artefact_type)
+            .bind(&outcome.timestamp)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to import outcome {}: {}", outcome.id, e))?
+            .rows_affected()
+                == 1;
+            report.outcomes.record(inserted);
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit import: {}", e))?;
+        Ok(())
+    }
+
+    /// Obtenir le trust d'un utilisateur
+    pub async fn get_user_trust(
+        &self,
+        device_id: &str,
+    ) -> Result<crate::learning::trust::UserTrust, String> {
+        let row = sqlx::query(
+            "SELECT id, device_id, pos, neg, trust, quarantine, last_updated, created_at FROM user_trust WHERE device_id = ?"
+        )
+        .bind(device_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to get user trust: {}", e))?;
+
+        match row {
+            Some(row) => Ok(crate::learning::trust::UserTrust {
+                id: row.get::<String, _>("id"),
+                device_id: row.get::<String, _>("device_id"),
+                pos: row.get::<f32, _>("pos"),
+                neg: row.get::<f32, _>("neg"),
+                trust: row.get::<f32, _>("trust"),
+                quarantine: row.get::<i32, _>("quarantine") != 0,
+                last_updated: std::time::Instant::now(), // TODO: Convert from timestamp
+                created_at: std::time::Instant::now(),   // TODO: Convert from timestamp
+            }),
+            None => Err("User trust not found".to_string()),
+        }
+    }
+
+    /// Créer un nouveau trust utilisateur
+    pub async fn create_user_trust(
+        &self,
+        trust: &crate::learning::trust::UserTrust,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO user_trust (id, device_id, pos, neg, trust, quarantine, last_updated, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&trust.id)
+        .bind(&trust.device_id)
+        .bind(trust.pos)
+        .bind(trust.neg)
+        .bind(trust.trust)
+        .bind(if trust.quarantine { 1 } else { 0 })
+        .bind(chrono::Utc::now())
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to create user trust: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Mettre à jour le trust d'un utilisateur
+    pub async fn update_user_trust(
+        &self,
+        trust: &crate::learning::trust::UserTrust,
+    ) -> Result<(), String> {
+        sqlx::query(
+            "UPDATE user_trust SET pos = ?, neg = ?, trust = ?, quarantine = ?, last_updated = ? WHERE device_id = ?"
+        )
+        .bind(trust.pos)
+        .bind(trust.neg)
+        .bind(trust.trust)
+        .bind(if trust.quarantine { 1 } else { 0 })
+        .bind(chrono::Utc::now())
+        .bind(&trust.device_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to update user trust: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Sauvegarder un événement de trust
+    pub async fn save_trust_event(
+        &self,
+        event: &crate::learning::trust::TrustEvent,
+    ) -> Result<(), String> {
+        sqlx::query(
             "INSERT INTO trust_events (id, device_id, reward, timestamp) VALUES (?, ?, ?, ?)",
         )
         .bind(&event.id)
@@ -515,7 +1629,7 @@ impl DatabaseManager {
         .bind(if reverted { 1 } else { 0 })
         .bind(time_to_flow_ms)
         .bind(reward)
-        .bind(cluster_id)
+        .bind(self.seal(cluster_id)?)
         .bind(artefact_type)
         .bind(chrono::Utc::now())
         .execute(&self.pool)
@@ -565,3 +1679,735 @@ impl DatabaseManager {
         Ok(outcomes)
     }
 }
+
+/// Lit une date stockée en base : RFC 3339, ou `CURRENT_TIMESTAMP` SQLite (UTC). Epoch si illisible.
+fn parse_datetime(value: &str) -> chrono::DateTime<Utc> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .map(|dt| dt.and_utc())
+        })
+        .unwrap_or_default()
+}
+
+/// Rôle de message depuis sa valeur SQL
+fn parse_message_role(value: &str) -> MessageRole {
+    match value {
+        "assistant" => MessageRole::Assistant,
+        "system" => MessageRole::System,
+        _ => MessageRole::User,
+    }
+}
+
+/// Nom SQL d'un rôle de message
+fn message_role_str(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::User => "user",
+        MessageRole::Assistant => "assistant",
+        MessageRole::System => "system",
+    }
+}
+
+/// Nombre de mots gardés de part et d'autre du premier terme trouvé
+const SNIPPET_RADIUS: usize = 8;
+
+/// Termes de recherche normalisés (minuscules, sans guillemets)
+fn search_terms(input: &str) -> Vec<String> {
+    input
+        .split_whitespace()
+        .map(|t| t.replace('"', "").to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Convertit les termes en expression FTS5 sûre :
+/// chaque terme est cité, le dernier accepte un préfixe.
+fn build_match_expression(terms: &[String]) -> Option<String> {
+    if terms.is_empty() {
+        return None;
+    }
+
+    let quoted: Vec<String> = terms.iter().map(|t| format!("\"{}\"", t)).collect();
+    Some(format!("{}*", quoted.join(" ")))
+}
+
+/// Variante pour un index à jetons : mots exacts, sans recherche par préfixe
+fn build_token_match_expression(cipher: &ColumnCipher, terms: &[String]) -> Option<String> {
+    let tokens: Vec<String> = terms
+        .iter()
+        .flat_map(|term| {
+            word_spans(term)
+                .into_iter()
+                .map(|(s, e)| format!("\"{}\"", cipher.search_token(&term[s..e])))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    if tokens.is_empty() {
+        None
+    } else {
+        Some(tokens.join(" "))
+    }
+}
+
+/// Un jeton par mot (en minuscules), séparés par des espaces
+fn search_tokens(cipher: &ColumnCipher, text: &str) -> String {
+    word_spans(text)
+        .into_iter()
+        .map(|(s, e)| cipher.search_token(&text[s..e].to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Reconstruit l'index plein texte avec les jetons de la clé courante,
+/// à partir des valeurs (déchiffrées) des tables d'origine
+async fn rebuild_search_index(
+    conn: &mut SqliteConnection,
+    cipher: &ColumnCipher,
+) -> Result<usize, String> {
+    let rows = sqlx::query(
+        r#"
+        SELECT d.rowid AS rowid, d.title AS title,
+               CASE d.kind
+                   WHEN 'message' THEN m.content
+                   WHEN 'context' THEN c.clipboard_content
+                   ELSE NULL
+               END AS source_content
+        FROM search_documents d
+        LEFT JOIN messages m ON d.kind = 'message' AND m.id = d.ref_id
+        LEFT JOIN captured_contexts c ON d.kind = 'context' AND c.id = d.ref_id
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to read search documents: {}", e))?;
+
+    sqlx::query("INSERT INTO search_fts (search_fts) VALUES ('delete-all')")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to clear search index: {}", e))?;
+
+    for row in &rows {
+        let title = cipher.decrypt(&row.get::<String, _>("title"))?;
+        let content = row
+            .get::<Option<String>, _>("source_content")
+            .map(|value| cipher.decrypt(&value))
+            .transpose()?
+            .unwrap_or_default();
+
+        sqlx::query("INSERT INTO search_fts (rowid, title, content) VALUES (?, ?, ?)")
+            .bind(row.get::<i64, _>("rowid"))
+            .bind(search_tokens(cipher, &title))
+            .bind(search_tokens(cipher, &content))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to rebuild search index: {}", e))?;
+    }
+
+    debug!("🔎 Search index rebuilt ({} document(s))", rows.len());
+    Ok(rows.len())
+}
+
+/// Positions (en octets) des mots d'un texte
+fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, text.len()));
+    }
+
+    spans
+}
+
+fn is_term_match(word: &str, terms: &[String]) -> bool {
+    let word = word.to_lowercase();
+    terms.iter().any(|t| word.starts_with(t.as_str()))
+}
+
+fn has_term(text: &str, terms: &[String]) -> bool {
+    word_spans(text)
+        .into_iter()
+        .any(|(s, e)| is_term_match(&text[s..e], terms))
+}
+
+/// Extrait autour du premier terme trouvé, termes entourés de `**`
+fn build_snippet(text: &str, terms: &[String], radius: usize) -> String {
+    let words = word_spans(text);
+    if words.is_empty() {
+        return String::new();
+    }
+
+    let first = words
+        .iter()
+        .position(|&(s, e)| is_term_match(&text[s..e], terms))
+        .unwrap_or(0);
+    let from = first.saturating_sub(radius);
+    let to = (first + radius + 1).min(words.len());
+
+    let mut out = String::new();
+    if from > 0 {
+        out.push('…');
+    }
+    let mut cursor = words[from].0;
+    for &(s, e) in &words[from..to] {
+        out.push_str(&text[cursor..s]);
+        if is_term_match(&text[s..e], terms) {
+            out.push_str("**");
+            out.push_str(&text[s..e]);
+            out.push_str("**");
+        } else {
+            out.push_str(&text[s..e]);
+        }
+        cursor = e;
+    }
+    if to < words.len() {
+        out.push('…');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    /// Termes présents dans l'index plein texte
+    async fn indexed_terms(db: &DatabaseManager) -> Vec<String> {
+        let mut conn = db.pool.acquire().await.unwrap();
+        sqlx::query(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS temp.search_vocab USING fts5vocab(main, search_fts, 'row')",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        sqlx::query_scalar("SELECT term FROM temp.search_vocab")
+            .fetch_all(&mut *conn)
+            .await
+            .unwrap()
+    }
+
+    async fn seeded_db() -> DatabaseManager {
+        let db = DatabaseManager::open(":memory:").await.unwrap();
+
+        let conversation = Conversation {
+            id: "conv-1".to_string(),
+            title: "Rust lifetimes".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            app_context: Some("VS Code".to_string()),
+            message_count: 0,
+            is_archived: false,
+        };
+        db.save_conversation(&conversation).await.unwrap();
+
+        for (role, content) in [
+            (MessageRole::User, "Why does the borrow checker reject this?"),
+            (
+                MessageRole::Assistant,
+                "The borrow checker error E0502 means you hold a mutable and an immutable borrow at once.",
+            ),
+        ] {
+            let message = Message {
+                id: Uuid::new_v4().to_string(),
+                conversation_id: conversation.id.clone(),
+                role,
+                content: content.to_string(),
+                created_at: Utc::now(),
+                metadata: None,
+            };
+            db.save_message(&message).await.unwrap();
+        }
+
+        let context = CapturedContext {
+            id: "ctx-1".to_string(),
+            conversation_id: None,
+            app_name: "Terminal".to_string(),
+            app_bundle_id: "com.apple.Terminal".to_string(),
+            window_title: "cargo build — borrow errors".to_string(),
+            clipboard_content: Some("error[E0502]: cannot borrow `v` as mutable".to_string()),
+            idle_seconds: 3.0,
+            screenshot_data: None,
+            created_at: Utc::now(),
+            trigger_reason: "idle_ok".to_string(),
+            capture_duration_ms: 10,
+        };
+        db.save_context(&context).await.unwrap();
+
+        db
+    }
+
+    #[test]
+    fn test_match_expression_is_quoted() {
+        assert_eq!(
+            build_match_expression(&search_terms("Borrow \"checker")),
+            Some("\"borrow\" \"checker\"*".to_string())
+        );
+        assert_eq!(build_match_expression(&search_terms("   ")), None);
+    }
+
+    #[test]
+    fn test_snippet_highlights_terms() {
+        let terms = search_terms("borrow");
+        let snippet = build_snippet(
+            "one two three four five six seven eight nine ten the borrow checker says no",
+            &terms,
+            2,
+        );
+        assert_eq!(snippet, "…ten the **borrow** checker says…");
+    }
+
+    #[tokio::test]
+    async fn test_export_import_merges_without_duplicates() {
+        let source = seeded_db().await;
+        let mut bundle = ExportBundle::new(source.schema_version().await.unwrap());
+        source.export_records(&mut bundle).await.unwrap();
+        assert_eq!(bundle.conversations.len(), 1);
+        assert_eq!(bundle.messages.len(), 2);
+        assert_eq!(bundle.captured_contexts.len(), 1);
+
+        let target = DatabaseManager::open(":memory:").await.unwrap();
+        let mut report = ImportReport::default();
+        target.import_records(&bundle, &mut report).await.unwrap();
+        assert_eq!(report.messages.imported, 2);
+
+        // Re-importing the same bundle is a no-op
+        let mut again = ImportReport::default();
+        target.import_records(&bundle, &mut again).await.unwrap();
+        assert_eq!(again.messages.imported, 0);
+        assert_eq!(again.messages.skipped, 2);
+
+        let conversations = target.get_recent_conversations(10).await.unwrap();
+        assert_eq!(conversations[0].message_count, 2);
+        let hits = target
+            .search(&SearchQuery {
+                query: "borrow".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_leaves_nothing_behind() {
+        let source = seeded_db().await;
+        let mut bundle = ExportBundle::new(source.schema_version().await.unwrap());
+        source.export_records(&mut bundle).await.unwrap();
+        bundle.trust_events.push(TrustEventRecord {
+            id: "event-1".to_string(),
+            device_id: "device".to_string(),
+            reward: 1.0,
+            timestamp: Utc::now().to_rfc3339(),
+        });
+
+        // Trust events are imported last: make that step fail
+        let target = DatabaseManager::open(":memory:").await.unwrap();
+        sqlx::query("DROP TABLE trust_events")
+            .execute(&target.pool)
+            .await
+            .unwrap();
+
+        let mut report = ImportReport::default();
+        assert!(target.import_records(&bundle, &mut report).await.is_err());
+        assert!(target.get_recent_conversations(10).await.unwrap().is_empty());
+        assert!(target.get_conversation_messages("conv-1").await.unwrap().is_empty());
+        let hits = target
+            .search(&SearchQuery {
+                query: "borrow".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_columns_encrypted_at_rest() {
+        let cipher = ColumnCipher::new(Arc::new(KeyManager::volatile()))
+            .await
+            .unwrap();
+        let db = DatabaseManager::open_with_cipher(":memory:", Some(Arc::new(cipher)))
+            .await
+            .unwrap();
+
+        let conversation = Conversation {
+            id: "conv-enc".to_string(),
+            title: "Secrets".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            app_context: None,
+            message_count: 0,
+            is_archived: false,
+        };
+        db.save_conversation(&conversation).await.unwrap();
+        let message = Message {
+            id: "msg-enc".to_string(),
+            conversation_id: conversation.id.clone(),
+            role: MessageRole::User,
+            content: "my password is hunter2".to_string(),
+            created_at: Utc::now(),
+            metadata: None,
+        };
+        db.save_message(&message).await.unwrap();
+
+        let raw: (String,) = sqlx::query_as("SELECT content FROM messages WHERE id = 'msg-enc'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert!(ColumnCipher::is_encrypted(&raw.0));
+        assert!(!raw.0.contains("hunter2"));
+
+        let messages = db.get_conversation_messages("conv-enc").await.unwrap();
+        assert_eq!(messages[0].content, "my password is hunter2");
+
+        let hits = db
+            .search(&SearchQuery {
+                query: "hunter2".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].snippet.contains("**hunter2**"));
+
+        // The index holds keyed tokens, not the words themselves
+        let terms = indexed_terms(&db).await;
+        assert!(!terms.is_empty());
+        assert!(!terms.iter().any(|t| t == "hunter2" || t == "secrets"));
+
+        // Rotation keeps data readable under the new key
+        let rotated = db.rotate_encryption_key(&[]).await.unwrap();
+        assert!(rotated >= 1);
+        let messages = db.get_conversation_messages("conv-enc").await.unwrap();
+        assert_eq!(messages[0].content, "my password is hunter2");
+
+        // ...and searchable, the index being rebuilt with the new key's tokens
+        let hits = db
+            .search(&SearchQuery {
+                query: "hunter2".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(!indexed_terms(&db).await.iter().any(|t| terms.contains(t)));
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_rotation_can_be_resumed() {
+        let key_manager = Arc::new(KeyManager::volatile());
+        let cipher = Arc::new(ColumnCipher::new(key_manager.clone()).await.unwrap());
+        let db = DatabaseManager::open_with_cipher(":memory:", Some(cipher.clone()))
+            .await
+            .unwrap();
+        let conversation = Conversation {
+            id: "conv-rot".to_string(),
+            title: "Rotation".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            app_context: None,
+            message_count: 0,
+            is_archived: false,
+        };
+        db.save_conversation(&conversation).await.unwrap();
+        db.save_message(&Message {
+            id: "msg-rot".to_string(),
+            conversation_id: conversation.id.clone(),
+            role: MessageRole::User,
+            content: "sealed under the first key".to_string(),
+            created_at: Utc::now(),
+            metadata: None,
+        })
+        .await
+        .unwrap();
+
+        // Le premier répertoire fait échouer la rotation : le second reste sous l'ancienne clé
+        let corrupt = tempfile::TempDir::new().unwrap();
+        std::fs::write(corrupt.path().join("broken.enc"), b"garbage").unwrap();
+        let files = tempfile::TempDir::new().unwrap();
+        let frame = files.path().join("frame.enc");
+        std::fs::write(&frame, cipher.encrypt_bytes(b"frame").unwrap()).unwrap();
+        let dirs = vec![corrupt.path().to_path_buf(), files.path().to_path_buf()];
+
+        assert!(db.rotate_encryption_key(&dirs).await.is_err());
+        assert!(key_manager.previous_key().await.unwrap().is_some());
+
+        std::fs::remove_file(corrupt.path().join("broken.enc")).unwrap();
+        db.rotate_encryption_key(&dirs).await.unwrap();
+        assert_eq!(key_manager.previous_key().await.unwrap(), None);
+
+        let messages = db.get_conversation_messages("conv-rot").await.unwrap();
+        assert_eq!(messages[0].content, "sealed under the first key");
+        let sealed = std::fs::read(&frame).unwrap();
+        assert_eq!(cipher.decrypt_bytes(&sealed).unwrap(), b"frame");
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_index_is_tokenized_when_encryption_starts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("shadowlearn.db");
+
+        {
+            let db = DatabaseManager::open(&path).await.unwrap();
+            let conversation = Conversation {
+                id: "conv-legacy".to_string(),
+                title: "Legacy".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                app_context: None,
+                message_count: 0,
+                is_archived: false,
+            };
+            db.save_conversation(&conversation).await.unwrap();
+            db.save_message(&Message {
+                id: "msg-legacy".to_string(),
+                conversation_id: conversation.id.clone(),
+                role: MessageRole::User,
+                content: "borrow checker complaints".to_string(),
+                created_at: Utc::now(),
+                metadata: None,
+            })
+            .await
+            .unwrap();
+            assert!(indexed_terms(&db).await.contains(&"borrow".to_string()));
+            db.pool.close().await;
+        }
+
+        let cipher = ColumnCipher::new(Arc::new(KeyManager::volatile()))
+            .await
+            .unwrap();
+        let db = DatabaseManager::open_with_cipher(&path, Some(Arc::new(cipher)))
+            .await
+            .unwrap();
+
+        let terms = indexed_terms(&db).await;
+        assert!(!terms.iter().any(|t| t == "borrow" || t == "legacy"));
+        let hits = db
+            .search(&SearchQuery {
+                query: "Borrow".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "msg-legacy");
+        assert!(hits[0].snippet.contains("**borrow**"));
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_and_contexts() {
+        let db = seeded_db().await;
+
+        let hits = db
+            .search(&SearchQuery {
+                query: "borrow".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().any(|h| h.kind == SearchHitKind::Context));
+        assert!(hits.iter().all(|h| h.snippet.contains("**")));
+    }
+
+    #[tokio::test]
+    async fn test_failed_index_rolls_back_message() {
+        let db = seeded_db().await;
+        // L'index refuse toute écriture : le message ne doit pas être enregistré à moitié
+        sqlx::query("DROP TABLE search_fts")
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let message = Message {
+            id: "msg-unindexed".to_string(),
+            conversation_id: "conv-1".to_string(),
+            role: MessageRole::User,
+            content: "never indexed".to_string(),
+            created_at: Utc::now(),
+            metadata: None,
+        };
+        assert!(db.save_message(&message).await.is_err());
+
+        let saved: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE id = ?")
+            .bind(&message.id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(saved, 0);
+        let conversation = db.get_conversation("conv-1").await.unwrap().unwrap();
+        assert_eq!(conversation.message_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_filters() {
+        let db = seeded_db().await;
+
+        let by_role = db
+            .search(&SearchQuery {
+                query: "borrow checker".to_string(),
+                role: Some("assistant".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_role.len(), 1);
+        assert_eq!(by_role[0].role.as_deref(), Some("assistant"));
+
+        let by_app = db
+            .search(&SearchQuery {
+                query: "E0502".to_string(),
+                app_name: Some("Terminal".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_app.len(), 1);
+        assert_eq!(by_app[0].id, "ctx-1");
+
+        let in_future = db
+            .search(&SearchQuery {
+                query: "borrow".to_string(),
+                since: Some(Utc::now() + chrono::Duration::days(1)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(in_future.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_context_retention_removes_rows_and_index() {
+        let db = seeded_db().await;
+        let now = Utc::now();
+
+        let stale = CapturedContext {
+            id: "ctx-old".to_string(),
+            conversation_id: None,
+            app_name: "Terminal".to_string(),
+            app_bundle_id: "com.apple.Terminal".to_string(),
+            window_title: "old E0502 session".to_string(),
+            clipboard_content: None,
+            idle_seconds: 1.0,
+            screenshot_data: None,
+            created_at: now - chrono::Duration::days(40),
+            trigger_reason: "idle_ok".to_string(),
+            capture_duration_ms: 5,
+        };
+        db.save_context(&stale).await.unwrap();
+
+        let policy = ResolvedPolicy {
+            max_age: Some(chrono::Duration::days(30)),
+            ..Default::default()
+        };
+        let report = db.apply_context_retention(&policy, now).await.unwrap();
+        assert_eq!(report.deleted, 1);
+        assert_eq!(report.remaining, 1);
+
+        let hits = db
+            .search(&SearchQuery {
+                query: "E0502".to_string(),
+                kinds: Some(vec![SearchHitKind::Context]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "ctx-1");
+
+        // Nothing left to purge
+        let report = db.apply_context_retention(&policy, now).await.unwrap();
+        assert_eq!(report.deleted, 0);
+    }
+
+    #[tokio::test]
+    async fn test_forget_app_removes_contexts_conversations_and_index() {
+        use crate::privacy::erasure::ErasureTarget;
+
+        let db = seeded_db().await;
+        let linked = CapturedContext {
+            id: "ctx-2".to_string(),
+            conversation_id: Some("conv-1".to_string()),
+            app_name: "Acme CRM".to_string(),
+            app_bundle_id: "com.acme.crm".to_string(),
+            window_title: "Client X — borrow limits".to_string(),
+            clipboard_content: None,
+            idle_seconds: 1.0,
+            screenshot_data: None,
+            created_at: Utc::now(),
+            trigger_reason: "idle_ok".to_string(),
+            capture_duration_ms: 10,
+        };
+        db.save_context(&linked).await.unwrap();
+
+        let matcher = AppMatcher::new(&ErasureTarget {
+            app_name: Some("acme crm".to_string()),
+            title_pattern: None,
+        })
+        .unwrap();
+        let erased = db.forget_app(&matcher).await.unwrap();
+        assert_eq!(
+            erased,
+            ErasedRecords {
+                contexts: 1,
+                conversations: 1,
+                messages: 2,
+            }
+        );
+
+        // Only the unrelated Terminal context is left, in the tables and in the index
+        assert!(db.get_conversation("conv-1").await.unwrap().is_none());
+        let hits = db
+            .search(&SearchQuery {
+                query: "borrow".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "ctx-1");
+
+        // Nothing left to erase
+        assert_eq!(
+            db.forget_app(&matcher).await.unwrap(),
+            ErasedRecords::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conversation_summary_upsert() {
+        let db = seeded_db().await;
+        assert!(db.get_conversation_summary("conv-1").await.unwrap().is_none());
+
+        for (covered, text) in [(2, "first summary"), (4, "second summary")] {
+            db.save_conversation_summary(&ConversationSummary {
+                conversation_id: "conv-1".to_string(),
+                summary: text.to_string(),
+                covers_until_message_id: format!("m{}", covered),
+                covered_messages: covered,
+                updated_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        }
+
+        let summary = db.get_conversation_summary("conv-1").await.unwrap().unwrap();
+        assert_eq!(summary.summary, "second summary");
+        assert_eq!(summary.covered_messages, 4);
+        assert!(db.get_conversation("conv-1").await.unwrap().is_some());
+        assert!(db.get_conversation("missing").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,241 @@
+// Allow deprecated warnings from generic-array 0.x used by aes_gcm
+#![allow(deprecated)]
+
+use aes_gcm::{Aes256Gcm, Key};
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::{Arc, RwLock};
+use tracing::warn;
+
+use crate::crypto::keymanager::KeyManager;
+
+/// Préfixe des valeurs chiffrées : `enc:v1:<key_id>:<base64(nonce || ciphertext)>`
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Valeur renvoyée quand une colonne ne peut pas être déchiffrée (clé perdue)
+pub const UNREADABLE_PLACEHOLDER: &str = "[encrypted: key unavailable]";
+
+/// Colonnes sensibles chiffrées au repos, par table
+pub const ENCRYPTED_COLUMNS: &[(&str, &[&str])] = &[
+    ("messages", &["content", "metadata"]),
+    (
+        "captured_contexts",
+        &["window_title", "clipboard_content", "screenshot_data"],
+    ),
+    ("outcomes", &["cluster_id"]),
+    ("search_documents", &["title"]),
+    ("conversation_summaries", &["summary"]),
+];
+
+struct LoadedKeys {
+    current_id: String,
+    current: Key<Aes256Gcm>,
+    previous: Option<(String, Key<Aes256Gcm>)>,
+}
+
+/// Chiffrement transparent des colonnes sensibles via `KeyManager`
+pub struct ColumnCipher {
+    key_manager: Arc<KeyManager>,
+    keys: RwLock<LoadedKeys>,
+}
+
+impl ColumnCipher {
+    pub async fn new(key_manager: Arc<KeyManager>) -> Result<Self, String> {
+        let keys = Self::load_keys(&key_manager).await?;
+        Ok(Self {
+            key_manager,
+            keys: RwLock::new(keys),
+        })
+    }
+
+    async fn load_keys(key_manager: &KeyManager) -> Result<LoadedKeys, String> {
+        let current = key_manager
+            .get_or_create_key()
+            .await
+            .map_err(|e| e.to_string())?;
+        let previous = key_manager
+            .previous_key()
+            .await
+            .map_err(|e| e.to_string())?
+            .map(|key| (KeyManager::key_id(&key), key));
+
+        Ok(LoadedKeys {
+            current_id: KeyManager::key_id(&current),
+            current,
+            previous,
+        })
+    }
+
+    /// Recharge les clés depuis le `KeyManager` (après une rotation)
+    pub async fn reload(&self) -> Result<(), String> {
+        let keys = Self::load_keys(&self.key_manager).await?;
+        *self.keys.write().unwrap() = keys;
+        Ok(())
+    }
+
+    pub fn key_manager(&self) -> Arc<KeyManager> {
+        self.key_manager.clone()
+    }
+
+    pub fn current_key_id(&self) -> String {
+        self.keys.read().unwrap().current_id.clone()
+    }
+
+    #[cfg(test)]
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Identifiant de clé d'une valeur chiffrée
+    pub fn key_id_of(value: &str) -> Option<&str> {
+        value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .and_then(|rest| rest.split_once(':'))
+            .map(|(key_id, _)| key_id)
+    }
+
+    /// Chiffre une valeur avec la clé courante
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, String> {
+        let keys = self.keys.read().unwrap();
+        let sealed = self
+            .key_manager
+            .encrypt_with_key(&keys.current, plaintext.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        Ok(format!(
+            "{}{}:{}",
+            ENCRYPTED_PREFIX,
+            keys.current_id,
+            general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    /// HMAC-SHA256 de `data` sous une sous-clé de la clé courante propre à `domain`.
+    /// Sert aux identifiants qui ne doivent pas révéler le contenu dont ils dérivent.
+    pub fn keyed_digest(&self, domain: &str, data: &[u8]) -> [u8; 32] {
+        let keys = self.keys.read().unwrap();
+        let mut derive = <Hmac<Sha256> as Mac>::new_from_slice(keys.current.as_slice())
+            .expect("HMAC accepts keys of any length");
+        derive.update(domain.as_bytes());
+        let subkey = derive.finalize().into_bytes();
+
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&subkey)
+            .expect("HMAC accepts keys of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().into()
+    }
+
+    /// Jeton d'un mot dans l'index de recherche (change à chaque rotation de clé)
+    pub fn search_token(&self, word: &str) -> String {
+        self.keyed_digest("search-index", word.as_bytes())[..12]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Déchiffre une valeur ; les valeurs sans préfixe (données historiques) sont renvoyées telles quelles
+    pub fn decrypt(&self, value: &str) -> Result<String, String> {
+        let Some(rest) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let (key_id, payload) = rest
+            .split_once(':')
+            .ok_or_else(|| "Malformed encrypted value".to_string())?;
+
+        let keys = self.keys.read().unwrap();
+        let key = if key_id == keys.current_id {
+            &keys.current
+        } else {
+            match &keys.previous {
+                Some((previous_id, previous)) if previous_id == key_id => previous,
+                _ => return Err(format!("Unknown encryption key id: {}", key_id)),
+            }
+        };
+
+        let sealed = general_purpose::STANDARD
+            .decode(payload)
+            .map_err(|e| format!("Invalid encrypted payload: {}", e))?;
+        let plain = self
+            .key_manager
+            .decrypt_with_key(key, &sealed)
+            .map_err(|e| e.to_string())?;
+
+        String::from_utf8(plain).map_err(|e| format!("Decrypted value is not UTF-8: {}", e))
+    }
+
+    /// Chiffre un contenu binaire avec la clé courante : `nonce || ciphertext`, le format des
+    /// fichiers `.enc` re-chiffrés par `KeyManager::reencrypt_files`
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let keys = self.keys.read().unwrap();
+        self.key_manager
+            .encrypt_with_key(&keys.current, plaintext)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Déchiffre un contenu binaire ; pendant une rotation l'ancienne clé est essayée ensuite
+    pub fn decrypt_bytes(&self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+        let keys = self.keys.read().unwrap();
+        match self.key_manager.decrypt_with_key(&keys.current, sealed) {
+            Ok(plain) => Ok(plain),
+            Err(e) => match &keys.previous {
+                Some((_, previous)) => self
+                    .key_manager
+                    .decrypt_with_key(previous, sealed)
+                    .map_err(|e| e.to_string()),
+                None => Err(e.to_string()),
+            },
+        }
+    }
+
+    /// Déchiffre en remplaçant les valeurs illisibles par un marqueur
+    pub fn decrypt_lossy(&self, value: &str) -> String {
+        match self.decrypt(value) {
+            Ok(plain) => plain,
+            Err(e) => {
+                warn!("⚠️ Failed to decrypt column value: {}", e);
+                UNREADABLE_PLACEHOLDER.to_string()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_roundtrip_and_key_prefix() {
+        let cipher = ColumnCipher::new(Arc::new(KeyManager::volatile()))
+            .await
+            .unwrap();
+
+        let sealed = cipher.encrypt("my secret clipboard").unwrap();
+        assert!(ColumnCipher::is_encrypted(&sealed));
+        assert_eq!(
+            ColumnCipher::key_id_of(&sealed),
+            Some(cipher.current_key_id().as_str())
+        );
+        assert!(!sealed.contains("secret"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "my secret clipboard");
+
+        // Legacy plaintext passes through
+        assert_eq!(cipher.decrypt("plain").unwrap(), "plain");
+    }
+
+    #[tokio::test]
+    async fn test_previous_key_readable_during_rotation() {
+        let key_manager = Arc::new(KeyManager::volatile());
+        let cipher = ColumnCipher::new(key_manager.clone()).await.unwrap();
+        let sealed = cipher.encrypt("before rotation").unwrap();
+
+        key_manager.begin_rotation().await.unwrap();
+        cipher.reload().await.unwrap();
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "before rotation");
+
+        key_manager.finish_rotation().await.unwrap();
+        cipher.reload().await.unwrap();
+        assert!(cipher.decrypt(&sealed).is_err());
+        assert_eq!(cipher.decrypt_lossy(&sealed), UNREADABLE_PLACEHOLDER);
+    }
+}
@@ -0,0 +1,273 @@
+//! Format d'export versionné de ShadowLearn.
+//!
+//! Un bundle est un document JSON unique :
+//!
+//! ```json
+//! {
+//!   "format": "shadowlearn-export",
+//!   "version": 1,
+//!   "exported_at": "2026-01-01T00:00:00+00:00",
+//!   "schema_version": 5,
+//!   "conversations": [...],
+//!   "messages": [...],
+//!   "captured_contexts": [...],
+//!   "trust_state": [...],
+//!   "trust_events": [...],
+//!   "outcomes": [...],
+//!   "patterns": [...],
+//!   "repetitive_tasks": [...],
+//!   "replay_events": [...]
+//! }
+//! ```
+//!
+//! Les données sont exportées en clair (déchiffrées) : le fichier doit être traité comme sensible.
+//! Chaque enregistrement porte son `id` ; l'import ignore les ids déjà présents (fusion sans écrasement).
+//! Les exports historiques (`{"conversations": [{"conversation", "messages"}], "export_timestamp"}`)
+//! sont lus comme un bundle de version 0.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::patterns::learning::WorkflowPattern;
+use crate::patterns::repetition::RepetitiveTask;
+use crate::persistence::models::{CapturedContext, Conversation, Message};
+use crate::replay::ReplayEvent;
+
+/// Identifiant du format d'export
+pub const EXPORT_FORMAT: &str = "shadowlearn-export";
+
+/// Version courante du format d'export
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// État de confiance d'un appareil (table `user_trust`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustStateRecord {
+    pub id: String,
+    pub device_id: String,
+    pub pos: f32,
+    pub neg: f32,
+    pub trust: f32,
+    pub quarantine: bool,
+    pub last_updated: String,
+    pub created_at: String,
+}
+
+/// Événement de confiance (table `trust_events`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustEventRecord {
+    pub id: String,
+    pub device_id: String,
+    pub reward: f32,
+    pub timestamp: String,
+}
+
+/// Outcome d'une suggestion (table `outcomes`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutcomeRecord {
+    pub id: String,
+    pub suggestion_id: String,
+    pub used: bool,
+    pub helpful: bool,
+    pub reverted: bool,
+    pub time_to_flow_ms: i64,
+    pub reward: f32,
+    pub cluster_id: String,
+    pub artefact_type: String,
+    pub timestamp: String,
+}
+
+/// Bundle d'export complet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub schema_version: i64,
+    #[serde(default)]
+    pub conversations: Vec<Conversation>,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub captured_contexts: Vec<CapturedContext>,
+    #[serde(default)]
+    pub trust_state: Vec<TrustStateRecord>,
+    #[serde(default)]
+    pub trust_events: Vec<TrustEventRecord>,
+    #[serde(default)]
+    pub outcomes: Vec<OutcomeRecord>,
+    #[serde(default)]
+    pub patterns: Vec<WorkflowPattern>,
+    #[serde(default)]
+    pub repetitive_tasks: Vec<RepetitiveTask>,
+    #[serde(default)]
+    pub replay_events: Vec<ReplayEvent>,
+}
+
+impl ExportBundle {
+    pub fn new(schema_version: i64) -> Self {
+        Self {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            schema_version,
+            conversations: Vec::new(),
+            messages: Vec::new(),
+            captured_contexts: Vec::new(),
+            trust_state: Vec::new(),
+            trust_events: Vec::new(),
+            outcomes: Vec::new(),
+            patterns: Vec::new(),
+            repetitive_tasks: Vec::new(),
+            replay_events: Vec::new(),
+        }
+    }
+
+    /// Lit un bundle JSON en validant le format et la version
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid export file: {}", e))?;
+
+        match value.get("format").and_then(|f| f.as_str()) {
+            Some(EXPORT_FORMAT) => {}
+            Some(other) => return Err(format!("Unsupported export format: {}", other)),
+            None if value.get("export_timestamp").is_some() => return Self::from_legacy(value),
+            None => return Err("Not a ShadowLearn export file".to_string()),
+        }
+
+        let version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| "Export file has no version".to_string())?;
+        if version == 0 || version > EXPORT_FORMAT_VERSION as u64 {
+            return Err(format!(
+                "Export format version {} is not supported (this build reads up to {})",
+                version, EXPORT_FORMAT_VERSION
+            ));
+        }
+
+        serde_json::from_value(value).map_err(|e| format!("Invalid export bundle: {}", e))
+    }
+
+    /// Convertit un export historique (conversations + messages uniquement)
+    fn from_legacy(value: serde_json::Value) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct LegacyEntry {
+            conversation: Conversation,
+            #[serde(default)]
+            messages: Vec<Message>,
+        }
+
+        let entries: Vec<LegacyEntry> = serde_json::from_value(
+            value
+                .get("conversations")
+                .cloned()
+                .unwrap_or(serde_json::Value::Array(Vec::new())),
+        )
+        .map_err(|e| format!("Invalid legacy export: {}", e))?;
+
+        let mut bundle = Self::new(0);
+        bundle.version = 0;
+        if let Some(ts) = value
+            .get("export_timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        {
+            bundle.exported_at = ts.with_timezone(&Utc);
+        }
+        for entry in entries {
+            bundle.conversations.push(entry.conversation);
+            bundle.messages.extend(entry.messages);
+        }
+
+        Ok(bundle)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize export data: {}", e))
+    }
+}
+
+/// Compteurs d'une section importée
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportCount {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+impl ImportCount {
+    pub fn record(&mut self, inserted: bool) {
+        if inserted {
+            self.imported += 1;
+        } else {
+            self.skipped += 1;
+        }
+    }
+}
+
+/// Rapport d'import : nouveaux enregistrements et doublons ignorés par section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub bundle_version: u32,
+    pub conversations: ImportCount,
+    pub messages: ImportCount,
+    pub captured_contexts: ImportCount,
+    pub trust_state: ImportCount,
+    pub trust_events: ImportCount,
+    pub outcomes: ImportCount,
+    pub patterns: ImportCount,
+    pub repetitive_tasks: ImportCount,
+    pub replay_events: ImportCount,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_current_version() {
+        let bundle = ExportBundle::new(5);
+        let parsed = ExportBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.version, EXPORT_FORMAT_VERSION);
+        assert_eq!(parsed.schema_version, 5);
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions() {
+        let json = serde_json::json!({
+            "format": EXPORT_FORMAT,
+            "version": EXPORT_FORMAT_VERSION + 1,
+            "exported_at": Utc::now(),
+            "schema_version": 5
+        })
+        .to_string();
+        assert!(ExportBundle::from_json(&json).is_err());
+
+        assert!(ExportBundle::from_json(r#"{"format": "other", "version": 1}"#).is_err());
+        assert!(ExportBundle::from_json(r#"{"hello": "world"}"#).is_err());
+    }
+
+    #[test]
+    fn test_reads_legacy_export() {
+        let now = Utc::now();
+        let json = serde_json::json!({
+            "export_timestamp": now.to_rfc3339(),
+            "conversations": [{
+                "conversation": {
+                    "id": "c1", "title": "Old", "created_at": now, "updated_at": now,
+                    "app_context": null, "message_count": 1, "is_archived": false
+                },
+                "messages": [{
+                    "id": "m1", "conversation_id": "c1", "role": "User",
+                    "content": "hi", "created_at": now, "metadata": null
+                }]
+            }]
+        })
+        .to_string();
+
+        let bundle = ExportBundle::from_json(&json).unwrap();
+        assert_eq!(bundle.version, 0);
+        assert_eq!(bundle.conversations.len(), 1);
+        assert_eq!(bundle.messages.len(), 1);
+    }
+}
@@ -0,0 +1,27 @@
+-- Migration 003: Align core tables with DatabaseManager queries
+
+-- Conversations: metadata used by the chat history views
+ALTER TABLE conversations ADD COLUMN updated_at DATETIME NOT NULL DEFAULT '';
+ALTER TABLE conversations ADD COLUMN app_context TEXT;
+ALTER TABLE conversations ADD COLUMN message_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE conversations ADD COLUMN is_archived BOOLEAN NOT NULL DEFAULT 0;
+UPDATE conversations SET updated_at = created_at WHERE updated_at = '';
+
+-- Messages: JSON metadata (trigger context, etc.)
+ALTER TABLE messages ADD COLUMN metadata TEXT;
+
+-- Captured contexts: full capture payload
+ALTER TABLE captured_contexts ADD COLUMN app_bundle_id TEXT NOT NULL DEFAULT '';
+ALTER TABLE captured_contexts ADD COLUMN clipboard_content TEXT;
+ALTER TABLE captured_contexts ADD COLUMN idle_seconds REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE captured_contexts ADD COLUMN screenshot_data TEXT;
+ALTER TABLE captured_contexts ADD COLUMN trigger_reason TEXT NOT NULL DEFAULT '';
+ALTER TABLE captured_contexts ADD COLUMN capture_duration_ms INTEGER NOT NULL DEFAULT 0;
+
+-- Indexes
+CREATE INDEX IF NOT EXISTS idx_conversations_updated_at ON conversations(updated_at DESC);
+CREATE INDEX IF NOT EXISTS idx_messages_conversation_id ON messages(conversation_id);
+CREATE INDEX IF NOT EXISTS idx_captured_contexts_app_name ON captured_contexts(app_name);
+CREATE INDEX IF NOT EXISTS idx_captured_contexts_created_at ON captured_contexts(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_trust_events_device_id ON trust_events(device_id, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_outcomes_timestamp ON outcomes(timestamp DESC);
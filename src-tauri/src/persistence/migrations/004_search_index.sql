@@ -0,0 +1,56 @@
+-- Migration 004: Full-text search over conversations, messages and captured contexts
+-- The FTS table is contentless: it keeps only the inverted index. Document text
+-- lives in the source tables and search metadata in search_documents.
+
+CREATE TABLE IF NOT EXISTS search_documents (
+    rowid INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL,
+    ref_id TEXT NOT NULL,
+    conversation_id TEXT,
+    app_name TEXT,
+    role TEXT,
+    title TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    UNIQUE (kind, ref_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_documents_created_at ON search_documents(created_at);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_fts USING fts5(
+    title,
+    content,
+    content = '',
+    contentless_delete = 1,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- Backfill existing data
+INSERT INTO search_documents (kind, ref_id, conversation_id, app_name, role, title, created_at)
+SELECT 'conversation', id, id, app_context, NULL, COALESCE(title, ''), created_at
+FROM conversations;
+
+INSERT INTO search_documents (kind, ref_id, conversation_id, app_name, role, title, created_at)
+SELECT 'message', m.id, m.conversation_id, c.app_context, m.role, COALESCE(c.title, ''), m.created_at
+FROM messages m
+LEFT JOIN conversations c ON c.id = m.conversation_id;
+
+INSERT INTO search_documents (kind, ref_id, conversation_id, app_name, role, title, created_at)
+SELECT 'context', id, conversation_id, app_name, NULL, COALESCE(window_title, ''), created_at
+FROM captured_contexts;
+
+INSERT INTO search_fts (rowid, title, content)
+SELECT d.rowid, d.title, ''
+FROM search_documents d
+WHERE d.kind = 'conversation';
+
+INSERT INTO search_fts (rowid, title, content)
+SELECT d.rowid, d.title, m.content
+FROM search_documents d
+JOIN messages m ON m.id = d.ref_id
+WHERE d.kind = 'message';
+
+INSERT INTO search_fts (rowid, title, content)
+SELECT d.rowid, d.title, COALESCE(x.clipboard_content, '')
+FROM search_documents d
+JOIN captured_contexts x ON x.id = d.ref_id
+WHERE d.kind = 'context';
@@ -0,0 +1,11 @@
+-- Migration 005: Rolling summaries of older conversation turns
+-- One row per conversation; covers every message up to covers_until_message_id.
+
+CREATE TABLE IF NOT EXISTS conversation_summaries (
+    conversation_id TEXT PRIMARY KEY,
+    summary TEXT NOT NULL,
+    covers_until_message_id TEXT NOT NULL,
+    covered_messages INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+);
@@ -0,0 +1,291 @@
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use tracing::{debug, info, warn};
+
+/// Une migration SQL embarquée dans le binaire
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    /// Empreinte SHA-256 du script, pour détecter une migration modifiée après application
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+/// Liste ordonnée des migrations connues par ce binaire.
+/// Ne jamais modifier une migration déjà publiée : en ajouter une nouvelle.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sql: include_str!("migrations/001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "add_trust_tables",
+        sql: include_str!("migrations/002_add_trust_tables.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "align_core_schema",
+        sql: include_str!("migrations/003_align_core_schema.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "search_index",
+        sql: include_str!("migrations/004_search_index.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "conversation_summaries",
+        sql: include_str!("migrations/005_conversation_summaries.sql"),
+    },
+];
+
+/// Migration déjà appliquée, telle qu'enregistrée dans `schema_migrations`
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+}
+
+/// Applique les migrations en attente et suit le schéma dans `schema_migrations`
+pub struct Migrator<'a> {
+    pool: &'a SqlitePool,
+    migrations: &'a [Migration],
+}
+
+impl<'a> Migrator<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self::with_migrations(pool, MIGRATIONS)
+    }
+
+    pub fn with_migrations(pool: &'a SqlitePool, migrations: &'a [Migration]) -> Self {
+        Self { pool, migrations }
+    }
+
+    /// Version la plus récente connue par ce binaire
+    pub fn latest_version(&self) -> i64 {
+        self.migrations.iter().map(|m| m.version).max().unwrap_or(0)
+    }
+
+    /// Version actuelle du schéma en base (0 si aucune migration appliquée)
+    pub async fn current_version(&self) -> Result<i64, String> {
+        self.ensure_version_table().await?;
+
+        let row: (Option<i64>,) = sqlx::query_as("SELECT MAX(version) FROM schema_migrations")
+            .fetch_one(self.pool)
+            .await
+            .map_err(|e| format!("Failed to read schema version: {}", e))?;
+
+        Ok(row.0.unwrap_or(0))
+    }
+
+    /// Exécute toutes les migrations en attente, chacune dans sa propre transaction.
+    /// Retourne le nombre de migrations appliquées.
+    pub async fn run(&self) -> Result<usize, String> {
+        self.ensure_version_table().await?;
+
+        let applied = self.applied_migrations().await?;
+        let current = applied.iter().map(|m| m.version).max().unwrap_or(0);
+        let latest = self.latest_version();
+
+        if current > latest {
+            return Err(format!(
+                "Database schema version {} is newer than this build supports ({}). Please update ShadowLearn.",
+                current, latest
+            ));
+        }
+
+        for migration in self.migrations {
+            if let Some(existing) = applied.iter().find(|m| m.version == migration.version) {
+                if existing.checksum != migration.checksum() {
+                    warn!(
+                        "⚠️ Migration {:03}_{} was modified after being applied (as {:03}_{})",
+                        migration.version, migration.name, existing.version, existing.name
+                    );
+                }
+            }
+        }
+
+        let mut pending: Vec<&Migration> = self
+            .migrations
+            .iter()
+            .filter(|m| !applied.iter().any(|a| a.version == m.version))
+            .collect();
+        pending.sort_by_key(|m| m.version);
+
+        if pending.is_empty() {
+            debug!("Database schema up to date (version {})", current);
+            return Ok(0);
+        }
+
+        for migration in &pending {
+            self.apply(migration).await?;
+        }
+
+        info!(
+            "✅ Applied {} migration(s), schema now at version {}",
+            pending.len(),
+            latest
+        );
+        Ok(pending.len())
+    }
+
+    /// Liste les migrations déjà appliquées
+    pub async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, String> {
+        let rows = sqlx::query(
+            "SELECT version, name, checksum FROM schema_migrations ORDER BY version ASC",
+        )
+        .fetch_all(self.pool)
+        .await
+        .map_err(|e| format!("Failed to read applied migrations: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AppliedMigration {
+                version: row.get("version"),
+                name: row.get("name"),
+                checksum: row.get("checksum"),
+            })
+            .collect())
+    }
+
+    async fn ensure_version_table(&self) -> Result<(), String> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                applied_at DATETIME NOT NULL
+            )
+            "#,
+        )
+        .execute(self.pool)
+        .await
+        .map_err(|e| format!("Failed to create schema_migrations table: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn apply(&self, migration: &Migration) -> Result<(), String> {
+        debug!(
+            "Running migration {:03}_{}",
+            migration.version, migration.name
+        );
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin migration transaction: {}", e))?;
+
+        sqlx::raw_sql(migration.sql)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                format!(
+                    "Migration {:03}_{} failed: {}",
+                    migration.version, migration.name, e
+                )
+            })?;
+
+        sqlx::query(
+            "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(migration.checksum())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record migration {}: {}", migration.version, e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit migration {}: {}", migration.version, e))?;
+
+        info!(
+            "📦 Migration {:03}_{} applied",
+            migration.version, migration.name
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_runs_all_migrations_once() {
+        let pool = memory_pool().await;
+        let migrator = Migrator::new(&pool);
+
+        let applied = migrator.run().await.unwrap();
+        assert_eq!(applied, MIGRATIONS.len());
+        assert_eq!(migrator.current_version().await.unwrap(), migrator.latest_version());
+
+        // Second run is a no-op
+        assert_eq!(migrator.run().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_migration_is_rolled_back() {
+        let pool = memory_pool().await;
+        let broken = [
+            MIGRATIONS[0],
+            Migration {
+                version: 2,
+                name: "broken",
+                sql: "CREATE TABLE half_done (id TEXT); INSERT INTO missing_table VALUES (1);",
+            },
+        ];
+        let migrator = Migrator::with_migrations(&pool, &broken);
+
+        assert!(migrator.run().await.is_err());
+        assert_eq!(migrator.current_version().await.unwrap(), 1);
+
+        let leftover: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'half_done'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(leftover.0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_newer_schema() {
+        let pool = memory_pool().await;
+        let migrator = Migrator::new(&pool);
+        migrator.run().await.unwrap();
+
+        sqlx::query(
+            "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES (999, 'future', '', '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let err = migrator.run().await.unwrap_err();
+        assert!(err.contains("newer"));
+    }
+}
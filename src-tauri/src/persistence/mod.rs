@@ -1,21 +1,38 @@
 pub mod compression;
 pub mod database;
+pub mod encryption;
+pub mod export;
+pub mod migrator;
 pub mod models;
 
 pub use compression::CompressionManager;
 pub use database::DatabaseManager;
+pub use export::{ExportBundle, ImportReport};
 pub use models::*;
 
+use base64::{engine::general_purpose, Engine as _};
 use chrono::Utc;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::privacy::audit::count_redactions;
+use crate::privacy::erasure::{AppMatcher, ErasedRecords};
+use crate::privacy::{AuditEvent, AuditLog, Redactor};
+use crate::retention::{CategoryReport, ResolvedPolicy};
+use crate::screenshot::store::{parse_reference, ScreenshotHolder, ScreenshotStore};
+
 /// Gestionnaire principal de persistance
 pub struct PersistenceManager {
     database: Arc<Mutex<DatabaseManager>>,
     compression: CompressionManager,
+    /// Secrets et données personnelles masqués avant écriture
+    redactor: Arc<Redactor>,
+    /// Journal d'audit des contextes persistés
+    audit: Option<Arc<AuditLog>>,
+    /// Magasin où sont rangées les captures des contextes
+    screenshots: Option<Arc<ScreenshotStore>>,
 }
 
 impl PersistenceManager {
@@ -29,12 +46,35 @@ impl PersistenceManager {
         let manager = Self {
             database: Arc::new(Mutex::new(database)),
             compression,
+            redactor: Arc::new(Redactor::default()),
+            audit: None,
+            screenshots: None,
         };
 
         info!("✅ PersistenceManager initialized successfully");
         Ok(manager)
     }
 
+    /// Utilise le `Redactor` partagé (configuration utilisateur, statistiques communes)
+    pub fn set_redactor(&mut self, redactor: Arc<Redactor>) {
+        self.redactor = redactor;
+    }
+
+    /// Consigne chaque contexte persisté dans le journal d'audit partagé
+    pub fn set_audit_log(&mut self, audit: Arc<AuditLog>) {
+        self.audit = Some(audit);
+    }
+
+    /// Range les captures des contextes dans le magasin partagé plutôt qu'en base
+    pub fn set_screenshot_store(&mut self, store: Arc<ScreenshotStore>) {
+        self.screenshots = Some(store);
+    }
+
+    /// Chiffre des colonnes sensibles (None si le chiffrement est désactivé)
+    pub async fn cipher(&self) -> Option<Arc<encryption::ColumnCipher>> {
+        self.database.lock().await.cipher()
+    }
+
     /// Crée une nouvelle conversation
     pub async fn create_conversation(
         &self,
@@ -43,7 +83,7 @@ impl PersistenceManager {
     ) -> Result<Conversation, String> {
         let conversation = Conversation {
             id: Uuid::new_v4().to_string(),
-            title,
+            title: self.redactor.redact(&title),
             created_at: Utc::now(),
             updated_at: Utc::now(),
             app_context,
@@ -74,7 +114,7 @@ impl PersistenceManager {
             id: Uuid::new_v4().to_string(),
             conversation_id: conversation_id.to_string(),
             role,
-            content,
+            content: self.redactor.redact(&content),
             created_at: Utc::now(),
             metadata,
         };
@@ -108,11 +148,46 @@ impl PersistenceManager {
         db.get_conversation_messages(conversation_id).await
     }
 
+    /// Récupère une conversation par son id
+    pub async fn get_conversation(&self, id: &str) -> Result<Option<Conversation>, String> {
+        let db = self.database.lock().await;
+        db.get_conversation(id).await
+    }
+
+    /// Récupère le résumé glissant d'une conversation
+    pub async fn get_conversation_summary(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Option<ConversationSummary>, String> {
+        let db = self.database.lock().await;
+        db.get_conversation_summary(conversation_id).await
+    }
+
+    /// Enregistre le résumé glissant d'une conversation
+    pub async fn save_conversation_summary(
+        &self,
+        summary: &ConversationSummary,
+    ) -> Result<(), String> {
+        let summary = ConversationSummary {
+            summary: self.redactor.redact(&summary.summary),
+            ..summary.clone()
+        };
+        let db = self.database.lock().await;
+        db.save_conversation_summary(&summary).await?;
+        debug!("💾 Summary saved for conversation: {}", summary.conversation_id);
+        Ok(())
+    }
+
     /// Sauvegarde un contexte capturé
     pub async fn save_context(&self, context: CapturedContext) -> Result<(), String> {
-        // Compresser les données de screenshot si nécessaire
         let mut compressed_context = context;
-        if let Some(screenshot_data) = &compressed_context.screenshot_data {
+        compressed_context.window_title = self.redactor.redact(&compressed_context.window_title);
+        self.redactor.redact_option(&mut compressed_context.clipboard_content);
+
+        // Ranger la capture dans le magasin, sinon compresser les données si nécessaire
+        if let Some(reference) = self.store_context_screenshot(&compressed_context) {
+            compressed_context.screenshot_data = Some(reference);
+        } else if let Some(screenshot_data) = &compressed_context.screenshot_data {
             if self.compression.should_compress(screenshot_data.len()) {
                 match self.compression.compress_to_base64(screenshot_data) {
                     Ok(compressed) => {
@@ -136,9 +211,51 @@ impl PersistenceManager {
         let db = self.database.lock().await;
         db.save_context(&compressed_context).await?;
         debug!("💾 Context saved: {}", compressed_context.id);
+
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::ContextPersisted {
+                context_id: compressed_context.id.clone(),
+                app_name: compressed_context.app_name.clone(),
+                conversation_id: compressed_context.conversation_id.clone(),
+                redactions: count_redactions(&compressed_context.window_title)
+                    + compressed_context
+                        .clipboard_content
+                        .as_deref()
+                        .map_or(0, count_redactions),
+                with_screenshot: compressed_context.screenshot_data.is_some(),
+            });
+        }
         Ok(())
     }
 
+    /// Range la capture (base64) d'un contexte dans le magasin et renvoie sa référence ;
+    /// None sans magasin ou si les données ne sont pas une image base64
+    fn store_context_screenshot(&self, context: &CapturedContext) -> Option<String> {
+        let store = self.screenshots.as_ref()?;
+        let data = context.screenshot_data.as_deref()?;
+        let holder = ScreenshotHolder::Context(context.id.clone());
+
+        let stored = if parse_reference(data).is_some() {
+            store.add_holder(data, holder).map(|_| data.to_string())
+        } else {
+            let bytes = general_purpose::STANDARD.decode(data).ok()?;
+            store.put(&bytes, Some(holder))
+        };
+        match stored {
+            Ok(reference) => Some(reference),
+            Err(e) => {
+                error!("❌ Failed to store context screenshot: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Contextes dont la capture est rangée dans le magasin (id du contexte, référence)
+    pub async fn context_screenshot_refs(&self) -> Result<Vec<(String, String)>, String> {
+        let db = self.database.lock().await;
+        db.context_screenshot_refs().await
+    }
+
     /// Récupère les contextes récents pour une app
     pub async fn get_recent_contexts_for_app(
         &self,
@@ -155,46 +272,154 @@ impl PersistenceManager {
         Ok(contexts)
     }
 
+    /// Recherche plein texte dans les conversations, messages et contextes
+    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, String> {
+        let db = self.database.lock().await;
+        let hits = db.search(query).await?;
+        debug!("🔍 Search '{}' returned {} hits", query.query, hits.len());
+        Ok(hits)
+    }
+
+    /// Fait tourner la clé de chiffrement (colonnes sensibles + fichiers `.enc` du répertoire de
+    /// données et du magasin de captures)
+    pub async fn rotate_encryption_key(&self) -> Result<usize, String> {
+        let db = self.database.lock().await;
+        let dirs: Vec<std::path::PathBuf> = db
+            .db_path()
+            .parent()
+            .map(|p| p.to_path_buf())
+            .into_iter()
+            .chain(self.screenshots.iter().map(|store| store.root().to_path_buf()))
+            .collect();
+        db.rotate_encryption_key(&dirs).await
+    }
+
+    /// Applique une politique de rétention aux contextes capturés
+    pub async fn apply_context_retention(
+        &self,
+        policy: &ResolvedPolicy,
+        now: chrono::DateTime<Utc>,
+    ) -> Result<CategoryReport, String> {
+        let db = self.database.lock().await;
+        db.apply_context_retention(policy, now).await
+    }
+
+    /// Supprime contextes, conversations et messages d'une app oubliée
+    pub async fn forget_app(&self, matcher: &AppMatcher) -> Result<ErasedRecords, String> {
+        let db = self.database.lock().await;
+        db.forget_app(matcher).await
+    }
+
+    /// Répertoire contenant le fichier de base de données
+    pub async fn data_dir(&self) -> Option<std::path::PathBuf> {
+        let db = self.database.lock().await;
+        db.db_path().parent().map(|p| p.to_path_buf())
+    }
+
     /// Obtient les statistiques de persistance
     pub async fn get_stats(&self) -> Result<PersistenceStats, String> {
         let db = self.database.lock().await;
         db.get_persistence_stats().await
     }
 
-    /// Exporte toutes les données vers un fichier JSON
-    pub async fn export_data(&self, file_path: &str) -> Result<(), String> {
-        info!("📤 Exporting data to: {}", file_path);
-
-        let conversations = self.get_recent_conversations(1000).await?;
-        let mut export_data = serde_json::Map::new();
+    /// Construit un bundle d'export avec toutes les tables de la base
+    pub async fn export_bundle(&self) -> Result<ExportBundle, String> {
+        let db = self.database.lock().await;
+        let mut bundle = ExportBundle::new(db.schema_version().await?);
+        db.export_records(&mut bundle).await?;
+        self.inline_context_screenshots(&mut bundle.captured_contexts);
+        Ok(bundle)
+    }
 
-        // Exporter les conversations avec leurs messages
-        let mut conversations_data = Vec::new();
-        for conversation in conversations {
-            let messages = self.get_conversation_messages(&conversation.id).await?;
-            let conversation_data = serde_json::json!({
-                "conversation": conversation,
-                "messages": messages
-            });
-            conversations_data.push(conversation_data);
+    /// Remplace les références au magasin par l'image (base64) : un export doit se suffire
+    /// à lui-même. Une capture qui n'est plus dans le magasin n'est pas exportée
+    fn inline_context_screenshots(&self, contexts: &mut [CapturedContext]) {
+        for context in contexts {
+            let Some(reference) = context
+                .screenshot_data
+                .as_deref()
+                .filter(|data| parse_reference(data).is_some())
+            else {
+                continue;
+            };
+            let image = self
+                .screenshots
+                .as_ref()
+                .ok_or_else(|| "no screenshot store".to_string())
+                .and_then(|store| store.read(reference));
+            context.screenshot_data = match image {
+                Ok(bytes) => Some(general_purpose::STANDARD.encode(bytes)),
+                Err(e) => {
+                    warn!(
+                        "⚠️ Screenshot of context {} not exported: {}",
+                        context.id, e
+                    );
+                    None
+                }
+            };
         }
+    }
 
-        export_data.insert(
-            "conversations".to_string(),
-            serde_json::Value::Array(conversations_data),
-        );
-        export_data.insert(
-            "export_timestamp".to_string(),
-            serde_json::Value::String(Utc::now().to_rfc3339()),
-        );
+    /// Range dans le magasin les captures (base64) des contextes importés ; une référence
+    /// qui ne désigne aucune capture du magasin est retirée
+    fn store_imported_screenshots(&self, contexts: &mut [CapturedContext]) {
+        for context in contexts {
+            let Some(data) = context.screenshot_data.as_deref() else {
+                continue;
+            };
+            let is_reference = parse_reference(data).is_some();
+            match self.store_context_screenshot(context) {
+                Some(reference) => context.screenshot_data = Some(reference),
+                None if is_reference => context.screenshot_data = None,
+                None => {}
+            }
+        }
+    }
 
-        let json_data = serde_json::to_string_pretty(&export_data)
-            .map_err(|e| format!("Failed to serialize export data: {}", e))?;
+    /// Écrit un bundle d'export dans un fichier JSON
+    pub async fn export_data(&self, file_path: &str, bundle: &ExportBundle) -> Result<(), String> {
+        info!("📤 Exporting data to: {}", file_path);
 
+        let json_data = bundle.to_json()?;
         std::fs::write(file_path, json_data)
             .map_err(|e| format!("Failed to write export file: {}", e))?;
 
-        info!("✅ Data exported successfully");
+        info!(
+            "✅ Data exported successfully ({} conversations, {} messages, {} contexts)",
+            bundle.conversations.len(),
+            bundle.messages.len(),
+            bundle.captured_contexts.len()
+        );
+        Ok(())
+    }
+
+    /// Lit et valide un fichier d'export
+    pub fn read_export_file(file_path: &str) -> Result<ExportBundle, String> {
+        let contents = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read export file: {}", e))?;
+        ExportBundle::from_json(&contents)
+    }
+
+    /// Fusionne les tables d'un bundle dans la base (dédupliqué par id) ; les captures des
+    /// contextes sont rangées dans le magasin et remplacées par leur référence
+    pub async fn import_bundle(
+        &self,
+        bundle: &mut ExportBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), String> {
+        info!(
+            "📥 Importing export bundle v{} from {}",
+            bundle.version, bundle.exported_at
+        );
+        self.store_imported_screenshots(&mut bundle.captured_contexts);
+
+        let db = self.database.lock().await;
+        db.import_records(bundle, report).await?;
+
+        info!(
+            "✅ Imported {} conversations, {} messages, {} contexts",
+            report.conversations.imported, report.messages.imported, report.captured_contexts.imported
+        );
         Ok(())
     }
 
@@ -203,3 +428,71 @@ impl PersistenceManager {
         self.database.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    async fn manager(store: &Path) -> PersistenceManager {
+        let database = DatabaseManager::open(":memory:").await.unwrap();
+        let mut manager = PersistenceManager {
+            database: Arc::new(Mutex::new(database)),
+            compression: CompressionManager::new(),
+            redactor: Arc::new(Redactor::default()),
+            audit: None,
+            screenshots: None,
+        };
+        manager.set_screenshot_store(Arc::new(ScreenshotStore::open(store, None, false).unwrap()));
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_export_import_carries_context_screenshots() {
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let source = manager(source_dir.path()).await;
+        let image = general_purpose::STANDARD.encode(b"frame");
+        source
+            .save_context(CapturedContext {
+                id: "ctx-1".to_string(),
+                conversation_id: None,
+                app_name: "Code".to_string(),
+                app_bundle_id: "com.microsoft.VSCode".to_string(),
+                window_title: "main.rs".to_string(),
+                clipboard_content: None,
+                idle_seconds: 1.0,
+                screenshot_data: Some(image.clone()),
+                created_at: Utc::now(),
+                trigger_reason: "idle_ok".to_string(),
+                capture_duration_ms: 10,
+            })
+            .await
+            .unwrap();
+
+        // L'export porte l'image, pas la référence au magasin local
+        let bundle = source.export_bundle().await.unwrap();
+        assert_eq!(
+            bundle.captured_contexts[0].screenshot_data.as_deref(),
+            Some(image.as_str())
+        );
+
+        let target_dir = tempfile::TempDir::new().unwrap();
+        let target = manager(target_dir.path()).await;
+        let mut bundle = ExportBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        let mut report = ImportReport::default();
+        target
+            .import_bundle(&mut bundle, &mut report)
+            .await
+            .unwrap();
+        assert_eq!(report.captured_contexts.imported, 1);
+
+        let contexts = target
+            .get_recent_contexts_for_app("Code", 10)
+            .await
+            .unwrap();
+        let reference = contexts[0].screenshot_data.clone().unwrap();
+        assert!(parse_reference(&reference).is_some());
+        let store = target.screenshots.as_ref().unwrap();
+        assert_eq!(store.read(&reference).unwrap(), b"frame");
+    }
+}
@@ -24,6 +24,16 @@ pub struct Message {
     pub metadata: Option<String>, // JSON metadata (trigger context, etc.)
 }
 
+/// Résumé glissant des anciens messages d'une conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    pub conversation_id: String,
+    pub summary: String,
+    pub covers_until_message_id: String, // Dernier message inclus dans le résumé
+    pub covered_messages: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
 /// Rôle d'un message
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "message_role", rename_all = "lowercase")]
@@ -88,3 +98,57 @@ impl<T> PersistenceResult<T> {
         }
     }
 }
+
+/// Type de document indexé pour la recherche plein texte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchHitKind {
+    Conversation,
+    Message,
+    Context,
+}
+
+impl SearchHitKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchHitKind::Conversation => "conversation",
+            SearchHitKind::Message => "message",
+            SearchHitKind::Context => "context",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "conversation" => Some(SearchHitKind::Conversation),
+            "message" => Some(SearchHitKind::Message),
+            "context" => Some(SearchHitKind::Context),
+            _ => None,
+        }
+    }
+}
+
+/// Requête de recherche plein texte avec filtres optionnels
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub query: String,
+    pub app_name: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub role: Option<String>, // "user" | "assistant" | "system" (messages uniquement)
+    pub kinds: Option<Vec<SearchHitKind>>,
+    pub limit: Option<i64>,
+}
+
+/// Résultat de recherche classé
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub id: String,
+    pub conversation_id: Option<String>,
+    pub title: String,
+    pub snippet: String, // Termes trouvés entourés de `**`
+    pub app_name: Option<String>,
+    pub role: Option<String>,
+    pub created_at: String,
+    pub score: f64, // BM25 : plus petit = plus pertinent
+}
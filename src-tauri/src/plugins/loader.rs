@@ -1,3 +1,4 @@
+use super::permissions::Permission;
 use super::types::{PluginManifest, PluginMetadata, PluginConfig, PluginHook, HookAction};
 use std::fs;
 use std::path::Path;
@@ -75,7 +76,12 @@ pub fn create_example_plugin(plugin_dir: &Path, plugin_id: &str) -> Result<(), S
                     },
                 },
             ],
-            permissions: vec!["notifications".to_string()],
+            // A script hook is a native process: it needs the unconfined permissions
+            permissions: vec![
+                Permission::ReadContext,
+                Permission::Network,
+                Permission::WriteFiles,
+            ],
             settings: None,
         },
     };
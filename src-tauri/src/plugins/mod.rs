@@ -8,15 +8,22 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 
 pub mod loader;
+pub mod permissions;
 pub mod runtime;
 pub mod types;
+pub mod wasm;
 
+pub use permissions::{Permission, PermissionViolation};
+pub use runtime::{HookOutput, PluginNotification, ScriptLimits};
 pub use types::{Plugin, PluginManifest};
+pub use wasm::{WasmLimits, WasmRuntime};
+
+use permissions::{Grants, ViolationLog};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginInfo {
@@ -27,6 +34,7 @@ pub struct PluginInfo {
     pub description: String,
     pub enabled: bool,
     pub hooks: Vec<String>,
+    pub permissions: Vec<Permission>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,18 +43,25 @@ pub struct PluginStats {
     pub enabled_plugins: usize,
     pub total_hooks: usize,
     pub plugin_directory: String,
+    pub permission_violations: u64,
+    pub recent_violations: Vec<PermissionViolation>,
 }
 
 pub struct PluginManager {
     plugins: HashMap<String, Plugin>,
     plugin_dir: PathBuf,
     hooks: HashMap<String, Vec<String>>, // hook_name -> [plugin_ids]
+    wasm: Arc<WasmRuntime>, // Function hooks
+    script_limits: ScriptLimits,
+    violations: Arc<ViolationLog>,
 }
 
 impl PluginManager {
     pub fn new() -> Result<Self, String> {
-        let plugin_dir = Self::get_plugin_directory()?;
+        Self::with_directory(Self::get_plugin_directory()?)
+    }
 
+    pub fn with_directory(plugin_dir: PathBuf) -> Result<Self, String> {
         // Create plugin directory if it doesn't exist
         if !plugin_dir.exists() {
             fs::create_dir_all(&plugin_dir)
@@ -57,9 +72,22 @@ impl PluginManager {
             plugins: HashMap::new(),
             plugin_dir,
             hooks: HashMap::new(),
+            wasm: Arc::new(WasmRuntime::default()),
+            script_limits: ScriptLimits::default(),
+            violations: Arc::new(ViolationLog::default()),
         })
     }
 
+    pub fn with_wasm_limits(mut self, limits: WasmLimits) -> Self {
+        self.wasm = Arc::new(WasmRuntime::new(limits));
+        self
+    }
+
+    pub fn with_script_limits(mut self, limits: ScriptLimits) -> Self {
+        self.script_limits = limits;
+        self
+    }
+
     fn get_plugin_directory() -> Result<PathBuf, String> {
         let data_dir = dirs::data_dir()
             .ok_or_else(|| "Could not find data directory".to_string())?;
@@ -185,33 +213,29 @@ impl PluginManager {
         Ok(())
     }
 
-    pub fn execute_hook(&self, hook_name: &str, context: &str) -> Vec<String> {
-        let mut results = Vec::new();
-
-        if let Some(plugin_ids) = self.hooks.get(hook_name) {
-            for plugin_id in plugin_ids {
-                if let Some(plugin) = self.plugins.get(plugin_id) {
-                    if !plugin.enabled {
-                        continue;
-                    }
+    pub async fn execute_hook(&self, hook_name: &str, context: &str) -> HookOutput {
+        self.prepare_hook(hook_name).execute(context).await
+    }
 
-                    // Find the hook configuration
-                    if let Some(hook) = plugin.config.hooks.iter().find(|h| h.name == hook_name) {
-                        info!("🪝 Executing hook '{}' for plugin '{}'", hook_name, plugin_id);
-
-                        // Execute the hook action
-                        match runtime::execute_hook_action(&plugin.path, &hook.action, context) {
-                            Ok(result) => results.push(result),
-                            Err(e) => {
-                                error!("❌ Hook execution failed for {}: {}", plugin_id, e);
-                            }
-                        }
-                    }
-                }
-            }
+    /// Snapshot of what a hook needs to run, so the manager isn't locked while it runs
+    pub fn prepare_hook(&self, hook_name: &str) -> HookRun {
+        let plugins = self
+            .hooks
+            .get(hook_name)
+            .into_iter()
+            .flatten()
+            .filter_map(|plugin_id| self.plugins.get(plugin_id))
+            .filter(|plugin| plugin.enabled)
+            .cloned()
+            .collect();
+
+        HookRun {
+            hook_name: hook_name.to_string(),
+            plugins,
+            wasm: self.wasm.clone(),
+            script_limits: self.script_limits.clone(),
+            violations: self.violations.clone(),
         }
-
-        results
     }
 
     pub fn get_all_plugins(&self) -> Vec<PluginInfo> {
@@ -225,6 +249,7 @@ impl PluginManager {
                 description: p.metadata.description.clone(),
                 enabled: p.enabled,
                 hooks: p.config.hooks.iter().map(|h| h.name.clone()).collect(),
+                permissions: p.config.permissions.clone(),
             })
             .collect()
     }
@@ -238,6 +263,7 @@ impl PluginManager {
             description: p.metadata.description.clone(),
             enabled: p.enabled,
             hooks: p.config.hooks.iter().map(|h| h.name.clone()).collect(),
+            permissions: p.config.permissions.clone(),
         })
     }
 
@@ -252,6 +278,8 @@ impl PluginManager {
             enabled_plugins: enabled_count,
             total_hooks,
             plugin_directory: self.plugin_dir.to_string_lossy().to_string(),
+            permission_violations: self.violations.total(),
+            recent_violations: self.violations.recent(),
         }
     }
 
@@ -267,6 +295,58 @@ impl PluginManager {
     }
 }
 
+/// One hook invocation over the enabled plugins that implement it
+pub struct HookRun {
+    hook_name: String,
+    plugins: Vec<Plugin>,
+    wasm: Arc<WasmRuntime>,
+    script_limits: ScriptLimits,
+    violations: Arc<ViolationLog>,
+}
+
+impl HookRun {
+    pub async fn execute(&self, context: &str) -> HookOutput {
+        let hook_name = self.hook_name.as_str();
+        let mut results = HookOutput::default();
+
+        for plugin in &self.plugins {
+            let plugin_id = &plugin.id;
+
+            // Find the hook configuration
+            if let Some(hook) = plugin.config.hooks.iter().find(|h| h.name == hook_name) {
+                info!("🪝 Executing hook '{}' for plugin '{}'", hook_name, plugin_id);
+
+                // Only what the manifest declares is granted
+                let grants = Grants::new(
+                    plugin_id,
+                    hook_name,
+                    &plugin.config.permissions,
+                    self.violations.clone(),
+                );
+
+                // Execute the hook action
+                match runtime::execute_hook_action(
+                    &self.wasm,
+                    &self.script_limits,
+                    plugin,
+                    grants,
+                    &hook.action,
+                    context,
+                )
+                .await
+                {
+                    Ok(output) => results.merge(output),
+                    Err(e) => {
+                        error!("❌ Hook execution failed for {}: {}", plugin_id, e);
+                    }
+                }
+            }
+        }
+
+        results
+    }
+}
+
 // Tauri Commands
 #[tauri::command]
 pub async fn get_all_plugins(
@@ -331,10 +411,21 @@ pub async fn get_plugin_stats(
 
 #[tauri::command]
 pub async fn execute_plugin_hook(
+    app: AppHandle,
     hook_name: String,
     context: String,
     plugin_manager: State<'_, Arc<Mutex<PluginManager>>>,
 ) -> Result<Vec<String>, String> {
-    let manager = plugin_manager.lock().await;
-    Ok(manager.execute_hook(&hook_name, &context))
+    // The lock is released before the hooks run: a slow script mustn't block the other commands
+    let run = plugin_manager.lock().await.prepare_hook(&hook_name);
+    let output = run.execute(&context).await;
+
+    // Notifications only exist for plugins granted `show_notification`
+    for notification in &output.notifications {
+        if let Err(e) = app.emit("plugin-notification", notification) {
+            warn!("Failed to emit plugin notification: {}", e);
+        }
+    }
+
+    Ok(output.results)
 }
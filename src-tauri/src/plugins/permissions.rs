@@ -0,0 +1,190 @@
+//! Capabilities a plugin declares in its manifest, checked by the host whenever a hook
+//! reaches for data or a service it provides.
+//!
+//! Script hooks are native processes the host cannot confine: they can open sockets and
+//! write anywhere the user can, so they only run if the plugin declares `network` and
+//! `write_files`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Violations kept for `PluginStats`
+const MAX_RECENT_VIOLATIONS: usize = 50;
+
+/// Capability a plugin declares in `config.permissions`.
+///
+/// Deserialization is strict on purpose: a permission this version does not know cannot be
+/// enforced, so a manifest declaring one fails to load (fail closed) instead of running
+/// with a subset of what it asked for. Only that plugin is skipped; the loader logs the
+/// serde error, which names the unknown value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// The hook context JSON
+    ReadContext,
+    /// The current clipboard text
+    ReadClipboard,
+    Network,
+    /// Files in the plugin's `data/` directory (WASM), or anywhere (scripts)
+    WriteFiles,
+    /// Older manifests (the example plugin included) spell it `notifications`
+    #[serde(alias = "notifications")]
+    ShowNotification,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ReadContext => "read_context",
+            Permission::ReadClipboard => "read_clipboard",
+            Permission::Network => "network",
+            Permission::WriteFiles => "write_files",
+            Permission::ShowNotification => "show_notification",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionViolation {
+    pub plugin_id: String,
+    pub hook: String,
+    pub permission: Permission,
+    /// What the hook attempted
+    pub action: String,
+    pub timestamp: i64,
+}
+
+/// Every denied access, shared by the script and WASM runtimes
+#[derive(Debug, Default)]
+pub struct ViolationLog {
+    recent: Mutex<VecDeque<PermissionViolation>>,
+    total: AtomicU64,
+}
+
+impl ViolationLog {
+    pub fn record(&self, violation: PermissionViolation) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == MAX_RECENT_VIOLATIONS {
+            recent.pop_front();
+        }
+        recent.push_back(violation);
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Most recent last
+    pub fn recent(&self) -> Vec<PermissionViolation> {
+        self.recent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Permissions granted to one hook run, and where denied accesses are reported
+#[derive(Debug, Clone)]
+pub struct Grants {
+    plugin_id: String,
+    hook: String,
+    granted: Vec<Permission>,
+    log: Arc<ViolationLog>,
+}
+
+impl Grants {
+    pub fn new(
+        plugin_id: &str,
+        hook: &str,
+        granted: &[Permission],
+        log: Arc<ViolationLog>,
+    ) -> Self {
+        Self {
+            plugin_id: plugin_id.to_string(),
+            hook: hook.to_string(),
+            granted: granted.to_vec(),
+            log,
+        }
+    }
+
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    pub fn has(&self, permission: Permission) -> bool {
+        self.granted.contains(&permission)
+    }
+
+    /// Deny `action` and record the violation unless `permission` was declared
+    pub fn check(&self, permission: Permission, action: &str) -> Result<(), String> {
+        if self.has(permission) {
+            return Ok(());
+        }
+
+        self.log.record(PermissionViolation {
+            plugin_id: self.plugin_id.clone(),
+            hook: self.hook.clone(),
+            permission,
+            action: action.to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+        });
+        Err(format!(
+            "Permission denied: plugin '{}' needs '{}' to {}",
+            self.plugin_id,
+            permission.as_str(),
+            action
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_records_violations() {
+        let log = Arc::new(ViolationLog::default());
+        let grants = Grants::new(
+            "spotter",
+            "on_context",
+            &[Permission::ReadContext],
+            log.clone(),
+        );
+
+        assert!(grants
+            .check(Permission::ReadContext, "read the context")
+            .is_ok());
+        let err = grants
+            .check(Permission::ReadClipboard, "read the clipboard")
+            .unwrap_err();
+        assert!(err.contains("read_clipboard"), "{}", err);
+
+        assert_eq!(log.total(), 1);
+        let recent = log.recent();
+        assert_eq!(recent[0].permission, Permission::ReadClipboard);
+        assert_eq!(recent[0].hook, "on_context");
+    }
+
+    #[test]
+    fn test_unknown_permission_is_rejected() {
+        let parsed: Result<Vec<Permission>, _> =
+            serde_json::from_str(r#"["read_context", "root_access"]"#);
+        let err = parsed.unwrap_err().to_string();
+        assert!(err.contains("root_access"), "{}", err);
+    }
+
+    #[test]
+    fn test_legacy_notifications_spelling_is_accepted() {
+        let parsed: Vec<Permission> =
+            serde_json::from_str(r#"["notifications", "show_notification"]"#).unwrap();
+        assert_eq!(
+            parsed,
+            vec![Permission::ShowNotification, Permission::ShowNotification]
+        );
+    }
+}
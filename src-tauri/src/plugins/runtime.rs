@@ -1,36 +1,139 @@
-use super::types::HookAction;
-use std::path::Path;
-use std::process::Command;
+use super::permissions::{Grants, Permission};
+use super::types::{HookAction, Plugin};
+use super::wasm::WasmRuntime;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
 use tracing::{error, info};
 
-pub fn execute_hook_action(
-    plugin_path: &Path,
+/// Variables a script inherits; everything else (API keys, tokens...) is scrubbed
+const INHERITED_ENV: &[&str] = &[
+    "PATH",
+    "LANG",
+    "LC_ALL",
+    "TZ",
+    "TMPDIR",
+    "SystemRoot",
+    "TEMP",
+    "TMP",
+];
+
+/// Resources a single script hook may use
+#[derive(Debug, Clone)]
+pub struct ScriptLimits {
+    pub timeout: Duration,
+    /// Cap on stdout; a script printing more fails
+    pub max_output_bytes: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_output_bytes: 64 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginNotification {
+    pub plugin_id: String,
+    pub message: String,
+}
+
+/// What a hook produced: the script output or the suggestions emitted by a WASM function,
+/// plus the notifications it asked the host to show
+#[derive(Debug, Clone, Default)]
+pub struct HookOutput {
+    pub results: Vec<String>,
+    pub notifications: Vec<PluginNotification>,
+}
+
+impl HookOutput {
+    pub fn merge(&mut self, other: HookOutput) {
+        self.results.extend(other.results);
+        self.notifications.extend(other.notifications);
+    }
+}
+
+pub async fn execute_hook_action(
+    wasm: &Arc<WasmRuntime>,
+    scripts: &ScriptLimits,
+    plugin: &Plugin,
+    grants: Grants,
     action: &HookAction,
     context: &str,
-) -> Result<String, String> {
+) -> Result<HookOutput, String> {
     match action {
         HookAction::Script { command, args } => {
-            execute_script(plugin_path, command, args, context)
+            let output = execute_script(plugin, &grants, command, args, context, scripts).await?;
+            Ok(HookOutput {
+                results: vec![output],
+                notifications: Vec::new(),
+            })
         }
         HookAction::Function { module, function } => {
-            // For now, we'll just return a placeholder
-            // In a full implementation, this would use a scripting engine like rhai or lua
-            Ok(format!(
-                "Function hook: {}::{} (context: {})",
-                module, function, context
-            ))
+            // Fuel and deadline bound the call; keep it off the async workers
+            let wasm = wasm.clone();
+            let plugin_path = plugin.path.clone();
+            let settings = plugin.config.settings.clone();
+            let (module, function, context) =
+                (module.clone(), function.clone(), context.to_string());
+            tokio::task::spawn_blocking(move || {
+                wasm.call(
+                    &grants,
+                    &plugin_path,
+                    &module,
+                    &function,
+                    &context,
+                    settings.as_ref(),
+                )
+            })
+            .await
+            .map_err(|e| format!("WASM task failed: {}", e))?
         }
     }
 }
 
-fn execute_script(
-    plugin_path: &Path,
+/// `relative` resolved inside the plugin directory; `..` and absolute paths are refused
+pub(crate) fn plugin_file(plugin_path: &Path, relative: &str) -> Result<PathBuf, String> {
+    let inside = Path::new(relative)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if !inside || relative.is_empty() {
+        return Err(format!(
+            "Path must stay inside the plugin directory: {}",
+            relative
+        ));
+    }
+    Ok(plugin_path.join(relative))
+}
+
+/// Current clipboard text, empty if unavailable
+pub(crate) fn clipboard_text() -> String {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .unwrap_or_default()
+}
+
+/// Run a script with a scrubbed environment, the plugin directory as cwd, a timeout and
+/// a cap on its output. The context and clipboard are only passed if declared.
+async fn execute_script(
+    plugin: &Plugin,
+    grants: &Grants,
     command: &str,
     args: &[String],
     context: &str,
+    limits: &ScriptLimits,
 ) -> Result<String, String> {
-    let script_path = plugin_path.join(command);
+    grants.check(Permission::Network, "run a native script")?;
+    grants.check(Permission::WriteFiles, "run a native script")?;
 
+    let script_path = plugin_file(&plugin.path, command)?;
     if !script_path.exists() {
         return Err(format!("Script not found: {:?}", script_path));
     }
@@ -38,20 +141,302 @@ fn execute_script(
     info!("🚀 Executing script: {:?}", script_path);
 
     let mut cmd = Command::new(&script_path);
-    cmd.args(args);
-    cmd.env("SHADOWLEARN_CONTEXT", context);
-    cmd.env("SHADOWLEARN_PLUGIN_PATH", plugin_path);
+    cmd.args(args)
+        .current_dir(&plugin.path)
+        .env_clear()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Own process group, so whatever the script starts is killed along with it
+    #[cfg(unix)]
+    cmd.process_group(0);
+    for key in INHERITED_ENV {
+        if let Some(value) = std::env::var_os(key) {
+            cmd.env(key, value);
+        }
+    }
+    cmd.env("SHADOWLEARN_PLUGIN_ID", &plugin.id);
+    cmd.env("SHADOWLEARN_PLUGIN_PATH", &plugin.path);
+    cmd.env(
+        "SHADOWLEARN_SETTINGS",
+        serde_json::to_string(&plugin.config.settings).unwrap_or_default(),
+    );
+    if grants.has(Permission::ReadContext) {
+        cmd.env("SHADOWLEARN_CONTEXT", context);
+    }
+    if grants.has(Permission::ReadClipboard) {
+        cmd.env("SHADOWLEARN_CLIPBOARD", clipboard_text());
+    }
 
-    let output = cmd
-        .output()
+    let mut child = cmd
+        .spawn()
         .map_err(|e| format!("Failed to execute script: {}", e))?;
+    let pid = child.id();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    let run = tokio::time::timeout(limits.timeout, async {
+        tokio::join!(
+            read_capped(stdout, limits.max_output_bytes),
+            read_capped(stderr, limits.max_output_bytes),
+            child.wait()
+        )
+    })
+    .await;
+    // Processes left behind by the script (background jobs) don't outlive the hook
+    kill_process_group(pid);
+    let Ok((stdout, stderr, status)) = run else {
+        let _ = child.start_kill();
+        return Err(format!("Script timed out after {:?}", limits.timeout));
+    };
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    let (stdout, truncated) = stdout.map_err(|e| format!("Failed to read script output: {}", e))?;
+    let (stderr, _) = stderr.unwrap_or_default();
+    let status = status.map_err(|e| format!("Failed to wait for script: {}", e))?;
+
+    if truncated {
+        return Err(format!(
+            "Script output exceeded {} bytes",
+            limits.max_output_bytes
+        ));
+    }
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
         error!("Script execution failed: {}", stderr);
         return Err(format!("Script failed: {}", stderr));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stdout = String::from_utf8_lossy(&stdout).to_string();
     Ok(stdout.trim().to_string())
 }
+
+/// Kill the script's process group
+#[cfg(unix)]
+fn kill_process_group(pid: Option<u32>) {
+    if let Some(pid) = pid.and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+        // SAFETY: plain syscall with no memory involved; a group already gone fails with ESRCH
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: Option<u32>) {}
+
+/// Read at most `max` bytes; the pipe is closed past that, and the flag tells it overflowed
+async fn read_capped<R: AsyncRead + Unpin>(
+    pipe: Option<R>,
+    max: usize,
+) -> std::io::Result<(Vec<u8>, bool)> {
+    let Some(pipe) = pipe else {
+        return Ok((Vec::new(), false));
+    };
+    let mut buffer = Vec::new();
+    pipe.take(max as u64 + 1).read_to_end(&mut buffer).await?;
+    let truncated = buffer.len() > max;
+    buffer.truncate(max);
+    Ok((buffer, truncated))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::plugins::permissions::ViolationLog;
+    use crate::plugins::types::{PluginConfig, PluginMetadata};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn script_plugin(dir: &TempDir, script: &str, permissions: Vec<Permission>) -> Plugin {
+        let path = dir.path().join("hook.sh");
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        Plugin {
+            id: "scripted".to_string(),
+            metadata: PluginMetadata {
+                id: "scripted".to_string(),
+                name: "Scripted".to_string(),
+                version: "1.0.0".to_string(),
+                author: "tests".to_string(),
+                description: String::new(),
+                homepage: None,
+                repository: None,
+            },
+            config: PluginConfig {
+                hooks: Vec::new(),
+                permissions,
+                settings: Some(serde_json::json!({ "greeting": "hi" })),
+            },
+            enabled: true,
+            path: dir.path().to_path_buf(),
+        }
+    }
+
+    async fn run(
+        plugin: &Plugin,
+        limits: &ScriptLimits,
+        log: &Arc<ViolationLog>,
+    ) -> Result<String, String> {
+        let grants = Grants::new(
+            &plugin.id,
+            "on_test",
+            &plugin.config.permissions,
+            log.clone(),
+        );
+        let action = HookAction::Script {
+            command: "hook.sh".to_string(),
+            args: Vec::new(),
+        };
+        execute_hook_action(
+            &Arc::new(WasmRuntime::default()),
+            limits,
+            plugin,
+            grants,
+            &action,
+            r#"{"app":"Code"}"#,
+        )
+        .await
+        .map(|output| output.results.join("\n"))
+    }
+
+    const UNCONFINED: [Permission; 2] = [Permission::Network, Permission::WriteFiles];
+
+    #[tokio::test]
+    async fn test_script_environment_is_scrubbed() {
+        // A variable of this process outside the allowlist (HOME, USER...); the environment is
+        // only read, as setting one would race with the other tests' threads
+        let (secret, _) = std::env::vars()
+            .find(|(key, value)| {
+                !INHERITED_ENV.contains(&key.as_str())
+                    && !key.starts_with("SHADOWLEARN_")
+                    && key.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+                    && !value.is_empty()
+            })
+            .expect("test process has an environment");
+        let dir = TempDir::new().unwrap();
+        let plugin = script_plugin(
+            &dir,
+            &format!(
+                "#!/bin/sh\necho \"cwd=$(pwd)\"\necho \"secret=${}\"\n\
+                 echo \"context=$SHADOWLEARN_CONTEXT\"\necho \"settings=$SHADOWLEARN_SETTINGS\"\n",
+                secret
+            ),
+            UNCONFINED.to_vec(),
+        );
+        let log = Arc::new(ViolationLog::default());
+
+        let output = run(&plugin, &ScriptLimits::default(), &log).await.unwrap();
+        let cwd = fs::canonicalize(dir.path()).unwrap();
+        assert!(
+            output.contains(&format!("cwd={}", cwd.display())),
+            "{}",
+            output
+        );
+        assert!(output.contains("secret=\n"), "{}", output);
+        // No `read_context`: the context is withheld
+        assert!(output.contains("context=\n"), "{}", output);
+        assert!(
+            output.contains(r#"settings={"greeting":"hi"}"#),
+            "{}",
+            output
+        );
+
+        let mut plugin = plugin;
+        plugin.config.permissions.push(Permission::ReadContext);
+        let output = run(&plugin, &ScriptLimits::default(), &log).await.unwrap();
+        assert!(output.contains(r#"context={"app":"Code"}"#), "{}", output);
+        assert_eq!(log.total(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_script_needs_unconfined_permissions() {
+        let dir = TempDir::new().unwrap();
+        let plugin = script_plugin(&dir, "#!/bin/sh\necho ok\n", vec![Permission::ReadContext]);
+        let log = Arc::new(ViolationLog::default());
+
+        let err = run(&plugin, &ScriptLimits::default(), &log)
+            .await
+            .unwrap_err();
+        assert!(err.contains("Permission denied"), "{}", err);
+        assert_eq!(log.total(), 1);
+        assert_eq!(log.recent()[0].permission, Permission::Network);
+    }
+
+    #[tokio::test]
+    async fn test_script_limits() {
+        let dir = TempDir::new().unwrap();
+        let log = Arc::new(ViolationLog::default());
+        let limits = ScriptLimits {
+            timeout: Duration::from_millis(200),
+            max_output_bytes: 1024,
+        };
+
+        let slow = script_plugin(&dir, "#!/bin/sh\nsleep 5\n", UNCONFINED.to_vec());
+        let err = run(&slow, &limits, &log).await.unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+
+        let chatty = script_plugin(
+            &dir,
+            "#!/bin/sh\nwhile true; do echo 0123456789abcdef; done\n",
+            UNCONFINED.to_vec(),
+        );
+        let err = run(&chatty, &limits, &log).await.unwrap_err();
+        assert!(err.contains("exceeded 1024 bytes"), "{}", err);
+
+        let escaping = Plugin {
+            path: dir.path().join("nested"),
+            ..slow
+        };
+        let err = execute_script(
+            &escaping,
+            &Grants::new("scripted", "on_test", &UNCONFINED, log.clone()),
+            "../hook.sh",
+            &[],
+            "{}",
+            &limits,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("inside the plugin directory"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_script_children_are_killed_with_it() {
+        let dir = TempDir::new().unwrap();
+        let log = Arc::new(ViolationLog::default());
+        let limits = ScriptLimits {
+            timeout: Duration::from_millis(300),
+            max_output_bytes: 1024,
+        };
+
+        let forking = script_plugin(
+            &dir,
+            "#!/bin/sh\nsleep 30 &\necho $! > child.pid\nsleep 30\n",
+            UNCONFINED.to_vec(),
+        );
+        let err = run(&forking, &limits, &log).await.unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+
+        let pid = fs::read_to_string(dir.path().join("child.pid")).unwrap();
+        let alive = || {
+            let ps = std::process::Command::new("ps")
+                .args(["-o", "stat=", "-p", pid.trim()])
+                .output()
+                .unwrap();
+            // A zombie waiting to be reaped is already dead
+            let stat = String::from_utf8_lossy(&ps.stdout);
+            !stat.trim().is_empty() && !stat.trim().starts_with('Z')
+        };
+        for _ in 0..20 {
+            if !alive() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!alive(), "background job {} outlived the script", pid.trim());
+    }
+}
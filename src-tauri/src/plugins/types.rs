@@ -1,3 +1,4 @@
+use super::permissions::Permission;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -21,7 +22,7 @@ pub struct PluginMetadata {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginConfig {
     pub hooks: Vec<PluginHook>,
-    pub permissions: Vec<String>,
+    pub permissions: Vec<Permission>,
     pub settings: Option<serde_json::Value>,
 }
 
@@ -0,0 +1,546 @@
+//! WebAssembly runtime for `HookAction::Function` hooks.
+//!
+//! A hook names a compiled module inside the plugin directory and one of its exports,
+//! which must take no arguments and return an `i32` status (0 = success). The module
+//! talks to the host through the `shadowlearn` import namespace:
+//!
+//! - `context_len() -> i32` / `context_read(ptr, len) -> i32`: the hook context JSON
+//!   (`read_context`)
+//! - `settings_len() -> i32` / `settings_read(ptr, len) -> i32`: the plugin's own settings JSON
+//! - `clipboard_len() -> i32` / `clipboard_read(ptr, len) -> i32`: the clipboard text
+//!   (`read_clipboard`)
+//! - `emit_suggestion(ptr, len)`: a UTF-8 suggestion returned to the caller
+//! - `notify(ptr, len)`: a notification shown by the host (`show_notification`)
+//! - `write_file(name_ptr, name_len, data_ptr, data_len) -> i32`: writes `data/<name>` in the
+//!   plugin directory, 0 on success (`write_files`)
+//! - `log(level, ptr, len)`: 0 = debug, 1 = info, 2 = warn, 3 = error
+//!
+//! `*_read` copies at most `len` bytes and returns how many were written. Calling a
+//! function whose permission the manifest does not declare traps and is recorded as a
+//! violation. Every call runs in a fresh store with a fuel budget, a wall-clock deadline
+//! and a memory cap.
+
+use super::permissions::{Grants, Permission};
+use super::runtime::{clipboard_text, plugin_file, HookOutput, PluginNotification};
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, error, info, warn};
+use wasmtime::{Caller, Config, Engine, Linker, Memory, Module, ResourceLimiter, Store, Trap};
+
+/// Import namespace of the host API
+pub const HOST_MODULE: &str = "shadowlearn";
+
+/// Interval of the shared epoch ticker that enforces wall-clock deadlines
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Hook calls in progress; the epoch ticker sleeps while there are none
+struct ActiveCalls {
+    count: Mutex<usize>,
+    changed: Condvar,
+}
+
+impl ActiveCalls {
+    fn enter(&'static self) -> CallGuard {
+        *self.count.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.changed.notify_all();
+        CallGuard(self)
+    }
+
+    /// Block until a call is in progress
+    fn wait(&self) {
+        let count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        drop(
+            self.changed
+                .wait_while(count, |count| *count == 0)
+                .unwrap_or_else(|e| e.into_inner()),
+        );
+    }
+}
+
+struct CallGuard(&'static ActiveCalls);
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        *self.0.count.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+    }
+}
+
+static ACTIVE_CALLS: ActiveCalls = ActiveCalls {
+    count: Mutex::new(0),
+    changed: Condvar::new(),
+};
+
+/// Shared engine: fuel metering plus epoch interruption, ticked by a single background thread
+/// while a call runs
+static ENGINE: Lazy<Result<Engine, String>> = Lazy::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    config.epoch_interruption(true);
+    let engine =
+        Engine::new(&config).map_err(|e| format!("Failed to create WASM engine: {}", e))?;
+
+    let ticker = engine.clone();
+    std::thread::Builder::new()
+        .name("wasm-epoch".to_string())
+        .spawn(move || loop {
+            ACTIVE_CALLS.wait();
+            std::thread::sleep(EPOCH_TICK);
+            ticker.increment_epoch();
+        })
+        .map_err(|e| format!("Failed to start WASM epoch ticker: {}", e))?;
+
+    Ok(engine)
+});
+
+/// Resources a single hook call may use
+#[derive(Debug, Clone)]
+pub struct WasmLimits {
+    /// Fuel units, roughly one per executed instruction
+    pub fuel: u64,
+    pub timeout: Duration,
+    /// Linear memory cap, all memories of the instance included
+    pub max_memory_bytes: usize,
+    pub max_suggestions: usize,
+    /// Cap on the bytes emitted through `emit_suggestion` and `log`
+    pub max_output_bytes: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 50_000_000,
+            timeout: Duration::from_secs(2),
+            max_memory_bytes: 16 * 1024 * 1024,
+            max_suggestions: 16,
+            max_output_bytes: 64 * 1024,
+        }
+    }
+}
+
+/// Directory of the plugin that `write_file` writes into
+const DATA_DIR: &str = "data";
+
+/// Store data: what the host API reads from and writes to
+struct HostState {
+    grants: Grants,
+    plugin_path: PathBuf,
+    context: Vec<u8>,
+    settings: Vec<u8>,
+    /// Read on first use
+    clipboard: Option<Vec<u8>>,
+    suggestions: Vec<String>,
+    notifications: Vec<PluginNotification>,
+    output_bytes: usize,
+    limits: WasmLimits,
+    memory: MemoryLimiter,
+}
+
+/// Caps the instance's linear memory; a denied growth traps with an error naming the cap
+struct MemoryLimiter {
+    max_bytes: usize,
+}
+
+impl ResourceLimiter for MemoryLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        if desired > self.max_bytes {
+            return Err(wasmtime::Error::msg(format!(
+                "memory limit exceeded ({} bytes)",
+                self.max_bytes
+            )));
+        }
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        Ok(true)
+    }
+
+    fn instances(&self) -> usize {
+        1
+    }
+
+    /// A single memory, so the cap covers all of the instance's memory
+    fn memories(&self) -> usize {
+        1
+    }
+}
+
+impl HostState {
+    /// Account emitted bytes against the output cap
+    fn charge_output(&mut self, len: usize) -> wasmtime::Result<()> {
+        self.output_bytes += len;
+        if self.output_bytes > self.limits.max_output_bytes {
+            return Err(wasmtime::Error::msg(format!(
+                "output limit exceeded ({} bytes)",
+                self.limits.max_output_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Trap (and record the violation) unless the manifest declares `permission`
+    fn require(&self, permission: Permission, action: &str) -> wasmtime::Result<()> {
+        self.grants
+            .check(permission, action)
+            .map_err(wasmtime::Error::msg)
+    }
+
+    fn clipboard(&mut self) -> &[u8] {
+        self.clipboard
+            .get_or_insert_with(|| clipboard_text().into_bytes())
+    }
+}
+
+/// Compiles plugin modules once (until the file changes) and runs hook calls
+pub struct WasmRuntime {
+    limits: WasmLimits,
+    modules: Mutex<HashMap<PathBuf, (SystemTime, Module)>>,
+}
+
+impl WasmRuntime {
+    pub fn new(limits: WasmLimits) -> Self {
+        Self {
+            limits,
+            modules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `function` from `module` (relative to `plugin_path`) and return the emitted
+    /// suggestions and notifications
+    pub fn call(
+        &self,
+        grants: &Grants,
+        plugin_path: &Path,
+        module: &str,
+        function: &str,
+        context: &str,
+        settings: Option<&serde_json::Value>,
+    ) -> Result<HookOutput, String> {
+        let engine = ENGINE.as_ref().map_err(|e| e.clone())?;
+        let module = self.load_module(engine, plugin_path, module)?;
+
+        let state = HostState {
+            grants: grants.clone(),
+            plugin_path: plugin_path.to_path_buf(),
+            context: context.as_bytes().to_vec(),
+            settings: serde_json::to_vec(&settings.unwrap_or(&serde_json::Value::Null))
+                .map_err(|e| format!("Failed to serialize plugin settings: {}", e))?,
+            clipboard: None,
+            suggestions: Vec::new(),
+            notifications: Vec::new(),
+            output_bytes: 0,
+            limits: self.limits.clone(),
+            memory: MemoryLimiter {
+                max_bytes: self.limits.max_memory_bytes,
+            },
+        };
+        let mut store = Store::new(engine, state);
+        store.limiter(|state| &mut state.memory);
+        store
+            .set_fuel(self.limits.fuel)
+            .map_err(|e| format!("Failed to set fuel: {}", e))?;
+        let ticks = (self.limits.timeout.as_millis() / EPOCH_TICK.as_millis()).max(1) as u64;
+        let _ticking = ACTIVE_CALLS.enter();
+        store.set_epoch_deadline(ticks);
+
+        let linker = host_linker(engine).map_err(|e| format!("Failed to link host API: {}", e))?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| describe_failure("instantiate", &e, &self.limits))?;
+        let entry = instance
+            .get_typed_func::<(), i32>(&mut store, function)
+            .map_err(|e| format!("Function '{}' not exported as () -> i32: {}", function, e))?;
+
+        let status = entry
+            .call(&mut store, ())
+            .map_err(|e| describe_failure(function, &e, &self.limits))?;
+        if status != 0 {
+            return Err(format!(
+                "Function '{}' returned status {}",
+                function, status
+            ));
+        }
+
+        let state = store.into_data();
+        Ok(HookOutput {
+            results: state.suggestions,
+            notifications: state.notifications,
+        })
+    }
+
+    fn load_module(
+        &self,
+        engine: &Engine,
+        plugin_path: &Path,
+        module: &str,
+    ) -> Result<Module, String> {
+        let path = plugin_file(plugin_path, module)?;
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| format!("Module not found: {:?} ({})", path, e))?;
+
+        let mut modules = self.modules.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((compiled_at, module)) = modules.get(&path) {
+            if *compiled_at == modified {
+                return Ok(module.clone());
+            }
+        }
+
+        info!("🧩 Compiling WASM module: {:?}", path);
+        let compiled = Module::from_file(engine, &path)
+            .map_err(|e| format!("Failed to compile {:?}: {}", path, e))?;
+        modules.insert(path, (modified, compiled.clone()));
+        Ok(compiled)
+    }
+}
+
+impl Default for WasmRuntime {
+    fn default() -> Self {
+        Self::new(WasmLimits::default())
+    }
+}
+
+fn describe_failure(stage: &str, error: &wasmtime::Error, limits: &WasmLimits) -> String {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => format!("'{}' ran out of fuel ({} units)", stage, limits.fuel),
+        Some(Trap::Interrupt) => format!("'{}' timed out after {:?}", stage, limits.timeout),
+        _ => format!("'{}' failed: {:#}", stage, error),
+    }
+}
+
+fn guest_memory(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("module does not export 'memory'"))
+}
+
+/// Copy up to `len` bytes of `source` into guest memory at `ptr`
+fn write_guest(
+    caller: &mut Caller<'_, HostState>,
+    source: fn(&mut HostState) -> &[u8],
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<i32> {
+    let memory = guest_memory(caller)?;
+    let data = source(caller.data_mut());
+    let count = data.len().min(len.max(0) as usize);
+    let bytes = data[..count].to_vec();
+    memory.write(&mut *caller, ptr as u32 as usize, &bytes)?;
+    Ok(count as i32)
+}
+
+/// Read `len` raw bytes from guest memory, charged against the output cap
+fn read_guest_bytes(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<Vec<u8>> {
+    let len = len.max(0) as usize;
+    caller.data_mut().charge_output(len)?;
+    let memory = guest_memory(caller)?;
+    let mut buffer = vec![0u8; len];
+    memory.read(&*caller, ptr as u32 as usize, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Read a UTF-8 string of `len` bytes from guest memory, charged against the output cap
+fn read_guest_string(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<String> {
+    let buffer = read_guest_bytes(caller, ptr, len)?;
+    String::from_utf8(buffer).map_err(|_| wasmtime::Error::msg("string is not valid UTF-8"))
+}
+
+fn host_linker(engine: &Engine) -> wasmtime::Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "context_len",
+        |caller: Caller<'_, HostState>| -> wasmtime::Result<i32> {
+            let state = caller.data();
+            state.require(Permission::ReadContext, "read the hook context")?;
+            Ok(state.context.len() as i32)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "context_read",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            caller
+                .data()
+                .require(Permission::ReadContext, "read the hook context")?;
+            write_guest(&mut caller, |state| &state.context, ptr, len)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "settings_len",
+        |caller: Caller<'_, HostState>| caller.data().settings.len() as i32,
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "settings_read",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            write_guest(&mut caller, |state| &state.settings, ptr, len)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "clipboard_len",
+        |mut caller: Caller<'_, HostState>| -> wasmtime::Result<i32> {
+            let state = caller.data_mut();
+            state.require(Permission::ReadClipboard, "read the clipboard")?;
+            Ok(state.clipboard().len() as i32)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "clipboard_read",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            caller
+                .data()
+                .require(Permission::ReadClipboard, "read the clipboard")?;
+            write_guest(&mut caller, |state| state.clipboard(), ptr, len)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "emit_suggestion",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+            let suggestion = read_guest_string(&mut caller, ptr, len)?;
+            let state = caller.data_mut();
+            if state.suggestions.len() >= state.limits.max_suggestions {
+                return Err(wasmtime::Error::msg(format!(
+                    "suggestion limit exceeded ({})",
+                    state.limits.max_suggestions
+                )));
+            }
+            state.suggestions.push(suggestion);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "notify",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+            caller
+                .data()
+                .require(Permission::ShowNotification, "show a notification")?;
+            let message = read_guest_string(&mut caller, ptr, len)?;
+            let state = caller.data_mut();
+            let plugin_id = state.grants.plugin_id().to_string();
+            state
+                .notifications
+                .push(PluginNotification { plugin_id, message });
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "write_file",
+        |mut caller: Caller<'_, HostState>,
+         name_ptr: i32,
+         name_len: i32,
+         data_ptr: i32,
+         data_len: i32|
+         -> wasmtime::Result<i32> {
+            caller
+                .data()
+                .require(Permission::WriteFiles, "write a file")?;
+            let name = read_guest_string(&mut caller, name_ptr, name_len)?;
+            let data = read_guest_bytes(&mut caller, data_ptr, data_len)?;
+
+            let state = caller.data();
+            // A bare file name: no sub-directories, nothing outside `data/`
+            if name.contains(['/', '\\']) {
+                return Err(wasmtime::Error::msg(format!("invalid file name: {}", name)));
+            }
+            let data_dir = state.plugin_path.join(DATA_DIR);
+            let path = plugin_file(&data_dir, &name).map_err(wasmtime::Error::msg)?;
+            let written =
+                std::fs::create_dir_all(&data_dir).and_then(|_| std::fs::write(&path, data));
+            Ok(match written {
+                Ok(()) => 0,
+                Err(e) => {
+                    warn!(
+                        "🧩 [{}] write_file {:?} failed: {}",
+                        state.grants.plugin_id(),
+                        path,
+                        e
+                    );
+                    -1
+                }
+            })
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, HostState>,
+         level: i32,
+         ptr: i32,
+         len: i32|
+         -> wasmtime::Result<()> {
+            let message = read_guest_string(&mut caller, ptr, len)?;
+            let plugin_id = caller.data().grants.plugin_id();
+            match level {
+                0 => debug!("🧩 [{}] {}", plugin_id, message),
+                1 => info!("🧩 [{}] {}", plugin_id, message),
+                2 => warn!("🧩 [{}] {}", plugin_id, message),
+                _ => error!("🧩 [{}] {}", plugin_id, message),
+            }
+            Ok(())
+        },
+    )?;
+
+    Ok(linker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::permissions::ViolationLog;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_module_path_must_stay_in_plugin_dir() {
+        let dir = TempDir::new().unwrap();
+        let runtime = WasmRuntime::default();
+        let grants = Grants::new("test", "on_test", &[], Arc::new(ViolationLog::default()));
+
+        for module in [
+            "../escape.wasm",
+            "/etc/escape.wasm",
+            "./nested/../../x.wasm",
+        ] {
+            let err = runtime
+                .call(&grants, dir.path(), module, "run", "{}", None)
+                .unwrap_err();
+            assert!(err.contains("inside the plugin directory"), "{}", err);
+        }
+
+        let err = runtime
+            .call(&grants, dir.path(), "missing.wasm", "run", "{}", None)
+            .unwrap_err();
+        assert!(err.contains("Module not found"), "{}", err);
+    }
+}
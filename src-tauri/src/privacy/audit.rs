@@ -0,0 +1,551 @@
+//! Journal d'audit de confidentialité : « qu'est-ce que ShadowLearn a vu et envoyé ? ».
+//!
+//! Une ligne JSON par événement (capture d'écran, contexte persisté, requête sortante LLM
+//! ou vision), ajoutée en fin de fichier et jamais réécrite, sauf par sa propre politique
+//! de rétention (`RetentionConfig::audit_log`). Le journal ne contient que des métadonnées
+//! (apps, tailles, compteurs) : aucun texte ni image capturés.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::retention::{CategoryReport, ResolvedPolicy};
+
+/// Durée de conservation par défaut, indépendante de celle des données décrites
+pub const AUDIT_DEFAULT_PERIOD: &str = "90d";
+
+/// Marqueur laissé par le `Redactor` à la place de chaque secret masqué
+const REDACTION_MARKER: &str = "[REDACTED:";
+
+/// Sort d'une requête vis-à-vis de la politique de sortie
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EgressOutcome {
+    /// Fournisseur local, la politique ne s'applique pas
+    Local,
+    /// Fournisseur distant autorisé
+    Allowed,
+    /// Fournisseur distant autorisé après confirmation de l'utilisateur
+    Confirmed,
+    /// Bloquée pour le distant, basculée vers un fournisseur local
+    Rerouted,
+    /// Bloquée, rien n'a été envoyé
+    Refused,
+}
+
+/// Canal d'une requête sortante
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboundChannel {
+    Llm,
+    Vision,
+}
+
+/// Événement audité
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Capture d'écran : gardée (écrite sur disque, zones masquées) ou écartée
+    Capture {
+        app_name: Option<String>,
+        masked_zones: usize,
+        kept: bool,
+        /// Raison de l'abandon (capture écartée)
+        reason: Option<String>,
+        size_bytes: usize,
+    },
+    /// Contexte capturé écrit en base
+    ContextPersisted {
+        context_id: String,
+        app_name: String,
+        conversation_id: Option<String>,
+        redactions: usize,
+        with_screenshot: bool,
+    },
+    /// Requête vers un fournisseur LLM ou vision (une entrée par fournisseur sollicité)
+    Outbound {
+        channel: OutboundChannel,
+        provider: String,
+        remote: bool,
+        /// Taille du contenu envoyé (ou qui l'aurait été pour une requête refusée)
+        bytes: usize,
+        redactions: usize,
+        app_name: Option<String>,
+        egress: EgressOutcome,
+        /// Règle ou mode de la politique de sortie à l'origine du verdict
+        reason: Option<String>,
+    },
+}
+
+/// Ligne du journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// Nombre de secrets masqués par le `Redactor` dans `text`
+pub fn count_redactions(text: &str) -> usize {
+    text.matches(REDACTION_MARKER).count()
+}
+
+/// Journal d'audit en ajout seul (JSON Lines), partagé (`Arc`) entre la capture, la
+/// persistance, le client LLM et le client vision
+pub struct AuditLog {
+    path: PathBuf,
+    /// Sérialise les écritures (ajouts et compaction par la rétention)
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Ajoute un événement ; un échec d'écriture est journalisé sans interrompre l'appelant
+    pub fn record(&self, event: AuditEvent) {
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            event,
+        };
+        if let Err(e) = self.append(&entry) {
+            warn!("⚠️ Audit log: {}", e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize entry: {}", e))?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+
+    /// Entrées entre `from` et `to` (bornes incluses, absentes : pas de limite), plus anciennes d'abord
+    pub fn query(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<AuditEntry>, String> {
+        Ok(self
+            .read_lines()?
+            .into_iter()
+            .filter_map(|(_, entry)| entry)
+            .filter(|entry| from.is_none_or(|from| entry.timestamp >= from))
+            .filter(|entry| to.is_none_or(|to| entry.timestamp <= to))
+            .collect())
+    }
+
+    /// Lignes du fichier avec leur entrée (None : ligne illisible)
+    fn read_lines(&self) -> Result<Vec<(String, Option<AuditEntry>)>, String> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.path.display(), e)),
+        };
+
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| (line.to_string(), serde_json::from_str(line).ok()))
+            .collect())
+    }
+
+    /// Applique la politique de rétention du journal (les lignes illisibles sont écartées)
+    pub fn apply_retention(
+        &self,
+        policy: &ResolvedPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<CategoryReport, String> {
+        let _guard = self.write_lock.lock().unwrap();
+
+        let (lines, unreadable): (Vec<_>, Vec<_>) = self
+            .read_lines()?
+            .into_iter()
+            .partition(|(_, entry)| entry.is_some());
+        let items: Vec<(DateTime<Utc>, u64)> = lines
+            .iter()
+            .filter_map(|(line, entry)| Some((entry.as_ref()?.timestamp, line.len() as u64 + 1)))
+            .collect();
+        let expired = policy.select_expired(&items, now);
+        let mut report = CategoryReport::from_selection(&items, &expired);
+
+        if expired.is_empty() && unreadable.is_empty() {
+            return Ok(report);
+        }
+
+        let mut kept = String::new();
+        for (index, (line, _)) in lines.iter().enumerate() {
+            if expired.binary_search(&index).is_err() {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+        for (line, _) in &unreadable {
+            report.deleted += 1;
+            report.freed_bytes += line.len() as u64 + 1;
+        }
+
+        // Remplacement atomique : un arrêt en cours de route laisse l'ancien journal intact
+        let tmp = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, kept)
+            .and_then(|_| std::fs::rename(&tmp, &self.path))
+            .map_err(|e| format!("Failed to rewrite {}: {}", self.path.display(), e))?;
+
+        if !unreadable.is_empty() {
+            warn!(
+                "⚠️ Audit log: dropped {} unreadable lines",
+                unreadable.len()
+            );
+        }
+        info!("🧹 Removed {} audit entries (retention)", report.deleted);
+        Ok(report)
+    }
+}
+
+/// Format d'export du rapport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditReportFormat {
+    Markdown,
+    Json,
+}
+
+/// Volume envoyé à un fournisseur
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderUsage {
+    pub requests: usize,
+    pub bytes: usize,
+    pub remote: bool,
+}
+
+/// Synthèse du journal sur une période
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+    pub captures_kept: usize,
+    pub captures_discarded: usize,
+    pub masked_zones: usize,
+    /// Captures gardées par app
+    pub captures_by_app: BTreeMap<String, usize>,
+    pub contexts_persisted: usize,
+    /// Requêtes ayant effectivement quitté l'app (refusées exclues)
+    pub outbound_requests: usize,
+    pub remote_requests: usize,
+    pub outbound_bytes: usize,
+    pub rerouted_requests: usize,
+    pub refused_requests: usize,
+    /// Secrets masqués dans les contextes persistés et les requêtes envoyées
+    pub redactions: usize,
+    pub by_provider: BTreeMap<String, ProviderUsage>,
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditReport {
+    pub fn build(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        entries: Vec<AuditEntry>,
+    ) -> Self {
+        let mut report = Self {
+            from,
+            to,
+            generated_at: Utc::now(),
+            captures_kept: 0,
+            captures_discarded: 0,
+            masked_zones: 0,
+            captures_by_app: BTreeMap::new(),
+            contexts_persisted: 0,
+            outbound_requests: 0,
+            remote_requests: 0,
+            outbound_bytes: 0,
+            rerouted_requests: 0,
+            refused_requests: 0,
+            redactions: 0,
+            by_provider: BTreeMap::new(),
+            entries: Vec::new(),
+        };
+
+        for entry in &entries {
+            match &entry.event {
+                AuditEvent::Capture {
+                    app_name,
+                    masked_zones,
+                    kept,
+                    ..
+                } => {
+                    if *kept {
+                        report.captures_kept += 1;
+                        report.masked_zones += masked_zones;
+                        let app = app_name.clone().unwrap_or_else(|| "unknown".to_string());
+                        *report.captures_by_app.entry(app).or_default() += 1;
+                    } else {
+                        report.captures_discarded += 1;
+                    }
+                }
+                AuditEvent::ContextPersisted { redactions, .. } => {
+                    report.contexts_persisted += 1;
+                    report.redactions += redactions;
+                }
+                AuditEvent::Outbound {
+                    provider,
+                    remote,
+                    bytes,
+                    redactions,
+                    egress,
+                    ..
+                } => {
+                    if *egress == EgressOutcome::Refused {
+                        report.refused_requests += 1;
+                        continue;
+                    }
+                    if *egress == EgressOutcome::Rerouted {
+                        report.rerouted_requests += 1;
+                    }
+                    report.outbound_requests += 1;
+                    report.outbound_bytes += bytes;
+                    report.redactions += redactions;
+                    if *remote {
+                        report.remote_requests += 1;
+                    }
+                    let usage = report.by_provider.entry(provider.clone()).or_default();
+                    usage.requests += 1;
+                    usage.bytes += bytes;
+                    usage.remote = *remote;
+                }
+            }
+        }
+
+        report.entries = entries;
+        report
+    }
+
+    pub fn render(&self, format: AuditReportFormat) -> Result<String, String> {
+        match format {
+            AuditReportFormat::Markdown => Ok(self.to_markdown()),
+            AuditReportFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Failed to serialize audit report: {}", e)),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let bound = |date: Option<DateTime<Utc>>| {
+            date.map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_else(|| "…".to_string())
+        };
+        let mut md = format!(
+            "# ShadowLearn privacy audit\n\n_{} → {}_\n\n",
+            bound(self.from),
+            bound(self.to)
+        );
+
+        md.push_str("## Seen\n\n");
+        md.push_str(&format!(
+            "- Screenshots kept: {} ({} privacy zones masked)\n",
+            self.captures_kept, self.masked_zones
+        ));
+        md.push_str(&format!(
+            "- Screenshots discarded: {}\n",
+            self.captures_discarded
+        ));
+        md.push_str(&format!(
+            "- Contexts persisted: {}\n",
+            self.contexts_persisted
+        ));
+        if !self.captures_by_app.is_empty() {
+            md.push_str("\n| App | Screenshots |\n|---|---|\n");
+            for (app, count) in &self.captures_by_app {
+                md.push_str(&format!("| {} | {} |\n", app, count));
+            }
+        }
+
+        md.push_str("\n## Sent\n\n");
+        md.push_str(&format!(
+            "- Requests: {} ({} to remote providers, {} rerouted to local)\n",
+            self.outbound_requests, self.remote_requests, self.rerouted_requests
+        ));
+        md.push_str(&format!("- Bytes sent: {}\n", self.outbound_bytes));
+        md.push_str(&format!("- Requests refused: {}\n", self.refused_requests));
+        md.push_str(&format!("- Secrets redacted: {}\n", self.redactions));
+        if !self.by_provider.is_empty() {
+            md.push_str("\n| Provider | Location | Requests | Bytes |\n|---|---|---|---|\n");
+            for (provider, usage) in &self.by_provider {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    provider,
+                    if usage.remote { "remote" } else { "local" },
+                    usage.requests,
+                    usage.bytes
+                ));
+            }
+        }
+
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn outbound(provider: &str, remote: bool, egress: EgressOutcome) -> AuditEvent {
+        AuditEvent::Outbound {
+            channel: OutboundChannel::Llm,
+            provider: provider.to_string(),
+            remote,
+            bytes: 100,
+            redactions: 1,
+            app_name: Some("Code".to_string()),
+            egress,
+            reason: None,
+        }
+    }
+
+    fn capture(kept: bool) -> AuditEvent {
+        AuditEvent::Capture {
+            app_name: Some("Code".to_string()),
+            masked_zones: 2,
+            kept,
+            reason: (!kept).then(|| "protected app".to_string()),
+            size_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_record_and_query_by_range() {
+        let dir = TempDir::new().unwrap();
+        let log = AuditLog::new(dir.path().join("audit").join("privacy_audit.jsonl"));
+        assert!(log.query(None, None).unwrap().is_empty());
+
+        log.record(capture(true));
+        log.record(outbound("anthropic", true, EgressOutcome::Allowed));
+        // A line written by hand with an older timestamp
+        let old = AuditEntry {
+            timestamp: Utc::now() - Duration::days(3),
+            event: capture(false),
+        };
+        log.append(&old).unwrap();
+
+        let all = log.query(None, None).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(
+            all[1].event,
+            outbound("anthropic", true, EgressOutcome::Allowed)
+        );
+
+        let recent = log
+            .query(Some(Utc::now() - Duration::days(1)), None)
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+        let older = log
+            .query(None, Some(Utc::now() - Duration::days(1)))
+            .unwrap();
+        assert_eq!(older, vec![old]);
+    }
+
+    #[test]
+    fn test_retention_drops_old_and_unreadable_entries() {
+        let dir = TempDir::new().unwrap();
+        let log = AuditLog::new(dir.path().join("privacy_audit.jsonl"));
+        let now = Utc::now();
+        for days_ago in [100, 10, 1] {
+            log.append(&AuditEntry {
+                timestamp: now - Duration::days(days_ago),
+                event: capture(true),
+            })
+            .unwrap();
+        }
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let policy = ResolvedPolicy {
+            max_age: Some(Duration::days(90)),
+            ..Default::default()
+        };
+        let report = log.apply_retention(&policy, now).unwrap();
+
+        assert_eq!(report.deleted, 2);
+        assert_eq!(report.remaining, 2);
+        assert_eq!(log.query(None, None).unwrap().len(), 2);
+
+        // Nothing left to do on a second pass
+        assert_eq!(log.apply_retention(&policy, now).unwrap().deleted, 0);
+    }
+
+    #[test]
+    fn test_report_summary_and_markdown() {
+        let entries = [
+            capture(true),
+            capture(false),
+            AuditEvent::ContextPersisted {
+                context_id: "ctx-1".to_string(),
+                app_name: "Code".to_string(),
+                conversation_id: None,
+                redactions: 2,
+                with_screenshot: false,
+            },
+            outbound("anthropic", true, EgressOutcome::Allowed),
+            outbound("ollama", false, EgressOutcome::Rerouted),
+            outbound("anthropic", true, EgressOutcome::Refused),
+        ]
+        .into_iter()
+        .map(|event| AuditEntry {
+            timestamp: Utc::now(),
+            event,
+        })
+        .collect();
+
+        let report = AuditReport::build(None, None, entries);
+        assert_eq!(report.captures_kept, 1);
+        assert_eq!(report.captures_discarded, 1);
+        assert_eq!(report.masked_zones, 2);
+        assert_eq!(report.contexts_persisted, 1);
+        assert_eq!(report.outbound_requests, 2);
+        assert_eq!(report.remote_requests, 1);
+        assert_eq!(report.outbound_bytes, 200);
+        assert_eq!(report.rerouted_requests, 1);
+        assert_eq!(report.refused_requests, 1);
+        assert_eq!(report.redactions, 4);
+        assert_eq!(report.by_provider["anthropic"].requests, 1);
+
+        let md = report.render(AuditReportFormat::Markdown).unwrap();
+        assert!(md.contains("- Screenshots kept: 1 (2 privacy zones masked)"));
+        assert!(md.contains("| anthropic | remote | 1 | 100 |"));
+        assert!(md.contains("| ollama | local | 1 | 100 |"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(AuditReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["entries"][0]["kind"], "capture");
+        assert_eq!(json["entries"][5]["egress"], "refused");
+    }
+}
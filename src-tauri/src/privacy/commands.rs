@@ -1,4 +1,19 @@
-use super::{PrivacyZone, PrivacyZoneManager, PrivacyZonesConfig};
+use super::audit::{AuditEntry, AuditReport, AuditReportFormat};
+use super::erasure::delete_screenshots;
+use super::{
+    AppMatcher, AuditLog, ErasureReport, ErasureTarget, PrivacyZone, PrivacyZoneManager,
+    PrivacyZonesConfig,
+};
+use crate::digest::DigestManager;
+use crate::learning::LearningSystem;
+use crate::ml::personalization::PersonalizationManager;
+use crate::patterns::commands::PatternManager;
+use crate::persistence::PersistenceManager;
+use crate::productivity::ProductivityManager;
+use crate::replay::ReplayManager;
+use crate::retention::RetentionEngine;
+use crate::screenshot::store::parse_reference;
+use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::info;
@@ -19,6 +34,7 @@ pub async fn add_privacy_zone(
     zone: PrivacyZone,
 ) -> Result<(), String> {
     info!("🔒 add_privacy_zone command called");
+    zone.validate()?;
     let mut manager = privacy.lock().await;
     manager.add_zone(zone);
     Ok(())
@@ -56,3 +72,128 @@ pub async fn is_app_protected(
     let manager = privacy.lock().await;
     Ok(manager.is_app_protected(&app_name))
 }
+
+/// Forget an app (or windows matching a title pattern) everywhere, then block it
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn forget_app(
+    target: ErasureTarget,
+    privacy: tauri::State<'_, Arc<Mutex<PrivacyZoneManager>>>,
+    persistence: tauri::State<'_, Arc<Mutex<PersistenceManager>>>,
+    replay: tauri::State<'_, Arc<Mutex<ReplayManager>>>,
+    patterns: tauri::State<'_, Arc<PatternManager>>,
+    personalization: tauri::State<'_, Arc<Mutex<PersonalizationManager>>>,
+    learning: tauri::State<'_, Arc<Mutex<LearningSystem>>>,
+    digest: tauri::State<'_, Arc<Mutex<DigestManager>>>,
+    productivity: tauri::State<'_, Arc<Mutex<ProductivityManager>>>,
+    retention: tauri::State<'_, Arc<RetentionEngine>>,
+) -> Result<ErasureReport, String> {
+    info!("🗑️ forget_app command called: {:?}", target);
+    let matcher = AppMatcher::new(&target)?;
+    let mut report = ErasureReport::new(target);
+
+    // Block first so nothing new is captured while the stores are purged
+    let zone = matcher.block_zone();
+    {
+        let mut manager = privacy.lock().await;
+        if !manager.config().zones.contains(&zone) {
+            manager.add_zone(zone.clone());
+            report.blocked_zone = Some(zone);
+        }
+    }
+
+    // Captures du magasin détenues avant l'effacement : celles qui n'ont plus de détenteur
+    // après coup ne sont tenues que par l'app oubliée
+    let held_before = match retention.held_screenshots().await {
+        Ok(held) => held,
+        Err(e) => {
+            report.errors.push(e);
+            Default::default()
+        }
+    };
+
+    match persistence.lock().await.forget_app(&matcher).await {
+        Ok(erased) => report.database = erased,
+        Err(e) => report.errors.push(format!("Database: {}", e)),
+    }
+
+    let removed_events = replay.lock().await.forget_app(&matcher);
+    report.replay_events = removed_events.len();
+    // Captures hors magasin ; celles du magasin partent avec leur dernier détenteur
+    report.screenshots = delete_screenshots(
+        removed_events
+            .iter()
+            .filter_map(|event| event.screenshot_path.as_deref())
+            .filter(|path| parse_reference(path).is_none()),
+        &mut report.errors,
+    );
+
+    match patterns.forget_app(&matcher).await {
+        Ok(erased) => report.patterns = erased,
+        Err(e) => report.errors.push(format!("Patterns: {}", e)),
+    }
+
+    {
+        let mut manager = personalization.lock().await;
+        report.ml_entries = manager.forget_app(&matcher);
+        if let Some(path) = dirs::data_dir()
+            .map(|dir| dir.join("ShadowLearn").join("ml_patterns.json"))
+            .filter(|path| path.exists())
+        {
+            if let Err(e) = manager.save_patterns(&path.to_string_lossy()) {
+                report.errors.push(format!("ML patterns: {}", e));
+            }
+        }
+    }
+
+    report.clusters = learning.lock().await.forget_app(&matcher);
+    report.digest_entries = digest.lock().await.forget_app(&matcher);
+    report.productivity_entries = productivity.lock().await.forget_app(&matcher);
+
+    // Captures du magasin dont l'app oubliée était le dernier détenteur, sans délai de grâce ;
+    // les autres captures sans détenteur restent au passage de rétention
+    let (stored, errors) = retention.collect_released_screenshots(held_before).await;
+    report.screenshots += stored.deleted;
+    report.errors.extend(errors);
+
+    info!(
+        "🗑️ Forgot {:?}: {} items deleted, {} errors",
+        report.target,
+        report.total_deleted(),
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+/// Audit log entries between `from` and `to` (RFC 3339, both optional), oldest first
+#[tauri::command]
+pub async fn query_audit_log(
+    audit: tauri::State<'_, Arc<AuditLog>>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<AuditEntry>, String> {
+    let audit = audit.inner().clone();
+    tokio::task::spawn_blocking(move || audit.query(from, to))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// What was seen and sent between `from` (default: 24h ago) and `to` (default: now),
+/// rendered as Markdown or JSON
+#[tauri::command]
+pub async fn generate_audit_report(
+    audit: tauri::State<'_, Arc<AuditLog>>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    format: AuditReportFormat,
+) -> Result<String, String> {
+    info!("🔒 generate_audit_report command called ({:?})", format);
+    let to = to.unwrap_or_else(Utc::now);
+    let from = from.unwrap_or(to - Duration::hours(24));
+
+    let audit = audit.inner().clone();
+    let entries = tokio::task::spawn_blocking(move || audit.query(Some(from), Some(to)))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+    AuditReport::build(Some(from), Some(to), entries).render(format)
+}
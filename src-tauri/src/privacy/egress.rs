@@ -0,0 +1,311 @@
+use crate::config::types::{EgressAction, EgressConfig, EgressFallback};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use tracing::info;
+
+/// Provenance d'une requête : app et fenêtre d'où vient le contenu envoyé
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestOrigin {
+    pub app_name: String,
+    #[serde(default)]
+    pub window_title: String,
+}
+
+impl RequestOrigin {
+    pub fn new(app_name: impl Into<String>, window_title: impl Into<String>) -> Self {
+        Self {
+            app_name: app_name.into(),
+            window_title: window_title.into(),
+        }
+    }
+}
+
+/// Verdict de la politique pour une requête
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EgressDecision {
+    Allow,
+    /// Fournisseurs locaux uniquement
+    LocalOnly {
+        reason: String,
+    },
+    /// Envoi distant seulement après confirmation de l'utilisateur
+    Confirm {
+        reason: String,
+    },
+}
+
+/// Requête refusée par la politique de sortie
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EgressError {
+    #[error("Remote providers blocked by egress policy ({reason})")]
+    Blocked { reason: String },
+    #[error("Remote providers require confirmation ({reason})")]
+    ConfirmationRequired { reason: String },
+}
+
+struct CompiledRule {
+    /// En minuscules
+    app: Option<String>,
+    title: Option<Regex>,
+    action: EgressAction,
+}
+
+impl CompiledRule {
+    fn matches(&self, origin: Option<&RequestOrigin>) -> bool {
+        let app_ok = match (&self.app, origin) {
+            (None, _) => true,
+            (Some(app), Some(origin)) => origin.app_name.to_lowercase().contains(app.as_str()),
+            (Some(_), None) => false,
+        };
+        let title_ok = match (&self.title, origin) {
+            (None, _) => true,
+            (Some(title), Some(origin)) => title.is_match(&origin.window_title),
+            (Some(_), None) => false,
+        };
+        app_ok && title_ok
+    }
+
+    fn describe(&self) -> String {
+        match (&self.app, &self.title) {
+            (Some(app), Some(title)) => format!("rule app '{}' / title /{}/", app, title),
+            (Some(app), None) => format!("rule app '{}'", app),
+            (None, Some(title)) => format!("rule title /{}/", title),
+            (None, None) => "catch-all rule".to_string(),
+        }
+    }
+}
+
+/// Politique compilée par `EgressPolicy::compile`, à mettre en place via `install`
+pub struct EgressState {
+    local_only: bool,
+    require_confirmation: bool,
+    rules: Vec<CompiledRule>,
+    on_block: EgressFallback,
+}
+
+/// Politique de sortie consultée avant chaque appel à un fournisseur (LLM et vision).
+/// Rechargeable à chaud via `apply`.
+pub struct EgressPolicy {
+    state: RwLock<EgressState>,
+}
+
+impl EgressPolicy {
+    pub fn new(config: &EgressConfig) -> Result<Self, String> {
+        Ok(Self {
+            state: RwLock::new(Self::compile(config)?),
+        })
+    }
+
+    /// Remplace la politique par une politique déjà compilée
+    pub fn install(&self, state: EgressState) {
+        info!(
+            "🔒 Egress policy updated (local_only={}, {} rules)",
+            state.local_only,
+            state.rules.len()
+        );
+        *self.state.write().unwrap() = state;
+    }
+
+    /// Compile `config` sans rien changer ; erreur si une regex est invalide
+    pub fn compile(config: &EgressConfig) -> Result<EgressState, String> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let title = match rule.title_pattern.as_deref() {
+                    Some(pattern) => Some(Regex::new(pattern).map_err(|e| {
+                        format!("Invalid egress title pattern '{}': {}", pattern, e)
+                    })?),
+                    None => None,
+                };
+                Ok(CompiledRule {
+                    app: rule
+                        .app
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|app| !app.is_empty())
+                        .map(str::to_lowercase),
+                    title,
+                    action: rule.action,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(EgressState {
+            local_only: config.local_only,
+            require_confirmation: config.require_confirmation,
+            rules,
+            on_block: config.on_block,
+        })
+    }
+
+    /// Verdict pour un contenu venant de `origin` (None : contenu sans app d'origine)
+    pub fn decide(&self, origin: Option<&RequestOrigin>) -> EgressDecision {
+        let state = self.state.read().unwrap();
+        if state.local_only {
+            return EgressDecision::LocalOnly {
+                reason: "local-only mode".to_string(),
+            };
+        }
+
+        if let Some(rule) = state.rules.iter().find(|rule| rule.matches(origin)) {
+            return match rule.action {
+                EgressAction::Allow => EgressDecision::Allow,
+                EgressAction::LocalOnly => EgressDecision::LocalOnly {
+                    reason: rule.describe(),
+                },
+                EgressAction::Confirm => EgressDecision::Confirm {
+                    reason: rule.describe(),
+                },
+            };
+        }
+
+        if state.require_confirmation {
+            EgressDecision::Confirm {
+                reason: "confirmation required for remote providers".to_string(),
+            }
+        } else {
+            EgressDecision::Allow
+        }
+    }
+
+    /// Ok si le contenu peut partir vers un fournisseur distant ; `confirmed` vaut accord
+    /// explicite de l'utilisateur pour cette requête
+    pub fn check_remote(
+        &self,
+        origin: Option<&RequestOrigin>,
+        confirmed: bool,
+    ) -> Result<(), EgressError> {
+        match self.decide(origin) {
+            EgressDecision::Allow => Ok(()),
+            EgressDecision::Confirm { .. } if confirmed => Ok(()),
+            EgressDecision::Confirm { reason } => Err(EgressError::ConfirmationRequired { reason }),
+            EgressDecision::LocalOnly { reason } => Err(EgressError::Blocked { reason }),
+        }
+    }
+
+    /// Sort des requêtes bloquées : basculer en local ou refuser
+    pub fn on_block(&self) -> EgressFallback {
+        self.state.read().unwrap().on_block
+    }
+}
+
+impl Default for EgressPolicy {
+    /// Tout est autorisé
+    fn default() -> Self {
+        Self::new(&EgressConfig::default()).expect("default egress config is valid")
+    }
+}
+
+/// Vrai si l'URL désigne la machine locale (localhost, 127.0.0.0/8, ::1)
+pub fn is_local_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<std::net::IpAddr>() {
+        Ok(ip) => ip.is_loopback(),
+        Err(_) => host.eq_ignore_ascii_case("localhost") || host.ends_with(".localhost"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::EgressRule;
+
+    fn rule(app: Option<&str>, title: Option<&str>, action: EgressAction) -> EgressRule {
+        EgressRule {
+            app: app.map(str::to_string),
+            title_pattern: title.map(str::to_string),
+            action,
+        }
+    }
+
+    #[test]
+    fn test_rules_first_match_wins() {
+        let policy = EgressPolicy::new(&EgressConfig {
+            rules: vec![
+                rule(Some("1Password"), None, EgressAction::LocalOnly),
+                rule(None, Some(r"(?i)\bbank\b"), EgressAction::Confirm),
+                rule(Some("firefox"), None, EgressAction::LocalOnly),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let vault = RequestOrigin::new("1password 8", "Vault");
+        assert!(matches!(
+            policy.decide(Some(&vault)),
+            EgressDecision::LocalOnly { .. }
+        ));
+
+        let bank = RequestOrigin::new("Firefox", "My Bank - Accounts");
+        assert!(matches!(
+            policy.decide(Some(&bank)),
+            EgressDecision::Confirm { .. }
+        ));
+        assert!(policy.check_remote(Some(&bank), true).is_ok());
+        assert!(matches!(
+            policy.check_remote(Some(&bank), false),
+            Err(EgressError::ConfirmationRequired { .. })
+        ));
+
+        let code = RequestOrigin::new("Code", "main.rs");
+        assert_eq!(policy.decide(Some(&code)), EgressDecision::Allow);
+        // Sans provenance, seules les règles sans critère s'appliquent
+        assert_eq!(policy.decide(None), EgressDecision::Allow);
+    }
+
+    #[test]
+    fn test_global_modes() {
+        let policy = EgressPolicy::new(&EgressConfig {
+            local_only: true,
+            rules: vec![rule(None, None, EgressAction::Allow)],
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(matches!(
+            policy.check_remote(None, true),
+            Err(EgressError::Blocked { .. })
+        ));
+
+        policy.install(
+            EgressPolicy::compile(&EgressConfig {
+                require_confirmation: true,
+                rules: vec![rule(Some("terminal"), None, EgressAction::Allow)],
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let terminal = RequestOrigin::new("Terminal", "zsh");
+        assert!(policy.check_remote(Some(&terminal), false).is_ok());
+        assert!(matches!(
+            policy.check_remote(None, false),
+            Err(EgressError::ConfirmationRequired { .. })
+        ));
+
+        // Une regex invalide est refusée avant que la politique ne soit remplacée
+        assert!(EgressPolicy::compile(&EgressConfig {
+            rules: vec![rule(None, Some("("), EgressAction::Allow)],
+            ..Default::default()
+        })
+        .is_err());
+        assert!(policy.check_remote(Some(&terminal), false).is_ok());
+    }
+
+    #[test]
+    fn test_is_local_url() {
+        assert!(is_local_url("http://localhost:11434"));
+        assert!(is_local_url("http://127.0.0.1:1234/v1"));
+        assert!(is_local_url("http://[::1]:8080"));
+        assert!(!is_local_url("https://api.openai.com/v1"));
+        assert!(!is_local_url("http://192.168.1.20:11434"));
+        assert!(!is_local_url("not a url"));
+    }
+}
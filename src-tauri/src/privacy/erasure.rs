@@ -0,0 +1,249 @@
+//! Effacement « oublier cette app ».
+//!
+//! Supprime toute trace d'une app (ou des fenêtres dont le titre correspond à un motif)
+//! dans chaque magasin de données, puis l'ajoute aux zones de confidentialité pour qu'elle
+//! ne soit plus capturée. Chaque magasin expose un `forget_app(&AppMatcher)` ;
+//! `ErasureReport` récapitule ce qui a été supprimé.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::zones::PrivacyZone;
+
+/// Ce qu'il faut oublier : une app entière, les fenêtres dont le titre correspond à
+/// `title_pattern` (regex), ou les fenêtres d'une app dont le titre correspond
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErasureTarget {
+    #[serde(default)]
+    pub app_name: Option<String>,
+    #[serde(default)]
+    pub title_pattern: Option<String>,
+}
+
+/// Cible compilée, partagée par tous les magasins
+#[derive(Debug, Clone)]
+pub struct AppMatcher {
+    app_name: Option<String>,
+    title: Option<Regex>,
+}
+
+impl AppMatcher {
+    pub fn new(target: &ErasureTarget) -> Result<Self, String> {
+        let app_name = target
+            .app_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        let title = match target
+            .title_pattern
+            .as_deref()
+            .filter(|p| !p.trim().is_empty())
+        {
+            Some(pattern) => {
+                Some(Regex::new(pattern).map_err(|e| format!("Invalid title pattern: {}", e))?)
+            }
+            None => None,
+        };
+        if app_name.is_none() && title.is_none() {
+            return Err("An app name or a window-title pattern is required".to_string());
+        }
+
+        Ok(Self { app_name, title })
+    }
+
+    /// Vrai pour une donnée rattachée à une app sans titre de fenêtre (statistiques,
+    /// événements ML...) : seule une cible « app entière » les concerne
+    pub fn matches_app(&self, app_name: &str) -> bool {
+        match (&self.app_name, &self.title) {
+            (Some(app), None) => same_app(app, app_name),
+            _ => false,
+        }
+    }
+
+    /// Vrai pour une donnée prise dans la fenêtre `window_title` de `app_name`
+    pub fn matches_window(&self, app_name: &str, window_title: &str) -> bool {
+        let app_ok = self
+            .app_name
+            .as_ref()
+            .is_none_or(|app| same_app(app, app_name));
+        let title_ok = self
+            .title
+            .as_ref()
+            .is_none_or(|title| title.is_match(window_title));
+        app_ok && title_ok
+    }
+
+    /// Comme `matches_window`, titre éventuellement inconnu (une cible par titre ne
+    /// correspond alors pas)
+    pub fn matches_window_opt(&self, app_name: &str, window_title: Option<&str>) -> bool {
+        match window_title {
+            Some(title) => self.matches_window(app_name, title),
+            None => self.matches_app(app_name),
+        }
+    }
+
+    /// Zone à ajouter pour que la cible ne soit plus capturée
+    pub fn block_zone(&self) -> PrivacyZone {
+        match (&self.app_name, &self.title) {
+            (Some(app_name), None) => PrivacyZone::Window {
+                app_name: app_name.clone(),
+                fuzzy_match: false,
+            },
+            (app_name, Some(title)) => PrivacyZone::WindowTitle {
+                pattern: title.as_str().to_string(),
+                label: match app_name {
+                    Some(app_name) => format!("Forgotten: {} ({})", app_name, title),
+                    None => format!("Forgotten: {}", title),
+                },
+            },
+            (None, None) => unreachable!("validated by AppMatcher::new"),
+        }
+    }
+}
+
+fn same_app(target: &str, app_name: &str) -> bool {
+    app_name.trim().to_lowercase() == target.to_lowercase()
+}
+
+/// Enregistrements supprimés en base
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErasedRecords {
+    pub contexts: usize,
+    pub conversations: usize,
+    pub messages: usize,
+}
+
+/// Historique et modèles de patterns supprimés
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErasedPatterns {
+    pub actions: usize,
+    pub patterns: usize,
+    pub repetitive_tasks: usize,
+}
+
+/// Rapport d'un effacement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErasureReport {
+    pub target: ErasureTarget,
+    pub erased_at: DateTime<Utc>,
+    pub database: ErasedRecords,
+    pub replay_events: usize,
+    pub screenshots: usize,
+    pub patterns: ErasedPatterns,
+    /// Événements ML et entrées de `UsagePatterns`
+    pub ml_entries: usize,
+    pub clusters: usize,
+    pub digest_entries: usize,
+    pub productivity_entries: usize,
+    /// Zone ajoutée aux zones de confidentialité (absente si elle existait déjà)
+    pub blocked_zone: Option<PrivacyZone>,
+    pub errors: Vec<String>,
+}
+
+impl ErasureReport {
+    pub fn new(target: ErasureTarget) -> Self {
+        Self {
+            target,
+            erased_at: Utc::now(),
+            database: ErasedRecords::default(),
+            replay_events: 0,
+            screenshots: 0,
+            patterns: ErasedPatterns::default(),
+            ml_entries: 0,
+            clusters: 0,
+            digest_entries: 0,
+            productivity_entries: 0,
+            blocked_zone: None,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn total_deleted(&self) -> usize {
+        self.database.contexts
+            + self.database.conversations
+            + self.database.messages
+            + self.replay_events
+            + self.screenshots
+            + self.patterns.actions
+            + self.patterns.patterns
+            + self.patterns.repetitive_tasks
+            + self.ml_entries
+            + self.clusters
+            + self.digest_entries
+            + self.productivity_entries
+    }
+}
+
+/// Supprime les fichiers de capture `paths` ; les fichiers déjà absents sont ignorés
+pub fn delete_screenshots<'a>(
+    paths: impl IntoIterator<Item = &'a str>,
+    errors: &mut Vec<String>,
+) -> usize {
+    let mut deleted = 0;
+    for path in paths {
+        let path = Path::new(path);
+        match std::fs::remove_file(path) {
+            Ok(()) => deleted += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => errors.push(format!("Failed to delete {}: {}", path.display(), e)),
+        }
+    }
+    deleted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(app: Option<&str>, title: Option<&str>) -> ErasureTarget {
+        ErasureTarget {
+            app_name: app.map(str::to_string),
+            title_pattern: title.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_matcher_targets() {
+        let app = AppMatcher::new(&target(Some(" Acme CRM "), None)).unwrap();
+        assert!(app.matches_app("acme crm"));
+        assert!(app.matches_window("Acme CRM", "Clients"));
+        assert!(!app.matches_window("Acme CRM Helper", "Clients"));
+        assert!(app.matches_window_opt("Acme CRM", None));
+
+        let title = AppMatcher::new(&target(None, Some(r"(?i)client x"))).unwrap();
+        assert!(!title.matches_app("Firefox"));
+        assert!(title.matches_window("Firefox", "Client X — Invoices"));
+        assert!(!title.matches_window("Firefox", "Docs"));
+        assert!(!title.matches_window_opt("Firefox", None));
+
+        let both = AppMatcher::new(&target(Some("Firefox"), Some("Client X"))).unwrap();
+        assert!(!both.matches_app("Firefox"));
+        assert!(both.matches_window("Firefox", "Client X"));
+        assert!(!both.matches_window("Chrome", "Client X"));
+
+        assert!(AppMatcher::new(&target(Some("  "), None)).is_err());
+        assert!(AppMatcher::new(&target(None, Some("("))).is_err());
+    }
+
+    #[test]
+    fn test_block_zone() {
+        assert_eq!(
+            AppMatcher::new(&target(Some("Acme CRM"), None))
+                .unwrap()
+                .block_zone(),
+            PrivacyZone::Window {
+                app_name: "Acme CRM".to_string(),
+                fuzzy_match: false,
+            }
+        );
+
+        let title = AppMatcher::new(&target(Some("Firefox"), Some("Client X"))).unwrap();
+        assert!(matches!(
+            title.block_zone(),
+            PrivacyZone::WindowTitle { pattern, .. } if pattern == "Client X"
+        ));
+    }
+}
@@ -0,0 +1,220 @@
+use image::{imageops, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Écart-type du flou gaussien, en pixels de l'image masquée
+pub const BLUR_SIGMA: f32 = 12.0;
+
+/// Rectangle à masquer, en pixels de l'écran
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaskRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MaskRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Même zone, exprimée relativement à `origin` et coupée à ses bords (capture d'une
+    /// seule fenêtre). None si elle est hors de `origin`
+    pub fn relative_to(&self, origin: &MaskRect) -> Option<MaskRect> {
+        let left = self.x.max(origin.x);
+        let top = self.y.max(origin.y);
+        let right = self
+            .x
+            .saturating_add(self.width)
+            .min(origin.x.saturating_add(origin.width));
+        let bottom = self
+            .y
+            .saturating_add(self.height)
+            .min(origin.y.saturating_add(origin.height));
+        if left >= right || top >= bottom {
+            return None;
+        }
+        Some(MaskRect::new(
+            left - origin.x,
+            top - origin.y,
+            right - left,
+            bottom - top,
+        ))
+    }
+
+    /// Ramené à l'échelle `scale` de l'image puis borné à `width` x `height`.
+    /// Arrondi vers l'extérieur pour ne rien laisser dépasser. None si vide.
+    pub(crate) fn scaled_within(
+        &self,
+        scale: f32,
+        width: u32,
+        height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        let x0 = ((self.x as f32) * scale).floor() as u32;
+        let y0 = ((self.y as f32) * scale).floor() as u32;
+        let x1 = ((self.x.saturating_add(self.width) as f32) * scale).ceil() as u32;
+        let y1 = ((self.y.saturating_add(self.height) as f32) * scale).ceil() as u32;
+        let (x1, y1) = (x1.min(width), y1.min(height));
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        Some((x0, y0, x1 - x0, y1 - y0))
+    }
+}
+
+/// Façon de masquer une zone
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MaskMode {
+    /// Remplie en noir
+    BlackOut,
+    /// Flou gaussien
+    Blur,
+}
+
+/// Ce que les zones de confidentialité imposent à une capture
+#[derive(Debug, Clone, PartialEq)]
+pub enum FramePlan {
+    /// Capture abandonnée : l'app active est protégée
+    Skip { app_name: String },
+    /// Zones à masquer avant toute écriture ou analyse (vide : rien à faire)
+    Mask {
+        rects: Vec<MaskRect>,
+        mode: MaskMode,
+    },
+}
+
+/// Masque `rects` (coordonnées écran) sur `img`, à l'échelle `scale` de l'écran.
+/// Renvoie le nombre de zones effectivement masquées.
+pub fn apply_masks(img: &mut RgbImage, rects: &[MaskRect], mode: MaskMode, scale: f32) -> usize {
+    let (width, height) = img.dimensions();
+    let mut masked = 0;
+
+    for rect in rects {
+        let Some((x, y, w, h)) = rect.scaled_within(scale, width, height) else {
+            continue;
+        };
+        match mode {
+            MaskMode::BlackOut => {
+                for py in y..y + h {
+                    for px in x..x + w {
+                        img.put_pixel(px, py, image::Rgb([0, 0, 0]));
+                    }
+                }
+            }
+            MaskMode::Blur => {
+                let region = imageops::crop_imm(img, x, y, w, h).to_image();
+                let blurred = imageops::blur(&region, BLUR_SIGMA);
+                imageops::replace(img, &blurred, x as i64, y as i64);
+            }
+        }
+        masked += 1;
+    }
+
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Damier noir et blanc de cases 1px (contraste maximal)
+    fn checkerboard(width: u32, height: u32) -> RgbImage {
+        RgbImage::from_fn(width, height, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgb([255, 255, 255])
+            } else {
+                image::Rgb([0, 0, 0])
+            }
+        })
+    }
+
+    fn variance(img: &RgbImage, x: u32, y: u32, w: u32, h: u32) -> f64 {
+        let values: Vec<f64> = (y..y + h)
+            .flat_map(|py| (x..x + w).map(move |px| (px, py)))
+            .map(|(px, py)| img.get_pixel(px, py)[0] as f64)
+            .collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn test_black_out_only_touches_zone() {
+        let original = checkerboard(40, 30);
+        let mut img = original.clone();
+
+        let masked = apply_masks(
+            &mut img,
+            &[MaskRect::new(10, 5, 8, 6)],
+            MaskMode::BlackOut,
+            1.0,
+        );
+
+        assert_eq!(masked, 1);
+        for (x, y, pixel) in img.enumerate_pixels() {
+            if (10..18).contains(&x) && (5..11).contains(&y) {
+                assert_eq!(pixel.0, [0, 0, 0]);
+            } else {
+                assert_eq!(pixel, original.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_blur_smooths_zone() {
+        let original = checkerboard(64, 64);
+        let mut img = original.clone();
+
+        apply_masks(
+            &mut img,
+            &[MaskRect::new(0, 0, 32, 32)],
+            MaskMode::Blur,
+            1.0,
+        );
+
+        // Le damier devient un gris quasi uniforme dans la zone...
+        assert!(variance(&original, 4, 4, 24, 24) > 16_000.0);
+        assert!(variance(&img, 4, 4, 24, 24) < 100.0);
+        // ...et reste intact en dehors
+        assert_eq!(
+            variance(&img, 32, 32, 32, 32),
+            variance(&original, 32, 32, 32, 32)
+        );
+        assert_eq!(img.get_pixel(40, 40), original.get_pixel(40, 40));
+    }
+
+    #[test]
+    fn test_zones_are_scaled_and_clipped() {
+        // Écran de 1440px de large, capture réduite à 720px : zone "toute la largeur" de 50px de haut
+        let mut img = checkerboard(720, 400);
+        let masked = apply_masks(
+            &mut img,
+            &[
+                MaskRect::new(0, 0, 9999, 50),
+                MaskRect::new(5000, 5000, 10, 10),
+            ],
+            MaskMode::BlackOut,
+            0.5,
+        );
+
+        assert_eq!(masked, 1);
+        assert_eq!(img.get_pixel(719, 24).0, [0, 0, 0]);
+        assert_eq!(img.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_ne!(img.get_pixel(1, 25).0, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_relative_to_window() {
+        let window = MaskRect::new(100, 50, 400, 300);
+        assert_eq!(
+            MaskRect::new(80, 60, 40, 10).relative_to(&window),
+            Some(MaskRect::new(0, 10, 20, 10))
+        );
+        assert_eq!(MaskRect::new(0, 0, 50, 50).relative_to(&window), None);
+    }
+}
@@ -1,4 +1,13 @@
 pub mod zones;
 pub mod commands;
+pub mod redaction;
+pub mod egress;
+pub mod masking;
+pub mod erasure;
+pub mod audit;
 
 pub use zones::{PrivacyZone, PrivacyZoneManager, PrivacyZonesConfig};
+pub use redaction::{RedactionStats, Redactor};
+pub use egress::{EgressError, EgressPolicy, RequestOrigin};
+pub use erasure::{AppMatcher, ErasureReport, ErasureTarget};
+pub use audit::{AuditEvent, AuditLog};
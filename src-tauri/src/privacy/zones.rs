@@ -1,18 +1,31 @@
+use super::masking::{FramePlan, MaskMode, MaskRect};
+use crate::context::app_detector::{ActiveApp, WindowBounds};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tracing::{debug, info};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Version courante du format de `PrivacyZonesConfig` (fichier `privacy_zones.json`)
+pub const PRIVACY_ZONES_SCHEMA_VERSION: u32 = 2;
+
+/// Titres des fenêtres de navigation privée (Chrome, Firefox, Edge, Safari, Brave, Tor Browser)
+const PRIVATE_BROWSING_PATTERN: &str = r"(?i)\b(incognito|inprivate|private browsing|private window|navigation privée|privates fenster)\b|tor browser";
 
 /// Type de zone de confidentialité
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum PrivacyZone {
-    /// Zone rectangulaire définie par coordonnées
+    /// Zone rectangulaire définie par coordonnées (relatives à l'écran capturé)
     Rectangle {
         x: u32,
         y: u32,
         width: u32,
         height: u32,
         label: String,
+        /// Écran concerné ; tous si absent
+        #[serde(default)]
+        display: Option<u32>,
     },
     /// Fenêtre d'application spécifique (nom de l'app)
     Window {
@@ -22,21 +35,127 @@ pub enum PrivacyZone {
     /// Région prédéfinie (barre de tâches, menu système, etc.)
     Region {
         region: PredefinedRegion,
+        /// Écran concerné ; tous si absent
+        #[serde(default)]
+        display: Option<u32>,
+    },
+    /// Partie d'une fenêtre, en pourcentage de ses dimensions : suit la fenêtre quand elle
+    /// bouge ou change de taille (ex: barre d'adresse du navigateur)
+    WindowArea {
+        /// Nom d'app (sous-chaîne, insensible à la casse)
+        app_name: String,
+        /// Restreint aux fenêtres dont le titre correspond (regex)
+        #[serde(default)]
+        title_pattern: Option<String>,
+        area: RelativeArea,
+        label: String,
     },
+    /// Fenêtres dont le titre correspond à l'expression régulière (onglet de banque, coffre...)
+    WindowTitle { pattern: String, label: String },
+    /// Fenêtres de navigation privée (Incognito, InPrivate, Private Browsing...)
+    PrivateBrowsing,
+}
+
+/// Rectangle en pourcentage (0-100) de la largeur / hauteur d'une fenêtre
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct RelativeArea {
+    pub x: u8,
+    pub y: u8,
+    pub width: u8,
+    pub height: u8,
+}
+
+/// Écran capturé, en coordonnées globales du bureau
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayGeometry {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DisplayGeometry {
+    /// Partie de la fenêtre `bounds` (coordonnées bureau) visible sur cet écran, en
+    /// coordonnées écran ; None si la fenêtre n'y apparaît pas
+    pub fn visible_part(&self, bounds: &WindowBounds) -> Option<MaskRect> {
+        let left = bounds.x.max(self.x);
+        let top = bounds.y.max(self.y);
+        let right = (bounds.x + bounds.width as i32).min(self.x + self.width as i32);
+        let bottom = (bounds.y + bounds.height as i32).min(self.y + self.height as i32);
+        if left >= right || top >= bottom {
+            return None;
+        }
+        Some(MaskRect::new(
+            (left - self.x) as u32,
+            (top - self.y) as u32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        ))
+    }
+
+    /// Écran 1920x1080 placé à `x` sur le bureau, pour les tests
+    #[cfg(test)]
+    pub(crate) fn full_hd(id: u32, x: i32) -> Self {
+        Self {
+            id,
+            x,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    }
+}
+
+/// Ce qui est capturé : l'écran et la fenêtre au premier plan
+#[derive(Debug, Clone, Copy)]
+pub struct CaptureTarget<'a> {
+    pub display: DisplayGeometry,
+    pub active_app: Option<&'a ActiveApp>,
+}
+
+impl PrivacyZone {
+    /// Vérifie les expressions régulières et les pourcentages
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            PrivacyZone::WindowArea {
+                title_pattern,
+                area,
+                ..
+            } => {
+                if let Some(pattern) = title_pattern {
+                    Regex::new(pattern).map_err(|e| format!("Invalid title pattern: {}", e))?;
+                }
+                if area.x as u32 + area.width as u32 > 100
+                    || area.y as u32 + area.height as u32 > 100
+                {
+                    return Err("Window area must stay within 0-100%".to_string());
+                }
+                Ok(())
+            }
+            PrivacyZone::WindowTitle { pattern, .. } => Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid title pattern: {}", e)),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "kebab-case")]
 pub enum PredefinedRegion {
-    TopBar,       // Barre supérieure (menu, URL bar)
-    Taskbar,      // Barre des tâches
-    SystemTray,   // Zone de notification système
-    Dock,         // Dock macOS / Linux
+    TopBar,     // Barre supérieure (menu, URL bar)
+    Taskbar,    // Barre des tâches
+    SystemTray, // Zone de notification système
+    Dock,       // Dock macOS / Linux
 }
 
 /// Configuration des zones de confidentialité
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrivacyZonesConfig {
+    /// Version du format (absente avant la v2)
+    #[serde(default)]
+    pub schema_version: u32,
     pub enabled: bool,
     pub zones: Vec<PrivacyZone>,
     pub blur_instead_of_skip: bool, // Si true, flouter au lieu de skip
@@ -45,6 +164,7 @@ pub struct PrivacyZonesConfig {
 impl Default for PrivacyZonesConfig {
     fn default() -> Self {
         Self {
+            schema_version: PRIVACY_ZONES_SCHEMA_VERSION,
             enabled: true,
             zones: vec![
                 // Par défaut: protéger la barre URL/menu (top 50px)
@@ -54,6 +174,7 @@ impl Default for PrivacyZonesConfig {
                     width: 9999, // Full width
                     height: 50,
                     label: "Top Menu Bar".to_string(),
+                    display: None,
                 },
             ],
             blur_instead_of_skip: false,
@@ -61,17 +182,125 @@ impl Default for PrivacyZonesConfig {
     }
 }
 
+impl PrivacyZonesConfig {
+    /// Charge la configuration depuis `path` (défaut si absent) et la migre au format courant ;
+    /// un fichier migré est réécrit
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read privacy zones: {}", e))?;
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse privacy zones: {}", e))?;
+
+        let (config, migrated) = Self::migrate(value)?;
+        if migrated {
+            info!(
+                "🔄 Privacy zones migrated to schema v{}",
+                PRIVACY_ZONES_SCHEMA_VERSION
+            );
+            config.save(path)?;
+        }
+        Ok(config)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize privacy zones: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to write privacy zones: {}", e))
+    }
+
+    /// Met un document JSON d'une version antérieure au format courant.
+    /// v1 (sans `schema_version`) : rectangles et régions sans écran, valables partout.
+    fn migrate(mut value: serde_json::Value) -> Result<(Self, bool), String> {
+        let version = value
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1) as u32;
+        if version > PRIVACY_ZONES_SCHEMA_VERSION {
+            return Err(format!(
+                "Privacy zones schema v{} is newer than supported v{}",
+                version, PRIVACY_ZONES_SCHEMA_VERSION
+            ));
+        }
+
+        if version < 2 {
+            if let Some(zones) = value.get_mut("zones").and_then(|z| z.as_array_mut()) {
+                for zone in zones.iter_mut().filter_map(|z| z.as_object_mut()) {
+                    let kind = zone
+                        .get("type")
+                        .and_then(|t| t.as_str())
+                        .unwrap_or_default();
+                    if kind == "rectangle" || kind == "region" {
+                        zone.entry("display").or_insert(serde_json::Value::Null);
+                    }
+                }
+            }
+        }
+
+        let migrated = version < PRIVACY_ZONES_SCHEMA_VERSION;
+        if let Some(object) = value.as_object_mut() {
+            object.insert(
+                "schema_version".to_string(),
+                PRIVACY_ZONES_SCHEMA_VERSION.into(),
+            );
+        }
+        let config =
+            serde_json::from_value(value).map_err(|e| format!("Invalid privacy zones: {}", e))?;
+        Ok((config, migrated))
+    }
+}
+
 pub struct PrivacyZoneManager {
     config: PrivacyZonesConfig,
     // Cache des fenêtres bloquées (app_name -> blocked)
     blocked_apps: HashMap<String, bool>,
+    /// Regex des zones par titre, compilées une fois
+    title_patterns: HashMap<String, Regex>,
+    /// Fichier où la configuration est enregistrée à chaque modification
+    storage: Option<PathBuf>,
 }
 
 impl PrivacyZoneManager {
     pub fn new(config: PrivacyZonesConfig) -> Self {
-        Self {
+        let mut manager = Self {
             config,
             blocked_apps: HashMap::new(),
+            title_patterns: HashMap::new(),
+            storage: None,
+        };
+        manager.compile_patterns();
+        manager
+    }
+
+    /// Charge la configuration enregistrée dans `path` (migrée si besoin) et l'y garde à jour.
+    /// Un fichier illisible laisse la configuration par défaut ; il est mis de côté
+    /// (`.invalid`) avant d'être remplacé, et n'est jamais écrasé s'il ne peut pas l'être.
+    pub fn load(path: PathBuf) -> Self {
+        match PrivacyZonesConfig::load(&path) {
+            Ok(config) => {
+                let mut manager = Self::new(config);
+                manager.storage = Some(path);
+                manager
+            }
+            Err(e) => {
+                warn!("⚠️ {} — using default privacy zones", e);
+                let mut manager = Self::new(PrivacyZonesConfig::default());
+                let mut aside = path.clone().into_os_string();
+                aside.push(".invalid");
+                match std::fs::rename(&path, &aside) {
+                    Ok(()) => {
+                        warn!("⚠️ Unreadable privacy zones moved to {:?}", aside);
+                        manager.storage = Some(path);
+                    }
+                    Err(e) => warn!(
+                        "⚠️ Failed to move privacy zones aside ({}) — changes will not be saved",
+                        e
+                    ),
+                }
+                manager
+            }
         }
     }
 
@@ -96,7 +325,7 @@ impl PrivacyZoneManager {
                         return true;
                     }
                 }
-                PrivacyZone::Region { region } => {
+                PrivacyZone::Region { region, .. } => {
                     // Check predefined regions
                     if Self::is_in_predefined_region(x, y, width, height, region) {
                         debug!("🔒 Zone protected by region: {:?}", region);
@@ -128,7 +357,9 @@ impl PrivacyZoneManager {
             } = zone
             {
                 let matches = if *fuzzy_match {
-                    app_name.to_lowercase().contains(&blocked_app.to_lowercase())
+                    app_name
+                        .to_lowercase()
+                        .contains(&blocked_app.to_lowercase())
                 } else {
                     app_name.eq_ignore_ascii_case(blocked_app)
                 };
@@ -143,12 +374,208 @@ impl PrivacyZoneManager {
         false
     }
 
+    /// Vérifie si une fenêtre doit être protégée : app protégée, titre correspondant à une
+    /// zone `WindowTitle`, ou navigation privée si `PrivateBrowsing` est configurée
+    pub fn is_window_protected(&self, app_name: &str, window_title: &str) -> bool {
+        if self.is_app_protected(app_name) {
+            return true;
+        }
+        if !self.config.enabled {
+            return false;
+        }
+
+        let protected = self.config.zones.iter().any(|zone| match zone {
+            PrivacyZone::WindowTitle { pattern, .. } => self.title_matches(pattern, window_title),
+            PrivacyZone::PrivateBrowsing => {
+                self.title_matches(PRIVATE_BROWSING_PATTERN, window_title)
+            }
+            _ => false,
+        });
+        if protected {
+            info!("🔒 Window protected: {} — {}", app_name, window_title);
+        }
+        protected
+    }
+
+    /// Masquage à appliquer à une capture de l'écran `target.display` (coordonnées écran)
+    /// prise pendant que `target.active_app` est au premier plan. Une fenêtre protégée
+    /// visible sur cet écran fait abandonner la capture, ou est floutée si
+    /// `blur_instead_of_skip` (tout l'écran si sa géométrie est inconnue) ; les autres zones
+    /// sont noircies, ou floutées avec `blur_instead_of_skip`.
+    pub fn frame_plan(&self, target: &CaptureTarget) -> FramePlan {
+        let mode = if self.config.blur_instead_of_skip {
+            MaskMode::Blur
+        } else {
+            MaskMode::BlackOut
+        };
+        if !self.config.enabled {
+            return FramePlan::Mask {
+                rects: Vec::new(),
+                mode,
+            };
+        }
+
+        let display = &target.display;
+        let mut rects = Vec::new();
+
+        if let Some(app) = target.active_app {
+            if self.is_window_protected(&app.name, &app.window_title) {
+                // Une fenêtre affichée sur un autre écran ne concerne pas cette capture
+                if let Some(window) = Self::window_rect(display, app.bounds) {
+                    if !self.config.blur_instead_of_skip {
+                        return FramePlan::Skip {
+                            app_name: app.name.clone(),
+                        };
+                    }
+                    rects.push(window);
+                }
+            }
+        }
+
+        let on_display =
+            |zone_display: &Option<u32>| zone_display.is_none_or(|id| id == display.id);
+        for zone in &self.config.zones {
+            let rect = match zone {
+                PrivacyZone::Rectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                    display: zone_display,
+                    ..
+                } if on_display(zone_display) => Some(MaskRect::new(*x, *y, *width, *height)),
+                PrivacyZone::Region {
+                    region,
+                    display: zone_display,
+                } if on_display(zone_display) => {
+                    Self::region_rect(region, display.width, display.height)
+                }
+                PrivacyZone::WindowArea {
+                    app_name,
+                    title_pattern,
+                    area,
+                    ..
+                } => target
+                    .active_app
+                    .filter(|app| {
+                        app.name.to_lowercase().contains(&app_name.to_lowercase())
+                            && title_pattern.as_deref().is_none_or(|pattern| {
+                                self.title_matches(pattern, &app.window_title)
+                            })
+                    })
+                    .and_then(|app| Self::window_area_rect(display, app.bounds, area)),
+                _ => None,
+            };
+            rects.extend(rect);
+        }
+
+        FramePlan::Mask { rects, mode }
+    }
+
+    /// Fenêtre `bounds` (coordonnées bureau) ramenée à l'écran `display` ; l'écran entier si
+    /// la géométrie est inconnue, None si la fenêtre n'y apparaît pas
+    fn window_rect(display: &DisplayGeometry, bounds: Option<WindowBounds>) -> Option<MaskRect> {
+        match bounds {
+            Some(bounds) => display.visible_part(&bounds),
+            None => Some(MaskRect::new(0, 0, display.width, display.height)),
+        }
+    }
+
+    /// Partie `area` de la fenêtre `bounds`, ramenée à l'écran `display`
+    fn window_area_rect(
+        display: &DisplayGeometry,
+        bounds: Option<WindowBounds>,
+        area: &RelativeArea,
+    ) -> Option<MaskRect> {
+        let window = bounds.unwrap_or(WindowBounds {
+            x: display.x,
+            y: display.y,
+            width: display.width,
+            height: display.height,
+        });
+        let percent = |length: u32, value: u8| (length as u64 * value as u64 / 100) as u32;
+        let zone = WindowBounds {
+            x: window.x + percent(window.width, area.x) as i32,
+            y: window.y + percent(window.height, area.y) as i32,
+            // Arrondi vers le haut pour ne rien laisser dépasser
+            width: (window.width as u64 * area.width as u64).div_ceil(100) as u32,
+            height: (window.height as u64 * area.height as u64).div_ceil(100) as u32,
+        };
+        Self::window_rect(display, Some(zone))
+    }
+
+    fn title_matches(&self, pattern: &str, window_title: &str) -> bool {
+        self.title_patterns
+            .get(pattern)
+            .is_some_and(|regex| regex.is_match(window_title))
+    }
+
+    /// Compile les regex de titre de la configuration ; une regex invalide est ignorée
+    fn compile_patterns(&mut self) {
+        self.title_patterns.clear();
+        for zone in &self.config.zones {
+            let pattern = match zone {
+                PrivacyZone::WindowTitle { pattern, .. } => pattern.as_str(),
+                PrivacyZone::WindowArea {
+                    title_pattern: Some(pattern),
+                    ..
+                } => pattern.as_str(),
+                PrivacyZone::PrivateBrowsing => PRIVATE_BROWSING_PATTERN,
+                _ => continue,
+            };
+            if self.title_patterns.contains_key(pattern) {
+                continue;
+            }
+            match Regex::new(pattern) {
+                Ok(regex) => {
+                    self.title_patterns.insert(pattern.to_string(), regex);
+                }
+                Err(e) => warn!(
+                    "⚠️ Ignoring invalid privacy title pattern '{}': {}",
+                    pattern, e
+                ),
+            }
+        }
+    }
+
+    /// Rectangle couvert par une région prédéfinie sur un écran de `width` x `height`
+    #[cfg_attr(target_os = "linux", allow(unused_variables))]
+    fn region_rect(region: &PredefinedRegion, width: u32, height: u32) -> Option<MaskRect> {
+        match region {
+            PredefinedRegion::TopBar => Some(MaskRect::new(0, 0, width, 80)),
+            PredefinedRegion::Taskbar => {
+                #[cfg(target_os = "windows")]
+                {
+                    Some(MaskRect::new(0, height.saturating_sub(48), width, 48))
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    None
+                }
+            }
+            PredefinedRegion::SystemTray => {
+                Some(MaskRect::new(width.saturating_sub(200), 0, 200, 80))
+            }
+            PredefinedRegion::Dock => {
+                #[cfg(target_os = "macos")]
+                {
+                    Some(MaskRect::new(0, height.saturating_sub(80), width, 80))
+                }
+                #[cfg(not(target_os = "macos"))]
+                {
+                    None
+                }
+            }
+        }
+    }
+
     /// Ajoute une nouvelle zone de confidentialité
     pub fn add_zone(&mut self, zone: PrivacyZone) {
         if !self.config.zones.contains(&zone) {
             self.config.zones.push(zone.clone());
             info!("✅ Added privacy zone: {:?}", zone);
             self.clear_cache();
+            self.persist();
         }
     }
 
@@ -161,6 +588,7 @@ impl PrivacyZoneManager {
         if removed {
             info!("✅ Removed privacy zone: {:?}", zone);
             self.clear_cache();
+            self.persist();
         }
 
         removed
@@ -169,8 +597,12 @@ impl PrivacyZoneManager {
     /// Active/désactive les zones de confidentialité
     pub fn set_enabled(&mut self, enabled: bool) {
         self.config.enabled = enabled;
-        info!("🔒 Privacy zones {}", if enabled { "enabled" } else { "disabled" });
+        info!(
+            "🔒 Privacy zones {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
         self.clear_cache();
+        self.persist();
     }
 
     /// Obtenir la configuration actuelle
@@ -181,6 +613,16 @@ impl PrivacyZoneManager {
     /// Clear cache (après modification de config)
     fn clear_cache(&mut self) {
         self.blocked_apps.clear();
+        self.compile_patterns();
+    }
+
+    /// Enregistre la configuration si le gestionnaire a été chargé depuis un fichier
+    fn persist(&self) {
+        if let Some(path) = &self.storage {
+            if let Err(e) = self.config.save(path) {
+                warn!("⚠️ {}", e);
+            }
+        }
     }
 
     /// Vérifie si deux rectangles se chevauchent
@@ -303,6 +745,226 @@ mod tests {
         assert!(manager.is_app_protected("KeePass Password Safe"));
     }
 
+    fn window(name: &str, title: &str, bounds: Option<WindowBounds>) -> ActiveApp {
+        ActiveApp {
+            bundle_id: name.to_lowercase(),
+            name: name.to_string(),
+            window_title: title.to_string(),
+            pid: 1,
+            timestamp: 0,
+            tcc_status: None,
+            bounds,
+        }
+    }
+
+    #[test]
+    fn test_frame_plan() {
+        let mut config = PrivacyZonesConfig::default();
+        config.zones.push(PrivacyZone::Region {
+            region: PredefinedRegion::SystemTray,
+            display: None,
+        });
+        config.zones.push(PrivacyZone::Window {
+            app_name: "KeePassXC".to_string(),
+            fuzzy_match: true,
+        });
+        let mut manager = PrivacyZoneManager::new(config);
+        let code = window("Code", "main.rs", None);
+        let keepass = window("KeePassXC", "Passwords.kdbx", None);
+        let target = |app| CaptureTarget {
+            display: DisplayGeometry::full_hd(0, 0),
+            active_app: Some(app),
+        };
+
+        assert_eq!(
+            manager.frame_plan(&target(&code)),
+            FramePlan::Mask {
+                rects: vec![
+                    MaskRect::new(0, 0, 9999, 50),
+                    MaskRect::new(1720, 0, 200, 80),
+                ],
+                mode: MaskMode::BlackOut,
+            }
+        );
+        assert_eq!(
+            manager.frame_plan(&target(&keepass)),
+            FramePlan::Skip {
+                app_name: "KeePassXC".to_string()
+            }
+        );
+
+        manager.config.blur_instead_of_skip = true;
+        assert_eq!(
+            manager.frame_plan(&target(&keepass)),
+            FramePlan::Mask {
+                rects: vec![
+                    MaskRect::new(0, 0, 1920, 1080),
+                    MaskRect::new(0, 0, 9999, 50),
+                    MaskRect::new(1720, 0, 200, 80),
+                ],
+                mode: MaskMode::Blur,
+            }
+        );
+
+        manager.set_enabled(false);
+        assert_eq!(
+            manager.frame_plan(&target(&keepass)),
+            FramePlan::Mask {
+                rects: vec![],
+                mode: MaskMode::Blur,
+            }
+        );
+    }
+
+    #[test]
+    fn test_window_zones_follow_window_and_display() {
+        let config = PrivacyZonesConfig {
+            zones: vec![
+                PrivacyZone::Rectangle {
+                    x: 0,
+                    y: 0,
+                    width: 100,
+                    height: 100,
+                    label: "Second screen corner".to_string(),
+                    display: Some(2),
+                },
+                PrivacyZone::WindowArea {
+                    app_name: "firefox".to_string(),
+                    title_pattern: None,
+                    area: RelativeArea {
+                        x: 0,
+                        y: 0,
+                        width: 100,
+                        height: 10,
+                    },
+                    label: "Address bar".to_string(),
+                },
+                PrivacyZone::WindowTitle {
+                    pattern: r"(?i)\bbank\b".to_string(),
+                    label: "Bank tabs".to_string(),
+                },
+                PrivacyZone::PrivateBrowsing,
+            ],
+            ..Default::default()
+        };
+        let manager = PrivacyZoneManager::new(config);
+        let bounds = Some(WindowBounds {
+            x: 2020,
+            y: 100,
+            width: 800,
+            height: 600,
+        });
+
+        // Fenêtre sur le second écran (x = 1920) : la barre d'adresse suit la fenêtre
+        let firefox = window("Firefox", "Rust docs — Mozilla Firefox", bounds);
+        assert_eq!(
+            manager.frame_plan(&CaptureTarget {
+                display: DisplayGeometry::full_hd(2, 1920),
+                active_app: Some(&firefox),
+            }),
+            FramePlan::Mask {
+                rects: vec![
+                    MaskRect::new(0, 0, 100, 100),
+                    MaskRect::new(100, 100, 800, 60)
+                ],
+                mode: MaskMode::BlackOut,
+            }
+        );
+        // ...et n'apparaît pas sur le premier, pas plus que la zone du second écran
+        assert_eq!(
+            manager.frame_plan(&CaptureTarget {
+                display: DisplayGeometry::full_hd(1, 0),
+                active_app: Some(&firefox),
+            }),
+            FramePlan::Mask {
+                rects: vec![],
+                mode: MaskMode::BlackOut,
+            }
+        );
+
+        let bank = window("Firefox", "My Bank — Accounts", bounds);
+        let private = window("Chromium", "New Tab - Chromium (Incognito)", bounds);
+        assert!(manager.is_window_protected(&bank.name, &bank.window_title));
+        assert!(manager.is_window_protected(&private.name, &private.window_title));
+        assert!(!manager.is_window_protected("Firefox", "Banking regulations — Wikipedia"));
+        assert!(matches!(
+            manager.frame_plan(&CaptureTarget {
+                display: DisplayGeometry::full_hd(2, 1920),
+                active_app: Some(&private),
+            }),
+            FramePlan::Skip { .. }
+        ));
+        // Fenêtre protégée sur un autre écran : la capture de celui-ci continue
+        assert!(matches!(
+            manager.frame_plan(&CaptureTarget {
+                display: DisplayGeometry::full_hd(1, 0),
+                active_app: Some(&bank),
+            }),
+            FramePlan::Mask { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_migrates_v1_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("privacy_zones.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "enabled": true,
+                "zones": [
+                    {"type": "rectangle", "x": 0, "y": 0, "width": 10, "height": 10, "label": "Corner"},
+                    {"type": "region", "region": "system-tray"},
+                    {"type": "window", "app_name": "1Password", "fuzzy_match": true}
+                ],
+                "blur_instead_of_skip": false
+            }"#,
+        )
+        .unwrap();
+
+        let mut manager = PrivacyZoneManager::load(path.clone());
+        assert_eq!(
+            manager.config().schema_version,
+            PRIVACY_ZONES_SCHEMA_VERSION
+        );
+        assert_eq!(manager.config().zones.len(), 3);
+
+        // Le fichier est réécrit au format courant, puis suit les modifications
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], PRIVACY_ZONES_SCHEMA_VERSION);
+        assert!(saved["zones"][0]["display"].is_null());
+
+        manager.add_zone(PrivacyZone::PrivateBrowsing);
+        let reloaded = PrivacyZonesConfig::load(&path).unwrap();
+        assert_eq!(reloaded.zones.len(), 4);
+
+        // Un format plus récent n'est pas interprété
+        std::fs::write(
+            &path,
+            r#"{"schema_version": 99, "enabled": true, "zones": []}"#,
+        )
+        .unwrap();
+        assert!(PrivacyZonesConfig::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_load_keeps_unreadable_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("privacy_zones.json");
+        let original = r#"{"schema_version": 99, "enabled": true, "zones": []}"#;
+        std::fs::write(&path, original).unwrap();
+
+        let mut manager = PrivacyZoneManager::load(path.clone());
+        assert_eq!(manager.config().zones, PrivacyZonesConfig::default().zones);
+
+        // Le fichier d'origine est mis de côté avant que la configuration ne soit réenregistrée
+        manager.add_zone(PrivacyZone::PrivateBrowsing);
+        let aside = dir.path().join("privacy_zones.json.invalid");
+        assert_eq!(std::fs::read_to_string(&aside).unwrap(), original);
+        assert!(PrivacyZonesConfig::load(&path).is_ok());
+    }
+
     #[test]
     fn test_add_remove_zone() {
         let mut manager = PrivacyZoneManager::default();
@@ -313,6 +975,7 @@ mod tests {
             width: 200,
             height: 200,
             label: "Test Zone".to_string(),
+            display: None,
         };
 
         // Add zone
@@ -10,6 +10,8 @@ use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
 
+use crate::privacy::erasure::AppMatcher;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductivityMetrics {
     pub today: DayMetrics,
@@ -216,6 +218,46 @@ impl ProductivityManager {
         }
     }
 
+    /// Forget an app: its per-day usage and its flow sessions
+    pub fn forget_app(&mut self, matcher: &AppMatcher) -> usize {
+        let mut removed = 0;
+
+        for day_data in self.daily_data.values_mut() {
+            day_data.app_usage.retain(|app_name, app_data| {
+                if !matcher.matches_app(app_name) {
+                    return true;
+                }
+                day_data.suggestions_shown =
+                    day_data.suggestions_shown.saturating_sub(app_data.count);
+                day_data.suggestions_accepted =
+                    day_data.suggestions_accepted.saturating_sub(app_data.accepted);
+                removed += 1;
+                false
+            });
+        }
+
+        let (forgotten, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.flow_sessions)
+            .into_iter()
+            .partition(|session| matcher.matches_app(&session.app_name));
+        self.flow_sessions = kept;
+
+        for session in &forgotten {
+            let Some(end) = DateTime::<Utc>::from_timestamp(session.end_time, 0) else {
+                continue;
+            };
+            if let Some(day_data) = self.daily_data.get_mut(&Self::get_date_key(&end)) {
+                day_data.flow_time_minutes = day_data
+                    .flow_time_minutes
+                    .saturating_sub(session.duration_minutes);
+            }
+            if let Some(hourly) = self.hourly_data.get_mut(&end.hour()) {
+                hourly.flow_sessions = hourly.flow_sessions.saturating_sub(1);
+            }
+        }
+
+        removed + forgotten.len()
+    }
+
     pub fn record_interruption(&mut self) {
         let now = Utc::now();
         let date_key = Self::get_date_key(&now);
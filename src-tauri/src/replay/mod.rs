@@ -9,7 +9,11 @@ use std::collections::VecDeque;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::privacy::erasure::AppMatcher;
+use crate::retention::{entry_size, from_unix_seconds, CategoryReport, ResolvedPolicy};
+use crate::screenshot::store::{ScreenshotHolder, ScreenshotStore};
 
 pub mod storage;
 pub mod player;
@@ -85,6 +89,7 @@ pub struct ReplayManager {
     current_playback_index: usize,
     is_playing: bool,
     playback_speed: f32,
+    screenshots: Option<Arc<ScreenshotStore>>,
 }
 
 impl ReplayManager {
@@ -95,9 +100,16 @@ impl ReplayManager {
             current_playback_index: 0,
             is_playing: false,
             playback_speed: 1.0,
+            screenshots: None,
         }
     }
 
+    /// Keep recorded screenshots in the shared content-addressed store
+    pub fn with_screenshot_store(mut self, store: Arc<ScreenshotStore>) -> Self {
+        self.screenshots = Some(store);
+        self
+    }
+
     pub fn record_event(&mut self, event: ReplayEvent) {
         info!("📼 Recording event: {} at {}", event.description, event.timestamp);
 
@@ -173,8 +185,10 @@ impl ReplayManager {
     }
 
     pub fn record_screenshot(&mut self, app_name: &str, screenshot_path: &str, analysis: Option<String>) {
+        let id = format!("screenshot_{}", Utc::now().timestamp_millis());
+        let screenshot_path = self.store_screenshot(&id, screenshot_path);
         let event = ReplayEvent {
-            id: format!("screenshot_{}", Utc::now().timestamp_millis()),
+            id,
             timestamp: Utc::now().timestamp(),
             event_type: EventType::Screenshot {
                 analysis: analysis.clone(),
@@ -182,12 +196,37 @@ impl ReplayManager {
             app_name: app_name.to_string(),
             description: format!("📸 Screenshot captured in {}", app_name),
             metadata: serde_json::json!({ "analysis": analysis }),
-            screenshot_path: Some(screenshot_path.to_string()),
+            screenshot_path: Some(screenshot_path),
         };
 
         self.record_event(event);
     }
 
+    /// Move a captured file into the screenshot store, held by event `id`.
+    /// Returns the `screenshot:<id>` reference, or the path unchanged without a store.
+    fn store_screenshot(&self, id: &str, screenshot_path: &str) -> String {
+        let Some(store) = &self.screenshots else {
+            return screenshot_path.to_string();
+        };
+        let holder = ScreenshotHolder::Replay(id.to_string());
+
+        match store.adopt(screenshot_path, holder) {
+            Ok(reference) => reference,
+            Err(e) => {
+                warn!("⚠️ Failed to store replay screenshot: {}", e);
+                screenshot_path.to_string()
+            }
+        }
+    }
+
+    /// `(event id, screenshot_path)` for every event that has a screenshot
+    pub fn screenshot_refs(&self) -> Vec<(String, String)> {
+        self.events
+            .iter()
+            .filter_map(|event| Some((event.id.clone(), event.screenshot_path.clone()?)))
+            .collect()
+    }
+
     pub fn get_events_for_date(&self, date: &str) -> Vec<ReplayEvent> {
         self.events
             .iter()
@@ -367,6 +406,90 @@ impl ReplayManager {
         Ok(())
     }
 
+    /// Merge imported events (deduplicated by id), keeping most recent first.
+    /// Returns the number of imported events still held once trimmed to `max_events`.
+    pub fn import_events(&mut self, events: Vec<ReplayEvent>) -> usize {
+        let mut known: std::collections::HashSet<String> =
+            self.events.iter().map(|e| e.id.clone()).collect();
+        let mut added = std::collections::HashSet::new();
+
+        for event in events {
+            if known.insert(event.id.clone()) {
+                added.insert(event.id.clone());
+                self.events.push_back(event);
+            }
+        }
+
+        self.events
+            .make_contiguous()
+            .sort_by_key(|event| std::cmp::Reverse(event.timestamp));
+        while self.events.len() > self.max_events {
+            self.events.pop_back();
+        }
+
+        let kept = self
+            .events
+            .iter()
+            .filter(|event| added.contains(&event.id))
+            .count();
+        info!(
+            "📼 Imported {} replay events ({} older ones beyond the limit dropped)",
+            kept,
+            added.len() - kept
+        );
+        kept
+    }
+
+    /// Apply a retention policy to recorded events
+    pub fn apply_retention(&mut self, policy: &ResolvedPolicy, now: DateTime<Utc>) -> CategoryReport {
+        let items: Vec<(DateTime<Utc>, u64)> = self
+            .events
+            .iter()
+            .map(|e| (from_unix_seconds(e.timestamp), entry_size(e)))
+            .collect();
+        let expired = policy.select_expired(&items, now);
+
+        if !expired.is_empty() {
+            let mut index = 0;
+            self.events.retain(|_| {
+                let keep = expired.binary_search(&index).is_err();
+                index += 1;
+                keep
+            });
+            if self.current_playback_index >= self.events.len() {
+                self.current_playback_index = 0;
+                self.is_playing = false;
+            }
+            info!("🧹 Removed {} replay events (retention)", expired.len());
+        }
+
+        CategoryReport::from_selection(&items, &expired)
+    }
+
+    /// Remove every event recorded in an app forgotten by the user (app switches included).
+    /// Returns the removed events so their screenshots can be deleted.
+    pub fn forget_app(&mut self, matcher: &AppMatcher) -> Vec<ReplayEvent> {
+        let (removed, kept): (Vec<_>, Vec<_>) = self.events.drain(..).partition(|event| {
+            matcher.matches_app(&event.app_name)
+                || match &event.event_type {
+                    EventType::AppSwitch { from_app, to_app } => {
+                        matcher.matches_app(from_app) || matcher.matches_app(to_app)
+                    }
+                    _ => false,
+                }
+        });
+        self.events = kept.into();
+
+        if !removed.is_empty() {
+            if self.current_playback_index >= self.events.len() {
+                self.current_playback_index = 0;
+                self.is_playing = false;
+            }
+            info!("🗑️ Removed {} replay events (forgotten app)", removed.len());
+        }
+        removed
+    }
+
     pub fn clear_all_events(&mut self) {
         self.events.clear();
         self.current_playback_index = 0;
@@ -490,3 +613,45 @@ pub async fn record_replay_flow_session(
     manager.record_flow_session(&app_name, duration_minutes, quality_score);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interruption(id: &str, timestamp: i64) -> ReplayEvent {
+        ReplayEvent {
+            id: id.to_string(),
+            timestamp,
+            event_type: EventType::Interruption {
+                source: "test".to_string(),
+            },
+            app_name: "Code".to_string(),
+            description: String::new(),
+            metadata: serde_json::Value::Null,
+            screenshot_path: None,
+        }
+    }
+
+    #[test]
+    fn test_import_counts_only_events_kept() {
+        let mut manager = ReplayManager::new(3);
+        manager.record_event(interruption("local-1", 100));
+        manager.record_event(interruption("local-2", 200));
+
+        // One duplicate, one recent event, two older than everything held
+        let kept = manager.import_events(vec![
+            interruption("local-1", 100),
+            interruption("imported-new", 300),
+            interruption("imported-old-1", 10),
+            interruption("imported-old-2", 20),
+        ]);
+
+        assert_eq!(kept, 1);
+        let ids: Vec<String> = manager
+            .get_recent_events(10)
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec!["imported-new", "local-2", "local-1"]);
+    }
+}
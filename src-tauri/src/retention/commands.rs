@@ -0,0 +1,35 @@
+use super::{RetentionEngine, RetentionReport};
+use crate::config::types::PrivacyStats;
+use crate::config::ConfigManager;
+use crate::privacy::Redactor;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Run a retention pass immediately with the current configuration
+#[tauri::command]
+pub async fn run_retention_now(
+    engine: tauri::State<'_, Arc<RetentionEngine>>,
+    config_manager: tauri::State<'_, Arc<Mutex<ConfigManager>>>,
+) -> Result<RetentionReport, String> {
+    info!("🧹 run_retention_now command called");
+    let config = config_manager.lock().await.get_config()?;
+    Ok(engine.run(&config.retention).await)
+}
+
+/// Get the report of the last retention pass (None if none ran yet)
+#[tauri::command]
+pub async fn get_last_retention_report(
+    engine: tauri::State<'_, Arc<RetentionEngine>>,
+) -> Result<Option<RetentionReport>, String> {
+    Ok(engine.last_report().await)
+}
+
+/// Get privacy statistics computed from the data actually stored, plus redaction counts
+#[tauri::command]
+pub async fn get_privacy_stats(
+    engine: tauri::State<'_, Arc<RetentionEngine>>,
+    redactor: tauri::State<'_, Arc<Redactor>>,
+) -> Result<PrivacyStats, String> {
+    engine.privacy_stats(redactor.stats()).await
+}
@@ -0,0 +1,786 @@
+//! Moteur de rétention des données.
+//!
+//! Applique `RetentionConfig` à chaque magasin : captures d'écran sur disque, contextes
+//! capturés en base, événements de replay, historique des patterns et événements ML.
+//! Les captures du magasin adressé par contenu sont supprimées dès que plus rien n'y fait
+//! référence.
+//! Chaque passage produit un `RetentionReport` détaillant ce qui a été supprimé ou réduit.
+
+pub mod commands;
+
+use chrono::{DateTime, Duration, Utc};
+use image::codecs::jpeg::JpegEncoder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::config::types::{PrivacyStats, RetentionConfig, RetentionPolicy};
+use crate::config::ConfigManager;
+use crate::learn::LearnManager;
+use crate::ml::PersonalizationManager;
+use crate::patterns::commands::PatternManager;
+use crate::persistence::PersistenceManager;
+use crate::privacy::audit::AUDIT_DEFAULT_PERIOD;
+use crate::privacy::{AuditLog, RedactionStats};
+use crate::replay::ReplayManager;
+use crate::screenshot::store::{HolderKind, GC_GRACE_MINUTES};
+use crate::screenshot::{screenshot_dir, ScreenshotStore, SCREENSHOT_FILE_PREFIX};
+
+/// Largeur maximale d'une capture réduite
+const DOWNSAMPLED_MAX_WIDTH: u32 = 360;
+
+/// Qualité JPEG d'une capture réduite
+const DOWNSAMPLED_QUALITY: u8 = 40;
+
+/// Délai avant le premier passage automatique après le démarrage
+const STARTUP_DELAY_SECS: u64 = 30;
+
+/// Durée de conservation la plus longue acceptée : au-delà, `now - durée` sortirait des dates
+/// représentables
+const MAX_PERIOD_DAYS: i64 = 100 * 365;
+
+/// Convertit une durée de configuration ("30m", "1h", "24h", "7d", "2w", "never") en `Duration`
+pub fn parse_period(value: &str) -> Result<Option<Duration>, String> {
+    let value = value.trim().to_lowercase();
+    if value.is_empty() || value == "never" {
+        return Ok(None);
+    }
+
+    let split = value.char_indices().last().map(|(i, _)| i).unwrap_or(0);
+    let (amount, unit) = value.split_at(split);
+    let invalid = || format!("Invalid retention period: {}", value);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    if amount <= 0 {
+        return Err(invalid());
+    }
+
+    let period = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+    match period {
+        Some(period) if period <= Duration::days(MAX_PERIOD_DAYS) => Ok(Some(period)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Vérifie que chaque politique de la configuration se résout, avant de l'enregistrer
+pub fn validate(config: &RetentionConfig) -> Result<(), String> {
+    let policies = [
+        ("screenshots", &config.screenshots),
+        ("contexts", &config.contexts),
+        ("replay_events", &config.replay_events),
+        ("pattern_history", &config.pattern_history),
+        ("ml_events", &config.ml_events),
+    ];
+    for (name, policy) in policies {
+        ResolvedPolicy::resolve(policy, &config.period).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    ResolvedPolicy::resolve(&config.audit_log, AUDIT_DEFAULT_PERIOD)
+        .map_err(|e| format!("audit_log: {}", e))?;
+    Ok(())
+}
+
+/// Limites effectives d'une catégorie, une fois les durées interprétées
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedPolicy {
+    pub max_age: Option<Duration>,
+    pub max_count: Option<usize>,
+    pub max_bytes: Option<u64>,
+    pub downsample_after: Option<Duration>,
+}
+
+impl ResolvedPolicy {
+    /// Résout une politique ; `max_age` hérite de la période globale s'il est absent
+    pub fn resolve(policy: &RetentionPolicy, default_period: &str) -> Result<Self, String> {
+        let max_age = match &policy.max_age {
+            Some(age) => parse_period(age)?,
+            None => parse_period(default_period)?,
+        };
+        let downsample_after = match &policy.downsample_after {
+            Some(age) => parse_period(age)?,
+            None => None,
+        };
+
+        Ok(Self {
+            max_age,
+            max_count: policy.max_count,
+            max_bytes: policy.max_bytes,
+            downsample_after,
+        })
+    }
+
+    /// Indices des éléments à supprimer parmi `(horodatage, taille)`.
+    /// Les éléments les plus récents sont conservés en priorité : dès qu'une limite
+    /// de nombre ou de taille est atteinte, tous les éléments plus anciens sont supprimés.
+    pub fn select_expired(&self, items: &[(DateTime<Utc>, u64)], now: DateTime<Utc>) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.sort_by(|a, b| items[*b].0.cmp(&items[*a].0));
+
+        let cutoff = self.max_age.map(|age| now - age);
+        let mut kept_count = 0usize;
+        let mut kept_bytes = 0u64;
+        let mut overflow = false;
+        let mut expired = Vec::new();
+
+        for index in order {
+            let (timestamp, size) = items[index];
+
+            let too_old = cutoff.is_some_and(|cutoff| timestamp < cutoff);
+            let over_count = self.max_count.is_some_and(|max| kept_count >= max);
+            let over_bytes = self
+                .max_bytes
+                .is_some_and(|max| kept_bytes.saturating_add(size) > max);
+            overflow = overflow || over_count || over_bytes;
+
+            if too_old || overflow {
+                expired.push(index);
+            } else {
+                kept_count += 1;
+                kept_bytes += size;
+            }
+        }
+
+        expired.sort_unstable();
+        expired
+    }
+
+    /// Indique si un élément conservé doit être réduit
+    pub fn should_downsample(&self, timestamp: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.downsample_after
+            .is_some_and(|after| timestamp < now - after)
+    }
+}
+
+/// Taille approximative d'une entrée en mémoire (sérialisée en JSON)
+pub fn entry_size<T: Serialize>(value: &T) -> u64 {
+    serde_json::to_vec(value)
+        .map(|bytes| bytes.len() as u64)
+        .unwrap_or(0)
+}
+
+/// Horodatage d'une entrée stockée en secondes Unix
+pub fn from_unix_seconds(timestamp: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(timestamp, 0).unwrap_or_default()
+}
+
+/// Résultat de la rétention pour une catégorie
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CategoryReport {
+    pub deleted: usize,
+    pub freed_bytes: u64,
+    pub downsampled: usize,
+    pub remaining: usize,
+    pub remaining_bytes: u64,
+}
+
+impl CategoryReport {
+    /// Construit le rapport d'une suppression par sélection sur `(horodatage, taille)`
+    pub fn from_selection(items: &[(DateTime<Utc>, u64)], expired: &[usize]) -> Self {
+        let freed_bytes: u64 = expired.iter().map(|&i| items[i].1).sum();
+        let total_bytes: u64 = items.iter().map(|item| item.1).sum();
+
+        Self {
+            deleted: expired.len(),
+            freed_bytes,
+            downsampled: 0,
+            remaining: items.len() - expired.len(),
+            remaining_bytes: total_bytes - freed_bytes,
+        }
+    }
+
+    /// Cumule le rapport d'un autre magasin de la même catégorie
+    pub fn merge(&mut self, other: CategoryReport) {
+        self.deleted += other.deleted;
+        self.freed_bytes += other.freed_bytes;
+        self.downsampled += other.downsampled;
+        self.remaining += other.remaining;
+        self.remaining_bytes += other.remaining_bytes;
+    }
+}
+
+/// Rapport d'un passage de rétention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub screenshots: CategoryReport,
+    pub contexts: CategoryReport,
+    pub replay_events: CategoryReport,
+    pub pattern_history: CategoryReport,
+    pub ml_events: CategoryReport,
+    pub audit_log: CategoryReport,
+    pub errors: Vec<String>,
+}
+
+impl RetentionReport {
+    fn new(started_at: DateTime<Utc>) -> Self {
+        Self {
+            started_at,
+            duration_ms: 0,
+            screenshots: CategoryReport::default(),
+            contexts: CategoryReport::default(),
+            replay_events: CategoryReport::default(),
+            pattern_history: CategoryReport::default(),
+            ml_events: CategoryReport::default(),
+            audit_log: CategoryReport::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn total_deleted(&self) -> usize {
+        self.screenshots.deleted
+            + self.contexts.deleted
+            + self.replay_events.deleted
+            + self.pattern_history.deleted
+            + self.ml_events.deleted
+            + self.audit_log.deleted
+    }
+
+    pub fn total_freed_bytes(&self) -> u64 {
+        self.screenshots.freed_bytes
+            + self.contexts.freed_bytes
+            + self.replay_events.freed_bytes
+            + self.pattern_history.freed_bytes
+            + self.ml_events.freed_bytes
+            + self.audit_log.freed_bytes
+    }
+}
+
+/// Fichier de capture présent sur disque
+#[derive(Debug, Clone)]
+pub struct ScreenshotFile {
+    pub path: PathBuf,
+    pub modified: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+/// Liste les captures écrites par ShadowLearn dans `dir`
+pub fn list_screenshots(dir: &Path) -> Vec<ScreenshotFile> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(SCREENSHOT_FILE_PREFIX) && name.ends_with(".jpg")
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            Some(ScreenshotFile {
+                path: entry.path(),
+                modified: metadata.modified().ok()?.into(),
+                size_bytes: metadata.len(),
+            })
+        })
+        .collect()
+}
+
+/// Applique la politique aux captures de `dir` : suppression, puis réduction des plus anciennes
+pub fn enforce_screenshots(
+    dir: &Path,
+    policy: &ResolvedPolicy,
+    now: DateTime<Utc>,
+) -> (CategoryReport, Vec<String>) {
+    let files = list_screenshots(dir);
+    let items: Vec<(DateTime<Utc>, u64)> =
+        files.iter().map(|f| (f.modified, f.size_bytes)).collect();
+    let expired = policy.select_expired(&items, now);
+
+    let mut report = CategoryReport::default();
+    let mut errors = Vec::new();
+
+    for (index, file) in files.iter().enumerate() {
+        if expired.binary_search(&index).is_ok() {
+            match std::fs::remove_file(&file.path) {
+                Ok(()) => {
+                    report.deleted += 1;
+                    report.freed_bytes += file.size_bytes;
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to delete {}: {}", file.path.display(), e));
+                    report.remaining += 1;
+                    report.remaining_bytes += file.size_bytes;
+                }
+            }
+            continue;
+        }
+
+        let mut size = file.size_bytes;
+        if policy.should_downsample(file.modified, now) {
+            match downsample_screenshot(&file.path, file.modified) {
+                Ok(Some(new_size)) => {
+                    report.downsampled += 1;
+                    report.freed_bytes += size.saturating_sub(new_size);
+                    size = new_size;
+                }
+                Ok(None) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+        report.remaining += 1;
+        report.remaining_bytes += size;
+    }
+
+    (report, errors)
+}
+
+/// Réduit une capture en place en conservant sa date de modification.
+/// Retourne la nouvelle taille, ou `None` si la capture est déjà réduite.
+fn downsample_screenshot(path: &Path, modified: DateTime<Utc>) -> Result<Option<u64>, String> {
+    let (width, height) = image::image_dimensions(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if width <= DOWNSAMPLED_MAX_WIDTH {
+        return Ok(None);
+    }
+
+    let img = image::open(path)
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?
+        .to_rgb8();
+    let new_height = ((height as f32 * DOWNSAMPLED_MAX_WIDTH as f32 / width as f32) as u32).max(1);
+    let resized = image::imageops::resize(
+        &img,
+        DOWNSAMPLED_MAX_WIDTH,
+        new_height,
+        image::imageops::FilterType::Triangle,
+    );
+
+    let mut buffer = Cursor::new(Vec::new());
+    resized
+        .write_with_encoder(JpegEncoder::new_with_quality(
+            &mut buffer,
+            DOWNSAMPLED_QUALITY,
+        ))
+        .map_err(|e| format!("Failed to encode {}: {}", path.display(), e))?;
+    let bytes = buffer.into_inner();
+
+    std::fs::write(path, &bytes)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    // Garder la date d'origine : l'âge de la capture ne doit pas repartir de zéro
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(modified.into()))
+        .map_err(|e| format!("Failed to restore mtime of {}: {}", path.display(), e))?;
+
+    Ok(Some(bytes.len() as u64))
+}
+
+/// Applique la configuration de rétention à tous les magasins de données
+pub struct RetentionEngine {
+    persistence: Arc<Mutex<PersistenceManager>>,
+    replay: Arc<Mutex<ReplayManager>>,
+    learn: Arc<Mutex<LearnManager>>,
+    patterns: Arc<PatternManager>,
+    personalization: Arc<Mutex<PersonalizationManager>>,
+    audit: Arc<AuditLog>,
+    screenshot_dir: PathBuf,
+    screenshot_store: Arc<ScreenshotStore>,
+    last_report: Mutex<Option<RetentionReport>>,
+}
+
+impl RetentionEngine {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        persistence: Arc<Mutex<PersistenceManager>>,
+        replay: Arc<Mutex<ReplayManager>>,
+        learn: Arc<Mutex<LearnManager>>,
+        patterns: Arc<PatternManager>,
+        personalization: Arc<Mutex<PersonalizationManager>>,
+        audit: Arc<AuditLog>,
+        screenshot_store: Arc<ScreenshotStore>,
+    ) -> Self {
+        Self {
+            persistence,
+            replay,
+            learn,
+            patterns,
+            personalization,
+            audit,
+            screenshot_dir: screenshot_dir(),
+            screenshot_store,
+            last_report: Mutex::new(None),
+        }
+    }
+
+    /// Recompte les références au magasin de captures (replay, workflows, contextes)
+    async fn sync_screenshot_holders(&self) -> Result<(), String> {
+        let replay = self.replay.lock().await.screenshot_refs();
+        let workflows = self.learn.lock().await.screenshot_refs();
+        // Sans la liste des contextes, leurs captures seraient prises pour orphelines
+        let contexts = self
+            .persistence
+            .lock()
+            .await
+            .context_screenshot_refs()
+            .await?;
+
+        let store = self.screenshot_store.clone();
+        tokio::task::spawn_blocking(move || {
+            store.sync_holders(HolderKind::Replay, &replay);
+            store.sync_holders(HolderKind::Workflow, &workflows);
+            store.sync_holders(HolderKind::Context, &contexts);
+        })
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    /// Recompte les références au magasin de captures puis supprime les captures sans
+    /// détenteur rangées avant `now - grace`
+    pub async fn collect_stored_screenshots(
+        &self,
+        grace: Duration,
+        now: DateTime<Utc>,
+    ) -> (CategoryReport, Vec<String>) {
+        if let Err(e) = self.sync_screenshot_holders().await {
+            return (
+                CategoryReport::default(),
+                vec![format!("screenshot store: {}", e)],
+            );
+        }
+        let store = self.screenshot_store.clone();
+        match tokio::task::spawn_blocking(move || store.collect_garbage(grace, now)).await {
+            Ok(collected) => collected,
+            Err(e) => (
+                CategoryReport::default(),
+                vec![format!("screenshot store: {}", e)],
+            ),
+        }
+    }
+
+    /// Captures du magasin détenues à cet instant, références recomptées
+    pub async fn held_screenshots(&self) -> Result<BTreeSet<String>, String> {
+        self.sync_screenshot_holders()
+            .await
+            .map_err(|e| format!("screenshot store: {}", e))?;
+        Ok(self.screenshot_store.held_ids())
+    }
+
+    /// Recompte les références puis supprime, sans délai de grâce, les captures de
+    /// `held_before` que plus rien ne détient
+    pub async fn collect_released_screenshots(
+        &self,
+        held_before: BTreeSet<String>,
+    ) -> (CategoryReport, Vec<String>) {
+        if let Err(e) = self.sync_screenshot_holders().await {
+            return (
+                CategoryReport::default(),
+                vec![format!("screenshot store: {}", e)],
+            );
+        }
+        let store = self.screenshot_store.clone();
+        match tokio::task::spawn_blocking(move || store.collect_released(&held_before)).await {
+            Ok(collected) => collected,
+            Err(e) => (
+                CategoryReport::default(),
+                vec![format!("screenshot store: {}", e)],
+            ),
+        }
+    }
+
+    /// Exécute un passage complet ; les erreurs d'une catégorie n'interrompent pas les autres
+    pub async fn run(&self, config: &RetentionConfig) -> RetentionReport {
+        let started = Instant::now();
+        let now = Utc::now();
+        let mut report = RetentionReport::new(now);
+
+        let resolve = |name: &str, policy: &RetentionPolicy, errors: &mut Vec<String>| {
+            ResolvedPolicy::resolve(policy, &config.period)
+                .map_err(|e| errors.push(format!("{}: {}", name, e)))
+                .ok()
+        };
+
+        if let Some(policy) = resolve("screenshots", &config.screenshots, &mut report.errors) {
+            let dir = self.screenshot_dir.clone();
+            match tokio::task::spawn_blocking(move || enforce_screenshots(&dir, &policy, now)).await
+            {
+                Ok((screenshots, errors)) => {
+                    report.screenshots = screenshots;
+                    report.errors.extend(errors);
+                }
+                Err(e) => report.errors.push(format!("screenshots: {}", e)),
+            }
+        }
+
+        if let Some(policy) = resolve("contexts", &config.contexts, &mut report.errors) {
+            let persistence = self.persistence.lock().await;
+            match persistence.apply_context_retention(&policy, now).await {
+                Ok(contexts) => report.contexts = contexts,
+                Err(e) => report.errors.push(format!("contexts: {}", e)),
+            }
+        }
+
+        if let Some(policy) = resolve("replay_events", &config.replay_events, &mut report.errors) {
+            report.replay_events = self.replay.lock().await.apply_retention(&policy, now);
+        }
+
+        if let Some(policy) = resolve(
+            "pattern_history",
+            &config.pattern_history,
+            &mut report.errors,
+        ) {
+            report.pattern_history = self.patterns.apply_retention(&policy, now).await;
+        }
+
+        if let Some(policy) = resolve("ml_events", &config.ml_events, &mut report.errors) {
+            report.ml_events = self
+                .personalization
+                .lock()
+                .await
+                .apply_retention(&policy, now);
+        }
+
+        // Après les contextes et le replay : leurs entrées supprimées libèrent des captures
+        let (stored, errors) = self
+            .collect_stored_screenshots(Duration::minutes(GC_GRACE_MINUTES), now)
+            .await;
+        report.screenshots.merge(stored);
+        report.errors.extend(errors);
+
+        // Le journal d'audit a sa propre durée : il survit aux données qu'il décrit
+        match ResolvedPolicy::resolve(&config.audit_log, AUDIT_DEFAULT_PERIOD) {
+            Ok(policy) => {
+                let audit = self.audit.clone();
+                match tokio::task::spawn_blocking(move || audit.apply_retention(&policy, now)).await
+                {
+                    Ok(Ok(audit_log)) => report.audit_log = audit_log,
+                    Ok(Err(e)) => report.errors.push(format!("audit_log: {}", e)),
+                    Err(e) => report.errors.push(format!("audit_log: {}", e)),
+                }
+            }
+            Err(e) => report.errors.push(format!("audit_log: {}", e)),
+        }
+
+        report.duration_ms = started.elapsed().as_millis() as u64;
+
+        info!(
+            "🧹 Retention pass: {} items deleted, {} bytes freed, {} screenshots downsampled ({}ms)",
+            report.total_deleted(),
+            report.total_freed_bytes(),
+            report.screenshots.downsampled,
+            report.duration_ms
+        );
+        for error in &report.errors {
+            warn!("⚠️ Retention: {}", error);
+        }
+
+        *self.last_report.lock().await = Some(report.clone());
+        report
+    }
+
+    pub async fn last_report(&self) -> Option<RetentionReport> {
+        self.last_report.lock().await.clone()
+    }
+
+    /// Statistiques de confidentialité calculées sur les données réellement présentes ;
+    /// les compteurs de masquage viennent du `Redactor` partagé
+    pub async fn privacy_stats(&self, redactions: RedactionStats) -> Result<PrivacyStats, String> {
+        let screenshots = list_screenshots(&self.screenshot_dir);
+        let stored = self.screenshot_store.stats();
+        let persistence = self.persistence.lock().await;
+        let stats = persistence.get_stats().await?;
+
+        Ok(PrivacyStats {
+            screenshots_count: screenshots.len() + stored.objects,
+            screenshots_size_bytes: screenshots.iter().map(|f| f.size_bytes).sum::<u64>()
+                + stored.size_bytes,
+            contexts_count: stats.total_contexts.max(0) as usize,
+            db_size_bytes: stats.database_size_bytes.max(0) as u64,
+            data_dir: persistence
+                .data_dir()
+                .await
+                .map(|dir| dir.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            redactions,
+        })
+    }
+}
+
+/// Boucle de rétention périodique ; relit la configuration avant chaque passage
+pub async fn run_retention_loop(
+    engine: Arc<RetentionEngine>,
+    config_manager: Arc<Mutex<ConfigManager>>,
+) {
+    tokio::time::sleep(std::time::Duration::from_secs(STARTUP_DELAY_SECS)).await;
+
+    loop {
+        let config = match config_manager.lock().await.get_config() {
+            Ok(config) => config.retention,
+            Err(e) => {
+                warn!("⚠️ Retention: failed to read config: {}", e);
+                RetentionConfig::default()
+            }
+        };
+
+        engine.run(&config).await;
+
+        let interval = config.interval_minutes.max(1);
+        debug!("🧹 Next retention pass in {} min", interval);
+        tokio::time::sleep(std::time::Duration::from_secs(interval * 60)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ts(now: DateTime<Utc>, hours_ago: i64) -> DateTime<Utc> {
+        now - Duration::hours(hours_ago)
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("never").unwrap(), None);
+        assert_eq!(parse_period("1h").unwrap(), Some(Duration::hours(1)));
+        assert_eq!(parse_period("24h").unwrap(), Some(Duration::hours(24)));
+        assert_eq!(parse_period("7d").unwrap(), Some(Duration::days(7)));
+        assert_eq!(parse_period("30D").unwrap(), Some(Duration::days(30)));
+        assert!(parse_period("soon").is_err());
+        assert!(parse_period("0d").is_err());
+        // Hors des bornes de `Duration` ou de la date courante : refusé, pas de panique
+        for huge in ["9223372036854775807w", "999999999999d", "200000w"] {
+            let err = parse_period(huge).unwrap_err();
+            assert!(err.contains("Invalid retention period"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_validate_resolves_every_policy() {
+        assert!(validate(&RetentionConfig::default()).is_ok());
+
+        let config = RetentionConfig {
+            ml_events: RetentionPolicy {
+                downsample_after: Some("9223372036854775807w".to_string()),
+                ..Default::default()
+            },
+            ..RetentionConfig::default()
+        };
+        let err = validate(&config).unwrap_err();
+        assert!(err.starts_with("ml_events:"), "{}", err);
+
+        let config = RetentionConfig {
+            period: "soon".to_string(),
+            ..RetentionConfig::default()
+        };
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_policy_inherits_global_period() {
+        let policy = ResolvedPolicy::resolve(&RetentionPolicy::default(), "24h").unwrap();
+        assert_eq!(policy.max_age, Some(Duration::hours(24)));
+
+        let never = RetentionPolicy {
+            max_age: Some("never".to_string()),
+            ..Default::default()
+        };
+        let policy = ResolvedPolicy::resolve(&never, "24h").unwrap();
+        assert_eq!(policy.max_age, None);
+    }
+
+    #[test]
+    fn test_select_expired_by_age_count_and_bytes() {
+        let now = Utc::now();
+        let items = vec![
+            (ts(now, 1), 10),
+            (ts(now, 50), 10),
+            (ts(now, 2), 10),
+            (ts(now, 3), 10),
+        ];
+
+        let by_age = ResolvedPolicy {
+            max_age: Some(Duration::hours(24)),
+            ..Default::default()
+        };
+        assert_eq!(by_age.select_expired(&items, now), vec![1]);
+
+        let by_count = ResolvedPolicy {
+            max_count: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(by_count.select_expired(&items, now), vec![1, 3]);
+
+        // Once the byte budget is hit, everything older goes too
+        let by_bytes = ResolvedPolicy {
+            max_bytes: Some(25),
+            ..Default::default()
+        };
+        assert_eq!(by_bytes.select_expired(&items, now), vec![1, 3]);
+
+        assert!(ResolvedPolicy::default()
+            .select_expired(&items, now)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_enforce_screenshots_only_touches_own_files() {
+        let dir = TempDir::new().unwrap();
+        let now = Utc::now();
+
+        let write = |name: &str, hours_ago: i64| {
+            let path = dir.path().join(name);
+            let img = image::RgbImage::from_pixel(800, 600, image::Rgb([120, 30, 200]));
+            img.save(&path).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(ts(now, hours_ago).into())
+                .unwrap();
+            path
+        };
+
+        let old = write(&format!("{}old.jpg", SCREENSHOT_FILE_PREFIX), 24 * 10);
+        let day_old = write(&format!("{}day.jpg", SCREENSHOT_FILE_PREFIX), 30);
+        let fresh = write(&format!("{}fresh.jpg", SCREENSHOT_FILE_PREFIX), 1);
+        let foreign = write("someone-else.jpg", 24 * 10);
+
+        let policy = ResolvedPolicy {
+            max_age: Some(Duration::days(7)),
+            downsample_after: Some(Duration::hours(24)),
+            ..Default::default()
+        };
+        let (report, errors) = enforce_screenshots(dir.path(), &policy, now);
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(report.deleted, 1);
+        assert_eq!(report.downsampled, 1);
+        assert_eq!(report.remaining, 2);
+        assert!(!old.exists());
+        assert!(foreign.exists());
+        assert_eq!(
+            image::image_dimensions(&day_old).unwrap().0,
+            DOWNSAMPLED_MAX_WIDTH
+        );
+        assert_eq!(image::image_dimensions(&fresh).unwrap().0, 800);
+
+        // The downsampled file keeps its age
+        let modified: DateTime<Utc> = std::fs::metadata(&day_old)
+            .unwrap()
+            .modified()
+            .unwrap()
+            .into();
+        assert!(modified < now - Duration::hours(24));
+
+        // A second pass has nothing left to do
+        let (report, _) = enforce_screenshots(dir.path(), &policy, now);
+        assert_eq!(report.deleted, 0);
+        assert_eq!(report.downsampled, 0);
+    }
+}
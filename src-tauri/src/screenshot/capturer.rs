@@ -1,11 +1,15 @@
 use super::errors::{PermissionStatus, ScreenshotError};
+use crate::context::app_detector::{ActiveApp, WindowBounds};
+use crate::privacy::masking::{apply_masks, FramePlan, MaskRect};
+use crate::privacy::zones::{CaptureTarget, DisplayGeometry};
+use crate::privacy::PrivacyZoneManager;
 use image::codecs::jpeg::JpegEncoder;
 use image::RgbImage;
 use screenshots::Screen;
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 use std::path::PathBuf;
 use std::time::Instant;
-use tempfile::NamedTempFile;
 use tracing::{debug, info, warn};
 
 const DEFAULT_QUALITY: u8 = 50; // OPTIMIZED: 60 → 50 (faster encode + smaller file)
@@ -13,6 +17,60 @@ const MAX_SIZE_BYTES: usize = 300_000; // OPTIMIZED: 500KB → 300KB
 const MIN_DIMENSION: u32 = 100; // Don't scale below this
 const MAX_WIDTH_AGGRESSIVE: u32 = 720; // OPTIMIZED: 960 → 720 (fewer pixels = MUCH faster)
 
+/// Préfixe des fichiers de capture (la rétention ne touche que les fichiers portant ce préfixe)
+pub const SCREENSHOT_FILE_PREFIX: &str = "shadowlearn-capture-";
+
+/// Répertoire dans lequel les captures sont écrites
+pub fn screenshot_dir() -> PathBuf {
+    std::env::temp_dir()
+}
+
+/// xdotool donne la géométrie des fenêtres en pixels X11 physiques, alors que `DisplayInfo`
+/// divise celle des écrans par le facteur d'échelle (Xft.dpi)
+const WINDOW_BOUNDS_ARE_PHYSICAL: bool = cfg!(target_os = "linux");
+
+/// Ce qui est capturé : l'écran entier ou seulement la fenêtre active
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    #[default]
+    Display,
+    ActiveWindow,
+}
+
+/// Pourquoi cet écran a été capturé
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayChoice {
+    /// Écran contenant (la plus grande partie de) la fenêtre active
+    ActiveWindow,
+    /// Géométrie de la fenêtre inconnue ou hors écran : écran principal
+    Primary,
+}
+
+/// Écran et zone effectivement capturés
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureSelection {
+    pub display_id: u32,
+    pub display_choice: DisplayChoice,
+    pub requested_mode: CaptureMode,
+    /// Mode appliqué : `Display` si la géométrie de la fenêtre active est inconnue
+    pub mode: CaptureMode,
+    /// Zone capturée, en coordonnées bureau
+    pub region: WindowBounds,
+    /// Pixels de l'image native par unité de coordonnées écran (2.0 sur un écran HiDPI)
+    pub scale_factor: f32,
+}
+
+/// Capture écrite sur disque, zones de confidentialité déjà masquées
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    pub path: PathBuf,
+    /// Nombre de zones masquées (noircies ou floutées)
+    pub masked_zones: usize,
+    pub selection: CaptureSelection,
+}
+
 pub struct ScreenshotCapturer {
     screens: Vec<Screen>,
     compression_quality: u8,
@@ -38,28 +96,113 @@ impl ScreenshotCapturer {
         })
     }
 
-    /// Capture le screen contenant le curseur (ou primary si échec)
+    /// Capture l'écran contenant la fenêtre active (l'écran principal si sa géométrie est
+    /// inconnue), ou seulement la fenêtre en mode `CaptureMode::ActiveWindow`.
     /// OPTIMIZED: Direct RGBA→RGB conversion without intermediate DynamicImage
-    pub fn capture_active_screen(&self) -> Result<PathBuf, ScreenshotError> {
+    /// Les zones de confidentialité sont appliquées avant l'écriture sur disque ;
+    /// `ScreenshotError::PrivacySkipped` si la fenêtre `active_app` est protégée.
+    pub fn capture_active_screen(
+        &self,
+        privacy: &PrivacyZoneManager,
+        active_app: Option<&ActiveApp>,
+        mode: CaptureMode,
+    ) -> Result<CapturedFrame, ScreenshotError> {
         let start = Instant::now();
 
-        let screen = self
-            .get_screen_under_cursor()
-            .or_else(|| self.screens.first())
+        // Géométrie de la fenêtre dans l'espace des écrans (zones de confidentialité comprises)
+        let displays: Vec<DisplayGeometry> = self.screens.iter().map(display_geometry).collect();
+        let window_bounds = active_app.and_then(|app| app.bounds).map(|bounds| {
+            if WINDOW_BOUNDS_ARE_PHYSICAL {
+                let scale = self
+                    .screens
+                    .first()
+                    .map_or(1.0, |s| s.display_info.scale_factor);
+                window_in_display_space(bounds, scale)
+            } else {
+                bounds
+            }
+        });
+        let active_app = active_app.map(|app| ActiveApp {
+            bounds: window_bounds,
+            ..app.clone()
+        });
+
+        let primary = self
+            .screens
+            .iter()
+            .position(|screen| screen.display_info.is_primary)
+            .unwrap_or(0);
+        let (index, display_choice) = select_display(&displays, primary, window_bounds.as_ref())
             .ok_or(ScreenshotError::NoScreens)?;
+        let screen = &self.screens[index];
+        let geometry = displays[index];
 
         debug!(
-            "📸 Capturing screen {} ({}x{})",
-            screen.display_info.id, screen.display_info.width, screen.display_info.height
+            "📸 Capturing screen {} ({}x{}, {:?})",
+            geometry.id, geometry.width, geometry.height, display_choice
         );
 
+        // Décidé avant la capture : une app protégée n'est jamais capturée
+        let plan = privacy.frame_plan(&CaptureTarget {
+            display: geometry,
+            active_app: active_app.as_ref(),
+        });
+        if let FramePlan::Skip { app_name } = plan {
+            info!("🔒 Capture skipped: {} is protected", app_name);
+            return Err(ScreenshotError::PrivacySkipped(app_name));
+        }
+
         let image = screen
             .capture()
             .map_err(|e| ScreenshotError::CaptureFailed(e.to_string()))?;
 
         // OPTIMIZED: Direct RGBA→RGB + downscale in ONE step to avoid double processing
         let rgba_data = image.to_vec();
-        let (width, height) = (image.width(), image.height());
+        let (full_width, full_height) = (image.width(), image.height());
+        // Mesuré sur l'image : l'écran est en unités logiques, la capture en pixels natifs
+        let scale_factor = full_width as f32 / geometry.width.max(1) as f32;
+
+        // Mode fenêtre : recadrage sur la partie visible de la fenêtre active
+        let window_crop = match mode {
+            CaptureMode::ActiveWindow => window_bounds
+                .and_then(|bounds| geometry.visible_part(&bounds))
+                .and_then(|area| {
+                    area.scaled_within(scale_factor, full_width, full_height)
+                        .map(|pixels| (area, pixels))
+                }),
+            CaptureMode::Display => None,
+        };
+        if mode == CaptureMode::ActiveWindow && window_crop.is_none() {
+            debug!("🪟 Active window geometry unknown, capturing the whole screen");
+        }
+        let (area, rgba_data, width, height) = match window_crop {
+            Some((area, (x, y, w, h))) => {
+                (area, crop_rgba(&rgba_data, full_width, x, y, w, h), w, h)
+            }
+            None => (
+                MaskRect::new(0, 0, geometry.width, geometry.height),
+                rgba_data,
+                full_width,
+                full_height,
+            ),
+        };
+        let selection = CaptureSelection {
+            display_id: geometry.id,
+            display_choice,
+            requested_mode: mode,
+            mode: if window_crop.is_some() {
+                CaptureMode::ActiveWindow
+            } else {
+                CaptureMode::Display
+            },
+            region: WindowBounds {
+                x: geometry.x + area.x as i32,
+                y: geometry.y + area.y as i32,
+                width: area.width,
+                height: area.height,
+            },
+            scale_factor,
+        };
         
         // Downscale DURING conversion if needed
         let scale = if width > MAX_WIDTH_AGGRESSIVE {
@@ -93,9 +236,31 @@ impl ScreenshotCapturer {
             rgba_data.chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect()
         };
         
-        let rgb_img = RgbImage::from_raw(new_width, new_height, rgb_data)
+        let mut rgb_img = RgbImage::from_raw(new_width, new_height, rgb_data)
             .ok_or_else(|| ScreenshotError::ProcessingFailed("RGB conversion failed".into()))?;
 
+        // Zones de confidentialité : masquées avant que l'image soit écrite, hachée, lue par
+        // l'OCR ou envoyée où que ce soit
+        let masked_zones = match plan {
+            FramePlan::Mask {
+                rects,
+                mode: mask_mode,
+            } => {
+                // Zones en coordonnées écran ; l'image peut être recadrée sur la fenêtre,
+                // réduite et en HiDPI
+                let rects: Vec<MaskRect> = rects
+                    .iter()
+                    .filter_map(|rect| rect.relative_to(&area))
+                    .collect();
+                let frame_scale = new_width as f32 / area.width.max(1) as f32;
+                apply_masks(&mut rgb_img, &rects, mask_mode, frame_scale)
+            }
+            FramePlan::Skip { .. } => unreachable!("skipped frames return early"),
+        };
+        if masked_zones > 0 {
+            debug!("🔒 {} privacy zone(s) masked", masked_zones);
+        }
+
         // Compress and save to temp file
         let temp_path = self.compress_and_save(rgb_img)?;
 
@@ -106,16 +271,11 @@ impl ScreenshotCapturer {
             temp_path.display()
         );
 
-        Ok(temp_path)
-    }
-
-    /// Trouve le screen sous le curseur
-    /// Note: Pour l'instant, retourne le premier screen (primary)
-    /// TODO: Implémenter détection cursor position pour multi-monitor
-    fn get_screen_under_cursor(&self) -> Option<&Screen> {
-        // Pour l'instant, on retourne toujours le premier screen
-        // Une implémentation complète nécessiterait des APIs plus complexes
-        self.screens.first()
+        Ok(CapturedFrame {
+            path: temp_path,
+            masked_zones,
+            selection,
+        })
     }
 
     /// Compresse et sauvegarde dans un fichier temporaire
@@ -181,7 +341,10 @@ impl ScreenshotCapturer {
 
         // Sauvegarder dans un fichier temporaire
         let save_start = Instant::now();
-        let mut temp_file = NamedTempFile::with_suffix(".jpg")?;
+        let mut temp_file = tempfile::Builder::new()
+            .prefix(SCREENSHOT_FILE_PREFIX)
+            .suffix(".jpg")
+            .tempfile_in(screenshot_dir())?;
         std::io::Write::write_all(&mut temp_file, &bytes)?;
         info!("⏱️ File write: {}ms", save_start.elapsed().as_millis());
 
@@ -247,6 +410,65 @@ impl ScreenshotCapturer {
     }
 }
 
+fn display_geometry(screen: &Screen) -> DisplayGeometry {
+    let info = &screen.display_info;
+    DisplayGeometry {
+        id: info.id,
+        x: info.x,
+        y: info.y,
+        width: info.width,
+        height: info.height,
+    }
+}
+
+/// Écran contenant la plus grande partie de la fenêtre `bounds`, sinon l'écran `primary`.
+/// None s'il n'y a aucun écran
+fn select_display(
+    displays: &[DisplayGeometry],
+    primary: usize,
+    bounds: Option<&WindowBounds>,
+) -> Option<(usize, DisplayChoice)> {
+    let by_window = bounds.and_then(|bounds| {
+        displays
+            .iter()
+            .enumerate()
+            .filter_map(|(index, display)| {
+                let visible = display.visible_part(bounds)?;
+                Some((index, visible.width as u64 * visible.height as u64))
+            })
+            .max_by_key(|(_, area)| *area)
+            .map(|(index, _)| index)
+    });
+    match by_window {
+        Some(index) => Some((index, DisplayChoice::ActiveWindow)),
+        None if displays.is_empty() => None,
+        None => Some((primary.min(displays.len() - 1), DisplayChoice::Primary)),
+    }
+}
+
+/// Géométrie de fenêtre en pixels physiques ramenée aux unités logiques des écrans
+fn window_in_display_space(bounds: WindowBounds, scale_factor: f32) -> WindowBounds {
+    if scale_factor <= 0.0 || (scale_factor - 1.0).abs() < f32::EPSILON {
+        return bounds;
+    }
+    WindowBounds {
+        x: (bounds.x as f32 / scale_factor).round() as i32,
+        y: (bounds.y as f32 / scale_factor).round() as i32,
+        width: (bounds.width as f32 / scale_factor).round() as u32,
+        height: (bounds.height as f32 / scale_factor).round() as u32,
+    }
+}
+
+/// Extrait le rectangle (`x`, `y`, `w`, `h`) d'un buffer RGBA de largeur `width`
+fn crop_rgba(data: &[u8], width: u32, x: u32, y: u32, w: u32, h: u32) -> Vec<u8> {
+    let mut cropped = Vec::with_capacity((w * h * 4) as usize);
+    for row in y..y + h {
+        let start = ((row * width + x) * 4) as usize;
+        cropped.extend_from_slice(&data[start..start + (w * 4) as usize]);
+    }
+    cropped
+}
+
 impl Default for ScreenshotCapturer {
     fn default() -> Self {
         Self::new().expect("Failed to create ScreenshotCapturer")
@@ -280,12 +502,16 @@ mod tests {
     #[ignore] // Only run manually (creates temp file)
     fn test_capture() {
         let capturer = ScreenshotCapturer::new().unwrap();
-        let result = capturer.capture_active_screen();
+        let result = capturer.capture_active_screen(
+            &PrivacyZoneManager::default(),
+            None,
+            CaptureMode::Display,
+        );
 
         match result {
-            Ok(path) => {
-                println!("Screenshot saved to: {}", path.display());
-                let metadata = std::fs::metadata(&path).unwrap();
+            Ok(frame) => {
+                println!("Screenshot saved to: {}", frame.path.display());
+                let metadata = std::fs::metadata(&frame.path).unwrap();
                 assert!(metadata.len() > 0);
                 assert!(metadata.len() <= MAX_SIZE_BYTES as u64);
             }
@@ -294,4 +520,60 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_select_display_follows_active_window() {
+        let displays = [
+            DisplayGeometry::full_hd(1, 0),
+            DisplayGeometry::full_hd(2, 1920),
+        ];
+        let window = |x: i32, width: u32| WindowBounds {
+            x,
+            y: 100,
+            width,
+            height: 600,
+        };
+
+        // Surtout sur le second écran
+        assert_eq!(
+            select_display(&displays, 0, Some(&window(1800, 800))),
+            Some((1, DisplayChoice::ActiveWindow))
+        );
+        // Hors écran ou géométrie inconnue : écran principal
+        assert_eq!(
+            select_display(&displays, 1, Some(&window(-5000, 800))),
+            Some((1, DisplayChoice::Primary))
+        );
+        assert_eq!(
+            select_display(&displays, 0, None),
+            Some((0, DisplayChoice::Primary))
+        );
+        assert_eq!(select_display(&[], 0, None), None);
+    }
+
+    #[test]
+    fn test_window_scaling_and_crop() {
+        let physical = WindowBounds {
+            x: 3840,
+            y: 200,
+            width: 1000,
+            height: 500,
+        };
+        assert_eq!(
+            window_in_display_space(physical, 2.0),
+            WindowBounds {
+                x: 1920,
+                y: 100,
+                width: 500,
+                height: 250,
+            }
+        );
+        assert_eq!(window_in_display_space(physical, 1.0), physical);
+
+        // Image RGBA 4x2, valeur du pixel = son indice
+        let data: Vec<u8> = (0..8u8).flat_map(|i| [i, i, i, 255]).collect();
+        let cropped = crop_rgba(&data, 4, 1, 0, 2, 2);
+        let pixels: Vec<u8> = cropped.chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(pixels, vec![1, 2, 5, 6]);
+    }
 }
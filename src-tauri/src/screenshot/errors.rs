@@ -19,6 +19,9 @@ pub enum ScreenshotError {
 
     #[error("Image encoding error: {0}")]
     ImageError(#[from] image::ImageError),
+
+    #[error("Capture skipped: {0} is protected by a privacy zone")]
+    PrivacySkipped(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -1,13 +1,20 @@
 pub mod capturer;
 pub mod errors;
 pub mod permissions;
+pub mod store;
 
-pub use capturer::ScreenshotCapturer;
+pub use capturer::{
+    screenshot_dir, CaptureMode, CaptureSelection, ScreenshotCapturer, SCREENSHOT_FILE_PREFIX,
+};
 pub use errors::{PermissionStatus, ScreenshotError};
 pub use permissions::open_system_preferences;
+pub use store::{ScreenshotStore, ScreenshotStoreStats};
 
+use crate::config::ConfigManager;
+use crate::context::ContextAggregator;
+use crate::privacy::{AuditEvent, AuditLog, PrivacyZoneManager};
 use base64::{engine::general_purpose, Engine as _};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use tauri::Manager;
 use tracing::{error, info};
 
@@ -28,11 +35,50 @@ pub fn init_capturer() -> Result<(), ScreenshotError> {
 }
 
 /// Tauri command: Capture screenshot (OPTIMIZED)
+/// `mode` remplace le mode configuré (`captures.mode`) pour cette capture
 #[tauri::command]
-pub async fn capture_screenshot(app: tauri::AppHandle) -> Result<CaptureResult, String> {
+pub async fn capture_screenshot(
+    app: tauri::AppHandle,
+    mode: Option<CaptureMode>,
+) -> Result<CaptureResult, String> {
     info!("📸 capture_screenshot command called");
+    capture_frame(&app, mode)
+        .await?
+        .ok_or_else(|| "Capture skipped: the active app is protected by a privacy zone".to_string())
+}
+
+/// Capture l'écran de la fenêtre active (ou la fenêtre seule selon `mode`, à défaut le
+/// mode configuré) avec les zones de confidentialité appliquées.
+/// `None` si la fenêtre active est protégée (aucune image n'est prise).
+pub async fn capture_frame(
+    app: &tauri::AppHandle,
+    mode: Option<CaptureMode>,
+) -> Result<Option<CaptureResult>, String> {
     let start = std::time::Instant::now();
 
+    let config = app.try_state::<Arc<tokio::sync::Mutex<ConfigManager>>>();
+    let mode = match (mode, config) {
+        (Some(mode), _) => mode,
+        (None, Some(config)) => config
+            .lock()
+            .await
+            .get_config()
+            .map(|config| config.captures.mode)
+            .unwrap_or_default(),
+        (None, None) => CaptureMode::default(),
+    };
+
+    // Snapshot of the privacy zones and of the foreground app for the blocking capture
+    let privacy = match app.try_state::<Arc<tokio::sync::Mutex<PrivacyZoneManager>>>() {
+        Some(manager) => PrivacyZoneManager::new(manager.lock().await.config().clone()),
+        None => PrivacyZoneManager::default(),
+    };
+    let active_app = match app.try_state::<Arc<tokio::sync::Mutex<ContextAggregator>>>() {
+        Some(aggregator) => aggregator.lock().await.peek().ok().map(|peek| peek.app),
+        None => None,
+    };
+    let app_name = active_app.as_ref().map(|active| active.name.clone());
+
     // OPTIMIZED: Hide windows
     let windows_to_hide = ["chat", "context"];
     for label in &windows_to_hide {
@@ -71,10 +117,15 @@ pub async fn capture_screenshot(app: tauri::AppHandle) -> Result<CaptureResult,
 
         // Capture
         let capture_start = std::time::Instant::now();
-        let path = capturer.capture_active_screen().map_err(|e| {
-            error!("❌ Capture failed: {}", e);
-            e.to_string()
-        })?;
+        let frame = match capturer.capture_active_screen(&privacy, active_app.as_ref(), mode) {
+            Ok(frame) => frame,
+            Err(ScreenshotError::PrivacySkipped(_)) => return Ok(None),
+            Err(e) => {
+                error!("❌ Capture failed: {}", e);
+                return Err(e.to_string());
+            }
+        };
+        let path = frame.path;
         info!("⏱️ Screen capture: {}ms", capture_start.elapsed().as_millis());
 
         // OPTIMIZED: Only read file once
@@ -94,11 +145,13 @@ pub async fn capture_screenshot(app: tauri::AppHandle) -> Result<CaptureResult,
             start_clone.elapsed().as_millis()
         );
 
-        Ok(CaptureResult {
+        Ok(Some(CaptureResult {
             data: base64_data,
             path: path.to_string_lossy().to_string(),
             size_bytes: file_size,
-        })
+            masked_zones: frame.masked_zones,
+            selection: frame.selection,
+        }))
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?;
@@ -110,6 +163,19 @@ pub async fn capture_screenshot(app: tauri::AppHandle) -> Result<CaptureResult,
         }
     }
 
+    // Journal d'audit : capture gardée (zones masquées) ou écartée
+    if let (Some(audit), Ok(frame)) = (app.try_state::<Arc<AuditLog>>(), &result) {
+        audit.record(AuditEvent::Capture {
+            app_name,
+            masked_zones: frame.as_ref().map_or(0, |frame| frame.masked_zones),
+            kept: frame.is_some(),
+            reason: frame
+                .is_none()
+                .then(|| "active app protected by a privacy zone".to_string()),
+            size_bytes: frame.as_ref().map_or(0, |frame| frame.size_bytes),
+        });
+    }
+
     info!("🎯 Total screenshot duration: {}ms", start.elapsed().as_millis());
     result
 }
@@ -119,6 +185,10 @@ pub struct CaptureResult {
     pub data: String, // base64
     pub path: String,
     pub size_bytes: usize,
+    /// Zones de confidentialité masquées dans l'image
+    pub masked_zones: usize,
+    /// Écran et zone capturés (écran de la fenêtre active, fenêtre seule...)
+    pub selection: CaptureSelection,
 }
 
 /// Tauri command: Check permissions
@@ -145,3 +215,24 @@ pub fn check_screenshot_permission() -> PermissionStatus {
 pub fn request_screenshot_permission() -> Result<(), String> {
     open_system_preferences().map_err(|e| e.to_string())
 }
+
+/// Tauri command: Relit une capture du magasin (`screenshot:<id>`), renvoyée en base64
+#[tauri::command]
+pub async fn read_stored_screenshot(
+    reference: String,
+    store: tauri::State<'_, Arc<ScreenshotStore>>,
+) -> Result<String, String> {
+    let store = store.inner().clone();
+    let bytes = tokio::task::spawn_blocking(move || store.read(&reference))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+    Ok(general_purpose::STANDARD.encode(bytes))
+}
+
+/// Tauri command: Statistiques du magasin de captures
+#[tauri::command]
+pub fn get_screenshot_store_stats(
+    store: tauri::State<'_, Arc<ScreenshotStore>>,
+) -> ScreenshotStoreStats {
+    store.stats()
+}
@@ -0,0 +1,625 @@
+//! Magasin de captures adressé par contenu.
+//!
+//! Chaque capture conservée est rangée sous `<données>/screenshots/<id>.jpg` (`.enc` si
+//! elle est chiffrée) : deux images identiques ne sont stockées qu'une fois. `<id>` est le
+//! SHA-256 de l'image, ou pour une capture chiffrée un HMAC sous la clé de chiffrement, dont
+//! le nom ne permet pas de vérifier qu'une image connue est présente. Le replay, les
+//! workflows et les contextes capturés y font référence par `screenshot:<id>` ; l'index
+//! note qui détient chaque capture et le ramasse-miettes supprime celles que plus personne
+//! ne détient.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::persistence::encryption::ColumnCipher;
+use crate::retention::CategoryReport;
+
+/// Préfixe des références enregistrées à la place d'un chemin ou d'une image base64
+pub const STORE_REFERENCE_PREFIX: &str = "screenshot:";
+
+/// Répertoire du magasin, dans le répertoire de données de l'app
+pub const STORE_DIR_NAME: &str = "screenshots";
+
+/// Délai avant qu'une capture sans détenteur soit supprimée par le passage de rétention :
+/// une capture rangée juste avant d'être rattachée à son contexte ne doit pas disparaître
+pub const GC_GRACE_MINUTES: i64 = 10;
+
+const INDEX_FILE: &str = "index.json";
+
+/// Référence `screenshot:<id>` d'une capture du magasin
+pub fn store_reference(id: &str) -> String {
+    format!("{}{}", STORE_REFERENCE_PREFIX, id)
+}
+
+/// Identifiant d'une référence du magasin ; None pour un chemin ou une image base64
+pub fn parse_reference(value: &str) -> Option<&str> {
+    let id = value.strip_prefix(STORE_REFERENCE_PREFIX)?;
+    let valid = id.len() == 64 && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+    valid.then_some(id)
+}
+
+/// Type de détenteur d'une capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolderKind {
+    Replay,
+    Workflow,
+    Context,
+}
+
+/// Détenteur d'une référence vers une capture
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum ScreenshotHolder {
+    /// Événement de replay (`ReplayEvent.screenshot_path`)
+    Replay(String),
+    /// Workflow ou tutoriel qui en est tiré (`WorkflowAction.screenshot_path`)
+    Workflow(String),
+    /// Contexte capturé (`captured_contexts.screenshot_data`)
+    Context(String),
+}
+
+impl ScreenshotHolder {
+    pub fn new(kind: HolderKind, id: impl Into<String>) -> Self {
+        match kind {
+            HolderKind::Replay => ScreenshotHolder::Replay(id.into()),
+            HolderKind::Workflow => ScreenshotHolder::Workflow(id.into()),
+            HolderKind::Context => ScreenshotHolder::Context(id.into()),
+        }
+    }
+
+    pub fn kind(&self) -> HolderKind {
+        match self {
+            ScreenshotHolder::Replay(_) => HolderKind::Replay,
+            ScreenshotHolder::Workflow(_) => HolderKind::Workflow,
+            ScreenshotHolder::Context(_) => HolderKind::Context,
+        }
+    }
+}
+
+/// Entrée de l'index : une capture sur disque et ses détenteurs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredScreenshot {
+    /// SHA-256 de l'image en clair, HMAC-SHA256 si elle est chiffrée
+    pub id: String,
+    /// Taille du fichier sur disque
+    pub size_bytes: u64,
+    pub encrypted: bool,
+    pub created_at: DateTime<Utc>,
+    /// Dernier détenteur ajouté : le délai de grâce court à partir de là, une capture déjà
+    /// rangée pouvant être reprise juste avant une synchronisation
+    #[serde(default)]
+    pub last_held_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub holders: BTreeSet<ScreenshotHolder>,
+}
+
+impl StoredScreenshot {
+    fn hold(&mut self, holder: ScreenshotHolder) {
+        self.last_held_at = Some(Utc::now());
+        self.holders.insert(holder);
+    }
+}
+
+/// Statistiques du magasin
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScreenshotStoreStats {
+    pub objects: usize,
+    pub size_bytes: u64,
+    /// Nombre total de références (une capture partagée compte pour chacun de ses détenteurs)
+    pub references: usize,
+    /// Captures sans détenteur, supprimées au prochain passage de rétention
+    pub unreferenced: usize,
+    pub encrypted: usize,
+    pub root: String,
+}
+
+/// Magasin de captures dédupliquées par contenu, avec comptage des références
+pub struct ScreenshotStore {
+    root: PathBuf,
+    cipher: Option<Arc<ColumnCipher>>,
+    /// Chiffrer les nouvelles captures (le chiffre sert aussi à relire les anciennes)
+    encrypt: bool,
+    index: Mutex<BTreeMap<String, StoredScreenshot>>,
+}
+
+impl ScreenshotStore {
+    /// Ouvre (ou crée) le magasin. Les fichiers absents de l'index (écriture interrompue,
+    /// index perdu) y sont réintégrés sans détenteur ; la prochaine synchronisation leur
+    /// rend les leurs
+    pub fn open(
+        root: impl Into<PathBuf>,
+        cipher: Option<Arc<ColumnCipher>>,
+        encrypt: bool,
+    ) -> Result<Self, String> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)
+            .map_err(|e| format!("Failed to create screenshot store {:?}: {}", root, e))?;
+        if encrypt && cipher.is_none() {
+            warn!(
+                "⚠️ Screenshot encryption requested without a key → storing screenshots in clear"
+            );
+        }
+
+        let mut index = Self::load_index(&root);
+        let repaired = Self::reconcile(&root, &mut index);
+        let store = Self {
+            root,
+            cipher,
+            encrypt,
+            index: Mutex::new(index),
+        };
+        if repaired > 0 {
+            warn!("⚠️ Screenshot store index repaired ({} entries)", repaired);
+            store.save_index(&store.index.lock().unwrap())?;
+        }
+
+        info!(
+            "🗂️ Screenshot store opened at {:?} ({} screenshots)",
+            store.root,
+            store.index.lock().unwrap().len()
+        );
+        Ok(store)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn load_index(root: &Path) -> BTreeMap<String, StoredScreenshot> {
+        let Ok(content) = std::fs::read_to_string(root.join(INDEX_FILE)) else {
+            return BTreeMap::new();
+        };
+        match serde_json::from_str::<Vec<StoredScreenshot>>(&content) {
+            Ok(entries) => entries.into_iter().map(|e| (e.id.clone(), e)).collect(),
+            Err(e) => {
+                warn!("⚠️ Screenshot store index unreadable, rebuilding: {}", e);
+                BTreeMap::new()
+            }
+        }
+    }
+
+    /// Aligne l'index sur les fichiers présents ; renvoie le nombre d'entrées corrigées
+    fn reconcile(root: &Path, index: &mut BTreeMap<String, StoredScreenshot>) -> usize {
+        let before = index.len();
+        index.retain(|_, entry| object_path(root, &entry.id, entry.encrypted).exists());
+        let mut repaired = before - index.len();
+
+        let Ok(entries) = std::fs::read_dir(root) else {
+            return repaired;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let encrypted = match path.extension().and_then(|s| s.to_str()) {
+                Some("jpg") => false,
+                Some("enc") => true,
+                // Écriture interrompue
+                Some("tmp") => {
+                    let _ = std::fs::remove_file(&path);
+                    continue;
+                }
+                _ => continue,
+            };
+            if parse_reference(&store_reference(id)).is_none() || index.contains_key(id) {
+                continue;
+            }
+
+            let metadata = entry.metadata().ok();
+            index.insert(
+                id.to_string(),
+                StoredScreenshot {
+                    id: id.to_string(),
+                    size_bytes: metadata.as_ref().map_or(0, |m| m.len()),
+                    encrypted,
+                    created_at: metadata
+                        .and_then(|m| m.modified().ok())
+                        .map(DateTime::<Utc>::from)
+                        .unwrap_or_else(Utc::now),
+                    last_held_at: None,
+                    holders: BTreeSet::new(),
+                },
+            );
+            repaired += 1;
+        }
+        repaired
+    }
+
+    fn save_index(&self, index: &BTreeMap<String, StoredScreenshot>) -> Result<(), String> {
+        let entries: Vec<&StoredScreenshot> = index.values().collect();
+        let content = serde_json::to_vec_pretty(&entries)
+            .map_err(|e| format!("Failed to serialize screenshot index: {}", e))?;
+        write_atomic(&self.root.join(INDEX_FILE), &content)
+    }
+
+    /// Range une capture et l'attribue à `holder` ; renvoie sa référence `screenshot:<id>`.
+    /// Une image identique déjà présente n'est pas réécrite
+    pub fn put(&self, bytes: &[u8], holder: Option<ScreenshotHolder>) -> Result<String, String> {
+        let id = self.object_id(bytes);
+        let mut index = self.index.lock().unwrap();
+
+        let present = index
+            .get(&id)
+            .is_some_and(|entry| object_path(&self.root, &id, entry.encrypted).exists());
+        if present {
+            debug!("🗂️ Screenshot {} already stored", &id[..12]);
+        } else {
+            let entry = self.write_object(&id, bytes)?;
+            index.insert(id.clone(), entry);
+        }
+        if let (Some(holder), Some(entry)) = (holder, index.get_mut(&id)) {
+            entry.hold(holder);
+        }
+
+        self.save_index(&index)?;
+        Ok(store_reference(&id))
+    }
+
+    /// Range le fichier d'une capture (`ScreenshotCapturer::compress_and_save`)
+    pub fn put_file(
+        &self,
+        path: &Path,
+        holder: Option<ScreenshotHolder>,
+    ) -> Result<String, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Failed to read screenshot {:?}: {}", path, e))?;
+        self.put(&bytes, holder)
+    }
+
+    /// Attribue à `holder` une capture donnée par son chemin (rangée au passage) ou par une
+    /// référence déjà obtenue ; renvoie sa référence
+    pub fn adopt(&self, value: &str, holder: ScreenshotHolder) -> Result<String, String> {
+        if parse_reference(value).is_some() {
+            self.add_holder(value, holder)?;
+            Ok(value.to_string())
+        } else {
+            self.put_file(Path::new(value), Some(holder))
+        }
+    }
+
+    /// Chiffre à utiliser pour les nouvelles captures
+    fn write_cipher(&self) -> Option<&Arc<ColumnCipher>> {
+        self.cipher.as_ref().filter(|_| self.encrypt)
+    }
+
+    /// Nom d'une nouvelle capture : un simple SHA-256 révélerait, pour une capture chiffrée,
+    /// si une image connue a été prise
+    fn object_id(&self, bytes: &[u8]) -> String {
+        match self.write_cipher() {
+            Some(cipher) => cipher
+                .keyed_digest("screenshot-store", bytes)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+            None => format!("{:x}", Sha256::digest(bytes)),
+        }
+    }
+
+    fn write_object(&self, id: &str, bytes: &[u8]) -> Result<StoredScreenshot, String> {
+        let cipher = self.write_cipher();
+        let content = match cipher {
+            Some(cipher) => cipher.encrypt_bytes(bytes)?,
+            None => bytes.to_vec(),
+        };
+        write_atomic(&object_path(&self.root, id, cipher.is_some()), &content)?;
+        debug!(
+            "🗂️ Stored screenshot {} ({} bytes)",
+            &id[..12],
+            content.len()
+        );
+
+        Ok(StoredScreenshot {
+            id: id.to_string(),
+            size_bytes: content.len() as u64,
+            encrypted: cipher.is_some(),
+            created_at: Utc::now(),
+            last_held_at: None,
+            holders: BTreeSet::new(),
+        })
+    }
+
+    /// Ajoute un détenteur à une capture déjà rangée
+    pub fn add_holder(&self, reference: &str, holder: ScreenshotHolder) -> Result<(), String> {
+        let id = parse_reference(reference)
+            .ok_or_else(|| format!("Not a screenshot store reference: {}", reference))?;
+        let mut index = self.index.lock().unwrap();
+        let entry = index
+            .get_mut(id)
+            .ok_or_else(|| format!("Unknown screenshot: {}", id))?;
+        entry.hold(holder);
+        self.save_index(&index)?;
+        Ok(())
+    }
+
+    /// Remplace les détenteurs de type `kind` par `current` (paires identifiant du détenteur,
+    /// valeur enregistrée). Les valeurs qui ne sont pas des références du magasin sont
+    /// ignorées. Renvoie le nombre de références ajoutées ou retirées
+    pub fn sync_holders(&self, kind: HolderKind, current: &[(String, String)]) -> usize {
+        let mut wanted: BTreeMap<&str, BTreeSet<ScreenshotHolder>> = BTreeMap::new();
+        for (holder_id, value) in current {
+            if let Some(id) = parse_reference(value) {
+                wanted
+                    .entry(id)
+                    .or_default()
+                    .insert(ScreenshotHolder::new(kind, holder_id.as_str()));
+            }
+        }
+
+        let mut index = self.index.lock().unwrap();
+        let mut changes = 0;
+        for entry in index.values_mut() {
+            let held = wanted.remove(entry.id.as_str()).unwrap_or_default();
+            let before = entry.holders.len();
+            entry
+                .holders
+                .retain(|holder| holder.kind() != kind || held.contains(holder));
+            changes += before - entry.holders.len();
+            for holder in held {
+                if !entry.holders.contains(&holder) {
+                    entry.hold(holder);
+                    changes += 1;
+                }
+            }
+        }
+        for id in wanted.keys() {
+            debug!("🗂️ Dangling screenshot reference: {}", id);
+        }
+
+        if changes > 0 {
+            if let Err(e) = self.save_index(&index) {
+                warn!("⚠️ Failed to save screenshot index: {}", e);
+            }
+        }
+        changes
+    }
+
+    /// Relit une capture (déchiffrée) à partir de sa référence
+    pub fn read(&self, reference: &str) -> Result<Vec<u8>, String> {
+        let id = parse_reference(reference)
+            .ok_or_else(|| format!("Not a screenshot store reference: {}", reference))?;
+        let encrypted = self
+            .index
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|entry| entry.encrypted)
+            .ok_or_else(|| format!("Unknown screenshot: {}", id))?;
+
+        let content = std::fs::read(object_path(&self.root, id, encrypted))
+            .map_err(|e| format!("Failed to read screenshot {}: {}", id, e))?;
+        if !encrypted {
+            return Ok(content);
+        }
+        self.cipher
+            .as_ref()
+            .ok_or_else(|| "Screenshot is encrypted but no key is available".to_string())?
+            .decrypt_bytes(&content)
+    }
+
+    /// Identifiants des captures qui ont au moins un détenteur
+    pub fn held_ids(&self) -> BTreeSet<String> {
+        let index = self.index.lock().unwrap();
+        index
+            .values()
+            .filter(|entry| !entry.holders.is_empty())
+            .map(|entry| entry.id.clone())
+            .collect()
+    }
+
+    /// Supprime les captures sans détenteur rangées (ou reprises) avant `now - grace`
+    pub fn collect_garbage(
+        &self,
+        grace: Duration,
+        now: DateTime<Utc>,
+    ) -> (CategoryReport, Vec<String>) {
+        let cutoff = now - grace;
+        self.collect(|entry| {
+            let last_used = entry
+                .last_held_at
+                .unwrap_or(entry.created_at)
+                .max(entry.created_at);
+            last_used <= cutoff
+        })
+    }
+
+    /// Supprime sans délai de grâce les captures de `released` qui n'ont plus de détenteur :
+    /// celles qu'une capture tout juste rangée attend encore ne sont pas concernées
+    pub fn collect_released(&self, released: &BTreeSet<String>) -> (CategoryReport, Vec<String>) {
+        self.collect(|entry| released.contains(&entry.id))
+    }
+
+    /// Supprime les captures sans détenteur pour lesquelles `expired` est vrai
+    fn collect(
+        &self,
+        expired: impl Fn(&StoredScreenshot) -> bool,
+    ) -> (CategoryReport, Vec<String>) {
+        let mut report = CategoryReport::default();
+        let mut errors = Vec::new();
+
+        let mut index = self.index.lock().unwrap();
+        index.retain(|id, entry| {
+            if !entry.holders.is_empty() || !expired(entry) {
+                return true;
+            }
+            match std::fs::remove_file(object_path(&self.root, id, entry.encrypted)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    errors.push(format!("screenshot store: {}: {}", id, e));
+                    return true;
+                }
+            }
+            report.deleted += 1;
+            report.freed_bytes += entry.size_bytes;
+            false
+        });
+        report.remaining = index.len();
+        report.remaining_bytes = index.values().map(|entry| entry.size_bytes).sum();
+
+        if report.deleted > 0 {
+            if let Err(e) = self.save_index(&index) {
+                errors.push(format!("screenshot store: {}", e));
+            }
+            info!(
+                "🧹 Removed {} unreferenced screenshots ({} bytes)",
+                report.deleted, report.freed_bytes
+            );
+        }
+        (report, errors)
+    }
+
+    pub fn stats(&self) -> ScreenshotStoreStats {
+        let index = self.index.lock().unwrap();
+        ScreenshotStoreStats {
+            objects: index.len(),
+            size_bytes: index.values().map(|entry| entry.size_bytes).sum(),
+            references: index.values().map(|entry| entry.holders.len()).sum(),
+            unreferenced: index.values().filter(|e| e.holders.is_empty()).count(),
+            encrypted: index.values().filter(|e| e.encrypted).count(),
+            root: self.root.to_string_lossy().to_string(),
+        }
+    }
+}
+
+fn object_path(root: &Path, id: &str, encrypted: bool) -> PathBuf {
+    root.join(format!("{}.{}", id, if encrypted { "enc" } else { "jpg" }))
+}
+
+/// Écrit dans un fichier temporaire puis le renomme (pas de fichier à moitié écrit)
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to rename {:?}: {}", tmp, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::keymanager::KeyManager;
+
+    fn files(dir: &Path, extension: &str) -> usize {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some(extension))
+            .count()
+    }
+
+    #[test]
+    fn test_dedupe_and_refcount() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScreenshotStore::open(dir.path(), None, false).unwrap();
+
+        let first = store
+            .put(b"frame", Some(ScreenshotHolder::Replay("e1".into())))
+            .unwrap();
+        let second = store
+            .put(b"frame", Some(ScreenshotHolder::Context("c1".into())))
+            .unwrap();
+        assert_eq!(first, second);
+        assert!(parse_reference(&first).is_some());
+        assert_eq!(files(dir.path(), "jpg"), 1);
+        assert_eq!(store.stats().references, 2);
+        let now = Utc::now();
+
+        // Encore détenue par le contexte
+        store.sync_holders(HolderKind::Replay, &[]);
+        assert_eq!(store.collect_garbage(Duration::zero(), now).0.deleted, 0);
+        assert_eq!(store.read(&first).unwrap(), b"frame");
+
+        store.sync_holders(HolderKind::Context, &[]);
+        let (report, errors) = store.collect_garbage(Duration::zero(), now);
+        assert!(errors.is_empty());
+        assert_eq!((report.deleted, report.freed_bytes), (1, 5));
+        assert_eq!(files(dir.path(), "jpg"), 0);
+        assert!(store.read(&first).is_err());
+    }
+
+    #[test]
+    fn test_sync_holders_and_grace_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScreenshotStore::open(dir.path(), None, false).unwrap();
+        let reference = store.put(b"frame", None).unwrap();
+        let now = Utc::now();
+
+        // Trop récente pour être supprimée
+        let grace = Duration::minutes(GC_GRACE_MINUTES);
+        assert_eq!(store.collect_garbage(grace, now).0.deleted, 0);
+
+        let events = vec![
+            ("e1".to_string(), reference.clone()),
+            ("e2".to_string(), "/tmp/legacy.jpg".to_string()),
+        ];
+        assert_eq!(store.sync_holders(HolderKind::Replay, &events), 1);
+        assert_eq!(store.sync_holders(HolderKind::Replay, &events), 0);
+
+        // L'index survit à la réouverture
+        drop(store);
+        let store = ScreenshotStore::open(dir.path(), None, false).unwrap();
+        assert_eq!(store.stats().references, 1);
+
+        assert_eq!(store.sync_holders(HolderKind::Replay, &[]), 1);
+        let later = now + grace + Duration::seconds(1);
+        assert_eq!(store.collect_garbage(grace, later).0.deleted, 1);
+    }
+
+    #[test]
+    fn test_collect_released_spares_pending_captures() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ScreenshotStore::open(dir.path(), None, false).unwrap();
+        let released = store
+            .put(b"forgotten", Some(ScreenshotHolder::Replay("e1".into())))
+            .unwrap();
+        let shared = store
+            .put(b"shared", Some(ScreenshotHolder::Replay("e2".into())))
+            .unwrap();
+        store
+            .add_holder(&shared, ScreenshotHolder::Context("c1".into()))
+            .unwrap();
+        // Rangée juste avant d'être rattachée à son contexte
+        let pending = store.put(b"pending", None).unwrap();
+
+        let held_before = store.held_ids();
+        assert_eq!(held_before.len(), 2);
+        store.sync_holders(HolderKind::Replay, &[]);
+
+        let (report, errors) = store.collect_released(&held_before);
+        assert!(errors.is_empty());
+        assert_eq!(report.deleted, 1);
+        assert!(store.read(&released).is_err());
+        assert_eq!(store.read(&shared).unwrap(), b"shared");
+        assert_eq!(store.read(&pending).unwrap(), b"pending");
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_objects_and_lost_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let cipher = ColumnCipher::new(Arc::new(KeyManager::volatile()))
+            .await
+            .unwrap();
+        let cipher = Some(Arc::new(cipher));
+        let store = ScreenshotStore::open(dir.path(), cipher.clone(), true).unwrap();
+
+        let reference = store.put(b"secret frame", None).unwrap();
+        assert_eq!(files(dir.path(), "enc"), 1);
+        assert_eq!(store.stats().encrypted, 1);
+        let id = parse_reference(&reference).unwrap();
+        let raw = std::fs::read(dir.path().join(format!("{}.enc", id))).unwrap();
+        assert!(!raw.windows(6).any(|w| w == b"secret"));
+        // Le nom ne se déduit pas de l'image
+        assert_ne!(id, format!("{:x}", Sha256::digest(b"secret frame")));
+        assert_eq!(store.put(b"secret frame", None).unwrap(), reference);
+
+        // Index perdu : la capture est retrouvée et reste lisible
+        drop(store);
+        std::fs::remove_file(dir.path().join(INDEX_FILE)).unwrap();
+        let store = ScreenshotStore::open(dir.path(), cipher, true).unwrap();
+        assert_eq!(store.read(&reference).unwrap(), b"secret frame");
+    }
+}
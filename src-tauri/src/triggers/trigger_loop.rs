@@ -6,10 +6,15 @@ use tracing::{debug, error, info, trace, warn};
 
 use super::manager::{TriggerDecision, TriggerManager};
 use super::state_machine::{TriggerEvent, TriggerStateMachine};
+use crate::budget::{BudgetGovernor, Subsystem};
 use crate::context::aggregator::{Context, ContextAggregator};
 use crate::ml::{EventType as MLEventType, PersonalizationManager, UserEvent};
+use crate::monitor::ScreenMonitor;
 use crate::snooze::SnoozeManager;
 
+/// Âge max des erreurs lues à l'écran ajoutées au contexte capturé
+const SCREEN_DIAGNOSTICS_MAX_AGE: Duration = Duration::from_secs(120);
+
 /// Lance la boucle de trigger en arrière-plan
 /// DOIT être appelé depuis un contexte async (ex: invoke_handler ou plugin init)
 pub async fn start_trigger_loop(app_handle: AppHandle) {
@@ -32,9 +37,24 @@ async fn trigger_loop(app_handle: AppHandle) {
     let mut ticker = interval(Duration::from_millis(5000)); // 5s (was 2s)
     let mut consecutive_failures = 0;
     const MAX_FAILURES: u32 = 3;
+    let mut tick: u64 = 0;
 
     loop {
         ticker.tick().await;
+        tick += 1;
+
+        // Sur batterie : un tick sur `factor` ; budget épuisé : tick sauté
+        let work = match app_handle.try_state::<Arc<BudgetGovernor>>() {
+            Some(budget) => {
+                let admission = budget.admit(Subsystem::Triggers);
+                let every = admission.interval_factor().ceil().max(1.0) as u64;
+                if !admission.is_allowed() || !tick.is_multiple_of(every) {
+                    continue;
+                }
+                Some(budget.start(Subsystem::Triggers))
+            }
+            None => None,
+        };
 
         // Get managed state
         let context_aggregator = match app_handle.try_state::<Arc<Mutex<ContextAggregator>>>() {
@@ -88,6 +108,8 @@ async fn trigger_loop(app_handle: AppHandle) {
 
                     if consecutive_failures >= MAX_FAILURES {
                         warn!("❌ Too many failures, cooling down 5s...");
+                        // La pause n'est pas du travail : hors budget
+                        drop(work);
                         tokio::time::sleep(Duration::from_secs(5)).await;
                         consecutive_failures = 0;
                     }
@@ -148,6 +170,7 @@ async fn trigger_loop(app_handle: AppHandle) {
                 .unwrap()
                 .as_secs(),
             capture_duration_ms: 0,
+            diagnostics: Vec::new(),
         };
 
         // Check trigger decision
@@ -221,7 +244,7 @@ async fn trigger_loop(app_handle: AppHandle) {
                 }
 
                 // Capture FULL context (with clipboard, screenshot, etc.)
-                let full_ctx = {
+                let mut full_ctx = {
                     let mut aggregator = context_aggregator.lock().await;
                     match aggregator.capture().await {
                         Ok(ctx) => ctx,
@@ -232,14 +255,42 @@ async fn trigger_loop(app_handle: AppHandle) {
                     }
                 };
 
+                // Add errors recently read on screen (OCR) to those found in the clipboard
+                if let Some(monitor) = app_handle.try_state::<Arc<Mutex<ScreenMonitor>>>() {
+                    let screen_diagnostics = monitor
+                        .lock()
+                        .await
+                        .recent_diagnostics(SCREEN_DIAGNOSTICS_MAX_AGE)
+                        .await;
+                    full_ctx.diagnostics.extend(screen_diagnostics);
+                }
+                let first_error = full_ctx.diagnostics.iter().find(|d| d.is_error());
+
                 // Update state machine with ShowPrompt event
                 {
                     let mut sm = state_machine.lock().await;
+                    let explanation = match first_error {
+                        Some(diagnostic) => format!(
+                            "App: {} (error: {})",
+                            full_ctx.app.name,
+                            diagnostic.summary()
+                        ),
+                        None => format!(
+                            "App: {} (idle: {:.1}s)",
+                            full_ctx.app.name, full_ctx.idle_seconds
+                        ),
+                    };
                     let opportunity = super::state_machine::OpportunityPreview {
                         detected_task: full_ctx.app.name.clone(),
-                        explanation: format!("App: {} (idle: {:.1}s)", full_ctx.app.name, full_ctx.idle_seconds),
+                        explanation,
+                    };
+                    let confidence = if first_error.is_some() {
+                        0.9
+                    } else if full_ctx.idle_seconds > 30.0 {
+                        0.8
+                    } else {
+                        0.6
                     };
-                    let confidence = if full_ctx.idle_seconds > 30.0 { 0.8 } else { 0.6 };
                     let suggestion_id = format!("sugg_{}", chrono::Utc::now().timestamp());
                     let _ = sm.transition(TriggerEvent::ContextAnalyzed { opportunity, confidence });
                     let _ = sm.transition(TriggerEvent::ShowPrompt { suggestion_id });
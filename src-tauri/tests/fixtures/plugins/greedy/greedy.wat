@@ -0,0 +1,7 @@
+;; Grows its memory to 64 MiB, past the runtime cap
+(module
+  (memory (export "memory") 1)
+  (func (export "run") (result i32)
+    (drop (memory.grow (i32.const 1024)))
+    (i32.const 0))
+)
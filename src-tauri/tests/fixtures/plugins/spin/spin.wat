@@ -0,0 +1,7 @@
+;; Never returns: stopped by the fuel budget or the deadline
+(module
+  (memory (export "memory") 1)
+  (func (export "run") (result i32)
+    (loop $forever (br $forever))
+    (i32.const 0))
+)
@@ -0,0 +1,279 @@
+//! Function hooks run in the WASM runtime, loaded from compiled modules on disk.
+//! Module sources (`.wat`) sit next to each `.wasm`.
+
+use shadowlearn_lib::plugins::permissions::{Grants, ViolationLog};
+use shadowlearn_lib::plugins::{Permission, PluginManager, WasmLimits, WasmRuntime};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn fixtures() -> PathBuf {
+    manifest_dir()
+        .join("tests")
+        .join("fixtures")
+        .join("plugins")
+}
+
+fn grants(plugin_id: &str, permissions: &[Permission]) -> Grants {
+    Grants::new(
+        plugin_id,
+        "on_context",
+        permissions,
+        Arc::new(ViolationLog::default()),
+    )
+}
+
+/// Copy of the example plugin whose manifest declares `permissions`
+fn spotter_with_permissions(permissions: serde_json::Value) -> TempDir {
+    let source = manifest_dir()
+        .join("examples")
+        .join("plugins")
+        .join("keyword-spotter");
+    let root = TempDir::new().unwrap();
+    let plugin = root.path().join("keyword-spotter");
+    fs::create_dir(&plugin).unwrap();
+    fs::copy(
+        source.join("keyword_spotter.wasm"),
+        plugin.join("keyword_spotter.wasm"),
+    )
+    .unwrap();
+
+    let mut manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(source.join("plugin.json")).unwrap()).unwrap();
+    manifest["config"]["permissions"] = permissions;
+    fs::write(plugin.join("plugin.json"), manifest.to_string()).unwrap();
+    root
+}
+
+#[tokio::test]
+async fn test_example_plugin_emits_suggestions() {
+    let mut manager =
+        PluginManager::with_directory(manifest_dir().join("examples").join("plugins")).unwrap();
+    assert_eq!(manager.load_all_plugins().unwrap(), 1);
+
+    let output = manager
+        .execute_hook(
+            "on_context",
+            r#"{"app":"Code","text":"// TODO: handle the empty case"}"#,
+        )
+        .await;
+    assert_eq!(
+        output.results,
+        vec!["Spotted \"TODO\" in the current context"]
+    );
+    assert!(output.notifications.is_empty());
+
+    let output = manager
+        .execute_hook("on_context", r#"{"app":"Code","text":"all done"}"#)
+        .await;
+    assert!(output.results.is_empty());
+    assert_eq!(manager.get_stats().permission_violations, 0);
+}
+
+#[tokio::test]
+async fn test_example_plugin_reads_its_settings() {
+    let runtime = WasmRuntime::default();
+    let plugin = manifest_dir()
+        .join("examples")
+        .join("plugins")
+        .join("keyword-spotter");
+    let settings = serde_json::json!("FIXME");
+    let grants = grants("keyword-spotter", &[Permission::ReadContext]);
+
+    let suggestions = runtime
+        .call(
+            &grants,
+            &plugin,
+            "keyword_spotter.wasm",
+            "on_context",
+            r#"{"text":"FIXME later"}"#,
+            Some(&settings),
+        )
+        .unwrap()
+        .results;
+    assert_eq!(
+        suggestions,
+        vec!["Spotted \"FIXME\" in the current context"]
+    );
+
+    // Settings that are not a JSON string are rejected by the plugin itself
+    let suggestions = runtime
+        .call(
+            &grants,
+            &plugin,
+            "keyword_spotter.wasm",
+            "on_context",
+            r#"{"text":"FIXME later"}"#,
+            None,
+        )
+        .unwrap()
+        .results;
+    assert!(suggestions.is_empty());
+
+    let err = runtime
+        .call(
+            &grants,
+            &plugin,
+            "keyword_spotter.wasm",
+            "missing",
+            "{}",
+            None,
+        )
+        .unwrap_err();
+    assert!(err.contains("not exported"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_runaway_module_runs_out_of_fuel() {
+    let runtime = WasmRuntime::default();
+    let err = runtime
+        .call(
+            &grants("spin", &[]),
+            &fixtures().join("spin"),
+            "spin.wasm",
+            "run",
+            "{}",
+            None,
+        )
+        .unwrap_err();
+    assert!(err.contains("ran out of fuel"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_runaway_module_hits_the_deadline() {
+    let runtime = WasmRuntime::new(WasmLimits {
+        fuel: u64::MAX,
+        timeout: Duration::from_millis(50),
+        ..WasmLimits::default()
+    });
+    let err = runtime
+        .call(
+            &grants("spin", &[]),
+            &fixtures().join("spin"),
+            "spin.wasm",
+            "run",
+            "{}",
+            None,
+        )
+        .unwrap_err();
+    assert!(err.contains("timed out"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_memory_growth_is_capped() {
+    let runtime = WasmRuntime::default();
+    let err = runtime
+        .call(
+            &grants("greedy", &[]),
+            &fixtures().join("greedy"),
+            "greedy.wasm",
+            "run",
+            "{}",
+            None,
+        )
+        .unwrap_err();
+    assert!(
+        err.contains("memory limit exceeded (16777216 bytes)"),
+        "{}",
+        err
+    );
+
+    // The same module runs with a cap above what it asks for
+    let roomy = WasmRuntime::new(WasmLimits {
+        max_memory_bytes: 128 * 1024 * 1024,
+        ..WasmLimits::default()
+    });
+    assert!(roomy
+        .call(
+            &grants("greedy", &[]),
+            &fixtures().join("greedy"),
+            "greedy.wasm",
+            "run",
+            "{}",
+            None
+        )
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_failing_hooks_do_not_stop_the_others() {
+    // The misbehaving fixtures next to a working plugin
+    let root = spotter_with_permissions(serde_json::json!(["read_context"]));
+    for fixture in ["spin", "greedy"] {
+        let plugin = root.path().join(fixture);
+        fs::create_dir(&plugin).unwrap();
+        for entry in fs::read_dir(fixtures().join(fixture)).unwrap() {
+            let entry = entry.unwrap();
+            fs::copy(entry.path(), plugin.join(entry.file_name())).unwrap();
+        }
+    }
+
+    let mut manager = PluginManager::with_directory(root.path().to_path_buf())
+        .unwrap()
+        .with_wasm_limits(WasmLimits {
+            fuel: 100_000,
+            ..WasmLimits::default()
+        });
+    assert_eq!(manager.load_all_plugins().unwrap(), 3);
+
+    let output = manager
+        .execute_hook("on_context", r#"{"text":"TODO"}"#)
+        .await;
+    assert_eq!(
+        output.results,
+        vec!["Spotted \"TODO\" in the current context"]
+    );
+    assert_eq!(manager.get_stats().total_hooks, 3);
+}
+
+#[tokio::test]
+async fn test_undeclared_permission_is_denied_and_reported() {
+    let root = spotter_with_permissions(serde_json::json!([]));
+    let mut manager = PluginManager::with_directory(root.path().to_path_buf()).unwrap();
+    assert_eq!(manager.load_all_plugins().unwrap(), 1);
+    assert!(manager.get_all_plugins()[0].permissions.is_empty());
+
+    let output = manager
+        .execute_hook("on_context", r#"{"text":"TODO"}"#)
+        .await;
+    assert!(output.results.is_empty());
+
+    let stats = manager.get_stats();
+    assert_eq!(stats.permission_violations, 1);
+    let violation = &stats.recent_violations[0];
+    assert_eq!(violation.plugin_id, "keyword-spotter");
+    assert_eq!(violation.hook, "on_context");
+    assert_eq!(violation.permission, Permission::ReadContext);
+
+    // Called directly, the denial is the error
+    let err = WasmRuntime::default()
+        .call(
+            &grants("keyword-spotter", &[]),
+            &root.path().join("keyword-spotter"),
+            "keyword_spotter.wasm",
+            "on_context",
+            r#"{"text":"TODO"}"#,
+            Some(&serde_json::json!("TODO")),
+        )
+        .unwrap_err();
+    assert!(err.contains("Permission denied"), "{}", err);
+    assert!(err.contains("read_context"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_unknown_permission_fails_to_load() {
+    let root = spotter_with_permissions(serde_json::json!(["read_context", "root_access"]));
+    let mut manager = PluginManager::with_directory(root.path().to_path_buf()).unwrap();
+    assert_eq!(manager.load_all_plugins().unwrap(), 0);
+
+    // The unknown permission alone is what kept it out
+    let valid = spotter_with_permissions(serde_json::json!(["read_context"]));
+    let mut manager = PluginManager::with_directory(valid.path().to_path_buf()).unwrap();
+    assert_eq!(manager.load_all_plugins().unwrap(), 1);
+}
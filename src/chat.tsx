@@ -29,6 +29,37 @@ interface Message {
   role: 'user' | 'assistant';
   content: string;
   timestamp: Date;
+  context?: ChatContext | null;
+}
+
+interface ChatContext {
+  app_name: string | null;
+  window_title: string | null;
+  intent: string | null;
+  intent_confidence: number | null;
+  cluster_id: string | null;
+  screen_findings: string[];
+  clipboard: string | null;
+  withheld: string[];
+  truncated: boolean;
+  system_message: string;
+}
+
+interface ChatReply {
+  conversation_id: string;
+  message_id: string | null;
+  content: string;
+  provider: string;
+  cancelled: boolean;
+  context: ChatContext | null;
+}
+
+interface ChatDelta {
+  request_id: string;
+  delta: string;
+  done: boolean;
+  cancelled: boolean;
+  error: string | null;
 }
 
 function ChatWindow() {
@@ -42,6 +73,8 @@ function ChatWindow() {
   // J3: Chat states
   const [isLoading, setIsLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [activeRequestId, setActiveRequestId] = useState<string | null>(null);
+  const [conversationId, setConversationId] = useState<string | null>(null);
   
   // Phase 2: Quick Actions context
   const [currentContext, setCurrentContext] = useState({
@@ -191,6 +224,7 @@ function ChatWindow() {
     const confirmed = window.confirm('Voulez-vous vraiment effacer tous les messages et commencer une nouvelle conversation ?');
     if (confirmed) {
       setMessages([]);
+      setConversationId(null);
       setOpportunityContext(null);
     }
   };
@@ -227,34 +261,79 @@ Suggestion en cours de discussion : "${opportunityContext.opportunity.suggestion
 Question de l'utilisateur : ${messageText}`;
       }
 
-      const response = await invoke<string>('chat_with_ai', {
-        message: enrichedMessage,
-        includeContext: true,
-      });
+      // Placeholder filled token by token from chat:delta events
+      const requestId = crypto.randomUUID();
+      const assistantId = Date.now() + 1;
+      setMessages((prev) => [
+        ...prev,
+        { id: assistantId, role: 'assistant', content: '', timestamp: new Date() },
+      ]);
+      const setAssistantContent = (update: (content: string) => string) =>
+        setMessages((prev) =>
+          prev.map((m) => (m.id === assistantId ? { ...m, content: update(m.content) } : m))
+        );
 
-      const assistantMessage: Message = {
-        id: Date.now() + 1,
-        role: 'assistant',
-        content: response,
-        timestamp: new Date(),
-      };
-      setMessages((prev) => [...prev, assistantMessage]);
+      const { listen } = await import('@tauri-apps/api/event');
+      const unlisten = await listen<ChatDelta>('chat:delta', (event) => {
+        if (event.payload.request_id !== requestId || event.payload.done) return;
+        setAssistantContent((content) => content + event.payload.delta);
+      });
+      setActiveRequestId(requestId);
+
+      try {
+        const send = (confirmEgress: boolean) =>
+          invoke<ChatReply>('chat_with_ai', {
+            message: enrichedMessage,
+            includeContext: true,
+            conversationId,
+            requestId,
+            confirmEgress,
+          });
+        let reply: ChatReply;
+        try {
+          reply = await send(false);
+        } catch (err: any) {
+          // Egress policy: nothing was sent or saved yet, ask before going to a remote provider
+          if (typeof err !== 'string' || !err.startsWith('EGRESS_CONFIRMATION_REQUIRED')) throw err;
+          const reason = err.slice(err.indexOf(':') + 1).trim();
+          if (!window.confirm(`Envoyer ce message à un fournisseur distant ?\n(${reason})`)) {
+            throw 'Envoi annulé : confirmation refusée.';
+          }
+          reply = await send(true);
+        }
+        setConversationId(reply.conversation_id);
+        setAssistantContent(() => reply.content);
+        setMessages((prev) =>
+          prev.map((m) => (m.id === assistantId ? { ...m, context: reply.context } : m))
+        );
+      } catch (err: any) {
+        console.error('Chat error:', err);
+        setError(err || 'Une erreur est survenue');
+        setAssistantContent(
+          () => err || 'Impossible de contacter l\'IA. Essayez de relancer l\'application ou utilisez Ollama local.'
+        );
+      } finally {
+        unlisten();
+        setActiveRequestId(null);
+      }
     } catch (err: any) {
       console.error('Chat error:', err);
       setError(err || 'Une erreur est survenue');
-      
-      const errorMessage: Message = {
-        id: Date.now() + 1,
-        role: 'assistant',
-        content: err || 'Impossible de contacter l\'IA. Essayez de relancer l\'application ou utilisez Ollama local.',
-        timestamp: new Date(),
-      };
-      setMessages((prev) => [...prev, errorMessage]);
     } finally {
       setIsLoading(false);
     }
   };
 
+  const handleCancel = async () => {
+    if (!activeRequestId) return;
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke('cancel_chat', { requestId: activeRequestId });
+    } catch (err) {
+      console.error('Failed to cancel chat:', err);
+    }
+  };
+
   const formatTime = (date: Date) => {
     return new Intl.DateTimeFormat('en-US', {
       hour: '2-digit',
@@ -500,6 +579,16 @@ Question de l'utilisateur : ${messageText}`;
                 <div>{message.content}</div>
                 <div className="message-timestamp">{formatTime(message.timestamp)}</div>
 
+                {message.context && (message.context.system_message || message.context.withheld.length > 0) && (
+                  <details className="message-context">
+                    <summary>Contexte envoyé</summary>
+                    {message.context.system_message && <pre>{message.context.system_message}</pre>}
+                    {message.context.withheld.length > 0 && (
+                      <div>Non envoyé : {message.context.withheld.join(', ')}</div>
+                    )}
+                  </details>
+                )}
+
                 {/* Clueless Phase 1: Message Feedback */}
                 {message.role === 'assistant' && (
                   <MessageFeedback
@@ -550,6 +639,23 @@ Question de l'utilisateur : ${messageText}`;
                  }}>
                    <span style={{ marginRight: '8px' }}>⟳</span>
                    Génération en cours...
+                   {activeRequestId && (
+                     <button
+                       onClick={handleCancel}
+                       style={{
+                         marginLeft: '8px',
+                         padding: '2px 8px',
+                         background: 'rgba(239, 68, 68, 0.2)',
+                         border: '1px solid rgba(239, 68, 68, 0.5)',
+                         borderRadius: '4px',
+                         color: 'white',
+                         cursor: 'pointer',
+                         fontSize: '11px',
+                       }}
+                     >
+                       Stop
+                     </button>
+                   )}
                  </div>
                )}
         
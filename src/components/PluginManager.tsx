@@ -10,6 +10,7 @@ interface Plugin {
   description: string;
   enabled: boolean;
   hooks: string[];
+  permissions: string[];
 }
 
 interface PluginStats {
@@ -17,6 +18,7 @@ interface PluginStats {
   enabled_plugins: number;
   total_hooks: number;
   plugin_directory: string;
+  permission_violations: number;
 }
 
 interface PluginManagerProps {
@@ -164,6 +166,8 @@ export function PluginManager({ isOpen, onClose }: PluginManagerProps) {
                     }}
                   >
                     {stats.enabled_plugins} of {stats.total_plugins} plugins enabled • {stats.total_hooks} hooks
+                    {stats.permission_violations > 0 &&
+                      ` • ${stats.permission_violations} permission violations`}
                   </p>
                 )}
               </div>
@@ -351,6 +355,11 @@ function PluginCard({
               ))}
             </div>
           )}
+          {plugin.permissions.length > 0 && (
+            <div style={{ marginTop: '8px', fontSize: '12px', color: 'var(--text-muted)' }}>
+              Permissions: {plugin.permissions.join(', ')}
+            </div>
+          )}
         </div>
         <div style={{ display: 'flex', gap: '8px', marginLeft: '16px' }}>
           <button
@@ -7,6 +7,15 @@ interface CaptureResult {
   data: string;  // base64
   path: string;
   size_bytes: number;
+  masked_zones: number;
+  selection: {
+    display_id: number;
+    display_choice: 'active_window' | 'primary';
+    requested_mode: 'display' | 'active_window';
+    mode: 'display' | 'active_window';
+    region: { x: number; y: number; width: number; height: number };
+    scale_factor: number;
+  };
 }
 
 export function ScreenshotButton() {
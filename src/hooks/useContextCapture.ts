@@ -10,6 +10,24 @@ export interface ActiveApp {
   tcc_status?: 'Granted' | 'Denied' | 'Unknown';
 }
 
+export interface StackFrame {
+  function: string | null;
+  file: string | null;
+  line: number | null;
+  column: number | null;
+}
+
+export interface Diagnostic {
+  tool: 'rust' | 'typescript' | 'python' | 'node' | 'gcc_clang' | 'blender';
+  severity: 'error' | 'warning';
+  code: string | null;
+  message: string;
+  file: string | null;
+  line: number | null;
+  column: number | null;
+  frames: StackFrame[];
+}
+
 export interface Context {
   id: string;
   app: ActiveApp;
@@ -17,6 +35,7 @@ export interface Context {
   idle_seconds: number;
   timestamp: number;
   capture_duration_ms: number;
+  diagnostics: Diagnostic[];
 }
 
 interface UseContextCaptureOptions {
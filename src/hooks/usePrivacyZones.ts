@@ -2,6 +2,7 @@ import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 
 export interface PrivacyZonesConfig {
+  schema_version: number;
   enabled: boolean;
   zones: PrivacyZone[];
   blur_instead_of_skip: boolean;
@@ -15,6 +16,7 @@ export type PrivacyZone =
       width: number;
       height: number;
       label: string;
+      display?: number | null;
     }
   | {
       type: 'window';
@@ -24,10 +26,56 @@ export type PrivacyZone =
   | {
       type: 'region';
       region: PredefinedRegion;
+      display?: number | null;
+    }
+  | {
+      type: 'window-area';
+      app_name: string;
+      title_pattern?: string | null;
+      area: RelativeArea;
+      label: string;
+    }
+  | {
+      type: 'window-title';
+      pattern: string;
+      label: string;
+    }
+  | {
+      type: 'private-browsing';
     };
 
+/** Rectangle en pourcentage (0-100) de la fenêtre */
+export interface RelativeArea {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
 export type PredefinedRegion = 'top-bar' | 'taskbar' | 'system-tray' | 'dock';
 
+/** Cible d'un effacement : une app entière, ou les fenêtres dont le titre correspond (regex) */
+export interface ErasureTarget {
+  app_name?: string | null;
+  title_pattern?: string | null;
+}
+
+/** Rapport renvoyé par `forget_app` */
+export interface ErasureReport {
+  target: ErasureTarget;
+  erased_at: string;
+  database: { contexts: number; conversations: number; messages: number };
+  replay_events: number;
+  screenshots: number;
+  patterns: { actions: number; patterns: number; repetitive_tasks: number };
+  ml_entries: number;
+  clusters: number;
+  digest_entries: number;
+  productivity_entries: number;
+  blocked_zone: PrivacyZone | null;
+  errors: string[];
+}
+
 /**
  * Hook pour gérer les zones de confidentialité
  *
@@ -107,6 +155,17 @@ export function usePrivacyZones() {
     }
   };
 
+  const forgetApp = async (target: ErasureTarget): Promise<ErasureReport> => {
+    try {
+      const report = await invoke<ErasureReport>('forget_app', { target });
+      await loadConfig(); // Reload config (zone de blocage ajoutée)
+      return report;
+    } catch (e) {
+      console.error('Failed to forget app:', e);
+      throw e;
+    }
+  };
+
   return {
     config,
     loading,
@@ -114,6 +173,7 @@ export function usePrivacyZones() {
     removeZone,
     setEnabled,
     isAppProtected,
+    forgetApp,
     reload: loadConfig,
   };
 }
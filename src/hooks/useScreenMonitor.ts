@@ -10,6 +10,22 @@ export interface ScreenChange {
   image_path: string;
   image_base64: string;
   analysis: string | null;
+  /** Tiles that changed since the previous capture (whole screen for the first one) */
+  changed_regions: ChangedRegion[];
+  /** Mean tile similarity with the previous capture (1 = identical) */
+  similarity: number;
+}
+
+/**
+ * Changed area of a capture, in image pixels
+ */
+export interface ChangedRegion {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  /** Share of the tile hash that changed (0.0 = identical, 1.0 = fully changed) */
+  difference: number;
 }
 
 /**